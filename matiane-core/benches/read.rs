@@ -0,0 +1,166 @@
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use criterion::{Criterion, criterion_group, criterion_main};
+use matiane_core::activity::{
+    MAX_EVENT_GAP, read_activity, read_activity_parallel,
+};
+use matiane_core::events::{Event, EventHead, Focused, TimedEvent};
+use matiane_core::store::{EventReader, OpenOptions};
+use std::hint::black_box;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+
+const DAYS: u32 = 7;
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Lines of a day with an event every 10 seconds, a focus change every
+/// 10th.
+fn day(day: u32) -> Vec<String> {
+    let midnight = start() + TimeDelta::days(day.into());
+
+    (0..8640)
+        .map(|i| {
+            let event = match i % 10 {
+                0 => Event::Focused(Box::new(Focused {
+                    title: format!("Some window title {}", i % 7),
+                    id: format!("app{}", i % 3),
+                    pid: 1000 + i % 3,
+                    process: None,
+                    raw_title: None,
+                    detail: None,
+                })),
+                _ => Event::Alive,
+            };
+            let timed = TimedEvent {
+                timestamp: midnight + TimeDelta::seconds(10 * i64::from(i)),
+                event,
+            };
+
+            serde_json::to_string(&timed).unwrap()
+        })
+        .collect()
+}
+
+fn store() -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+
+    for n in 0..DAYS {
+        let date = start() + TimeDelta::days(n.into());
+        let name = date.format("%Y%m%d.log").to_string();
+        std::fs::write(dir.path().join(name), day(n).join("\n") + "\n")
+            .unwrap();
+    }
+
+    dir
+}
+
+fn parse(c: &mut Criterion) {
+    let lines = day(0);
+    let mut group = c.benchmark_group("parse");
+
+    group.bench_function("event", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(serde_json::from_str::<TimedEvent>(line).unwrap());
+            }
+        })
+    });
+    group.bench_function("head", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(EventHead::parse(line).unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+async fn count(dir: &Path, heads: bool, mmap: bool) -> usize {
+    let from = start().fixed_offset();
+    let options = OpenOptions {
+        mmap,
+        ..Default::default()
+    };
+    let mut reader = EventReader::open_with(dir.to_path_buf(), &from, options)
+        .await
+        .unwrap();
+    let mut count = 0;
+
+    if heads {
+        while reader.next_head().await.unwrap().is_some() {
+            count += 1;
+        }
+    } else {
+        while reader.next_event().await.unwrap().is_some() {
+            count += 1;
+        }
+    }
+
+    count
+}
+
+fn read(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let dir = store();
+    let mut group = c.benchmark_group("read");
+    group.sample_size(10);
+
+    group.bench_function("events", |b| {
+        b.iter(|| runtime.block_on(count(dir.path(), false, false)))
+    });
+    group.bench_function("heads", |b| {
+        b.iter(|| runtime.block_on(count(dir.path(), true, false)))
+    });
+    group.bench_function("heads mmap", |b| {
+        b.iter(|| runtime.block_on(count(dir.path(), true, true)))
+    });
+
+    group.finish();
+}
+
+fn aggregate(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let dir = store();
+    let from = start().fixed_offset();
+    let to = start() + TimeDelta::days(DAYS.into());
+    let mut group = c.benchmark_group("aggregate");
+    group.sample_size(10);
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let dir = dir.path().to_path_buf();
+            runtime
+                .block_on(read_activity(dir, from, to, MAX_EVENT_GAP))
+                .unwrap()
+        })
+    });
+
+    for threads in [1, 4] {
+        let threads = NonZeroUsize::new(threads).unwrap();
+
+        group.bench_function(format!("parallel/{}", threads), |b| {
+            b.iter(|| {
+                let dir = dir.path().to_path_buf();
+                runtime
+                    .block_on(read_activity_parallel(
+                        dir,
+                        from,
+                        to,
+                        MAX_EVENT_GAP,
+                        threads,
+                    ))
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, parse, read, aggregate);
+criterion_main!(benches);
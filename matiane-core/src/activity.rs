@@ -0,0 +1,524 @@
+//! Focus spans folded from the events of the store.
+
+use crate::events::TimedEvent;
+use crate::sessions::{FocusSession, Sessionizer};
+#[cfg(feature = "async")]
+use crate::store::{
+    DayZone, EventReader, OnDecodeError, StorePath, StoreReadError,
+};
+#[cfg(feature = "async")]
+use anyhow::Result;
+use chrono::{
+    DateTime, Days, FixedOffset, Local, NaiveDate, TimeDelta, TimeZone, Utc,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[cfg(feature = "async")]
+mod downsample;
+#[cfg(feature = "async")]
+mod live;
+#[cfg(feature = "async")]
+mod parallel;
+
+#[cfg(feature = "async")]
+pub use downsample::downsample;
+#[cfg(feature = "async")]
+pub(crate) use downsample::summarized_spans;
+#[cfg(feature = "async")]
+pub use live::{
+    CONTROL_SOCKET_NAME, CURRENT_FILE_NAME, LiveActivity, Snapshot, read_today,
+    status_today,
+};
+#[cfg(feature = "async")]
+pub use parallel::read_activity_parallel;
+
+/// Anything longer than this between two events means the daemon was not
+/// running (or the machine was suspended), so nothing is attributed to it.
+/// For the default live interval, see [`crate::sessions::max_gap`].
+pub const MAX_EVENT_GAP: TimeDelta = TimeDelta::minutes(3);
+
+/// A continuous period of a single window being focused while active.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Span {
+    pub app: String,
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl From<FocusSession> for Span {
+    fn from(session: FocusSession) -> Self {
+        Span {
+            app: session.app,
+            title: session.title,
+            start: session.start,
+            end: session.end,
+        }
+    }
+}
+
+impl From<Span> for FocusSession {
+    fn from(span: Span) -> Self {
+        FocusSession {
+            app: span.app,
+            title: span.title,
+            detail: None,
+            workspace: None,
+            start: span.start,
+            end: span.end,
+        }
+    }
+}
+
+impl Span {
+    pub fn duration(&self) -> TimeDelta {
+        self.end - self.start
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Activity {
+    pub spans: Vec<Span>,
+    /// Span that is still going on at the end of the range.
+    pub current: Option<Span>,
+    /// Periods of being idle or asleep while the daemon was running.
+    pub away: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Gaps between events, nothing was tracked in them.
+    pub untracked: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl Activity {
+    pub fn total(&self) -> TimeDelta {
+        self.all_spans().map(Span::duration).sum()
+    }
+
+    /// Time nothing was tracked in.
+    pub fn untracked_total(&self) -> TimeDelta {
+        self.untracked
+            .iter()
+            .map(|(start, end)| *end - *start)
+            .sum()
+    }
+
+    pub fn all_spans(&self) -> impl Iterator<Item = &Span> {
+        self.spans.iter().chain(self.current.iter())
+    }
+
+    /// `(start, end)` of all spans, sorted by start.
+    pub fn sorted_intervals(&self) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let mut intervals: Vec<_> = self
+            .all_spans()
+            .map(|span| (span.start, span.end))
+            .collect();
+        intervals.sort();
+        intervals
+    }
+
+    /// Spans sorted by start, consecutive spans of the same app less than
+    /// `join_gap` apart are joined. A block keeps the title it started with.
+    pub fn blocks(&self, join_gap: TimeDelta) -> Vec<Span> {
+        let mut spans: Vec<&Span> = self.all_spans().collect();
+        spans.sort_by_key(|span| span.start);
+
+        let mut blocks: Vec<Span> = vec![];
+
+        for span in spans {
+            match blocks.last_mut() {
+                Some(last)
+                    if last.app == span.app
+                        && span.start - last.end <= join_gap =>
+                {
+                    last.end = last.end.max(span.end);
+                }
+                _ => blocks.push(span.clone()),
+            }
+        }
+
+        blocks
+    }
+
+    /// Keep only the spans matching `keep`.
+    pub fn retain<F>(&mut self, keep: F)
+    where
+        F: Fn(&Span) -> bool,
+    {
+        self.spans.retain(&keep);
+        self.current = self.current.take().filter(&keep);
+    }
+
+    /// Totals grouped by `key`, longest first.
+    pub fn totals_by<F>(&self, key: F) -> Vec<(String, TimeDelta)>
+    where
+        F: Fn(&Span) -> &str,
+    {
+        let mut totals: HashMap<&str, TimeDelta> = HashMap::new();
+
+        for span in self.all_spans() {
+            *totals.entry(key(span)).or_default() += span.duration();
+        }
+
+        let mut totals: Vec<(String, TimeDelta)> = totals
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        totals
+    }
+
+    /// Totals per day in `tz`, spans over midnight are split.
+    pub fn totals_by_day<Tz: TimeZone>(
+        &self,
+        tz: &Tz,
+    ) -> Vec<(NaiveDate, TimeDelta)> {
+        let mut totals: BTreeMap<NaiveDate, TimeDelta> = BTreeMap::new();
+
+        for span in self.all_spans() {
+            let mut start = span.start;
+
+            while start < span.end {
+                let (day, next_day) = day_of(start, tz);
+                let end = span.end.min(next_day);
+
+                *totals.entry(day).or_default() += end - start;
+                start = end;
+            }
+        }
+
+        totals.into_iter().collect()
+    }
+}
+
+/// Day of `at` in `tz` and the midnight it ends at.
+pub(crate) fn day_of<Tz: TimeZone>(
+    at: DateTime<Utc>,
+    tz: &Tz,
+) -> (NaiveDate, DateTime<Utc>) {
+    let day = at.with_timezone(tz).date_naive();
+    let midnight = (day + Days::new(1))
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is valid");
+    let next_day = tz
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.to_utc())
+        .unwrap_or_else(|| midnight.and_utc());
+
+    (day, next_day)
+}
+
+pub fn start_of_today() -> DateTime<FixedOffset> {
+    start_of_day(Local::now().date_naive())
+}
+
+/// Local midnight of `day`.
+pub fn start_of_day(day: NaiveDate) -> DateTime<FixedOffset> {
+    let midnight = day.and_hms_opt(0, 0, 0).expect("midnight is valid");
+
+    // Midnight may not exist on DST change, use the UTC instant then.
+    midnight
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|dt| dt.fixed_offset())
+        .unwrap_or_else(|| midnight.and_utc().fixed_offset())
+}
+
+#[cfg(feature = "async")]
+/// Read events in `[from, to)` and fold them into focus spans. Events
+/// further apart than `max_gap` leave the time between them untracked.
+///
+/// With an index of the first day the events start at the hour of `from`,
+/// so backfilled periods starting earlier that reach into the range are
+/// missed. Downsampled days are after the other spans.
+pub async fn read_activity(
+    store: impl Into<StorePath>,
+    from: DateTime<FixedOffset>,
+    to: DateTime<Utc>,
+    max_gap: TimeDelta,
+) -> Result<Activity> {
+    let store = store.into();
+    let dir = store.dir().to_path_buf();
+    let zone = DayZone::load(&dir).await?;
+    let opened = EventReader::open_indexed(store, &from).await;
+    let (reader, entry) = match opened {
+        Ok(opened) => opened,
+        Err(StoreReadError::NoFilesToOpen) => {
+            let spans =
+                downsample::summarized_spans(&dir, zone, from.to_utc(), to)
+                    .await?;
+            return Ok(Activity {
+                spans,
+                ..Default::default()
+            });
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut reader = reader.on_decode_error(OnDecodeError::Skip);
+    let mut sessionizer = match entry {
+        Some(e) => Sessionizer::resume(
+            e.previous,
+            e.focused.as_ref(),
+            e.workspace.as_deref(),
+            e.inactive,
+        ),
+        None => Sessionizer::default(),
+    }
+    .with_max_gap(max_gap)
+    .with_untracked(true)
+    .with_away(true);
+    let mut sessions = vec![];
+
+    while let Some(event) = reader.next_event().await? {
+        if event.timestamp >= to {
+            break;
+        }
+
+        sessions.extend(sessionizer.push(&event));
+    }
+
+    // Still under the read lock of the reader, so no day is downsampled
+    // in between.
+    let mut activity = finish(sessions, &sessionizer, from.to_utc(), to);
+    let summarized =
+        downsample::summarized_spans(&dir, zone, from.to_utc(), to).await?;
+    activity.spans.extend(summarized);
+
+    Ok(activity)
+}
+
+#[cfg(feature = "async")]
+/// Events written after `last_seen`.
+pub async fn events_after(
+    store: impl Into<StorePath>,
+    last_seen: DateTime<Utc>,
+) -> Result<Vec<TimedEvent>> {
+    let open_at = last_seen.fixed_offset();
+    let mut reader = match EventReader::open_from(store, &open_at).await {
+        Ok(reader) => reader.on_decode_error(OnDecodeError::Skip),
+        Err(StoreReadError::NoFilesToOpen) => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut events = vec![];
+
+    while let Some(event) = reader.next_event().await? {
+        if event.timestamp > last_seen {
+            events.push(event);
+        }
+    }
+
+    Ok(events)
+}
+
+/// Fold events in `[from, to)`, in the order they were written, into
+/// focus spans. Events further apart than `max_gap` leave the time between
+/// them untracked.
+pub fn fold<'a>(
+    events: impl IntoIterator<Item = &'a TimedEvent>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    max_gap: TimeDelta,
+) -> Activity {
+    let mut sessionizer = Sessionizer::default()
+        .with_max_gap(max_gap)
+        .with_untracked(true)
+        .with_away(true);
+    let mut sessions = vec![];
+
+    for event in events {
+        if event.timestamp >= to {
+            break;
+        }
+
+        sessions.extend(sessionizer.push(event));
+    }
+
+    finish(sessions, &sessionizer, from, to)
+}
+
+/// Activity in `[from, to)` of the `sessions` ended by `sessionizer`, with
+/// the ones it has going on now, away periods and gaps included.
+fn finish(
+    mut sessions: Vec<FocusSession>,
+    sessionizer: &Sessionizer,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Activity {
+    let now = Utc::now().min(to);
+    let mut current = None;
+
+    // A session going on ends at the last event when the daemon is gone.
+    for session in sessionizer.ongoing(now) {
+        match session.is_away() || session.end != now {
+            true => sessions.push(session),
+            false => current = Some(Span::from(session)),
+        }
+    }
+
+    let clip = |mut span: Span| {
+        span.start = span.start.max(from);
+        span.end = span.end.min(to);
+        (span.end > span.start).then_some(span)
+    };
+
+    let periods = |sessions: Vec<FocusSession>| {
+        sessions
+            .into_iter()
+            .map(|session| (session.start.max(from), session.end.min(to)))
+            .filter(|(start, end)| end > start)
+            .collect()
+    };
+
+    let (away, spans): (Vec<_>, Vec<_>) =
+        sessions.into_iter().partition(FocusSession::is_away);
+    let (untracked, spans): (Vec<_>, Vec<_>) =
+        spans.into_iter().partition(FocusSession::is_untracked);
+
+    Activity {
+        spans: spans.into_iter().map(Span::from).filter_map(clip).collect(),
+        current: current.and_then(clip),
+        away: periods(away),
+        untracked: periods(untracked),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Event, Focused};
+    use chrono::TimeZone;
+    use std::time::Duration;
+
+    fn at(min: u32, sec: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, 10, min, sec).unwrap()
+    }
+
+    fn focused(id: &str) -> Event {
+        Event::Focused(Box::new(Focused {
+            title: format!("{} title", id),
+            id: id.to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }))
+    }
+
+    fn fold(events: Vec<(DateTime<Utc>, Event)>) -> Activity {
+        let events: Vec<TimedEvent> = events
+            .into_iter()
+            .map(|(timestamp, event)| TimedEvent { timestamp, event })
+            .collect();
+
+        super::fold(&events, at(0, 0), at(59, 0), MAX_EVENT_GAP)
+    }
+
+    #[test]
+    fn fold_focus_and_idle() {
+        let activity = fold(vec![
+            (at(0, 0), focused("a")),
+            (at(1, 0), focused("b")),
+            (at(2, 0), Event::Idle),
+            (at(4, 0), Event::Active),
+            (at(5, 0), focused("a")),
+            (at(6, 0), Event::Sleep),
+        ]);
+
+        let totals = activity.totals_by(|s| &s.app);
+
+        assert_eq!(
+            totals,
+            vec![
+                ("a".to_string(), TimeDelta::minutes(2)),
+                ("b".to_string(), TimeDelta::minutes(2)),
+            ]
+        );
+        assert!(activity.current.is_none());
+        assert_eq!(activity.away, vec![(at(2, 0), at(4, 0))]);
+    }
+
+    #[test]
+    fn fold_missing_heartbeats() {
+        let activity = fold(vec![
+            (at(0, 0), focused("a")),
+            (at(1, 0), Event::Alive),
+            // daemon was dead for 9 minutes.
+            (at(10, 0), Event::Alive),
+            (at(11, 0), Event::Sleep),
+        ]);
+
+        assert_eq!(activity.total(), TimeDelta::minutes(2));
+        assert_eq!(activity.spans.len(), 2);
+        assert_eq!(activity.untracked, vec![(at(1, 0), at(10, 0))]);
+    }
+
+    #[test]
+    fn fold_longer_gap() {
+        let events: Vec<TimedEvent> = [
+            (at(0, 0), focused("a")),
+            (at(5, 0), Event::Alive),
+            (at(10, 0), Event::Alive),
+            (at(30, 0), Event::Alive),
+        ]
+        .into_iter()
+        .map(|(timestamp, event)| TimedEvent { timestamp, event })
+        .collect();
+        // Alive every five minutes.
+        let max_gap = crate::sessions::max_gap(Duration::from_secs(300));
+
+        let activity = super::fold(&events, at(0, 0), at(59, 0), max_gap);
+        assert_eq!(activity.total(), TimeDelta::minutes(10));
+        assert_eq!(activity.untracked, vec![(at(10, 0), at(30, 0))]);
+    }
+
+    #[test]
+    fn fold_away_across_gap() {
+        let activity = fold(vec![
+            (at(0, 0), focused("a")),
+            (at(1, 0), Event::Idle),
+            (at(2, 0), Event::Alive),
+            // daemon was dead, the idle state is still in effect after.
+            (at(10, 0), Event::Alive),
+            (at(12, 0), Event::Active),
+            (at(13, 0), Event::Alive),
+        ]);
+
+        assert_eq!(
+            activity.away,
+            vec![(at(1, 0), at(2, 0)), (at(10, 0), at(12, 0))]
+        );
+        assert_eq!(activity.total(), TimeDelta::minutes(2));
+    }
+
+    #[test]
+    fn totals_by_day_splits_midnight() {
+        let time = |d, h| Utc.with_ymd_and_hms(2026, 1, d, h, 0, 0).unwrap();
+        let span = |start, end| Span {
+            app: "a".to_string(),
+            title: String::new(),
+            start,
+            end,
+        };
+        let activity = Activity {
+            spans: vec![
+                span(time(1, 10), time(1, 12)),
+                span(time(1, 23), time(2, 1)),
+            ],
+            current: Some(span(time(3, 9), time(3, 10))),
+            away: vec![],
+            untracked: vec![],
+        };
+        let day = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+
+        assert_eq!(
+            activity.totals_by_day(&Utc),
+            vec![
+                (day(1), TimeDelta::hours(3)),
+                (day(2), TimeDelta::hours(1)),
+                (day(3), TimeDelta::hours(1)),
+            ]
+        );
+    }
+}
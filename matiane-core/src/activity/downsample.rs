@@ -0,0 +1,238 @@
+//! Replacing old day files with the totals of their apps.
+//!
+//! Downsampled days only keep how long every app was focused, read back
+//! as spans laid end to end from midnight of the day in app order, with empty
+//! titles. Sessions, titles and away periods of them are gone.
+
+use super::parallel::fold_files;
+use super::{Activity, Span};
+use crate::store::{
+    DaySummary, DayZone, EventReader, Index, StorePath,
+    acquire_maintenance_lock, read_summaries,
+};
+use anyhow::Result;
+use chrono::{DateTime, Days, NaiveDate, TimeDelta, Utc};
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+
+/// Downsample the day files of `store` before `before` into summaries and
+/// delete them, folded with `max_gap` like `read_activity`. The store must
+/// not be in use. Returns how many files were downsampled.
+pub async fn downsample(
+    store: impl Into<StorePath>,
+    before: NaiveDate,
+    max_gap: TimeDelta,
+    threads: NonZeroUsize,
+) -> Result<usize> {
+    let store = store.into();
+    let dir = store.dir();
+    let _lock = acquire_maintenance_lock(dir.to_path_buf()).await?;
+
+    let files: Vec<(NaiveDate, PathBuf)> = EventReader::list_files(dir)
+        .await?
+        .items
+        .iter()
+        .filter(|filepath| *filepath.date() < before)
+        .map(|filepath| (*filepath.date(), filepath.to_path_buf()))
+        .collect();
+
+    let Some((first, _)) = files.first() else {
+        return Ok(0);
+    };
+
+    let zone = DayZone::load(dir).await?;
+    let from = zone.start_of(*first);
+    let to = zone.start_of(before);
+    let key = store.key();
+    let activity =
+        fold_files(files.clone(), key, zone, from, to, max_gap, threads)
+            .await?;
+
+    for mut summary in summarize(&activity, zone) {
+        // Events written to a downsampled day later are added to it.
+        if let Some(existing) = DaySummary::read(dir, summary.date).await? {
+            summary.merge(&existing);
+        }
+
+        summary.write(dir).await?;
+    }
+
+    for (_, path) in &files {
+        log::debug!("Removing downsampled {:?}", path);
+
+        remove_if_exists(&Index::path(path)).await?;
+        tokio::fs::remove_file(path).await?;
+    }
+
+    Ok(files.len())
+}
+
+/// Spans of the downsampled days in `[from, to)`.
+pub(crate) async fn summarized_spans(
+    dir: &Path,
+    zone: DayZone,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Span>> {
+    let days = zone.date_of(from)..=zone.date_of(to);
+    let summaries = read_summaries(dir, days).await?;
+
+    let mut spans = vec![];
+    for summary in summaries {
+        let mut start = zone.start_of(summary.date);
+
+        for (app, seconds) in summary.apps {
+            let end = start + TimeDelta::seconds(seconds);
+            let span = Span {
+                app,
+                title: String::new(),
+                start: start.max(from),
+                end: end.min(to),
+            };
+            start = end;
+
+            if span.end > span.start {
+                spans.push(span);
+            }
+        }
+    }
+
+    Ok(spans)
+}
+
+/// Totals per day of `zone` and app, spans over midnight are split.
+fn summarize(activity: &Activity, zone: DayZone) -> Vec<DaySummary> {
+    let mut days: BTreeMap<NaiveDate, DaySummary> = BTreeMap::new();
+
+    for span in activity.all_spans() {
+        let mut start = span.start;
+
+        while start < span.end {
+            let date = zone.date_of(start);
+            let next = date
+                .checked_add_days(Days::new(1))
+                .map_or(span.end, |next| zone.start_of(next));
+            let end = span.end.min(next);
+
+            let summary =
+                days.entry(date).or_insert_with(|| DaySummary::new(date));
+            *summary.apps.entry(span.app.clone()).or_default() +=
+                (end - start).num_seconds();
+
+            start = end;
+        }
+    }
+
+    days.into_values().collect()
+}
+
+async fn remove_if_exists(path: &Path) -> Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::{
+        MAX_EVENT_GAP, read_activity, read_activity_parallel,
+    };
+    use crate::events::{Event, Focused, TimedEvent};
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn downsample_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = |d, h, m| Utc.with_ymd_and_hms(2026, 1, d, h, m, 0).unwrap();
+        let focused = |id: &str| {
+            Event::Focused(Box::new(Focused {
+                title: format!("{} title", id),
+                id: id.to_string(),
+                pid: 1,
+                process: None,
+                raw_title: None,
+                detail: None,
+            }))
+        };
+
+        let days = [
+            // Focus going on over midnight.
+            vec![
+                (at(1, 23, 0), focused("a")),
+                (at(1, 23, 2), focused("b")),
+                (at(1, 23, 59), Event::Alive),
+            ],
+            vec![(at(2, 0, 1), Event::Alive), (at(2, 0, 2), Event::Idle)],
+            vec![
+                (at(3, 10, 0), Event::Active),
+                (at(3, 10, 0), focused("a")),
+                (at(3, 10, 2), Event::Sleep),
+            ],
+        ];
+
+        for events in &days {
+            let lines: Vec<String> = events
+                .iter()
+                .map(|(timestamp, event)| {
+                    serde_json::to_string(&TimedEvent {
+                        timestamp: *timestamp,
+                        event: event.clone(),
+                    })
+                    .unwrap()
+                })
+                .collect();
+            let name = events[0].0.format("%Y%m%d.log").to_string();
+            std::fs::write(dir.path().join(name), lines.join("\n") + "\n")
+                .unwrap();
+        }
+
+        let path = dir.path().to_path_buf();
+        let from = at(1, 0, 0).fixed_offset();
+        let to = at(4, 0, 0);
+        let threads = NonZeroUsize::new(2).unwrap();
+        let totals = |activity: Activity| activity.totals_by(|s| &s.app);
+
+        let expected = totals(
+            read_activity(path.clone(), from, to, MAX_EVENT_GAP)
+                .await
+                .unwrap(),
+        );
+
+        let date = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        let downsampled =
+            downsample(path.clone(), date(3), MAX_EVENT_GAP, threads)
+                .await
+                .unwrap();
+        assert_eq!(downsampled, 2);
+        assert!(!dir.path().join("20260101.log").exists());
+        assert!(dir.path().join("20260103.log").exists());
+
+        let summary = DaySummary::read(dir.path(), date(2)).await.unwrap();
+        assert_eq!(summary.unwrap().apps["b"], 120);
+
+        let sequential = read_activity(path.clone(), from, to, MAX_EVENT_GAP)
+            .await
+            .unwrap();
+        let parallel = read_activity_parallel(
+            path.clone(),
+            from,
+            to,
+            MAX_EVENT_GAP,
+            threads,
+        )
+        .await
+        .unwrap();
+        assert_eq!(totals(sequential), expected);
+        assert_eq!(totals(parallel), expected);
+
+        // Only what is left in the range is counted.
+        let from = at(1, 0, 1).fixed_offset();
+        let activity = read_activity(path, from, at(2, 0, 0), MAX_EVENT_GAP)
+            .await
+            .unwrap();
+        assert_eq!(activity.total(), TimeDelta::minutes(2));
+    }
+}
@@ -0,0 +1,460 @@
+//! Today's activity kept up to date by the daemon and shared in
+//! `current.json` of the runtime dir and the `status` of its control
+//! socket, so reading it needs no store scan.
+
+use super::{Activity, Span, read_activity, start_of_day, start_of_today};
+use crate::events::TimedEvent;
+use crate::sessions::Sessionizer;
+use crate::store::{EventReader, OnDecodeError, StorePath, StoreReadError};
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, Local, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+pub const CURRENT_FILE_NAME: &str = "current.json";
+
+/// Control socket of the daemon, in the runtime dir shared by all apps.
+pub const CONTROL_SOCKET_NAME: &str = "matiane.sock";
+
+/// How long the daemon has to answer `status`.
+const STATUS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// What is going on today, as of the last event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Local midnight the totals start at.
+    pub day: DateTime<FixedOffset>,
+    /// Focused window, its span ends at the last event.
+    pub current: Option<Span>,
+    /// Seconds per app of the spans that ended today.
+    pub totals: BTreeMap<String, i64>,
+    pub last_event: Option<DateTime<Utc>>,
+}
+
+impl Snapshot {
+    pub fn path(runtime_dir: &Path) -> PathBuf {
+        runtime_dir.join(CURRENT_FILE_NAME)
+    }
+
+    /// Snapshot in `runtime_dir`, none without a daemon writing one.
+    pub async fn read(runtime_dir: &Path) -> Result<Option<Self>> {
+        match tokio::fs::read(Self::path(runtime_dir)).await {
+            Ok(content) => Ok(Some(serde_json::from_slice(&content)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn write(&self, runtime_dir: &Path) -> Result<()> {
+        let path = Self::path(runtime_dir);
+        let tmp_path = path.with_extension("json.tmp");
+
+        tokio::fs::create_dir_all(runtime_dir).await?;
+        tokio::fs::write(&tmp_path, serde_json::to_vec(self)?).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+
+        Ok(())
+    }
+
+    /// Whether the daemon wrote it today and is still running at `now`,
+    /// its last event no more than `max_gap` ago.
+    pub fn is_fresh(&self, now: DateTime<Utc>, max_gap: TimeDelta) -> bool {
+        let today = start_of_day(now.with_timezone(&Local).date_naive());
+
+        self.day == today
+            && self.last_event.is_some_and(|last| now - last <= max_gap)
+    }
+
+    /// Activity of the day up to `now`. Ended spans are only kept as
+    /// totals, they are laid end to end from midnight with empty titles.
+    pub fn activity(&self, now: DateTime<Utc>) -> Activity {
+        let mut start = self.day.to_utc();
+        let mut spans = vec![];
+
+        for (app, seconds) in &self.totals {
+            let end = start + TimeDelta::seconds(*seconds);
+            spans.push(Span {
+                app: app.clone(),
+                title: String::new(),
+                start,
+                end,
+            });
+            start = end;
+        }
+
+        let mut current = self.current.clone();
+        if let Some(span) = &mut current {
+            span.end = span.end.max(now);
+        }
+
+        Activity {
+            spans,
+            current,
+            away: vec![],
+            untracked: vec![],
+        }
+    }
+}
+
+/// Today's activity, folded event by event.
+pub struct LiveActivity {
+    day: DateTime<FixedOffset>,
+    sessionizer: Sessionizer,
+    totals: BTreeMap<String, i64>,
+}
+
+impl LiveActivity {
+    /// Sessions end at gaps between events longer than `max_gap`.
+    pub fn new(day: DateTime<FixedOffset>, max_gap: TimeDelta) -> Self {
+        LiveActivity {
+            day,
+            sessionizer: Sessionizer::default().with_max_gap(max_gap),
+            totals: BTreeMap::new(),
+        }
+    }
+
+    /// Activity of `store` since `day`.
+    pub async fn load(
+        store: impl Into<StorePath>,
+        day: DateTime<FixedOffset>,
+        max_gap: TimeDelta,
+    ) -> Result<Self> {
+        let mut live = Self::new(day, max_gap);
+
+        let opened = EventReader::open_indexed(store, &day).await;
+        let (reader, entry) = match opened {
+            Ok(opened) => opened,
+            Err(StoreReadError::NoFilesToOpen) => return Ok(live),
+            Err(e) => return Err(e.into()),
+        };
+        let mut reader = reader.on_decode_error(OnDecodeError::Skip);
+
+        if let Some(e) = entry {
+            live.sessionizer = Sessionizer::resume(
+                e.previous,
+                e.focused.as_ref(),
+                e.workspace.as_deref(),
+                e.inactive,
+            )
+            .with_max_gap(max_gap);
+        }
+
+        while let Some(event) = reader.next_event().await? {
+            live.push(&event);
+        }
+
+        Ok(live)
+    }
+
+    pub fn push(&mut self, event: &TimedEvent) {
+        let local = event.timestamp.with_timezone(&Local).date_naive();
+        let day = start_of_day(local);
+
+        // Spans going on over midnight count from there.
+        if day > self.day {
+            self.day = day;
+            self.totals.clear();
+        }
+
+        let from = self.day.to_utc();
+        for session in self.sessionizer.push(event) {
+            let spent = session.end - session.start.max(from);
+
+            if spent > TimeDelta::zero() {
+                *self.totals.entry(session.app).or_default() +=
+                    spent.num_seconds();
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let last_event = self.sessionizer.last();
+        let current = self
+            .sessionizer
+            .since()
+            .zip(self.sessionizer.focused())
+            .map(|(since, focused)| Span {
+                app: focused.id.clone(),
+                title: focused.title.clone(),
+                start: since.max(self.day.to_utc()),
+                end: last_event.unwrap_or(since),
+            });
+
+        Snapshot {
+            day: self.day,
+            current,
+            totals: self.totals.clone(),
+            last_event,
+        }
+    }
+}
+
+/// The parts of the reply to `status` read here.
+#[derive(Debug, Deserialize)]
+struct StatusReply {
+    error: Option<String>,
+    status: Option<DaemonStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DaemonStatus {
+    paused: bool,
+    today: Option<Snapshot>,
+}
+
+/// Activity since local midnight from the `status` of the daemon listening
+/// on `socket`, none when it keeps no snapshot fresh for `max_gap`. Nothing
+/// is focused while paused.
+pub async fn status_today(
+    socket: &Path,
+    max_gap: TimeDelta,
+) -> Result<Option<Activity>> {
+    let line = tokio::time::timeout(STATUS_TIMEOUT, async {
+        let mut stream = BufReader::new(UnixStream::connect(socket).await?);
+        stream
+            .get_mut()
+            .write_all(b"{\"cmd\":\"status\"}\n")
+            .await?;
+
+        let mut line = String::new();
+        stream.read_line(&mut line).await?;
+        anyhow::Ok(line)
+    })
+    .await??;
+
+    let reply: StatusReply = serde_json::from_str(&line)?;
+    if let Some(error) = reply.error {
+        anyhow::bail!("The daemon failed to answer status: {}", error);
+    }
+
+    let now = Utc::now();
+    let Some(DaemonStatus {
+        paused,
+        today: Some(snapshot),
+    }) = reply.status
+    else {
+        return Ok(None);
+    };
+
+    if !snapshot.is_fresh(now, max_gap) {
+        return Ok(None);
+    }
+
+    let mut activity = snapshot.activity(now);
+    if paused {
+        activity.current = None;
+    }
+
+    Ok(Some(activity))
+}
+
+/// Activity since local midnight, from the snapshot of the daemon when it
+/// is running, otherwise read with `max_gap`.
+pub async fn read_today(
+    store: impl Into<StorePath>,
+    runtime_dir: &Path,
+    max_gap: TimeDelta,
+) -> Result<Activity> {
+    let now = Utc::now();
+
+    match Snapshot::read(runtime_dir).await {
+        Ok(Some(snapshot)) if snapshot.is_fresh(now, max_gap) => {
+            return Ok(snapshot.activity(now));
+        }
+        Ok(_) => {}
+        Err(e) => log::debug!("Not reading the snapshot: {:#}", e),
+    }
+
+    read_activity(store, start_of_today(), now, max_gap).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::MAX_EVENT_GAP;
+    use crate::events::{Event, Focused};
+    use crate::sessions;
+
+    #[tokio::test]
+    async fn live_activity_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = start_of_day(Local::now().date_naive());
+        let at = |m| day.to_utc() + TimeDelta::minutes(m);
+        let focused = |id: &str| {
+            Event::Focused(Box::new(Focused {
+                title: format!("{} title", id),
+                id: id.to_string(),
+                pid: 1,
+                process: None,
+                raw_title: None,
+                detail: None,
+            }))
+        };
+        let events = [
+            (at(10), focused("a")),
+            (at(12), focused("b")),
+            (at(13), focused("a")),
+            (at(14), Event::Alive),
+        ];
+
+        let mut live = LiveActivity::new(day, MAX_EVENT_GAP);
+        for (timestamp, event) in events {
+            live.push(&TimedEvent { timestamp, event });
+        }
+
+        let snapshot = live.snapshot();
+        assert_eq!(snapshot.totals["a"], 120);
+        assert_eq!(snapshot.totals["b"], 60);
+        assert_eq!(snapshot.current.as_ref().unwrap().start, at(13));
+        assert_eq!(snapshot.last_event, Some(at(14)));
+        assert!(snapshot.is_fresh(at(16), MAX_EVENT_GAP));
+        assert!(!snapshot.is_fresh(at(18), MAX_EVENT_GAP));
+
+        let activity = snapshot.activity(at(15));
+        assert_eq!(activity.total(), TimeDelta::minutes(5));
+        assert_eq!(activity.totals_by(|span| &span.app)[0].0, "a");
+
+        snapshot.write(dir.path()).await.unwrap();
+        let read = Snapshot::read(dir.path()).await.unwrap();
+        assert_eq!(read, Some(snapshot));
+
+        // The next day starts from nothing but what is still focused.
+        let yesterday = start_of_day(day.date_naive() - TimeDelta::days(1));
+        let mut live = LiveActivity::new(yesterday, MAX_EVENT_GAP);
+        for (timestamp, event) in
+            [(at(-1), focused("a")), (at(1), Event::Alive)]
+        {
+            live.push(&TimedEvent { timestamp, event });
+        }
+
+        let snapshot = live.snapshot();
+        assert_eq!(snapshot.day, day);
+        assert!(snapshot.totals.is_empty());
+        assert_eq!(snapshot.current.unwrap().start, at(0));
+    }
+
+    #[test]
+    fn live_activity_max_gap() {
+        let day = start_of_day(Local::now().date_naive());
+        let at = |m| day.to_utc() + TimeDelta::minutes(m);
+        let focused = Event::Focused(Box::new(Focused {
+            title: "a title".to_string(),
+            id: "a".to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }));
+        // Alive every five minutes.
+        let max_gap = sessions::max_gap(Duration::from_secs(300));
+        let events = [
+            TimedEvent {
+                timestamp: at(10),
+                event: focused,
+            },
+            TimedEvent {
+                timestamp: at(15),
+                event: Event::Alive,
+            },
+            TimedEvent {
+                timestamp: at(20),
+                event: Event::Alive,
+            },
+        ];
+
+        let snapshot = |max_gap| {
+            let mut live = LiveActivity::new(day, max_gap);
+            for event in &events {
+                live.push(event);
+            }
+            live.snapshot()
+        };
+
+        let snapshot_of = snapshot(max_gap);
+        assert_eq!(snapshot_of.current.as_ref().unwrap().start, at(10));
+        assert!(snapshot_of.is_fresh(at(30), max_gap));
+        assert!(!snapshot_of.is_fresh(at(30), MAX_EVENT_GAP));
+
+        // The default gap is shorter than the interval.
+        let split = snapshot(MAX_EVENT_GAP);
+        assert_eq!(split.current.unwrap().start, at(20));
+    }
+
+    /// Serves one client of `socket`, replying `reply` to its line.
+    fn reply_once(socket: &Path, reply: serde_json::Value) {
+        let listener = tokio::net::UnixListener::bind(socket).unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+
+            let mut line = String::new();
+            stream.read_line(&mut line).await.unwrap();
+            assert_eq!(line, "{\"cmd\":\"status\"}\n");
+
+            let reply = format!("{}\n", reply);
+            stream.get_mut().write_all(reply.as_bytes()).await.unwrap();
+        });
+    }
+
+    #[tokio::test]
+    async fn status_today_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join(CONTROL_SOCKET_NAME);
+        let now = Utc::now();
+        let snapshot = Snapshot {
+            day: start_of_today(),
+            current: Some(Span {
+                app: "a".to_string(),
+                title: "a title".to_string(),
+                start: now - TimeDelta::minutes(1),
+                end: now,
+            }),
+            totals: BTreeMap::from([("b".to_string(), 60)]),
+            last_event: Some(now),
+        };
+        let status = |paused: bool, today: Option<&Snapshot>| {
+            serde_json::json!({
+                "ok": true,
+                "status": {
+                    "paused": paused,
+                    "started": now,
+                    "last_event": now,
+                    "today": today,
+                },
+            })
+        };
+
+        assert!(status_today(&socket, MAX_EVENT_GAP).await.is_err());
+
+        reply_once(&socket, status(false, Some(&snapshot)));
+        let activity =
+            status_today(&socket, MAX_EVENT_GAP).await.unwrap().unwrap();
+        assert_eq!(activity.current.unwrap().app, "a");
+        assert_eq!(activity.spans[0].duration(), TimeDelta::minutes(1));
+
+        std::fs::remove_file(&socket).unwrap();
+        reply_once(&socket, status(true, Some(&snapshot)));
+        let activity =
+            status_today(&socket, MAX_EVENT_GAP).await.unwrap().unwrap();
+        assert_eq!(activity.current, None);
+
+        std::fs::remove_file(&socket).unwrap();
+        reply_once(&socket, status(false, None));
+        assert!(
+            status_today(&socket, MAX_EVENT_GAP)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        std::fs::remove_file(&socket).unwrap();
+        reply_once(&socket, serde_json::json!({"ok": false, "error": "bad"}));
+        assert!(status_today(&socket, MAX_EVENT_GAP).await.is_err());
+    }
+}
@@ -0,0 +1,469 @@
+//! Folding every day file of a range on its own thread.
+
+use super::downsample::summarized_spans;
+use super::{Activity, Span, finish};
+use crate::events::{Event, EventHead, Focused, TimedEvent};
+use crate::sessions::Sessionizer;
+use crate::store::crypt::StoreKey;
+use crate::store::{DayZone, EventReader, StorePath, dayfile};
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeDelta, Utc};
+use futures::{StreamExt, stream};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Activity of a single day file.
+struct Day {
+    activity: Activity,
+    /// Backfilled periods, clipped to the whole range instead of the day.
+    backfilled: Vec<Span>,
+}
+
+/// What a day file starts and ends with, backfilled events aside.
+#[derive(Default, Clone)]
+struct Edges {
+    first: Option<TimedEvent>,
+    last: Option<DateTime<Utc>>,
+    /// Last focus change.
+    focused: Option<Focused>,
+    /// Whether idle or asleep after the last change of it.
+    inactive: Option<bool>,
+}
+
+impl Edges {
+    /// Edges of `self` following the ones of the files before.
+    fn after(&self, before: &Edges) -> Edges {
+        Edges {
+            first: None,
+            last: self.last.or(before.last),
+            focused: self.focused.clone().or_else(|| before.focused.clone()),
+            inactive: self.inactive.or(before.inactive),
+        }
+    }
+}
+
+/// Same as `read_activity`, but every day file is folded on its own with
+/// at most `threads` at once. Backfilled periods and downsampled days are
+/// after the other spans.
+pub async fn read_activity_parallel(
+    store: impl Into<StorePath>,
+    from: DateTime<FixedOffset>,
+    to: DateTime<Utc>,
+    max_gap: TimeDelta,
+    threads: NonZeroUsize,
+) -> Result<Activity> {
+    let store = store.into();
+    let dir = store.dir();
+    let _read_lock = EventReader::read_lock(dir).await?;
+    let zone = DayZone::load(dir).await?;
+    let from = from.to_utc();
+    let days = zone.date_of(from)..=zone.date_of(to);
+
+    let files: Vec<(NaiveDate, PathBuf)> = EventReader::list_files(dir)
+        .await?
+        .items
+        .iter()
+        .filter(|filepath| days.contains(filepath.date()))
+        .map(|filepath| (*filepath.date(), filepath.to_path_buf()))
+        .collect();
+
+    let key = store.key();
+    let mut activity =
+        fold_files(files, key, zone, from, to, max_gap, threads).await?;
+    activity
+        .spans
+        .extend(summarized_spans(dir, zone, from, to).await?);
+
+    Ok(activity)
+}
+
+/// Fold the day `files`, in order, in `[from, to)` without taking a lock.
+/// Days start at midnight of `zone`, encrypted files are read with `key`.
+pub(super) async fn fold_files(
+    files: Vec<(NaiveDate, PathBuf)>,
+    key: Option<&StoreKey>,
+    zone: DayZone,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    max_gap: TimeDelta,
+    threads: NonZeroUsize,
+) -> Result<Activity> {
+    let edges = in_parallel(
+        threads,
+        files.iter().map(|(_, path)| {
+            let path = path.clone();
+            let key = key.cloned();
+            move || read_edges(&path, key.as_ref())
+        }),
+    )
+    .await?;
+
+    // The first day starts from nothing like `read_activity`, the others
+    // from the end of the days before.
+    let mut carried = vec![None];
+    let mut before = Edges::default();
+    for edges in &edges[..edges.len().saturating_sub(1)] {
+        before = edges.after(&before);
+        carried.push(Some(before.clone()));
+    }
+
+    let folds = files.iter().enumerate().zip(carried).map(
+        |((i, (date, path)), carried)| {
+            let start = if i == 0 { from } else { zone.start_of(*date) };
+            let end = files
+                .get(i + 1)
+                .map_or(to, |(date, _)| zone.start_of(*date).min(to));
+            let next = edges.get(i + 1).and_then(|edges| edges.first.clone());
+            let last = i + 1 == files.len();
+            let path = path.clone();
+            let key = key.cloned();
+
+            move || {
+                let window = start..end;
+                let mut day = fold_day(
+                    &path,
+                    key.as_ref(),
+                    carried,
+                    next,
+                    window,
+                    to,
+                    max_gap,
+                )?;
+
+                if !last {
+                    let current = day.activity.current.take();
+                    day.activity.spans.extend(current);
+                }
+
+                Ok(day)
+            }
+        },
+    );
+    let days = in_parallel(threads, folds).await?;
+
+    Ok(merge(days, from, to))
+}
+
+/// Run `jobs` on blocking threads, at most `threads` at once.
+async fn in_parallel<T, F>(
+    threads: NonZeroUsize,
+    jobs: impl Iterator<Item = F>,
+) -> Result<Vec<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let mut running = stream::iter(jobs)
+        .map(tokio::task::spawn_blocking)
+        .buffered(threads.get());
+
+    let mut results = vec![];
+    while let Some(result) = running.next().await {
+        results.push(result??);
+    }
+
+    Ok(results)
+}
+
+fn read_edges(path: &Path, key: Option<&StoreKey>) -> Result<Edges> {
+    let content = dayfile::read_to_string(path, key)?;
+    let mut edges = Edges::default();
+
+    for (line, head) in heads(path, &content) {
+        if head.kind != "backfilled"
+            && let Some(event) = parse(path, line)
+        {
+            edges.first = Some(event);
+            break;
+        }
+    }
+
+    // Most days end with a focus change and an idle close to the end.
+    for (line, head) in heads(path, &content).rev() {
+        match head.kind {
+            "backfilled" => continue,
+            "focused" if edges.focused.is_none() => {
+                match parse(path, line).map(|event| event.event) {
+                    Some(Event::Focused(event)) => edges.focused = Some(*event),
+                    _ => continue,
+                }
+            }
+            "present" if edges.last.is_none() => {
+                if let Some(Event::Present(present)) =
+                    parse(path, line).map(|event| event.event)
+                {
+                    edges.last = Some(present.end);
+                }
+            }
+            "idle" | "sleep" => {
+                edges.inactive.get_or_insert(true);
+            }
+            "active" | "awake" => {
+                edges.inactive.get_or_insert(false);
+            }
+            _ => {}
+        }
+
+        edges.last.get_or_insert(head.timestamp);
+
+        if edges.focused.is_some() && edges.inactive.is_some() {
+            break;
+        }
+    }
+
+    Ok(edges)
+}
+
+/// Fold the day file at `path` in `window`, from what was going on at the
+/// end of the days before up to the first event of the next. The session
+/// going on at the end is left as the current one.
+fn fold_day(
+    path: &Path,
+    key: Option<&StoreKey>,
+    carried: Option<Edges>,
+    next: Option<TimedEvent>,
+    window: Range<DateTime<Utc>>,
+    to: DateTime<Utc>,
+    max_gap: TimeDelta,
+) -> Result<Day> {
+    let mut sessionizer = match carried {
+        Some(edges) => Sessionizer::resume(
+            edges.last,
+            edges.focused.as_ref(),
+            // Spans have no workspace.
+            None,
+            edges.inactive.unwrap_or(false),
+        ),
+        None => Sessionizer::default(),
+    }
+    .with_max_gap(max_gap)
+    .with_untracked(true)
+    .with_away(true);
+    let mut sessions = vec![];
+    let mut backfilled = vec![];
+
+    let content = dayfile::read_to_string(path, key)?;
+
+    for (line, _) in heads(path, &content) {
+        let Some(event) = parse(path, line) else {
+            continue;
+        };
+
+        if event.timestamp >= to {
+            break;
+        }
+
+        match &event.event {
+            Event::Backfilled(b) => backfilled.push(Span {
+                app: b.app.clone(),
+                title: b.title.clone(),
+                start: event.timestamp,
+                end: b.end,
+            }),
+            _ => sessions.extend(sessionizer.push(&event)),
+        }
+    }
+
+    // Spans and away periods going on at the end of the day end where the
+    // next event ends them.
+    if let Some(event) = next.filter(|event| event.timestamp < to) {
+        sessions.extend(sessionizer.push(&event));
+    }
+
+    let activity = finish(sessions, &sessionizer, window.start, window.end);
+
+    Ok(Day {
+        activity,
+        backfilled,
+    })
+}
+
+/// Lines of `content` with their heads, damaged lines are skipped like
+/// readers do with `OnDecodeError::Skip`.
+fn heads<'a>(
+    path: &'a Path,
+    content: &'a str,
+) -> impl DoubleEndedIterator<Item = (&'a str, EventHead)> {
+    content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(move |line| match EventHead::parse(line) {
+            Ok(head) => Some((line, head)),
+            Err(_) => {
+                damaged(path);
+                None
+            }
+        })
+}
+
+fn parse(path: &Path, line: &str) -> Option<TimedEvent> {
+    serde_json::from_str(line)
+        .inspect_err(|_| damaged(path))
+        .ok()
+}
+
+fn damaged(path: &Path) {
+    log::warn!("Skipping a damaged line of {:?}", path);
+}
+
+/// Join the days, spans, away periods and gaps cut at midnight are put
+/// back together.
+fn merge(days: Vec<Day>, from: DateTime<Utc>, to: DateTime<Utc>) -> Activity {
+    let mut merged = Activity::default();
+    let mut backfilled = vec![];
+
+    let continues = |a: &Span, b: &Span| {
+        a.end == b.start && a.app == b.app && a.title == b.title
+    };
+
+    for day in days {
+        let Activity {
+            mut spans,
+            mut current,
+            away,
+            untracked,
+        } = day.activity;
+
+        // Only the first span of a day can continue the day before.
+        let first = match spans.first_mut() {
+            Some(span) => Some(span),
+            None => current.as_mut(),
+        };
+        if let Some(first) = first
+            && let Some(last) = merged.spans.last()
+            && continues(last, first)
+        {
+            first.start = merged.spans.pop().expect("last span").start;
+        }
+
+        merged.spans.extend(spans);
+        merged.current = current;
+
+        join(&mut merged.away, away);
+        join(&mut merged.untracked, untracked);
+        backfilled.extend(day.backfilled);
+    }
+
+    merged
+        .spans
+        .extend(backfilled.into_iter().filter_map(|mut span| {
+            span.start = span.start.max(from);
+            span.end = span.end.min(to);
+            (span.end > span.start).then_some(span)
+        }));
+
+    merged
+}
+
+/// Add the `periods` of a day to the ones of the days before, the first
+/// one continues the last one when it starts where that ends.
+fn join(
+    merged: &mut Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    periods: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+) {
+    let mut periods = periods.into_iter();
+    if let Some((start, end)) = periods.next() {
+        match merged.last_mut() {
+            Some(last) if last.1 == start => last.1 = end,
+            _ => merged.push((start, end)),
+        }
+    }
+    merged.extend(periods);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::{MAX_EVENT_GAP, read_activity};
+    use crate::events::{Backfilled, Focused};
+    use chrono::{TimeDelta, TimeZone};
+
+    fn focused(id: &str) -> Event {
+        Event::Focused(Box::new(Focused {
+            title: format!("{} title", id),
+            id: id.to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }))
+    }
+
+    #[tokio::test]
+    async fn read_activity_parallel_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = |d, h, m| Utc.with_ymd_and_hms(2026, 1, d, h, m, 0).unwrap();
+
+        let days = [
+            // Focus and idle going on over midnight.
+            vec![(at(1, 22, 0), focused("a")), (at(1, 23, 58), Event::Alive)],
+            vec![
+                (at(2, 0, 1), Event::Alive),
+                (at(2, 0, 2), Event::Idle),
+                (at(2, 23, 59), Event::Alive),
+            ],
+            // Backfilled over midnight and the daemon restarting late.
+            vec![
+                (
+                    at(3, 0, 1),
+                    Event::Backfilled(Box::new(Backfilled {
+                        app: "meeting".into(),
+                        title: "standup".into(),
+                        source: "calendar".into(),
+                        end: at(3, 0, 30),
+                    })),
+                ),
+                (at(3, 8, 0), Event::Active),
+                (at(3, 8, 1), focused("b")),
+                (at(3, 23, 59), Event::Alive),
+            ],
+            vec![(at(4, 0, 1), focused("a")), (at(4, 0, 2), Event::Sleep)],
+        ];
+
+        for events in &days {
+            let lines: Vec<String> = events
+                .iter()
+                .map(|(timestamp, event)| {
+                    serde_json::to_string(&TimedEvent {
+                        timestamp: *timestamp,
+                        event: event.clone(),
+                    })
+                    .unwrap()
+                })
+                .collect();
+            let name = events[0].0.format("%Y%m%d.log").to_string();
+            std::fs::write(dir.path().join(name), lines.join("\n") + "\n")
+                .unwrap();
+        }
+
+        let ranges = [
+            (at(1, 0, 0), at(5, 0, 0)),
+            (at(1, 23, 0), at(3, 8, 30)),
+            (at(2, 12, 0), at(4, 0, 2)),
+        ];
+
+        for (from, to) in ranges {
+            let dir = dir.path().to_path_buf();
+            let from = from.fixed_offset();
+            let mut expected =
+                read_activity(dir.clone(), from, to, MAX_EVENT_GAP)
+                    .await
+                    .unwrap();
+            let threads = NonZeroUsize::new(2).unwrap();
+            let mut activity =
+                read_activity_parallel(dir, from, to, MAX_EVENT_GAP, threads)
+                    .await
+                    .unwrap();
+
+            expected.spans.sort_by_key(|span| span.start);
+            activity.spans.sort_by_key(|span| span.start);
+
+            assert_eq!(activity.spans, expected.spans);
+            assert_eq!(activity.current, expected.current);
+            assert_eq!(activity.away, expected.away);
+            assert_eq!(activity.untracked, expected.untracked);
+            assert!(activity.total() > TimeDelta::zero());
+        }
+    }
+}
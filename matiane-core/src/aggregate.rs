@@ -0,0 +1,551 @@
+//! Totals of focus sessions grouped by app, title, detail, category,
+//! workspace, day, weekday or hour of the day.
+//!
+//! Sessions are added one at a time and only the totals are kept, so long
+//! ranges are aggregated while they are read. Gaps nothing was tracked in
+//! are a group of their own whatever the grouping.
+
+use crate::activity::day_of;
+#[cfg(feature = "async")]
+use crate::activity::summarized_spans;
+use crate::categories::Categories;
+use crate::sessions::FocusSession;
+#[cfg(feature = "async")]
+use crate::sessions::Sessionizer;
+#[cfg(feature = "async")]
+use crate::store::{
+    DayZone, EventReader, OnDecodeError, StorePath, StoreReadError,
+};
+#[cfg(feature = "async")]
+use anyhow::Result;
+use chrono::{
+    DateTime, Datelike, NaiveDate, TimeDelta, TimeZone, Timelike, Utc, Weekday,
+};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    App,
+    Title,
+    /// App and the detail of its windows, e.g. the site of a browser tab.
+    Detail,
+    /// Category of the rules, sessions no rule matches are uncategorized.
+    Category,
+    /// Workspace the windows were on, see `Event::WorkspaceFocused`.
+    Workspace,
+    Day,
+    Weekday,
+    /// Hour of the day, 0 to 23.
+    Hour,
+}
+
+impl GroupBy {
+    pub const ALL: [GroupBy; 8] = [
+        GroupBy::App,
+        GroupBy::Title,
+        GroupBy::Detail,
+        GroupBy::Category,
+        GroupBy::Workspace,
+        GroupBy::Day,
+        GroupBy::Weekday,
+        GroupBy::Hour,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GroupBy::App => "app",
+            GroupBy::Title => "title",
+            GroupBy::Detail => "detail",
+            GroupBy::Category => "category",
+            GroupBy::Workspace => "workspace",
+            GroupBy::Day => "day",
+            GroupBy::Weekday => "weekday",
+            GroupBy::Hour => "hour",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|by| by.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case", tag = "by", content = "key")]
+pub enum Group {
+    App(String),
+    Title(String),
+    Detail {
+        app: String,
+        detail: Option<String>,
+    },
+    Category(Option<String>),
+    Workspace(Option<String>),
+    Day(NaiveDate),
+    Weekday(Weekday),
+    Hour(u32),
+    /// Time nothing was tracked in, see [`FocusSession::is_untracked`].
+    Untracked,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Total {
+    pub group: Group,
+    #[serde(serialize_with = "serialize_seconds")]
+    pub duration: TimeDelta,
+    /// Sessions with time in the group, a session over several days or
+    /// hours counts in each of them.
+    pub sessions: usize,
+}
+
+fn serialize_seconds<S: serde::Serializer>(
+    duration: &TimeDelta,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(duration.num_seconds())
+}
+
+/// Adds up sessions into totals per group. Days, weekdays and hours are
+/// the ones of `tz`, sessions over their boundaries are split.
+pub struct Aggregator<Tz: TimeZone> {
+    by: GroupBy,
+    tz: Tz,
+    categories: Categories,
+    totals: HashMap<Group, (TimeDelta, usize)>,
+}
+
+impl<Tz: TimeZone> Aggregator<Tz> {
+    pub fn new(by: GroupBy, tz: Tz) -> Self {
+        Aggregator {
+            by,
+            tz,
+            categories: Categories::default(),
+            totals: HashMap::new(),
+        }
+    }
+
+    /// Rules for grouping by category, without them every session is
+    /// uncategorized.
+    pub fn with_categories(mut self, categories: Categories) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    pub fn push(&mut self, session: &FocusSession) {
+        if session.is_untracked() {
+            self.add(Group::Untracked, session.duration());
+            return;
+        }
+
+        match self.by {
+            GroupBy::App => {
+                self.add(Group::App(session.app.clone()), session.duration())
+            }
+            GroupBy::Title => self
+                .add(Group::Title(session.title.clone()), session.duration()),
+            GroupBy::Detail => {
+                let group = Group::Detail {
+                    app: session.app.clone(),
+                    detail: session.detail.clone(),
+                };
+                self.add(group, session.duration());
+            }
+            GroupBy::Category => {
+                let category = self
+                    .categories
+                    .categorize(&session.app, &session.title, None)
+                    .map(str::to_string);
+                self.add(Group::Category(category), session.duration());
+            }
+            GroupBy::Workspace => {
+                let group = Group::Workspace(session.workspace.clone());
+                self.add(group, session.duration());
+            }
+            GroupBy::Day | GroupBy::Weekday | GroupBy::Hour => {
+                let mut start = session.start;
+
+                while start < session.end {
+                    let (group, next) = self.period_of(start);
+                    let end = session.end.min(next);
+
+                    self.add(group, end - start);
+                    start = end;
+                }
+            }
+        }
+    }
+
+    /// Totals by time for apps, titles, details, categories and workspaces,
+    /// longest first, and in order for days, weekdays and hours. Untracked
+    /// time is last.
+    pub fn finish(self) -> Vec<Total> {
+        let mut totals: Vec<Total> = self
+            .totals
+            .into_iter()
+            .map(|(group, (duration, sessions))| Total {
+                group,
+                duration,
+                sessions,
+            })
+            .collect();
+
+        totals.sort_by(|a, b| {
+            let longest = b.duration.cmp(&a.duration);
+
+            match (&a.group, &b.group) {
+                (Group::App(x), Group::App(y))
+                | (Group::Title(x), Group::Title(y)) => longest.then(x.cmp(y)),
+                (
+                    Group::Detail { app, detail },
+                    Group::Detail {
+                        app: other_app,
+                        detail: other_detail,
+                    },
+                ) => longest
+                    .then(app.cmp(other_app))
+                    .then(detail.cmp(other_detail)),
+                (Group::Category(x), Group::Category(y))
+                | (Group::Workspace(x), Group::Workspace(y)) => {
+                    longest.then(x.cmp(y))
+                }
+                (Group::Day(x), Group::Day(y)) => x.cmp(y),
+                (Group::Weekday(x), Group::Weekday(y)) => {
+                    x.num_days_from_monday().cmp(&y.num_days_from_monday())
+                }
+                (Group::Hour(x), Group::Hour(y)) => x.cmp(y),
+                (Group::Untracked, _) => Ordering::Greater,
+                (_, Group::Untracked) => Ordering::Less,
+                _ => longest,
+            }
+        });
+
+        totals
+    }
+
+    fn add(&mut self, group: Group, duration: TimeDelta) {
+        if duration <= TimeDelta::zero() {
+            return;
+        }
+
+        let total = self.totals.entry(group).or_default();
+        total.0 += duration;
+        total.1 += 1;
+    }
+
+    /// Group of the time `at` and when its day or hour ends.
+    fn period_of(&self, at: DateTime<Utc>) -> (Group, DateTime<Utc>) {
+        let (day, next_day) = day_of(at, &self.tz);
+
+        match self.by {
+            GroupBy::Weekday => (Group::Weekday(day.weekday()), next_day),
+            GroupBy::Hour => {
+                let local = at.with_timezone(&self.tz);
+                let hour = local.hour();
+                let into_hour = TimeDelta::minutes(local.minute().into())
+                    + TimeDelta::seconds(local.second().into())
+                    + TimeDelta::nanoseconds(local.nanosecond().into());
+
+                // Hours of offsets that are not whole hours end with them.
+                let next_hour = (at - into_hour + TimeDelta::hours(1))
+                    .min(next_day)
+                    .max(at + TimeDelta::nanoseconds(1));
+
+                (Group::Hour(hour), next_hour)
+            }
+            _ => (Group::Day(day), next_day),
+        }
+    }
+}
+
+/// Totals of `sessions` grouped by `by` in `tz`.
+pub fn aggregate<'a, Tz: TimeZone>(
+    sessions: impl IntoIterator<Item = &'a FocusSession>,
+    by: GroupBy,
+    tz: Tz,
+    categories: Categories,
+) -> Vec<Total> {
+    let mut aggregator = Aggregator::new(by, tz).with_categories(categories);
+
+    for session in sessions {
+        aggregator.push(session);
+    }
+
+    aggregator.finish()
+}
+
+#[cfg(feature = "async")]
+/// Read the sessions in `[from, to)` of `store` into `aggregator`, one
+/// event at a time. Events further apart than `max_gap`
+/// add the time between them as untracked, downsampled days add the spans
+/// of their apps.
+pub async fn aggregate_store<Tz: TimeZone>(
+    store: impl Into<StorePath>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    max_gap: TimeDelta,
+    mut aggregator: Aggregator<Tz>,
+) -> Result<Vec<Total>> {
+    let store = store.into();
+    let dir = store.dir().to_path_buf();
+    let zone = DayZone::load(&dir).await?;
+    let opened = EventReader::open_indexed(store, &from.fixed_offset()).await;
+    let (reader, entry) = match opened {
+        Ok(opened) => opened,
+        Err(StoreReadError::NoFilesToOpen) => {
+            for span in summarized_spans(&dir, zone, from, to).await? {
+                aggregator.push(&span.into());
+            }
+            return Ok(aggregator.finish());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut reader = reader.on_decode_error(OnDecodeError::Skip);
+    let mut sessionizer = match entry {
+        Some(e) => Sessionizer::resume(
+            e.previous,
+            e.focused.as_ref(),
+            e.workspace.as_deref(),
+            e.inactive,
+        ),
+        None => Sessionizer::default(),
+    }
+    .with_max_gap(max_gap)
+    .with_untracked(true);
+
+    let mut push = |mut session: FocusSession| {
+        session.start = session.start.max(from);
+        session.end = session.end.min(to);
+        aggregator.push(&session);
+    };
+
+    while let Some(event) = reader.next_event().await? {
+        if event.timestamp >= to {
+            break;
+        }
+
+        sessionizer.push(&event).for_each(&mut push);
+    }
+
+    if let Some(session) = sessionizer.current(Utc::now().min(to)) {
+        push(session);
+    }
+
+    // Still under the read lock of the reader, so no day is downsampled
+    // in between.
+    for span in summarized_spans(&dir, zone, from, to).await? {
+        aggregator.push(&span.into());
+    }
+
+    Ok(aggregator.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::categories::{CategoriesConfig, CategoryRule};
+    use crate::sessions::UNTRACKED_APP;
+    use chrono::FixedOffset;
+
+    fn at(d: u32, h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, d, h, m, 0).unwrap()
+    }
+
+    fn session(
+        app: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> FocusSession {
+        FocusSession {
+            app: app.to_string(),
+            title: format!("{} title", app),
+            detail: None,
+            workspace: None,
+            start,
+            end,
+        }
+    }
+
+    fn sessions() -> Vec<FocusSession> {
+        vec![
+            session("a", at(1, 10, 0), at(1, 10, 30)),
+            session("b", at(1, 23, 30), at(2, 0, 30)),
+            session("a", at(2, 9, 45), at(2, 10, 15)),
+        ]
+    }
+
+    fn totals(by: GroupBy) -> Vec<(Group, i64, usize)> {
+        aggregate(&sessions(), by, Utc, Categories::default())
+            .into_iter()
+            .map(|t| (t.group, t.duration.num_minutes(), t.sessions))
+            .collect()
+    }
+
+    #[test]
+    fn aggregate_by_app_and_category() {
+        assert_eq!(
+            totals(GroupBy::App),
+            [
+                (Group::App("a".to_string()), 60, 2),
+                (Group::App("b".to_string()), 60, 1),
+            ]
+        );
+
+        let config = CategoriesConfig {
+            rules: vec![CategoryRule {
+                category: "Work".to_string(),
+                app_id: Some("a".to_string()),
+                title: None,
+                cmdline: None,
+            }],
+        };
+        let categories = Categories::compile(&config).unwrap();
+        let totals: Vec<_> =
+            aggregate(&sessions()[..2], GroupBy::Category, Utc, categories)
+                .into_iter()
+                .map(|t| (t.group, t.duration.num_minutes()))
+                .collect();
+
+        assert_eq!(
+            totals,
+            [
+                (Group::Category(None), 60),
+                (Group::Category(Some("Work".to_string())), 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregate_by_title_and_workspace() {
+        assert_eq!(
+            totals(GroupBy::Title),
+            [
+                (Group::Title("a title".to_string()), 60, 2),
+                (Group::Title("b title".to_string()), 60, 1),
+            ]
+        );
+
+        let on = |workspace: &str, session| FocusSession {
+            workspace: Some(workspace.to_string()),
+            ..session
+        };
+        let [a, b, c] = sessions().try_into().unwrap();
+        let sessions = [on("1", a), on("2", b), c];
+
+        let totals: Vec<_> = aggregate(
+            &sessions,
+            GroupBy::Workspace,
+            Utc,
+            Categories::default(),
+        )
+        .into_iter()
+        .map(|t| (t.group, t.duration.num_minutes()))
+        .collect();
+        let workspace = |name: &str| Group::Workspace(Some(name.to_string()));
+
+        assert_eq!(
+            totals,
+            [
+                (workspace("2"), 60),
+                (Group::Workspace(None), 30),
+                (workspace("1"), 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregate_untracked() {
+        let mut sessions = sessions();
+        sessions.push(session(UNTRACKED_APP, at(1, 10, 30), at(1, 23, 30)));
+        let day = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+
+        let totals: Vec<_> =
+            aggregate(&sessions, GroupBy::Day, Utc, Categories::default())
+                .into_iter()
+                .map(|t| (t.group, t.duration.num_minutes()))
+                .collect();
+
+        assert_eq!(
+            totals,
+            [
+                (Group::Day(day(1)), 60),
+                (Group::Day(day(2)), 60),
+                (Group::Untracked, 13 * 60),
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregate_by_detail() {
+        let tab = |detail: &str, start, end| FocusSession {
+            detail: Some(detail.to_string()),
+            ..session("firefox", start, end)
+        };
+        let sessions = [
+            tab("GitHub", at(1, 10, 0), at(1, 10, 20)),
+            tab("Jira", at(1, 10, 20), at(1, 10, 30)),
+            tab("GitHub", at(1, 10, 30), at(1, 10, 40)),
+            session("firefox", at(1, 10, 40), at(1, 10, 45)),
+        ];
+
+        let totals: Vec<_> =
+            aggregate(&sessions, GroupBy::Detail, Utc, Categories::default())
+                .into_iter()
+                .map(|t| (t.group, t.duration.num_minutes(), t.sessions))
+                .collect();
+        let detail = |detail: Option<&str>| Group::Detail {
+            app: "firefox".to_string(),
+            detail: detail.map(str::to_string),
+        };
+
+        assert_eq!(
+            totals,
+            [
+                (detail(Some("GitHub")), 30, 2),
+                (detail(Some("Jira")), 10, 1),
+                (detail(None), 5, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregate_by_time() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+
+        assert_eq!(
+            totals(GroupBy::Day),
+            [(Group::Day(day(1)), 60, 2), (Group::Day(day(2)), 60, 2)]
+        );
+        assert_eq!(
+            totals(GroupBy::Weekday),
+            [
+                (Group::Weekday(Weekday::Thu), 60, 2),
+                (Group::Weekday(Weekday::Fri), 60, 2),
+            ]
+        );
+        assert_eq!(
+            totals(GroupBy::Hour),
+            [
+                (Group::Hour(0), 30, 1),
+                (Group::Hour(9), 15, 1),
+                (Group::Hour(10), 45, 2),
+                (Group::Hour(23), 30, 1),
+            ]
+        );
+
+        // Hours of the local time, with an offset of half an hour.
+        let tz = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+        let local: Vec<_> = aggregate(
+            &sessions()[..1],
+            GroupBy::Hour,
+            tz,
+            Categories::default(),
+        )
+        .into_iter()
+        .map(|t| (t.group, t.duration.num_minutes()))
+        .collect();
+
+        assert_eq!(local, [(Group::Hour(15), 30)]);
+    }
+}
@@ -3,7 +3,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 use clap::{
-    Arg, ArgMatches, arg,
+    Arg, ArgMatches, Command, arg,
     builder::{PossibleValuesParser, TypedValueParser},
     command, value_parser,
 };
@@ -14,6 +14,10 @@ use super::xdg::Xdg;
 pub struct GeneralArgs {
     pub config_file: PathBuf,
     pub log_level: LevelFilter,
+    /// Write the default config to `config_file` and exit, overwriting it
+    /// with `force`.
+    pub write_default_config: bool,
+    pub force: bool,
 }
 
 pub fn general_args() -> impl IntoIterator<Item = impl Into<Arg>> {
@@ -29,6 +33,9 @@ pub fn general_args() -> impl IntoIterator<Item = impl Into<Arg>> {
             )
             .ignore_case(true)
             .default_value("INFO"),
+        arg!(--"write-default-config" "Writes a commented config file and exits"),
+        arg!(--force "Overwrites an existing config file")
+            .requires("write-default-config"),
     ]
 }
 
@@ -42,6 +49,8 @@ pub fn match_general_args(xdg: &Xdg, matches: &ArgMatches) -> GeneralArgs {
     GeneralArgs {
         config_file,
         log_level,
+        write_default_config: matches.get_flag("write-default-config"),
+        force: matches.get_flag("force"),
     }
 }
 
@@ -50,9 +59,67 @@ pub fn parse_args(
     name: &'static str,
     args: impl IntoIterator<Item = impl Into<Arg>>,
 ) -> (ArgMatches, GeneralArgs) {
-    let matches = command!(name).args(general_args()).args(args).get_matches();
+    parse_command(xdg, command!(name).args(args))
+}
+
+/// Parse the arguments of `command` and its subcommands, the general
+/// arguments are added to all of them.
+pub fn parse_command(xdg: &Xdg, command: Command) -> (ArgMatches, GeneralArgs) {
+    let matches = with_general_args(command).get_matches();
 
     let general_args = match_general_args(xdg, &matches);
 
     (matches, general_args)
 }
+
+fn with_general_args(command: Command) -> Command {
+    command.args(
+        general_args()
+            .into_iter()
+            .map(|arg| arg.into().global(true)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_command_test() {
+        let command = || {
+            with_general_args(
+                Command::new("matiane")
+                    .subcommand(Command::new("report").arg(arg!(--previous))),
+            )
+        };
+        let xdg = Xdg::default();
+
+        let matches = command()
+            .try_get_matches_from([
+                "matiane",
+                "report",
+                "-c",
+                "/a.toml",
+                "--previous",
+            ])
+            .unwrap();
+        let general = match_general_args(&xdg, &matches);
+
+        assert_eq!(general.config_file, PathBuf::from("/a.toml"));
+        assert_eq!(general.log_level, LevelFilter::Info);
+        assert!(
+            matches
+                .subcommand_matches("report")
+                .unwrap()
+                .get_flag("previous")
+        );
+
+        let matches = command()
+            .try_get_matches_from(["matiane", "-l", "debug", "report"])
+            .unwrap();
+        assert_eq!(
+            match_general_args(&xdg, &matches).log_level,
+            LevelFilter::Debug
+        );
+    }
+}
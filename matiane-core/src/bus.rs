@@ -0,0 +1,124 @@
+//! Events of several sources funneled to a single writer.
+//!
+//! Every source sends through a clone of the `EventSink`, the writer reads
+//! them from the `EventSource` in the order they were sent. Events are
+//! stamped once there is room for them, so times never go back in that
+//! order.
+
+use crate::events::{Event, TimedEvent};
+use chrono::Utc;
+use futures::Stream;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Events waiting for the writer before sources wait for it.
+pub const DEFAULT_CAPACITY: usize = 64;
+
+#[derive(Debug, Error)]
+#[error("The event bus is closed")]
+pub struct BusClosed;
+
+/// Sending half, one clone for every source.
+#[derive(Debug, Clone)]
+pub struct EventSink {
+    tx: mpsc::Sender<TimedEvent>,
+}
+
+impl EventSink {
+    /// Send `event` at the current time. Waits while the bus is full.
+    pub async fn send(&self, event: Event) -> Result<(), BusClosed> {
+        let permit = self.tx.reserve().await.map_err(|_| BusClosed)?;
+        permit.send(TimedEvent {
+            timestamp: Utc::now(),
+            event,
+        });
+
+        Ok(())
+    }
+
+    /// Send an event that has a time already, like one read back from a
+    /// file. The writer expects times in order.
+    pub async fn send_timed(&self, event: TimedEvent) -> Result<(), BusClosed> {
+        self.tx.send(event).await.map_err(|_| BusClosed)
+    }
+
+    /// Whether the `EventSource` was dropped.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+/// Receiving half, read by the writer.
+#[derive(Debug)]
+pub struct EventSource {
+    rx: mpsc::Receiver<TimedEvent>,
+}
+
+impl EventSource {
+    /// Next event, none once every sink was dropped.
+    pub async fn recv(&mut self) -> Option<TimedEvent> {
+        self.rx.recv().await
+    }
+
+    /// An event that was sent already, without waiting for one.
+    pub fn try_recv(&mut self) -> Option<TimedEvent> {
+        self.rx.try_recv().ok()
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = TimedEvent> {
+        ReceiverStream::new(self.rx)
+    }
+}
+
+/// A bus of `capacity` events.
+pub fn channel(capacity: usize) -> (EventSink, EventSource) {
+    let (tx, rx) = mpsc::channel(capacity);
+
+    (EventSink { tx }, EventSource { rx })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn bus_test() {
+        let (sink, mut source) = channel(1);
+
+        let producers: Vec<_> = (0..3)
+            .map(|_| {
+                let sink = sink.clone();
+                tokio::spawn(async move {
+                    for _ in 0..10 {
+                        sink.send(Event::Alive).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(sink);
+
+        let mut events = vec![];
+        while let Some(event) = source.recv().await {
+            events.push(event);
+        }
+        for producer in producers {
+            producer.await.unwrap();
+        }
+
+        assert_eq!(events.len(), 30);
+        assert!(
+            events
+                .windows(2)
+                .all(|pair| pair[0].timestamp <= pair[1].timestamp)
+        );
+
+        let (sink, mut source) = channel(DEFAULT_CAPACITY);
+        assert!(source.try_recv().is_none());
+        sink.send(Event::Idle).await.unwrap();
+        assert!(matches!(source.try_recv().unwrap().event, Event::Idle));
+        drop(source);
+        assert!(sink.is_closed());
+        assert!(sink.send(Event::Alive).await.is_err());
+    }
+}
@@ -0,0 +1,244 @@
+//! Categories of activity from user defined rules, e.g.
+//!
+//! ```toml
+//! [[categories.rules]]
+//! category = "Coding"
+//! app-id = "code"
+//! title = "matiane|sway"
+//!
+//! [[categories.rules]]
+//! category = "Chat"
+//! cmdline = "^/usr/lib/slack/"
+//! ```
+//!
+//! The first rule an event matches picks its category.
+
+use crate::events::{Event, Process};
+use matiane_regex::{Regex, RegexCompileError};
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, PartialEq, Deserialize, Clone, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CategoriesConfig {
+    #[serde(default)]
+    pub rules: Vec<CategoryRule>,
+}
+
+/// Every condition that is set must match, a rule without any matches
+/// everything.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CategoryRule {
+    pub category: String,
+    /// App id of the window, matched exactly.
+    pub app_id: Option<String>,
+    /// Regex the title must match.
+    pub title: Option<String>,
+    /// Regex the command line of the process must match, its arguments
+    /// joined by spaces. Only events with process details can match.
+    pub cmdline: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum CategoryError {
+    #[error(
+        "Invalid {field} regex `{regex}` of category `{category}`: {source}"
+    )]
+    InvalidRegex {
+        category: String,
+        field: &'static str,
+        regex: String,
+        #[source]
+        source: RegexCompileError,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    category: String,
+    app_id: Option<String>,
+    title: Option<Regex>,
+    cmdline: Option<Regex>,
+}
+
+impl Rule {
+    fn compile(rule: &CategoryRule) -> Result<Self, CategoryError> {
+        let regex = |field, raw: &Option<String>| {
+            raw.as_deref()
+                .map(|raw| {
+                    Regex::compile(raw).map_err(|source| {
+                        CategoryError::InvalidRegex {
+                            category: rule.category.clone(),
+                            field,
+                            regex: raw.to_string(),
+                            source,
+                        }
+                    })
+                })
+                .transpose()
+        };
+
+        Ok(Rule {
+            category: rule.category.clone(),
+            app_id: rule.app_id.clone(),
+            title: regex("title", &rule.title)?,
+            cmdline: regex("cmdline", &rule.cmdline)?,
+        })
+    }
+
+    fn matches(
+        &self,
+        app_id: &str,
+        title: &str,
+        cmdline: Option<&str>,
+    ) -> bool {
+        self.app_id.as_ref().is_none_or(|id| id == app_id)
+            && self.title.as_ref().is_none_or(|re| re.is_match(title))
+            && self
+                .cmdline
+                .as_ref()
+                .is_none_or(|re| cmdline.is_some_and(|c| re.is_match(c)))
+    }
+}
+
+/// Compiled rules, for categorizing many events.
+#[derive(Debug, Clone, Default)]
+pub struct Categories {
+    rules: Vec<Rule>,
+}
+
+impl Categories {
+    pub fn compile(config: &CategoriesConfig) -> Result<Self, CategoryError> {
+        let rules = config
+            .rules
+            .iter()
+            .map(Rule::compile)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Categories { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Category of a window of `app_id` titled `title`, none when no rule
+    /// matches.
+    pub fn categorize(
+        &self,
+        app_id: &str,
+        title: &str,
+        process: Option<&Process>,
+    ) -> Option<&str> {
+        let cmdline = process
+            .filter(|process| !process.cmdline.is_empty())
+            .map(|process| process.cmdline.join(" "));
+
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(app_id, title, cmdline.as_deref()))
+            .map(|rule| rule.category.as_str())
+    }
+
+    /// Category of focused and backfilled events, none for the others.
+    pub fn categorize_event(&self, event: &Event) -> Option<&str> {
+        match event {
+            Event::Focused(focused) => self.categorize(
+                &focused.id,
+                &focused.title,
+                focused.process.as_ref(),
+            ),
+            Event::Backfilled(backfilled) => {
+                self.categorize(&backfilled.app, &backfilled.title, None)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Focused;
+
+    fn config() -> CategoriesConfig {
+        toml::from_str(
+            r#"
+            [[rules]]
+            category = "Coding"
+            app-id = "code"
+            title = "matiane|sway"
+
+            [[rules]]
+            category = "Chat"
+            cmdline = "^/usr/lib/slack/"
+
+            [[rules]]
+            category = "Browsing"
+            app-id = "firefox"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn categorize_test() {
+        let categories = Categories::compile(&config()).unwrap();
+        let slack = Process {
+            exe: Some("/usr/lib/electron/electron".to_string()),
+            cmdline: vec!["/usr/lib/slack/slack".into(), "--enable".into()],
+            cgroup: None,
+        };
+
+        assert_eq!(
+            categories.categorize("code", "main.rs - matiane", None),
+            Some("Coding")
+        );
+        assert_eq!(categories.categorize("code", "notes", None), None);
+        assert_eq!(
+            categories.categorize("electron", "Slack", Some(&slack)),
+            Some("Chat")
+        );
+        assert_eq!(categories.categorize("electron", "Slack", None), None);
+        assert_eq!(
+            categories.categorize("firefox", "anything", None),
+            Some("Browsing")
+        );
+
+        let focused = Event::Focused(Box::new(Focused {
+            title: "sway - code".to_string(),
+            id: "code".to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }));
+        assert_eq!(categories.categorize_event(&focused), Some("Coding"));
+        assert_eq!(categories.categorize_event(&Event::Alive), None);
+    }
+
+    #[test]
+    fn compile_error_test() {
+        let config = CategoriesConfig {
+            rules: vec![CategoryRule {
+                category: "Broken".to_string(),
+                app_id: None,
+                title: Some("(a".to_string()),
+                cmdline: None,
+            }],
+        };
+
+        let error = Categories::compile(&config).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Invalid title regex `(a` of category `Broken`")
+        );
+
+        let unknown = toml::from_str::<CategoriesConfig>(
+            "[[rules]]\ncategory = \"a\"\napp = \"b\"\n",
+        );
+        assert!(unknown.is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Reading the activity without knowing the store layout, for tools built
+//! on matiane.
+
+use crate::activity::{self, Activity, Span};
+use crate::config::{self, GeneralConfig, SwayConfig};
+use crate::store::StorePath;
+use crate::store::crypt::StoreKey;
+use crate::xdg;
+use anyhow::Result;
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use serde::Deserialize;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Same app spans less than this apart are one session.
+pub const SESSION_GAP: TimeDelta = TimeDelta::minutes(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    App,
+    Title,
+    /// Local day, formatted as `YYYY-MM-DD`.
+    Day,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ClientConfig {
+    #[serde(default)]
+    general: GeneralConfig,
+    #[serde(default)]
+    sway: SwayConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct Matiane {
+    store: StorePath,
+    threads: NonZeroUsize,
+    max_gap: TimeDelta,
+}
+
+impl Matiane {
+    pub fn new(state_dir: impl Into<PathBuf>) -> Self {
+        GeneralConfig {
+            state_dir: state_dir.into(),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    /// Store of the config at `path`.
+    pub fn from_config(path: impl AsRef<Path>) -> Result<Self> {
+        let cfg = config::load::<ClientConfig>(path)?;
+        let store = cfg.general.store()?;
+        let max_gap = cfg.sway.max_gap();
+
+        Ok(Matiane {
+            store,
+            max_gap,
+            ..cfg.general.into()
+        })
+    }
+
+    /// Store of the default config file.
+    pub fn from_default_config() -> Result<Self> {
+        Self::from_config(
+            xdg::config_dir(Some(crate::NAME)).join("config.toml"),
+        )
+    }
+
+    /// Day files read at once, all cores by default.
+    pub fn with_threads(mut self, threads: NonZeroUsize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Key of the encrypted day files of the store.
+    pub fn with_key(mut self, key: Option<StoreKey>) -> Self {
+        self.store = self.store.with_key(key);
+        self
+    }
+
+    pub fn state_dir(&self) -> &Path {
+        self.store.dir()
+    }
+
+    pub async fn activity(
+        &self,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Activity> {
+        activity::read_activity_parallel(
+            self.store.clone(),
+            range.start.fixed_offset(),
+            range.end,
+            self.max_gap,
+            self.threads,
+        )
+        .await
+    }
+
+    /// Activity since local midnight.
+    pub async fn today(&self) -> Result<Activity> {
+        activity::read_activity(
+            self.store.clone(),
+            activity::start_of_today(),
+            Utc::now(),
+            self.max_gap,
+        )
+        .await
+    }
+
+    /// Time spent in one app without longer breaks, sorted by start.
+    pub async fn sessions(
+        &self,
+        range: Range<DateTime<Utc>>,
+    ) -> Result<Vec<Span>> {
+        Ok(self.activity(range).await?.blocks(SESSION_GAP))
+    }
+
+    /// Totals per group, longest first; days are in order.
+    pub async fn summary(
+        &self,
+        range: Range<DateTime<Utc>>,
+        group_by: GroupBy,
+    ) -> Result<Vec<(String, TimeDelta)>> {
+        let activity = self.activity(range).await?;
+
+        Ok(summarize(&activity, group_by))
+    }
+}
+
+impl From<GeneralConfig> for Matiane {
+    fn from(general: GeneralConfig) -> Self {
+        Matiane {
+            threads: general.threads(),
+            store: StorePath::new(general.state_dir),
+            max_gap: SwayConfig::default().max_gap(),
+        }
+    }
+}
+
+fn summarize(
+    activity: &Activity,
+    group_by: GroupBy,
+) -> Vec<(String, TimeDelta)> {
+    match group_by {
+        GroupBy::App => activity.totals_by(|span| &span.app),
+        GroupBy::Title => activity.totals_by(|span| &span.title),
+        GroupBy::Day => activity
+            .totals_by_day(&Local)
+            .into_iter()
+            .map(|(day, total)| (day.to_string(), total))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Event, Focused, TimedEvent};
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn client_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 1, 1, h, m, 0).unwrap();
+        let line = |h, m, event| {
+            serde_json::to_string(&TimedEvent {
+                timestamp: at(h, m),
+                event,
+            })
+            .unwrap()
+        };
+        let focused = |id: &str| {
+            Event::Focused(Box::new(Focused {
+                title: format!("{} title", id),
+                id: id.to_string(),
+                pid: 1,
+                process: None,
+                raw_title: None,
+                detail: None,
+            }))
+        };
+        let lines = [
+            line(10, 0, focused("a")),
+            line(10, 1, focused("b")),
+            line(10, 2, focused("a")),
+            line(10, 3, focused("a")),
+            line(10, 4, Event::Sleep),
+        ];
+        std::fs::write(dir.path().join("20260101.log"), lines.join("\n"))
+            .unwrap();
+
+        let matiane = Matiane::new(dir.path());
+        let range = at(0, 0)..at(23, 0);
+
+        let sessions = matiane.sessions(range.clone()).await.unwrap();
+        let apps: Vec<_> = sessions.iter().map(|s| s.app.as_str()).collect();
+        assert_eq!(apps, ["a", "b", "a"]);
+
+        let summary = matiane.summary(range, GroupBy::App).await.unwrap();
+        assert_eq!(
+            summary,
+            vec![
+                ("a".to_string(), TimeDelta::minutes(3)),
+                ("b".to_string(), TimeDelta::minutes(1)),
+            ]
+        );
+    }
+}
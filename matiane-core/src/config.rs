@@ -1,41 +1,908 @@
+use crate::log::{LogFile, LogOptions};
+use crate::sessions;
+use crate::store::DayZone;
+#[cfg(feature = "async")]
+use crate::store::Retention;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use crate::store::StorePath;
+#[cfg(feature = "async")]
+use crate::store::acquire_lock_file;
+use crate::store::crypt::StoreKey;
 use crate::xdg;
-use anyhow::Context;
-use serde::Deserialize;
-use std::path::PathBuf;
+use anyhow::{Context, anyhow, bail};
+use chrono::TimeDelta;
+use log::LevelFilter;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use toml::{Table, Value};
 
+#[cfg(feature = "async")]
+mod watch;
+
+#[cfg(feature = "async")]
+pub use watch::watch;
+
+/// Prefix of the environment variables that override the config file.
+pub const ENV_PREFIX: &str = "MATIANE_";
+
+/// Sections of the config file, matiane and sway-matiane share it.
+const SECTIONS: [&str; 12] = [
+    "categories",
+    "general",
+    "gui",
+    "journal",
+    "log",
+    "otlp",
+    "report",
+    "serve",
+    "sink",
+    "sway",
+    "sync",
+    "webhooks",
+];
+
+/// Commented config with every setting, for `--write-default-config`.
+pub const DEFAULT_CONFIG: &str = include_str!("config/default.toml");
+
+/// The store is kept in the XDG state dir, stores of older versions in the
+/// data dir are used until they are moved.
 fn default_state_dir() -> PathBuf {
-    xdg::data_dir(Some(crate::NAME))
+    let state_dir = xdg::state_dir(Some(crate::NAME));
+    let legacy = xdg::data_dir(Some(crate::NAME));
+
+    if !state_dir.exists() && legacy.exists() {
+        legacy
+    } else {
+        state_dir
+    }
+}
+
+#[cfg(feature = "async")]
+/// Move a store of an older version from the XDG data dir to the state
+/// dir, unless `config_file` sets `state-dir`, the store is in use or the
+/// state dir exists. Returns where it was moved to.
+pub async fn migrate_state_dir(
+    config_file: &Path,
+) -> anyhow::Result<Option<PathBuf>> {
+    let table = load::<Table>(config_file)?;
+    let configured = table
+        .get("general")
+        .and_then(|general| general.get("state-dir"))
+        .is_some();
+
+    if configured {
+        return Ok(None);
+    }
+
+    migrate_store(
+        xdg::data_dir(Some(crate::NAME)),
+        xdg::state_dir(Some(crate::NAME)),
+    )
+    .await
+}
+
+#[cfg(feature = "async")]
+async fn migrate_store(
+    legacy: PathBuf,
+    state_dir: PathBuf,
+) -> anyhow::Result<Option<PathBuf>> {
+    if state_dir.exists() || !legacy.exists() {
+        return Ok(None);
+    }
+
+    let _lock = acquire_lock_file(legacy.clone())
+        .await
+        .with_context(|| format!("{} is in use", legacy.display()))?;
+
+    if let Some(parent) = state_dir.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    tokio::fs::rename(&legacy, &state_dir)
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to move {} to {}",
+                legacy.display(),
+                state_dir.display()
+            )
+        })?;
+
+    Ok(Some(state_dir))
 }
 
 #[derive(Debug, PartialEq, Deserialize, Clone)]
-#[serde(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct GeneralConfig {
     #[serde(default = "default_state_dir")]
     pub state_dir: PathBuf,
+    /// Day files read at once for longer ranges, all cores when unset.
+    pub threads: Option<NonZeroUsize>,
+    /// Days the store keeps, forever when unset.
+    pub retention_days: Option<NonZeroU32>,
+    /// Day files past the retention are moved here instead of removed.
+    pub archive_dir: Option<PathBuf>,
+    /// Key new day files are encrypted with, and encrypted ones read with.
+    pub key_file: Option<PathBuf>,
+    /// Zone the day files start at midnight of, saved in the store by the
+    /// daemon.
+    #[serde(default)]
+    pub day_zone: DayZone,
+}
+
+impl GeneralConfig {
+    pub fn threads(&self) -> NonZeroUsize {
+        self.threads.unwrap_or_else(|| {
+            std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN)
+        })
+    }
+
+    #[cfg(feature = "async")]
+    pub fn retention(&self) -> Option<Retention> {
+        Some(Retention {
+            days: self.retention_days?,
+            archive_dir: self.archive_dir.clone(),
+        })
+    }
+
+    /// Key of `key-file`, none without one.
+    pub fn load_key(&self) -> anyhow::Result<Option<StoreKey>> {
+        let Some(path) = &self.key_file else {
+            return Ok(None);
+        };
+
+        let key = StoreKey::load(path).with_context(|| {
+            format!("Failed to load the key {}", path.display())
+        })?;
+
+        Ok(Some(key))
+    }
+
+    /// The store of `state-dir`, with the key of `key-file`.
+    #[cfg(any(feature = "async", feature = "blocking"))]
+    pub fn store(&self) -> anyhow::Result<StorePath> {
+        Ok(StorePath::new(self.state_dir.clone()).with_key(self.load_key()?))
+    }
 }
 
 impl Default for GeneralConfig {
     fn default() -> Self {
         GeneralConfig {
             state_dir: default_state_dir(),
+            threads: None,
+            retention_days: None,
+            archive_dir: None,
+            key_file: None,
+            day_zone: DayZone::Utc,
+        }
+    }
+}
+
+fn default_live_interval() -> u64 {
+    60
+}
+
+/// The settings of `[sway]` readers of the store need, the others are only
+/// for the daemon.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct SwayConfig {
+    /// Seconds between the heartbeats of the daemon.
+    #[serde(default = "default_live_interval")]
+    pub live_interval: u64,
+}
+
+impl SwayConfig {
+    /// Gap between events nothing was tracked in, see
+    /// [`sessions::max_gap`].
+    pub fn max_gap(&self) -> TimeDelta {
+        sessions::max_gap(Duration::from_secs(self.live_interval))
+    }
+}
+
+impl Default for SwayConfig {
+    fn default() -> Self {
+        SwayConfig {
+            live_interval: default_live_interval(),
+        }
+    }
+}
+
+fn default_log_max_size() -> u64 {
+    10
+}
+
+fn default_log_keep() -> usize {
+    3
+}
+
+#[derive(Debug, PartialEq, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogBackend {
+    #[default]
+    Stderr,
+    /// The native journald protocol, with priorities and fields.
+    Journald,
+}
+
+/// Logging of one of the binaries.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ProgramLogConfig {
+    #[serde(default)]
+    pub backend: LogBackend,
+    /// Also log to `<state-dir>/log/<binary>.log`.
+    #[serde(default)]
+    pub file: bool,
+    /// MiB the file grows to before it is rotated.
+    #[serde(default = "default_log_max_size")]
+    pub max_size: u64,
+    /// Rotated files kept besides the current one.
+    #[serde(default = "default_log_keep")]
+    pub keep: usize,
+}
+
+impl Default for ProgramLogConfig {
+    fn default() -> Self {
+        ProgramLogConfig {
+            backend: LogBackend::default(),
+            file: false,
+            max_size: default_log_max_size(),
+            keep: default_log_keep(),
         }
     }
 }
 
+impl ProgramLogConfig {
+    fn options(&self, state_dir: &Path, name: &str) -> LogOptions {
+        let file = self.file.then(|| LogFile {
+            path: state_dir.join("log").join(format!("{}.log", name)),
+            max_size: self.max_size * 1024 * 1024,
+            keep: self.keep,
+        });
+        let journald =
+            (self.backend == LogBackend::Journald).then(|| name.to_string());
+
+        LogOptions {
+            file,
+            journald,
+            ..Default::default()
+        }
+    }
+}
+
+fn deserialize_filters<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, LevelFilter>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(module, level)| {
+            let level = LevelFilter::from_str(&level).map_err(|_| {
+                D::Error::custom(format!(
+                    "unknown log level `{}` of `{}`",
+                    level, module
+                ))
+            })?;
+
+            Ok((module, level))
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Deserialize, Clone, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LogConfig {
+    #[serde(default)]
+    pub sway_matiane: ProgramLogConfig,
+    #[serde(default)]
+    pub matiane: ProgramLogConfig,
+    /// Module path to the level of its logs, instead of `--level`.
+    #[serde(default, deserialize_with = "deserialize_filters")]
+    pub filters: BTreeMap<String, LevelFilter>,
+}
+
+impl LogConfig {
+    /// Log options of the binary `name`.
+    pub fn options(&self, state_dir: &Path, name: &str) -> LogOptions {
+        let program = match name {
+            "sway-matiane" => &self.sway_matiane,
+            _ => &self.matiane,
+        };
+
+        LogOptions {
+            filters: self.filters.clone().into_iter().collect(),
+            ..program.options(state_dir, name)
+        }
+    }
+}
+
+/// Load the config file, missing files are an empty config.
+///
+/// `include = ["work.toml"]` loads other files first, relative to the file
+/// including them, and the keys of the file override theirs. Sections under
+/// `[host."name"]` override the rest on the machine with that hostname.
+///
+/// `MATIANE_*` environment variables override keys of the file: the first
+/// word after the prefix is the section, the rest the key, and `__` nests
+/// deeper. `MATIANE_GENERAL_STATE_DIR` sets `state-dir` of `[general]`,
+/// `MATIANE_SYNC_TOGGL__API_TOKEN` sets `api-token` of `[sync.toggl]`.
+/// Values are read as TOML, falling back to a string, so `60` is a number
+/// and `'"60"'` a string.
+///
+/// Command line arguments take precedence over the environment, which takes
+/// precedence over the file, which takes precedence over the defaults.
 pub fn load<T>(path: impl AsRef<std::path::Path>) -> anyhow::Result<T>
 where
     T: for<'de> Deserialize<'de> + Default,
 {
-    let file_str = match std::fs::read_to_string(path.as_ref()) {
+    let path = path.as_ref();
+    let overrides: Vec<(String, String)> = std::env::vars()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect();
+    let has_overrides = !overrides.is_empty();
+
+    let file_str = match std::fs::read_to_string(path) {
         Ok(s) => s,
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-            return Ok(T::default());
+            if !has_overrides {
+                return Ok(T::default());
+            }
+
+            String::new()
         }
         Err(e) => return Err(e).context("Failed to read configuration file"),
     };
 
-    let parsed = toml::from_str::<T>(&file_str)
+    let mut table = read_table(path, &file_str, &mut vec![])
+        .context("Failed to parse TOML from configuration file")?;
+    let merged = table.contains_key(INCLUDE) || table.contains_key(HOST);
+
+    if let Some(hosts) = table.remove(HOST) {
+        let host = hostname().unwrap_or_default();
+
+        if let Value::Table(mut hosts) = hosts
+            && let Some(Value::Table(overrides)) = hosts.remove(&host)
+        {
+            merge(&mut table, overrides);
+        }
+    }
+
+    apply_env(&mut table, overrides)?;
+
+    // Parsing the file itself keeps the line numbers in errors.
+    let parsed = if merged || has_overrides {
+        Value::Table(table).try_into::<T>()
+    } else {
+        toml::from_str::<T>(&file_str)
+    };
+
+    let parsed = parsed
+        .map_err(|e| diagnose(&file_str, e))
         .context("Failed to parse TOML from configuration file")?;
 
     Ok(parsed)
 }
+
+/// Write [`DEFAULT_CONFIG`] to `path`, an existing file is only overwritten
+/// with `force`.
+pub fn write_default(path: &Path, force: bool) -> anyhow::Result<()> {
+    if !force && path.exists() {
+        bail!("{} exists, use --force to overwrite it", path.display());
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+
+    std::fs::write(path, DEFAULT_CONFIG)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Key listing the files to include.
+const INCLUDE: &str = "include";
+/// Section of the per host overrides.
+const HOST: &str = "host";
+
+/// The file at `path` with `source` merged over its includes. `parents`
+/// are the files including it, to catch include cycles.
+fn read_table(
+    path: &Path,
+    source: &str,
+    parents: &mut Vec<PathBuf>,
+) -> anyhow::Result<Table> {
+    let mut table = source.parse::<Table>().map_err(|e| diagnose(source, e))?;
+
+    check_sections(source, &table)?;
+
+    let includes = match table.remove(INCLUDE) {
+        None => vec![],
+        Some(Value::Array(includes)) => includes,
+        Some(_) => bail!("`{}` must be a list of files", INCLUDE),
+    };
+
+    let mut base = Table::new();
+    let dir = path.parent().unwrap_or(Path::new("."));
+    parents.push(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+
+    for include in includes {
+        let Value::String(include) = include else {
+            bail!("`{}` must be a list of files", INCLUDE);
+        };
+
+        let include = dir.join(include);
+
+        if let Ok(canonical) = include.canonicalize()
+            && parents.contains(&canonical)
+        {
+            bail!("{} includes itself", include.display());
+        }
+
+        let included = std::fs::read_to_string(&include)
+            .map_err(anyhow::Error::from)
+            .and_then(|source| read_table(&include, &source, parents))
+            .with_context(|| {
+                format!("Failed to include {}", include.display())
+            })?;
+
+        merge(&mut base, included);
+    }
+
+    parents.pop();
+    merge(&mut base, table);
+
+    Ok(base)
+}
+
+/// Merge `other` into `table`, keys of `other` win and sections are merged
+/// key by key.
+fn merge(table: &mut Table, other: Table) {
+    for (key, value) in other {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Table(inner)), Value::Table(other)) => {
+                merge(inner, other);
+            }
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Name of the machine, which picks its `[host."name"]` section.
+pub fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    let res = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+
+    if res != 0 {
+        return None;
+    }
+
+    let name = std::ffi::CStr::from_bytes_until_nul(&buf).ok()?;
+    Some(name.to_string_lossy().into_owned())
+}
+
+/// Top level sections are checked here, the programs reading the file only
+/// know their own.
+fn check_sections(source: &str, table: &Table) -> anyhow::Result<()> {
+    for (key, value) in table {
+        if key == INCLUDE {
+            continue;
+        }
+
+        if key == HOST {
+            let Value::Table(hosts) = value else {
+                bail!("`{}` must be a table of hostnames", HOST);
+            };
+
+            for host in hosts.values() {
+                let Value::Table(host) = host else {
+                    bail!("`{}` must be a table of hostnames", HOST);
+                };
+
+                check_sections(source, host)?;
+            }
+
+            continue;
+        }
+
+        if !SECTIONS.contains(&key.as_str()) {
+            bail!(unknown_key(key, line_of(source, key), &SECTIONS));
+        }
+    }
+
+    Ok(())
+}
+
+/// Line of the first `[key...]` header or `key = ...` line.
+fn line_of(source: &str, key: &str) -> Option<usize> {
+    source
+        .lines()
+        .position(|line| {
+            let line = line.trim_start();
+            let rest = line
+                .strip_prefix("[[")
+                .or_else(|| line.strip_prefix('['))
+                .unwrap_or(line)
+                .trim_start();
+
+            rest.strip_prefix(key).is_some_and(|after| {
+                let after = after.trim_start();
+                after.starts_with([']', '.', '='])
+            })
+        })
+        .map(|index| index + 1)
+}
+
+/// Unknown field errors become `unknown key `x` at line 7, did you mean
+/// `y`?`, other errors are kept as they are.
+fn diagnose(source: &str, error: toml::de::Error) -> anyhow::Error {
+    let Some((key, expected)) = unknown_field(error.message()) else {
+        return error.into();
+    };
+
+    let line = error
+        .span()
+        .map(|span| source[..span.start].matches('\n').count() + 1);
+
+    anyhow!(unknown_key(key, line, &expected))
+}
+
+/// Key and expected keys of serde's unknown field message.
+fn unknown_field(message: &str) -> Option<(&str, Vec<&str>)> {
+    let rest = message.strip_prefix("unknown field `")?;
+    let (key, rest) = rest.split_once('`')?;
+    let expected = rest.split('`').skip(1).step_by(2).collect();
+
+    Some((key, expected))
+}
+
+fn unknown_key(key: &str, line: Option<usize>, known: &[&str]) -> String {
+    let mut message = format!("unknown key `{}`", key);
+
+    if let Some(line) = line {
+        message.push_str(&format!(" at line {}", line));
+    }
+
+    if let Some(similar) = similar(key, known) {
+        message.push_str(&format!(", did you mean `{}`?", similar));
+    }
+
+    message
+}
+
+/// The known key closest to `key`, if at most a third of it differs.
+fn similar<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, candidate)| {
+            distance * 3 <= key.len().max(candidate.len())
+        })
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance, adjacent swaps count as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) =
+        (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    rows[0] = (0..=b.len()).collect();
+
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+/// Set the keys of `MATIANE_*` variables in `table`.
+fn apply_env(
+    table: &mut Table,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> anyhow::Result<()> {
+    for (name, raw) in vars {
+        let Some(path) = env_path(&name) else {
+            continue;
+        };
+
+        let (key, sections) = path.split_last().expect("path is not empty");
+        let mut current = &mut *table;
+
+        for section in sections {
+            let entry = current
+                .entry(section.clone())
+                .or_insert_with(|| Value::Table(Table::new()));
+
+            let Value::Table(inner) = entry else {
+                bail!("{}: {} is not a section", name, section);
+            };
+
+            current = inner;
+        }
+
+        current.insert(key.clone(), env_value(&raw));
+    }
+
+    Ok(())
+}
+
+/// Config path of a `MATIANE_*` variable, e.g. `["general", "state-dir"]`.
+fn env_path(name: &str) -> Option<Vec<String>> {
+    let rest = name.strip_prefix(ENV_PREFIX)?.to_lowercase();
+    let mut levels = rest.split("__");
+    let (section, key) = levels.next()?.split_once('_')?;
+
+    let path: Vec<String> = [section, key]
+        .into_iter()
+        .chain(levels)
+        .map(|level| level.replace('_', "-"))
+        .collect();
+
+    if path.iter().any(String::is_empty) {
+        return None;
+    }
+
+    Some(path)
+}
+
+fn env_value(raw: &str) -> Value {
+    format!("value = {}", raw)
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_path_test() {
+        let path = |name| env_path(name).map(|p| p.join("."));
+
+        assert_eq!(
+            path("MATIANE_GENERAL_STATE_DIR").as_deref(),
+            Some("general.state-dir")
+        );
+        assert_eq!(
+            path("MATIANE_SYNC_TOGGL__API_TOKEN").as_deref(),
+            Some("sync.toggl.api-token")
+        );
+        assert_eq!(path("MATIANE_GENERAL"), None);
+        assert_eq!(path("MATIANE_SYNC_TOGGL__"), None);
+        assert_eq!(path("OTHER_GENERAL_STATE_DIR"), None);
+    }
+
+    #[test]
+    fn apply_env_test() {
+        let mut table: Table =
+            "[general]\nstate-dir = \"/file\"\n[sway]\nidle-timeout = 10\n"
+                .parse()
+                .unwrap();
+        let vars = [
+            ("MATIANE_GENERAL_STATE_DIR", "/env"),
+            ("MATIANE_SWAY_IDLE_TIMEOUT", "60"),
+            ("MATIANE_SYNC_TOGGL__API_TOKEN", "\"123\""),
+            ("MATIANE_SINK_STDOUT", "true"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+
+        apply_env(&mut table, vars).unwrap();
+
+        assert_eq!(table["general"]["state-dir"].as_str(), Some("/env"));
+        assert_eq!(table["sway"]["idle-timeout"].as_integer(), Some(60));
+        assert_eq!(table["sync"]["toggl"]["api-token"].as_str(), Some("123"));
+        assert_eq!(table["sink"]["stdout"].as_bool(), Some(true));
+
+        let bad =
+            [("MATIANE_GENERAL_STATE_DIR__X".to_string(), "1".to_string())];
+        assert!(apply_env(&mut table, bad).is_err());
+    }
+
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(rename_all = "kebab-case", deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Sway {
+        idle_timeout: Option<u32>,
+        live_interval: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Config {
+        sway: Sway,
+    }
+
+    #[test]
+    fn diagnose_test() {
+        let source = "[sway]\n\nidle-timout = 5\n";
+        let error = toml::from_str::<Config>(source).unwrap_err();
+
+        assert_eq!(
+            diagnose(source, error).to_string(),
+            "unknown key `idle-timout` at line 3, did you mean `idle-timeout`?"
+        );
+
+        let source = "[sway]\nfoo = 5\n";
+        let error = toml::from_str::<Config>(source).unwrap_err();
+        assert_eq!(
+            diagnose(source, error).to_string(),
+            "unknown key `foo` at line 2"
+        );
+    }
+
+    #[test]
+    fn check_sections_test() {
+        let source = "[general]\nstate-dir = \"/a\"\n\n[ gneral.x ]\n";
+        let table: Table = source.parse().unwrap();
+
+        assert_eq!(
+            check_sections(source, &table).unwrap_err().to_string(),
+            "unknown key `gneral` at line 4, did you mean `general`?"
+        );
+        assert!(check_sections("", &Table::new()).is_ok());
+    }
+
+    #[test]
+    fn similar_test() {
+        let known = ["idle-timeout", "live-interval", "state-dir"];
+
+        assert_eq!(similar("idle-timout", &known), Some("idle-timeout"));
+        assert_eq!(similar("live-intreval", &known), Some("live-interval"));
+        assert_eq!(similar("stat-dir", &known), Some("state-dir"));
+        assert_eq!(similar("timeout", &known), None);
+        assert_eq!(similar("", &known), None);
+    }
+
+    #[test]
+    fn merge_test() {
+        let mut table: Table =
+            "include = [\"a\"]\n[general]\nstate-dir = \"/a\"\n\
+             [sync.toggl]\napi-token = \"a\"\nworkspace-id = 1\n"
+                .parse()
+                .unwrap();
+        let other: Table = "[sync.toggl]\napi-token = \"b\"\n[sway]\nx = 1\n"
+            .parse()
+            .unwrap();
+
+        merge(&mut table, other);
+
+        assert_eq!(table["general"]["state-dir"].as_str(), Some("/a"));
+        assert_eq!(table["sync"]["toggl"]["api-token"].as_str(), Some("b"));
+        assert_eq!(
+            table["sync"]["toggl"]["workspace-id"].as_integer(),
+            Some(1)
+        );
+        assert_eq!(table["sway"]["x"].as_integer(), Some(1));
+    }
+
+    #[test]
+    fn load_includes_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let host = hostname().unwrap();
+
+        std::fs::create_dir(dir.path().join("conf.d")).unwrap();
+        std::fs::write(
+            dir.path().join("conf.d/work.toml"),
+            "include = [\"../base.toml\"]\n[general]\nstate-dir = \"/work\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("base.toml"),
+            "[general]\nstate-dir = \"/base\"\n[sway]\nidle-timeout = 1\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &path,
+            format!(
+                "include = [\"conf.d/work.toml\"]\n\
+                 [sway]\nidle-timeout = 2\nlive-interval = 3\n\
+                 [host.\"{host}\".sway]\nlive-interval = 4\n\
+                 [host.\"other-{host}\".sway]\nlive-interval = 5\n"
+            ),
+        )
+        .unwrap();
+
+        #[derive(Debug, Default, Deserialize)]
+        struct Loaded {
+            #[serde(default)]
+            general: GeneralConfig,
+            sway: Sway,
+        }
+
+        let loaded: Loaded = load(&path).unwrap();
+        assert_eq!(loaded.general.state_dir, PathBuf::from("/work"));
+        assert_eq!(loaded.sway.idle_timeout, Some(2));
+        assert_eq!(loaded.sway.live_interval, Some(4));
+
+        std::fs::write(
+            dir.path().join("base.toml"),
+            "include = [\"config.toml\"]",
+        )
+        .unwrap();
+        let err = load::<Loaded>(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("config.toml includes itself"));
+    }
+
+    #[test]
+    fn write_default_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("matiane/config.toml");
+
+        write_default(&path, false).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DEFAULT_CONFIG);
+
+        std::fs::write(&path, "[general]").unwrap();
+        assert!(write_default(&path, false).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[general]");
+
+        write_default(&path, true).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), DEFAULT_CONFIG);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn migrate_store_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = dir.path().join("share/matiane");
+        let state_dir = dir.path().join("state/matiane");
+
+        assert_eq!(
+            migrate_store(legacy.clone(), state_dir.clone())
+                .await
+                .unwrap(),
+            None
+        );
+
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("20260101.log"), "{}\n").unwrap();
+
+        let lock = acquire_lock_file(legacy.clone()).await.unwrap();
+        assert!(
+            migrate_store(legacy.clone(), state_dir.clone())
+                .await
+                .is_err()
+        );
+        drop(lock);
+
+        assert_eq!(
+            migrate_store(legacy.clone(), state_dir.clone())
+                .await
+                .unwrap(),
+            Some(state_dir.clone())
+        );
+        assert!(!legacy.exists());
+        assert!(state_dir.join("20260101.log").exists());
+
+        std::fs::create_dir_all(&legacy).unwrap();
+        assert_eq!(migrate_store(legacy, state_dir).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Config reloading through inotify(7).
+
+use super::load;
+use futures::{Stream, stream};
+use serde::Deserialize;
+use std::ffi::{CString, OsString};
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::unix::AsyncFd;
+
+/// Changes within this window are reloaded once.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+const EVENT_SIZE: usize = std::mem::size_of::<libc::inotify_event>();
+
+/// Stream of configs loaded from `path` every time it changes.
+///
+/// The directory of `path` is watched, so files replaced by editors are
+/// picked up too. Included files are not watched.
+pub fn watch<T>(
+    path: impl Into<PathBuf>,
+) -> io::Result<impl Stream<Item = anyhow::Result<T>>>
+where
+    T: for<'de> Deserialize<'de> + Default,
+{
+    let watcher = Watcher::new(path.into())?;
+
+    Ok(stream::unfold(Some(watcher), |watcher| async {
+        let mut watcher = watcher?;
+
+        if let Err(e) = watcher.settled().await {
+            return Some((Err(e.into()), None));
+        }
+
+        Some((load(&watcher.path), Some(watcher)))
+    }))
+}
+
+struct Watcher {
+    fd: AsyncFd<OwnedFd>,
+    path: PathBuf,
+    name: OsString,
+    buf: Vec<u8>,
+}
+
+impl Watcher {
+    fn new(path: PathBuf) -> io::Result<Self> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "not a file")
+            })?
+            .to_owned();
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let dir = CString::new(dir.as_os_str().as_bytes())?;
+
+        let fd = unsafe {
+            libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC)
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let mask = libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO | libc::IN_CREATE;
+        let wd = unsafe {
+            libc::inotify_add_watch(fd.as_raw_fd(), dir.as_ptr(), mask)
+        };
+        if wd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Watcher {
+            fd: AsyncFd::new(fd)?,
+            path,
+            name,
+            buf: vec![0; 4096],
+        })
+    }
+
+    /// Wait for a change followed by `DEBOUNCE` of quiet.
+    async fn settled(&mut self) -> io::Result<()> {
+        self.changed().await?;
+
+        while let Ok(changed) =
+            tokio::time::timeout(DEBOUNCE, self.changed()).await
+        {
+            changed?;
+        }
+
+        Ok(())
+    }
+
+    async fn changed(&mut self) -> io::Result<()> {
+        loop {
+            let len = self.read().await?;
+
+            if names(&self.buf[..len]).any(|name| name == self.name) {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn read(&mut self) -> io::Result<usize> {
+        loop {
+            let mut guard = self.fd.readable().await?;
+            let buf = &mut self.buf;
+
+            let result = guard.try_io(|fd| {
+                let len = unsafe {
+                    libc::read(
+                        fd.as_raw_fd(),
+                        buf.as_mut_ptr().cast(),
+                        buf.len(),
+                    )
+                };
+
+                if len < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(len as usize)
+                }
+            });
+
+            if let Ok(result) = result {
+                return result;
+            }
+        }
+    }
+}
+
+/// File names of the events in `buf`.
+fn names(buf: &[u8]) -> impl Iterator<Item = &std::ffi::OsStr> {
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        while offset + EVENT_SIZE <= buf.len() {
+            let header = &buf[offset..offset + EVENT_SIZE];
+            let len = u32::from_ne_bytes(header[12..16].try_into().unwrap());
+            let name = &buf[offset + EVENT_SIZE..][..len as usize];
+            offset += EVENT_SIZE + len as usize;
+
+            let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            if end > 0 {
+                return Some(std::ffi::OsStr::from_bytes(&name[..end]));
+            }
+        }
+
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use toml::Table;
+
+    async fn next(
+        stream: &mut (impl Stream<Item = anyhow::Result<Table>> + Unpin),
+    ) -> Table {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn watch_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[general]\nstate-dir = \"/a\"\n").unwrap();
+
+        let mut configs = Box::pin(watch::<Table>(&path).unwrap());
+
+        std::fs::write(dir.path().join("other.toml"), "a = 1\n").unwrap();
+        std::fs::write(&path, "[general]\nstate-dir = \"/b\"\n").unwrap();
+        let config = next(&mut configs).await;
+        assert_eq!(config["general"]["state-dir"].as_str(), Some("/b"));
+
+        // Editors write a new file and rename it over the old one.
+        let tmp = dir.path().join(".config.toml.swp");
+        std::fs::write(&tmp, "[general]\nstate-dir = \"/c\"\n").unwrap();
+        std::fs::rename(&tmp, &path).unwrap();
+        let config = next(&mut configs).await;
+        assert_eq!(config["general"]["state-dir"].as_str(), Some("/c"));
+    }
+
+    #[tokio::test]
+    async fn watch_debounce_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "").unwrap();
+
+        let mut configs = Box::pin(watch::<Table>(&path).unwrap());
+
+        for n in 0..5 {
+            std::fs::write(&path, format!("[general]\nn = {n}\n")).unwrap();
+        }
+        let config = next(&mut configs).await;
+        assert_eq!(config["general"]["n"].as_integer(), Some(4));
+
+        let pending = tokio::time::timeout(DEBOUNCE * 2, configs.next()).await;
+        assert!(pending.is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Stable error codes and hints, e.g.
+//! `MAT-STORE-003: The store is locked by the running process 1234.`
+//!
+//! Codes are never reused or renumbered, new errors get new codes.
+
+use crate::categories::CategoryError;
+use crate::store::{LockFileError, StoreReadError, StoreWriteError};
+use std::error::Error;
+use std::fmt;
+
+pub trait Diagnostic: Error {
+    fn code(&self) -> &'static str;
+
+    /// What the user can do about it.
+    fn hint(&self) -> Option<String> {
+        None
+    }
+}
+
+impl Diagnostic for StoreWriteError {
+    fn code(&self) -> &'static str {
+        match self {
+            StoreWriteError::Io(_) => "MAT-STORE-001",
+            StoreWriteError::EncodeError(_) => "MAT-STORE-002",
+            StoreWriteError::CborEncodeError(_) => "MAT-STORE-006",
+            #[cfg(feature = "sqlite")]
+            StoreWriteError::Database(_) => "MAT-STORE-007",
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            StoreWriteError::Io(_) => Some(
+                "Check that the state dir exists, is writable and the disk \
+                 is not full."
+                    .into(),
+            ),
+            StoreWriteError::EncodeError(_)
+            | StoreWriteError::CborEncodeError(_) => None,
+            #[cfg(feature = "sqlite")]
+            StoreWriteError::Database(_) => None,
+        }
+    }
+}
+
+impl Diagnostic for LockFileError {
+    fn code(&self) -> &'static str {
+        match self {
+            LockFileError::Held(_) => "MAT-STORE-003",
+            LockFileError::TryLockError(_) => "MAT-STORE-004",
+            LockFileError::Io(_) => "MAT-STORE-005",
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            LockFileError::Held(holder) => Some(format!(
+                "Stop process {} or use --force-lock to take the lock over.",
+                holder.pid
+            )),
+            LockFileError::TryLockError(_) => {
+                Some("Another process is using the store, retry later.".into())
+            }
+            LockFileError::Io(_) => None,
+        }
+    }
+}
+
+impl Diagnostic for StoreReadError {
+    fn code(&self) -> &'static str {
+        match self {
+            StoreReadError::Io(_) => "MAT-STORE-010",
+            StoreReadError::EncodeError(_) => "MAT-STORE-011",
+            StoreReadError::FilePathError(_) => "MAT-STORE-012",
+            StoreReadError::NoFilesToOpen => "MAT-STORE-013",
+            StoreReadError::LineReaderError(_) => "MAT-STORE-014",
+            StoreReadError::Maintenance => "MAT-STORE-015",
+            #[cfg(feature = "sqlite")]
+            StoreReadError::Database(_) => "MAT-STORE-016",
+            StoreReadError::ChecksumMismatch(_) => "MAT-STORE-017",
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            StoreReadError::EncodeError(_)
+            | StoreReadError::LineReaderError(_) => Some(
+                "A store file is damaged, check the files in the state dir."
+                    .into(),
+            ),
+            StoreReadError::NoFilesToOpen => {
+                Some("Nothing has been recorded in this range.".into())
+            }
+            StoreReadError::Maintenance => {
+                Some("Retry once the maintenance has finished.".into())
+            }
+            StoreReadError::ChecksumMismatch(_) => Some(
+                "The file changed after it was written, check the disk or \
+                 filesystem it is on."
+                    .into(),
+            ),
+            StoreReadError::Io(_) | StoreReadError::FilePathError(_) => None,
+            #[cfg(feature = "sqlite")]
+            StoreReadError::Database(_) => None,
+        }
+    }
+}
+
+impl Diagnostic for CategoryError {
+    fn code(&self) -> &'static str {
+        match self {
+            CategoryError::InvalidRegex { .. } => "MAT-CONFIG-001",
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            CategoryError::InvalidRegex { .. } => Some(
+                "Fix the regex in [[categories.rules]] of the config file."
+                    .into(),
+            ),
+        }
+    }
+}
+
+/// Renders an error as `CODE: message`, followed by its hint.
+pub struct Report<'a>(pub &'a dyn Diagnostic);
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.0.code(), self.0)?;
+
+        if let Some(hint) = self.0.hint() {
+            write!(f, "\n  hint: {}", hint)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// First error with a code in the chain of `error`.
+pub fn find(error: &anyhow::Error) -> Option<&dyn Diagnostic> {
+    error.chain().find_map(|cause| -> Option<&dyn Diagnostic> {
+        if let Some(e) = cause.downcast_ref::<StoreWriteError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<LockFileError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<StoreReadError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<CategoryError>() {
+            Some(e)
+        } else {
+            None
+        }
+    })
+}
+
+/// `error` with its context, prefixed by the code found in its chain.
+pub fn render(error: &anyhow::Error) -> String {
+    match find(error) {
+        Some(diagnostic) => {
+            let mut out = format!("{}: {:#}", diagnostic.code(), error);
+
+            if let Some(hint) = diagnostic.hint() {
+                out.push_str(&format!("\n  hint: {}", hint));
+            }
+
+            out
+        }
+        None => format!("{:#}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::LockHolder;
+    use anyhow::Context;
+
+    #[test]
+    fn render_test() {
+        let held = LockFileError::Held(LockHolder {
+            pid: 1234,
+            start_time: 1,
+        });
+        assert_eq!(
+            Report(&held).to_string(),
+            "MAT-STORE-003: The store is locked by the running process 1234.\n  \
+             hint: Stop process 1234 or use --force-lock to take the lock over."
+        );
+
+        let error = Err::<(), _>(StoreReadError::Maintenance)
+            .context("Failed to read the activity")
+            .unwrap_err();
+        assert_eq!(
+            render(&error),
+            "MAT-STORE-015: Failed to read the activity: The store is locked \
+             for maintenance\n  hint: Retry once the maintenance has finished."
+        );
+
+        assert_eq!(render(&anyhow::anyhow!("plain")), "plain");
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -6,6 +6,139 @@ pub struct Focused {
     pub title: String,
     pub id: String,
     pub pid: i32,
+    /// Read from `/proc` when the window was focused, if the daemon was
+    /// configured to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process: Option<Process>,
+    /// The title before the daemon normalized it, when it was changed and
+    /// the daemon was configured to keep it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_title: Option<String>,
+    /// What the window shows, read from its title by the daemon, e.g. the
+    /// site of a browser tab.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// The process behind a focused window, for telling apart apps that share
+/// an app id, like Electron wrappers.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Process {
+    /// Path of the executable.
+    pub exe: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cmdline: Vec<String>,
+    /// Path of the cgroup v2 the process is in, e.g. a systemd scope.
+    pub cgroup: Option<String>,
+}
+
+/// A workspace got the focus, the windows focused after it are on it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorkspaceFocused {
+    pub name: String,
+    /// The output, or monitor, the workspace is on.
+    pub output: Option<String>,
+}
+
+/// Activity imported from an external source (e.g. a calendar) for a
+/// period that was not tracked. Covers `timestamp..end`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Backfilled {
+    pub app: String,
+    pub title: String,
+    pub source: String,
+    pub end: DateTime<Utc>,
+}
+
+/// A note on the time of the event, e.g. the meeting the focused windows
+/// were part of. Written by `store::annotate`, or by the daemon when asked
+/// on its control socket.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Annotation {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// A timer of the task the user intends to work on was started, e.g. a
+/// pomodoro. It runs for `duration` seconds unless it is stopped.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimerStarted {
+    pub task: String,
+    pub duration: u64,
+}
+
+/// The timer of `task` ended, `completed` when it ran for its whole
+/// duration.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimerEnded {
+    pub task: String,
+    pub completed: bool,
+}
+
+/// Alive events from `timestamp` up to `end`, collapsed into one by
+/// `store::compact`.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Present {
+    pub end: DateTime<Utc>,
+}
+
+/// Written by the daemon once the disk has space again, after writing
+/// failed from `since` for a full disk.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct StorageDegraded {
+    pub since: DateTime<Utc>,
+    /// Events dropped while the disk was full, when too many were kept.
+    pub dropped: usize,
+}
+
+/// Written by the daemon when it starts, before the window focused at the
+/// time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Started {
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// The kernel's boot id, which changes with every boot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boot_id: Option<String>,
+    /// The window backend, e.g. `sway`.
+    pub compositor: String,
+}
+
+/// An event of a type this version does not know, written by a newer one.
+/// Written back the same as it was read.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawUnknown")]
+pub struct Unknown {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(rename = "data", skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct RawUnknown {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default, rename = "data")]
+    payload: Option<serde_json::Value>,
+}
+
+impl TryFrom<RawUnknown> for Unknown {
+    type Error = String;
+
+    /// Known types that did not decode are errors, not unknown events.
+    fn try_from(raw: RawUnknown) -> Result<Self, String> {
+        if KINDS.contains(&raw.kind.as_str()) {
+            return Err(format!("invalid data of a {} event", raw.kind));
+        }
+
+        Ok(Unknown {
+            kind: raw.kind,
+            payload: raw.payload,
+        })
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -14,14 +147,64 @@ pub enum Event {
     Focused(Box<Focused>),
     /// An interval liveness check
     Alive,
-    /// swaylock: Screen is now locked or asleep
+    /// Screen is now locked or asleep
     Sleep,
-    /// swaylock: Screen is now unlocked or awake
+    /// Screen is now unlocked or awake
     Awake,
-    /// swayidle: Went to idle state
+    /// Went to idle state
     Idle,
-    /// swayidle: Back to active state
+    /// Back to active state
     Active,
+    /// Written by `matiane backfill`, never by the daemon.
+    Backfilled(Box<Backfilled>),
+    /// Written by compaction, never by the daemon.
+    Present(Present),
+    StorageDegraded(StorageDegraded),
+    WorkspaceFocused(Box<WorkspaceFocused>),
+    /// An output was added, removed or reconfigured.
+    OutputChanged,
+    Annotation(Box<Annotation>),
+    TimerStarted(Box<TimerStarted>),
+    TimerEnded(Box<TimerEnded>),
+    /// Recording focused windows was paused, none is focused until one is
+    /// recorded after `TrackingResumed`.
+    TrackingPaused,
+    TrackingResumed,
+    /// The daemon stopped, a day that ends without it may have crashed.
+    Shutdown,
+    /// The daemon started, nothing is focused or idle until it tells.
+    Started(Box<Started>),
+    /// Any other type, kept for older versions to read newer stores.
+    #[serde(untagged)]
+    Unknown(Box<Unknown>),
+}
+
+impl Event {
+    /// The `type` of the event in the store, `unknown` for the types this
+    /// version does not know.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::Focused(_) => "focused",
+            Event::Alive => "alive",
+            Event::Sleep => "sleep",
+            Event::Awake => "awake",
+            Event::Idle => "idle",
+            Event::Active => "active",
+            Event::Backfilled(_) => "backfilled",
+            Event::Present(_) => "present",
+            Event::StorageDegraded(_) => "storage_degraded",
+            Event::WorkspaceFocused(_) => "workspace_focused",
+            Event::OutputChanged => "output_changed",
+            Event::Annotation(_) => "annotation",
+            Event::TimerStarted(_) => "timer_started",
+            Event::TimerEnded(_) => "timer_ended",
+            Event::TrackingPaused => "tracking_paused",
+            Event::TrackingResumed => "tracking_resumed",
+            Event::Shutdown => "shutdown",
+            Event::Started(_) => "started",
+            Event::Unknown(_) => UNKNOWN_KIND,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -29,3 +212,246 @@ pub struct TimedEvent {
     pub timestamp: DateTime<Utc>,
     pub event: Event,
 }
+
+/// Time and type of an event, read without decoding the rest of the line,
+/// for filtering and counting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventHead {
+    pub timestamp: DateTime<Utc>,
+    /// Same as `Event::kind`.
+    pub kind: &'static str,
+}
+
+/// `kind` of the types that are not one of `KINDS`.
+pub const UNKNOWN_KIND: &str = "unknown";
+
+const KINDS: [&str; 18] = [
+    "focused",
+    "alive",
+    "sleep",
+    "awake",
+    "idle",
+    "active",
+    "backfilled",
+    "present",
+    "storage_degraded",
+    "workspace_focused",
+    "output_changed",
+    "annotation",
+    "timer_started",
+    "timer_ended",
+    "tracking_paused",
+    "tracking_resumed",
+    "shutdown",
+    "started",
+];
+
+#[derive(Deserialize)]
+struct Head {
+    timestamp: DateTime<Utc>,
+    event: HeadEvent,
+}
+
+#[derive(Deserialize)]
+struct HeadEvent {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+impl EventHead {
+    /// Head of a line written by the store, which starts with the
+    /// timestamp and type. Other lines are decoded, without the data of
+    /// the event. Types that are not known have the `UNKNOWN_KIND`.
+    pub fn parse(line: &str) -> serde_json::Result<Self> {
+        Self::parse_slice(line.as_bytes())
+    }
+
+    /// `parse` for a line that was not checked to be UTF-8.
+    pub fn parse_slice(line: &[u8]) -> serde_json::Result<Self> {
+        if let Some(head) = Self::parse_written(line) {
+            return Ok(head);
+        }
+
+        let head: Head = serde_json::from_slice(line)?;
+        let kind = KINDS
+            .into_iter()
+            .find(|kind| *kind == head.event.kind)
+            .unwrap_or(UNKNOWN_KIND);
+
+        Ok(EventHead {
+            timestamp: head.timestamp,
+            kind,
+        })
+    }
+
+    fn parse_written(line: &[u8]) -> Option<Self> {
+        let rest = line.strip_prefix(br#"{"timestamp":""#)?;
+        let (timestamp, rest) = split_quote(rest)?;
+        let rest = rest.strip_prefix(br#","event":{"type":""#)?;
+        let (kind, _) = split_quote(rest)?;
+        let timestamp = std::str::from_utf8(timestamp).ok()?;
+
+        Some(EventHead {
+            timestamp: parse_utc(timestamp)
+                .or_else(|| timestamp.parse().ok())?,
+            kind: KINDS.into_iter().find(|k| k.as_bytes() == kind)?,
+        })
+    }
+}
+
+/// `s` before and after its first `"`.
+fn split_quote(s: &[u8]) -> Option<(&[u8], &[u8])> {
+    let n = s.iter().position(|&c| c == b'"')?;
+    Some((&s[..n], &s[n + 1..]))
+}
+
+/// `2026-01-01T10:00:00.123Z`, the way UTC times are written, without
+/// going through the general RFC 3339 parser.
+fn parse_utc(s: &str) -> Option<DateTime<Utc>> {
+    let (datetime, fraction) = s.strip_suffix('Z')?.split_at_checked(19)?;
+    let b = datetime.as_bytes();
+
+    let separators = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':')];
+    if separators.iter().any(|&(i, c)| b[i] != c) {
+        return None;
+    }
+
+    let number = |from: usize, to: usize| digits(&b[from..to]);
+
+    let nanos = match fraction.strip_prefix('.') {
+        None if fraction.is_empty() => 0,
+        Some(fraction) if (1..=9).contains(&fraction.len()) => {
+            let n = digits(fraction.as_bytes())?;
+            n * 10u32.pow(9 - fraction.len() as u32)
+        }
+        _ => return None,
+    };
+
+    let date = NaiveDate::from_ymd_opt(
+        number(0, 4)? as i32,
+        number(5, 7)?,
+        number(8, 10)?,
+    )?;
+    let time = NaiveTime::from_hms_nano_opt(
+        number(11, 13)?,
+        number(14, 16)?,
+        number(17, 19)?,
+        nanos,
+    )?;
+
+    Some(date.and_time(time).and_utc())
+}
+
+/// Value of at most 9 ASCII digits.
+fn digits(digits: &[u8]) -> Option<u32> {
+    digits.iter().try_fold(0, |n: u32, c| {
+        c.is_ascii_digit().then(|| n * 10 + u32::from(c - b'0'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn event_head_test() {
+        let timestamp = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+        let head = |kind| EventHead { timestamp, kind };
+
+        let written = serde_json::to_string(&TimedEvent {
+            timestamp,
+            event: Event::Focused(Box::new(Focused {
+                title: "a \"title\"".into(),
+                id: "a".into(),
+                pid: 1,
+                process: None,
+                raw_title: None,
+                detail: None,
+            })),
+        })
+        .unwrap();
+        assert!(EventHead::parse_written(written.as_bytes()).is_some());
+        assert_eq!(EventHead::parse(&written).unwrap(), head("focused"));
+
+        // Only the head of a written line is read.
+        let mut invalid = written.into_bytes();
+        invalid.extend_from_slice(b"\xff");
+        assert_eq!(EventHead::parse_slice(&invalid).unwrap(), head("focused"));
+
+        let reordered = r#"{ "event": {"type": "idle"},
+            "timestamp": "2026-01-01T12:00:00+02:00" }"#;
+        assert_eq!(EventHead::parse(reordered).unwrap(), head("idle"));
+
+        for written in [
+            "2026-01-01T10:00:00Z",
+            "2026-01-01T10:00:00.5Z",
+            "2026-01-01T10:00:00.123456789Z",
+            "2024-02-29T23:59:59.000001Z",
+        ] {
+            assert_eq!(parse_utc(written), written.parse().ok());
+        }
+        for other in [
+            "2026-01-01T10:00:00+02:00",
+            "2026-01-01 10:00:00Z",
+            "2026-02-30T10:00:00Z",
+            "2026-01-01T10:00:00.Z",
+            "2026-01-01T1a:00:00Z",
+        ] {
+            assert_eq!(parse_utc(other), None);
+        }
+
+        let unknown =
+            r#"{"timestamp":"2026-01-01T10:00:00Z","event":{"type":"x"}}"#;
+        assert_eq!(EventHead::parse(unknown).unwrap(), head(UNKNOWN_KIND));
+        assert!(EventHead::parse("{").is_err());
+    }
+
+    #[test]
+    fn workspace_events_test() {
+        let timestamp = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+        let workspace = Event::WorkspaceFocused(Box::new(WorkspaceFocused {
+            name: "2".into(),
+            output: Some("eDP-1".into()),
+        }));
+
+        for (event, kind) in [
+            (workspace, "workspace_focused"),
+            (Event::OutputChanged, "output_changed"),
+        ] {
+            let line = serde_json::to_string(&TimedEvent { timestamp, event })
+                .unwrap();
+            assert_eq!(EventHead::parse(&line).unwrap().kind, kind);
+
+            let read: TimedEvent = serde_json::from_str(&line).unwrap();
+            assert_eq!(read.event.kind(), kind);
+        }
+    }
+
+    #[test]
+    fn unknown_event_test() {
+        let lines = [
+            r#"{"timestamp":"2026-01-01T10:00:00Z","event":{"type":"x","data":{"a":[1,"b"]}}}"#,
+            r#"{"timestamp":"2026-01-01T10:00:00Z","event":{"type":"y"}}"#,
+        ];
+
+        for line in lines {
+            let read: TimedEvent = serde_json::from_str(line).unwrap();
+            assert_eq!(read.event.kind(), UNKNOWN_KIND);
+            assert_eq!(serde_json::to_string(&read).unwrap(), line);
+        }
+
+        let Event::Unknown(unknown) =
+            serde_json::from_str(r#"{"type":"x","data":3}"#).unwrap()
+        else {
+            panic!("Must be an Unknown event.");
+        };
+        assert_eq!(unknown.kind, "x");
+        assert_eq!(unknown.payload, Some(serde_json::json!(3)));
+
+        // Known types with data that does not match are no unknown events.
+        let invalid = r#"{"type":"focused","data":{"title":1}}"#;
+        assert!(serde_json::from_str::<Event>(invalid).is_err());
+        assert!(serde_json::from_str::<Event>(r#"{"data":1}"#).is_err());
+    }
+}
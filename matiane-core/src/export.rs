@@ -0,0 +1,238 @@
+//! Events of the store as rows for analysis tools like pandas or DuckDB,
+//! written as CSV, a JSON array or Parquet.
+//!
+//! Every event is a row of its time, its type and the fields of a `Focused`
+//! payload, `title`, `id` and `pid`. Backfilled events fill `title` and
+//! `id` with their title and app, other events leave them empty.
+
+#[cfg(feature = "parquet")]
+mod parquet;
+
+use crate::events::{Event, Focused, TimedEvent};
+use crate::store::{EventReaderResult, StoreReadError};
+use chrono::{DateTime, Utc};
+use futures::{Stream, TryStreamExt};
+use serde::Serialize;
+use std::io::Write;
+use std::pin::pin;
+use thiserror::Error;
+
+/// Names of the columns, in order.
+pub const COLUMNS: [&str; 5] = ["timestamp", "type", "title", "id", "pid"];
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Export IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to read the events: {0}")]
+    Read(#[from] StoreReadError),
+    #[error("Failed to write CSV: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Failed to write JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "parquet")]
+    #[error("Failed to write Parquet: {0}")]
+    Parquet(#[from] ::parquet::errors::ParquetError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// A single array of row objects.
+    Json,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl ExportFormat {
+    pub const ALL: &[ExportFormat] = &[
+        ExportFormat::Csv,
+        ExportFormat::Json,
+        #[cfg(feature = "parquet")]
+        ExportFormat::Parquet,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Row {
+    pub timestamp: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub title: Option<String>,
+    pub id: Option<String>,
+    pub pid: Option<i32>,
+}
+
+impl From<TimedEvent> for Row {
+    fn from(event: TimedEvent) -> Self {
+        let kind = event.event.kind();
+        let (title, id, pid) = match event.event {
+            Event::Focused(focused) => {
+                let Focused { title, id, pid, .. } = *focused;
+                (Some(title), Some(id), Some(pid))
+            }
+            Event::Backfilled(backfilled) => {
+                (Some(backfilled.title), Some(backfilled.app), None)
+            }
+            Event::Annotation(annotation) => {
+                (Some(annotation.text), None, None)
+            }
+            Event::TimerStarted(timer) => (Some(timer.task), None, None),
+            Event::TimerEnded(timer) => (Some(timer.task), None, None),
+            _ => (None, None, None),
+        };
+
+        Row {
+            timestamp: event.timestamp,
+            kind,
+            title,
+            id,
+            pid,
+        }
+    }
+}
+
+/// Write `events` to `out` in `format`. Returns how many were written.
+pub async fn export<W: Write + Send>(
+    events: impl Stream<Item = EventReaderResult<TimedEvent>>,
+    format: ExportFormat,
+    out: W,
+) -> Result<usize, ExportError> {
+    let rows = events.map_ok(Row::from);
+
+    match format {
+        ExportFormat::Csv => write_csv(rows, out).await,
+        ExportFormat::Json => write_json(rows, out).await,
+        #[cfg(feature = "parquet")]
+        ExportFormat::Parquet => parquet::write(rows, out).await,
+    }
+}
+
+async fn write_csv(
+    rows: impl Stream<Item = EventReaderResult<Row>>,
+    out: impl Write,
+) -> Result<usize, ExportError> {
+    let mut rows = pin!(rows);
+    // The header is written even without rows.
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(out);
+    writer.write_record(COLUMNS)?;
+
+    let mut written = 0;
+    while let Some(row) = rows.try_next().await? {
+        writer.serialize(&row)?;
+        written += 1;
+    }
+
+    writer.flush()?;
+    Ok(written)
+}
+
+async fn write_json(
+    rows: impl Stream<Item = EventReaderResult<Row>>,
+    mut out: impl Write,
+) -> Result<usize, ExportError> {
+    let mut rows = pin!(rows);
+    out.write_all(b"[")?;
+
+    let mut written = 0;
+    while let Some(row) = rows.try_next().await? {
+        let separator: &[u8] = if written == 0 { b"\n" } else { b",\n" };
+        out.write_all(separator)?;
+        serde_json::to_writer(&mut out, &row)?;
+        written += 1;
+    }
+
+    let end: &[u8] = if written == 0 { b"]\n" } else { b"\n]\n" };
+    out.write_all(end)?;
+    out.flush()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, TimeZone};
+
+    fn events() -> Vec<EventReaderResult<TimedEvent>> {
+        let midnight = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let focused = Event::Focused(Box::new(Focused {
+            title: "a, \"quoted\" title".to_string(),
+            id: "a".to_string(),
+            pid: 12,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }));
+
+        [focused, Event::Alive, Event::Idle]
+            .into_iter()
+            .enumerate()
+            .map(|(m, event)| {
+                Ok(TimedEvent {
+                    timestamp: midnight + TimeDelta::minutes(m as i64),
+                    event,
+                })
+            })
+            .collect()
+    }
+
+    async fn export_to_string(format: ExportFormat) -> String {
+        let mut out = vec![];
+        let stream = futures::stream::iter(events());
+        let written = export(stream, format, &mut out).await.unwrap();
+        assert_eq!(written, 3);
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[tokio::test]
+    async fn export_csv_test() {
+        let csv = export_to_string(ExportFormat::Csv).await;
+        let lines: Vec<_> = csv.lines().collect();
+
+        assert_eq!(
+            lines,
+            [
+                "timestamp,type,title,id,pid",
+                r#"2026-01-01T00:00:00Z,focused,"a, ""quoted"" title",a,12"#,
+                "2026-01-01T00:01:00Z,alive,,,",
+                "2026-01-01T00:02:00Z,idle,,,",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn export_json_test() {
+        let json = export_to_string(ExportFormat::Json).await;
+        let rows: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0]["title"], "a, \"quoted\" title");
+        assert_eq!(rows[0]["pid"], 12);
+        assert_eq!(rows[1]["type"], "alive");
+        assert!(rows[1]["id"].is_null());
+
+        let mut out = vec![];
+        let empty = futures::stream::iter(vec![]);
+        export(empty, ExportFormat::Json, &mut out).await.unwrap();
+        assert_eq!(out, b"[]\n");
+    }
+}
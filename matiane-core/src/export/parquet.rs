@@ -0,0 +1,171 @@
+//! Parquet files written column by column, without Arrow, a row group of
+//! events at a time.
+
+use super::{ExportError, Row};
+use crate::store::EventReaderResult;
+use ::parquet::data_type::{ByteArray, ByteArrayType, DataType};
+use ::parquet::data_type::{Int32Type, Int64Type};
+use ::parquet::errors::ParquetError;
+use ::parquet::file::properties::WriterProperties;
+use ::parquet::file::writer::{SerializedFileWriter, SerializedRowGroupWriter};
+use ::parquet::schema::parser::parse_message_type;
+use futures::{Stream, TryStreamExt};
+use std::io::Write;
+use std::pin::pin;
+use std::sync::Arc;
+
+/// Columns of `super::COLUMNS`, timestamps in microseconds.
+const SCHEMA: &str = "
+    message event {
+        REQUIRED INT64 timestamp (TIMESTAMP(MICROS, true));
+        REQUIRED BINARY type (STRING);
+        OPTIONAL BINARY title (STRING);
+        OPTIONAL BINARY id (STRING);
+        OPTIONAL INT32 pid;
+    }
+";
+
+/// Events kept in memory before they are written.
+const ROW_GROUP_SIZE: usize = 100_000;
+
+pub(super) async fn write<W: Write + Send>(
+    rows: impl Stream<Item = EventReaderResult<Row>>,
+    out: W,
+) -> Result<usize, ExportError> {
+    let mut rows = pin!(rows);
+    let schema = Arc::new(parse_message_type(SCHEMA)?);
+    let properties = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(out, schema, properties)?;
+
+    let mut group = Vec::with_capacity(ROW_GROUP_SIZE);
+    let mut written = 0;
+
+    while let Some(row) = rows.try_next().await? {
+        group.push(row);
+
+        if group.len() == ROW_GROUP_SIZE {
+            write_row_group(&mut writer, &group)?;
+            written += group.len();
+            group.clear();
+        }
+    }
+
+    if !group.is_empty() {
+        write_row_group(&mut writer, &group)?;
+        written += group.len();
+    }
+
+    writer.close()?;
+    Ok(written)
+}
+
+fn write_row_group<W: Write + Send>(
+    writer: &mut SerializedFileWriter<W>,
+    rows: &[Row],
+) -> Result<(), ExportError> {
+    let mut group = writer.next_row_group()?;
+
+    // In the order of the schema.
+    let timestamps: Vec<_> = rows
+        .iter()
+        .map(|row| row.timestamp.timestamp_micros())
+        .collect();
+    write_column::<Int64Type, _>(&mut group, &timestamps, None)?;
+
+    let kinds: Vec<_> =
+        rows.iter().map(|row| ByteArray::from(row.kind)).collect();
+    write_column::<ByteArrayType, _>(&mut group, &kinds, None)?;
+
+    let (titles, levels) =
+        optional(rows, |row| row.title.as_deref().map(ByteArray::from));
+    write_column::<ByteArrayType, _>(&mut group, &titles, Some(&levels))?;
+
+    let (ids, levels) =
+        optional(rows, |row| row.id.as_deref().map(ByteArray::from));
+    write_column::<ByteArrayType, _>(&mut group, &ids, Some(&levels))?;
+
+    let (pids, levels) = optional(rows, |row| row.pid);
+    write_column::<Int32Type, _>(&mut group, &pids, Some(&levels))?;
+
+    group.close()?;
+    Ok(())
+}
+
+/// Values of the rows that have one, and whether each row has one as
+/// definition levels.
+fn optional<T>(
+    rows: &[Row],
+    value: impl Fn(&Row) -> Option<T>,
+) -> (Vec<T>, Vec<i16>) {
+    let mut values = vec![];
+    let levels = rows
+        .iter()
+        .map(|row| match value(row) {
+            Some(v) => {
+                values.push(v);
+                1
+            }
+            None => 0,
+        })
+        .collect();
+
+    (values, levels)
+}
+
+fn write_column<T: DataType, W: Write + Send>(
+    group: &mut SerializedRowGroupWriter<'_, W>,
+    values: &[T::T],
+    levels: Option<&[i16]>,
+) -> Result<(), ExportError> {
+    let mut column = group
+        .next_column()?
+        .ok_or_else(|| ParquetError::General("missing column".into()))?;
+
+    column.typed::<T>().write_batch(values, levels, None)?;
+    column.close()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Event, Focused, TimedEvent};
+    use ::parquet::file::reader::{FileReader, SerializedFileReader};
+    use chrono::{TimeZone, Utc};
+
+    #[tokio::test]
+    async fn export_parquet_test() {
+        let timestamp = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let focused = Event::Focused(Box::new(Focused {
+            title: "a title".to_string(),
+            id: "a".to_string(),
+            pid: 12,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }));
+        let rows = [focused, Event::Alive]
+            .map(|event| Ok(Row::from(TimedEvent { timestamp, event })));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.parquet");
+        let file = std::fs::File::create(&path).unwrap();
+        let written = write(futures::stream::iter(rows), file).await.unwrap();
+        assert_eq!(written, 2);
+
+        let reader =
+            SerializedFileReader::new(std::fs::File::open(&path).unwrap())
+                .unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 2);
+
+        let columns: Vec<_> = metadata
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect();
+        assert_eq!(columns, super::super::COLUMNS);
+    }
+}
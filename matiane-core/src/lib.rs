@@ -1,10 +1,23 @@
 pub const NAME: &str = "matiane";
 
+pub mod activity;
+pub mod aggregate;
 pub mod args;
+#[cfg(feature = "async")]
+pub mod bus;
+pub mod categories;
+#[cfg(feature = "async")]
+pub mod client;
 pub mod config;
+pub mod diagnostic;
 pub mod events;
+#[cfg(feature = "async")]
+pub mod export;
 pub mod log;
+#[cfg(feature = "async")]
 pub mod process;
+pub mod sessions;
 pub mod store;
+pub mod timer;
 pub mod util;
 pub mod xdg;
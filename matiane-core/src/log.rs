@@ -1,16 +1,163 @@
+use anyhow::Context;
 use chrono::Local;
+use journald::Journald;
+use log::kv::{self, Key, Value, VisitSource};
 use log::{LevelFilter, Log, Metadata, Record};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, RwLock};
+
+mod journald;
+
+/// The logger set by `init_global_logger`.
+static GLOBAL_LOGGER: OnceLock<&'static Logger> = OnceLock::new();
 
 pub struct Logger {
     level: LevelFilter,
     stderr: bool,
     stdout: bool,
     thread: bool,
+    file: Option<Mutex<RotatingFile>>,
+    journald: Option<Journald>,
+    /// Levels of modules, longest module first.
+    filters: RwLock<Vec<(String, LevelFilter)>>,
+}
+
+/// Where logs go besides stderr.
+#[derive(Debug, Default, Clone)]
+pub struct LogOptions {
+    pub file: Option<LogFile>,
+    /// Log to journald with this identifier instead of stderr.
+    pub journald: Option<String>,
+    /// Module path, e.g. `matiane_core::store`, to the level of its logs
+    /// and the logs of its submodules.
+    pub filters: Vec<(String, LevelFilter)>,
+}
+
+/// Log file rotated once it grows past `max_size` bytes. Rotated files are
+/// kept as `<path>.1`, the newest, up to `<path>.<keep>`.
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    pub path: PathBuf,
+    pub max_size: u64,
+    pub keep: usize,
+}
+
+struct RotatingFile {
+    config: LogFile,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(config: LogFile) -> std::io::Result<Self> {
+        if let Some(dir) = config.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config.path)?;
+        let size = file.metadata()?.len();
+
+        Ok(RotatingFile { config, file, size })
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = line.len() as u64 + 1;
+
+        if self.size > 0 && self.size + len > self.config.max_size {
+            self.rotate()?;
+        }
+
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let rotated = |n: usize| {
+            let mut path = self.config.path.clone().into_os_string();
+            path.push(format!(".{}", n));
+            PathBuf::from(path)
+        };
+
+        let keep = self.config.keep;
+
+        if keep == 0 {
+            std::fs::remove_file(&self.config.path)?;
+        } else {
+            for n in (1..keep).rev() {
+                if rotated(n).exists() {
+                    std::fs::rename(rotated(n), rotated(n + 1))?;
+                }
+            }
+
+            std::fs::rename(&self.config.path, rotated(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.config.path)?;
+        self.size = 0;
+
+        Ok(())
+    }
+}
+
+impl Logger {
+    /// Level of the most specific filter of `target`.
+    fn level(&self, target: &str) -> LevelFilter {
+        let Ok(filters) = self.filters.read() else {
+            return self.level;
+        };
+
+        filters
+            .iter()
+            .find(|(module, _)| {
+                target.strip_prefix(module.as_str()).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with("::")
+                })
+            })
+            .map_or(self.level, |(_, level)| *level)
+    }
+
+    /// Most verbose level of any module.
+    pub fn max_level(&self) -> LevelFilter {
+        let Ok(filters) = self.filters.read() else {
+            return self.level;
+        };
+
+        filters
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level, Ord::max)
+    }
+
+    /// Replace the levels of modules.
+    pub fn set_filters(&self, filters: Vec<(String, LevelFilter)>) {
+        if let Ok(mut current) = self.filters.write() {
+            *current = sorted_filters(filters);
+        }
+    }
+}
+
+/// Filters with the longest modules first, so the most specific one is
+/// found first.
+fn sorted_filters(
+    mut filters: Vec<(String, LevelFilter)>,
+) -> Vec<(String, LevelFilter)> {
+    filters.sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+    filters
 }
 
 impl Log for Logger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level().to_level_filter() <= self.level
+        metadata.level().to_level_filter() <= self.level(metadata.target())
     }
 
     fn log(&self, record: &Record) {
@@ -35,13 +182,17 @@ impl Log for Logger {
             "".to_string()
         };
 
+        let mut key_values = KeyValues(String::new());
+        let _ = record.key_values().visit(&mut key_values);
+
         let formatted = format!(
-            "{} {:<7} {}{} {}",
+            "{} {:<7} {}{} {}{}",
             timestamp,
             level,
             thread,
             target,
-            record.args()
+            record.args(),
+            key_values.0
         );
 
         if self.stderr {
@@ -51,9 +202,40 @@ impl Log for Logger {
         if self.stdout {
             println!("{}", formatted);
         }
+
+        // There is nowhere to report failures to, the line is lost.
+        if let Some(journald) = &self.journald {
+            let _ = journald.send(record);
+        }
+
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = file.write_line(&formatted);
+        }
     }
 
-    fn flush(&self) {}
+    fn flush(&self) {
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+/// Key values of a record as ` key=value` pairs.
+struct KeyValues(String);
+
+impl<'kvs> VisitSource<'kvs> for KeyValues {
+    fn visit_pair(
+        &mut self,
+        key: Key<'kvs>,
+        value: Value<'kvs>,
+    ) -> Result<(), kv::Error> {
+        self.0.push_str(&format!(" {}={}", key, value));
+        Ok(())
+    }
 }
 
 pub struct LoggerBuilder {
@@ -61,6 +243,9 @@ pub struct LoggerBuilder {
     stderr: bool,
     stdout: bool,
     thread: bool,
+    file: Option<LogFile>,
+    journald: Option<String>,
+    filters: Vec<(String, LevelFilter)>,
 }
 
 impl Default for LoggerBuilder {
@@ -70,6 +255,9 @@ impl Default for LoggerBuilder {
             stderr: false,
             stdout: false,
             thread: false,
+            file: None,
+            journald: None,
+            filters: vec![],
         }
     }
 }
@@ -99,27 +287,146 @@ impl LoggerBuilder {
         self
     }
 
-    pub fn build(self) -> Logger {
-        Logger {
+    pub fn to_file(mut self, file: Option<LogFile>) -> Self {
+        self.file = file;
+        self
+    }
+
+    pub fn to_journald(mut self, identifier: Option<String>) -> Self {
+        self.journald = identifier;
+        self
+    }
+
+    pub fn with_filters(mut self, filters: Vec<(String, LevelFilter)>) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    pub fn build(self) -> std::io::Result<Logger> {
+        let file = match self.file {
+            Some(file) => Some(Mutex::new(RotatingFile::open(file)?)),
+            None => None,
+        };
+
+        let journald = match self.journald {
+            Some(identifier) => Some(Journald::connect(identifier)?),
+            None => None,
+        };
+
+        Ok(Logger {
+            filters: RwLock::new(sorted_filters(self.filters)),
             level: self.level,
             stderr: self.stderr,
             stdout: self.stdout,
             thread: self.thread,
-        }
+            file,
+            journald,
+        })
     }
 }
 
 pub fn init_global_logger(
     level: LevelFilter,
-) -> Result<(), log::SetLoggerError> {
+    options: LogOptions,
+) -> anyhow::Result<()> {
     let logger = LoggerBuilder::new()
         .with_level(level)
-        .to_stderr(true)
+        .to_stderr(options.journald.is_none())
         .with_threads(true)
-        .build();
+        .to_file(options.file)
+        .to_journald(options.journald)
+        .with_filters(options.filters)
+        .build()
+        .context("Failed to open the log")?;
 
-    log::set_boxed_logger(Box::new(logger))?;
-    log::set_max_level(level);
+    let logger: &'static Logger = Box::leak(Box::new(logger));
+    log::set_logger(logger)?;
+    log::set_max_level(logger.max_level());
+    let _ = GLOBAL_LOGGER.set(logger);
 
     Ok(())
 }
+
+/// Replace the levels of modules of the global logger, e.g. after the
+/// config was reloaded. Does nothing before `init_global_logger`.
+pub fn set_global_filters(filters: Vec<(String, LevelFilter)>) {
+    if let Some(logger) = GLOBAL_LOGGER.get() {
+        logger.set_filters(filters);
+        log::set_max_level(logger.max_level());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_test() {
+        let logger = LoggerBuilder::new()
+            .with_level(LevelFilter::Info)
+            .with_filters(vec![
+                ("sway_matiane".to_string(), LevelFilter::Debug),
+                ("sway_matiane::sway".to_string(), LevelFilter::Trace),
+                ("matiane_core::store".to_string(), LevelFilter::Warn),
+            ])
+            .build()
+            .unwrap();
+
+        assert_eq!(logger.level("sway_matiane::sway"), LevelFilter::Trace);
+        assert_eq!(
+            logger.level("sway_matiane::sway::connection"),
+            LevelFilter::Trace
+        );
+        assert_eq!(logger.level("sway_matiane::sink"), LevelFilter::Debug);
+        assert_eq!(logger.level("sway_matiane_x"), LevelFilter::Info);
+        assert_eq!(
+            logger.level("matiane_core::store::read"),
+            LevelFilter::Warn
+        );
+        assert_eq!(logger.level("matiane_core::process"), LevelFilter::Info);
+        assert_eq!(logger.max_level(), LevelFilter::Trace);
+
+        logger.set_filters(vec![(
+            "sway_matiane::sway".to_string(),
+            LevelFilter::Error,
+        )]);
+        assert_eq!(logger.level("sway_matiane::sway"), LevelFilter::Error);
+        assert_eq!(logger.level("sway_matiane::sink"), LevelFilter::Info);
+        assert_eq!(logger.max_level(), LevelFilter::Info);
+    }
+
+    #[test]
+    fn rotate_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log/test.log");
+        let read = |name: &str| {
+            std::fs::read_to_string(dir.path().join("log").join(name)).ok()
+        };
+
+        let mut file = RotatingFile::open(LogFile {
+            path: path.clone(),
+            max_size: 10,
+            keep: 2,
+        })
+        .unwrap();
+
+        for line in ["aaaa", "bbbb", "cccc", "dddd", "eeeeeeeeeeeeeee", "f"] {
+            file.write_line(line).unwrap();
+        }
+
+        assert_eq!(read("test.log").as_deref(), Some("f\n"));
+        assert_eq!(read("test.log.1").as_deref(), Some("eeeeeeeeeeeeeee\n"));
+        assert_eq!(read("test.log.2").as_deref(), Some("cccc\ndddd\n"));
+        assert_eq!(read("test.log.3"), None);
+
+        let mut file = RotatingFile::open(LogFile {
+            path,
+            max_size: 10,
+            keep: 0,
+        })
+        .unwrap();
+        file.write_line("gggggggg").unwrap();
+
+        assert_eq!(read("test.log").as_deref(), Some("gggggggg\n"));
+    }
+}
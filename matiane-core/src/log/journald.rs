@@ -0,0 +1,136 @@
+//! Native journald protocol: https://systemd.io/JOURNAL_NATIVE_PROTOCOL/
+
+use log::kv::{self, Key, Value, VisitSource};
+use log::{Level, Record};
+use std::os::unix::net::UnixDatagram;
+
+const SOCKET: &str = "/run/systemd/journal/socket";
+
+pub struct Journald {
+    socket: UnixDatagram,
+    identifier: String,
+}
+
+impl Journald {
+    pub fn connect(identifier: String) -> std::io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(SOCKET)?;
+
+        Ok(Journald { socket, identifier })
+    }
+
+    pub fn send(&self, record: &Record) -> std::io::Result<()> {
+        self.socket.send(&entry(&self.identifier, record))?;
+        Ok(())
+    }
+}
+
+fn priority(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// Journal entry of `record`, its key values become fields, e.g.
+/// `app_id` becomes `APP_ID`.
+fn entry(identifier: &str, record: &Record) -> Vec<u8> {
+    let mut entry = vec![];
+
+    field(
+        &mut entry,
+        "PRIORITY",
+        &priority(record.level()).to_string(),
+    );
+    field(&mut entry, "MESSAGE", &record.args().to_string());
+    field(&mut entry, "SYSLOG_IDENTIFIER", identifier);
+    field(&mut entry, "TARGET", record.target());
+
+    if let Some(file) = record.file() {
+        field(&mut entry, "CODE_FILE", file);
+    }
+
+    if let Some(line) = record.line() {
+        field(&mut entry, "CODE_LINE", &line.to_string());
+    }
+
+    let _ = record.key_values().visit(&mut Fields(&mut entry));
+
+    entry
+}
+
+struct Fields<'a>(&'a mut Vec<u8>);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(
+        &mut self,
+        key: Key<'kvs>,
+        value: Value<'kvs>,
+    ) -> Result<(), kv::Error> {
+        // Field names are upper case letters, digits and underscores and
+        // start with a letter.
+        let name: String = key
+            .as_str()
+            .trim_start_matches(|c: char| !c.is_ascii_alphabetic())
+            .chars()
+            .map(|c| match c.is_ascii_alphanumeric() {
+                true => c.to_ascii_uppercase(),
+                false => '_',
+            })
+            .collect();
+
+        if !name.is_empty() {
+            field(self.0, &name, &value.to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Values with newlines are sent with their length instead of after `=`.
+fn field(entry: &mut Vec<u8>, name: &str, value: &str) {
+    entry.extend_from_slice(name.as_bytes());
+
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_test() {
+        let key_values = [("app_id", "foot"), ("2-event type", "focused")];
+        let entry = entry(
+            "sway-matiane",
+            &Record::builder()
+                .args(format_args!("a\nb"))
+                .level(Level::Warn)
+                .target("sway_matiane::sink")
+                .key_values(&key_values)
+                .build(),
+        );
+
+        let mut expected = b"PRIORITY=4\nMESSAGE\n".to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(
+            b"a\nb\n\
+              SYSLOG_IDENTIFIER=sway-matiane\n\
+              TARGET=sway_matiane::sink\n\
+              APP_ID=foot\n\
+              EVENT_TYPE=focused\n",
+        );
+
+        assert_eq!(entry, expected);
+    }
+}
@@ -1,12 +1,13 @@
-use log::debug;
+use log::{debug, info, warn};
 use std::io;
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command;
 use tokio::sync::Mutex;
 use tokio::task::{JoinHandle, spawn};
-use tokio::time::sleep;
+use tokio::time::{Instant, sleep};
 use tokio_util::sync::CancellationToken;
 
 pub struct AlwaysCommandOptions {
@@ -25,9 +26,63 @@ impl Default for AlwaysCommandOptions {
     }
 }
 
+/// When a supervised process is started again after it exits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    Always,
+    /// Only after a non-zero exit or a signal.
+    OnFailure,
+    Never,
+}
+
+pub struct SupervisorOptions {
+    pub name: String,
+    pub args: Vec<String>,
+    pub restart: RestartPolicy,
+    /// Delay of the first restart, doubled on every restart up to
+    /// `max_delay`.
+    pub restart_delay: Duration,
+    pub max_delay: Duration,
+    /// A run at least this long is stable, it resets the delay and the
+    /// restarts in a row.
+    pub reset_after: Duration,
+    /// Restarts in a row after which the supervisor gives up.
+    pub max_restarts: Option<u32>,
+    /// Log what the process prints, stdout as info and stderr as warnings.
+    pub capture_output: bool,
+}
+
+impl Default for SupervisorOptions {
+    fn default() -> Self {
+        SupervisorOptions {
+            name: Default::default(),
+            args: Default::default(),
+            restart: RestartPolicy::Always,
+            restart_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            reset_after: Duration::from_secs(60),
+            max_restarts: None,
+            capture_output: false,
+        }
+    }
+}
+
+impl From<AlwaysCommandOptions> for SupervisorOptions {
+    fn from(opts: AlwaysCommandOptions) -> Self {
+        SupervisorOptions {
+            name: opts.name,
+            args: opts.args,
+            restart_delay: opts.restart_delay,
+            max_delay: opts.restart_delay,
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct RunningStatus {
     pub pid: u32,
+    pub restarts: u32,
 }
 
 /// Will kill process on drop.
@@ -43,72 +98,156 @@ impl Drop for RunningHandle {
     }
 }
 
-pub fn run_always_command(
-    opts: AlwaysCommandOptions,
-    token: CancellationToken,
-) -> RunningHandle {
-    let status = Arc::new(Mutex::new(RunningStatus::default()));
-    let spawned_status = status.clone();
-    let cancel = token.clone();
+/// Runs a command and restarts it by its [`RestartPolicy`] until the token
+/// is cancelled.
+pub struct Supervisor {
+    opts: SupervisorOptions,
+}
 
-    let handle = spawn(async move {
-        let AlwaysCommandOptions {
+impl Supervisor {
+    pub fn new(opts: SupervisorOptions) -> Self {
+        Supervisor { opts }
+    }
+
+    pub fn spawn(self, token: CancellationToken) -> RunningHandle {
+        let status = Arc::new(Mutex::new(RunningStatus::default()));
+        let cancel = token.clone();
+        let handle = spawn(self.run(status.clone(), token));
+
+        RunningHandle {
+            handle,
+            status,
+            cancel,
+        }
+    }
+
+    async fn run(
+        self,
+        status: Arc<Mutex<RunningStatus>>,
+        token: CancellationToken,
+    ) -> Result<(), io::Error> {
+        let SupervisorOptions {
             name,
             args,
+            restart,
             restart_delay,
-        } = opts;
+            max_delay,
+            reset_after,
+            max_restarts,
+            capture_output,
+        } = self.opts;
+
+        let mut delay = restart_delay;
+        let mut restarts = 0;
 
-        loop {
+        let result = loop {
             if token.is_cancelled() {
-                break;
+                break Ok(());
             }
 
             debug!("Starting command: {}, args: {:?}", &name, &args);
+            let output = match capture_output {
+                true => Stdio::piped,
+                false => Stdio::inherit,
+            };
             let mut child = Command::new(&name)
                 .args(&args)
                 .kill_on_drop(true)
                 .stdin(Stdio::null())
+                .stdout(output())
+                .stderr(output())
                 .spawn()?;
 
+            if let Some(stdout) = child.stdout.take() {
+                spawn(log_lines(name.clone(), stdout, false));
+            }
+
+            if let Some(stderr) = child.stderr.take() {
+                spawn(log_lines(name.clone(), stderr, true));
+            }
+
             {
-                let mut locked = spawned_status.lock().await;
+                let mut locked = status.lock().await;
                 locked.pid = child.id().unwrap_or(0);
                 debug!("Running command pid: {}", locked.pid);
             }
 
-            tokio::select! {
-                status = child.wait() => {
-                    spawned_status.lock().await.pid = 0;
-
-                    match status {
-                        Ok(code) => debug!("Process exitted with {} code.", code),
-                        Err(e) => debug!("Process exitted with {} error", e),
-                    }
-
-                    debug!("Command is done or was killed, restarting in a sec...");
-                    tokio::select! {
-                        _ = sleep(restart_delay) => {},
-                        _ = token.cancelled() => {
-                            break;
-                        }
-                    }
-                },
-                _ = token.cancelled() => {
-                    break;
+            let started = Instant::now();
+
+            let exit = tokio::select! {
+                exit = child.wait() => exit,
+                _ = token.cancelled() => break Ok(()),
+            };
+
+            status.lock().await.pid = 0;
+
+            let failed = match exit {
+                Ok(code) => {
+                    debug!("Process exitted with {} code.", code);
+                    !code.success()
                 }
+                Err(e) => {
+                    debug!("Process exitted with {} error", e);
+                    true
+                }
+            };
+
+            let restart = match restart {
+                RestartPolicy::Always => true,
+                RestartPolicy::OnFailure => failed,
+                RestartPolicy::Never => false,
+            };
+
+            if !restart {
+                break Ok(());
             }
 
-            spawned_status.lock().await.pid = 0;
-        }
+            if started.elapsed() >= reset_after {
+                delay = restart_delay;
+                restarts = 0;
+            }
+
+            if max_restarts.is_some_and(|max| restarts >= max) {
+                break Err(io::Error::other(format!(
+                    "{} exited {} times in a row, giving up",
+                    name,
+                    restarts + 1
+                )));
+            }
 
-        spawned_status.lock().await.pid = 0;
+            restarts += 1;
+            status.lock().await.restarts += 1;
 
-        Ok(())
-    });
+            debug!("Command is done or was killed, restarting in {:?}.", delay);
+            tokio::select! {
+                _ = sleep(delay) => {},
+                _ = token.cancelled() => break Ok(()),
+            }
+
+            delay = (delay * 2).min(max_delay);
+        };
+
+        status.lock().await.pid = 0;
 
-    RunningHandle {
-        handle,
-        status,
-        cancel,
+        result
     }
 }
+
+async fn log_lines(name: String, output: impl AsyncRead + Unpin, stderr: bool) {
+    let mut lines = BufReader::new(output).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        match stderr {
+            true => warn!("{}: {}", name, line),
+            false => info!("{}: {}", name, line),
+        }
+    }
+}
+
+/// Runs the command again whenever it exits, after `restart_delay`.
+pub fn run_always_command(
+    opts: AlwaysCommandOptions,
+    token: CancellationToken,
+) -> RunningHandle {
+    Supervisor::new(opts.into()).spawn(token)
+}
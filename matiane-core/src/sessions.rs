@@ -0,0 +1,690 @@
+//! Focus sessions folded from the event stream: periods of one window
+//! being focused while the user was there.
+//!
+//! A session ends when the focus changes or the user goes idle or asleep,
+//! and starts again with the same window when they are back. Events more
+//! than [`MAX_EVENT_GAP`] apart mean the daemon was not running or the
+//! machine was suspended, a session going on then ends at the last event
+//! before the gap. The gaps can be kept as sessions of [`UNTRACKED_APP`],
+//! and the periods of being idle or asleep as sessions of [`AWAY_APP`].
+
+use crate::activity::{MAX_EVENT_GAP, day_of};
+use crate::events::{Event, Focused, TimedEvent};
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// `app` of the gaps between events, nothing is known about them.
+pub const UNTRACKED_APP: &str = "untracked";
+
+/// `app` of the periods of being idle or asleep while the daemon was
+/// running.
+pub const AWAY_APP: &str = "away";
+
+/// Heartbeats of the daemon missed before nothing counts as tracked.
+const MISSED_HEARTBEATS: i32 = 3;
+
+/// Events further apart than this mean nothing was tracked between them,
+/// for a daemon writing heartbeats every `live_interval`. The default live
+/// interval gives [`MAX_EVENT_GAP`].
+pub fn max_gap(live_interval: Duration) -> TimeDelta {
+    TimeDelta::from_std(live_interval)
+        .ok()
+        .and_then(|interval| interval.checked_mul(MISSED_HEARTBEATS))
+        .unwrap_or(TimeDelta::MAX)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusSession {
+    pub app: String,
+    pub title: String,
+    /// Detail of the focused window, see [`Focused::detail`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Workspace the window was focused on, see [`Event::WorkspaceFocused`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl FocusSession {
+    pub fn duration(&self) -> TimeDelta {
+        self.end - self.start
+    }
+
+    /// A gap between events, see [`Sessionizer::with_untracked`].
+    pub fn is_untracked(&self) -> bool {
+        self.app == UNTRACKED_APP
+    }
+
+    /// A period of being away, see [`Sessionizer::with_away`].
+    pub fn is_away(&self) -> bool {
+        self.app == AWAY_APP
+    }
+
+    /// The session cut at the midnights of `tz`, one part per day.
+    pub fn split_days<Tz: TimeZone>(&self, tz: &Tz) -> Vec<FocusSession> {
+        let mut parts = vec![];
+        let mut start = self.start;
+
+        while start < self.end {
+            let (_, next_day) = day_of(start, tz);
+            let end = self.end.min(next_day);
+
+            parts.push(FocusSession {
+                start,
+                end,
+                ..self.clone()
+            });
+            start = end;
+        }
+
+        parts
+    }
+}
+
+/// Folds events into sessions as they come, in the order they were
+/// written.
+#[derive(Debug)]
+pub struct Sessionizer {
+    focused: Option<Focused>,
+    workspace: Option<String>,
+    since: Option<DateTime<Utc>>,
+    inactive: bool,
+    away_since: Option<DateTime<Utc>>,
+    last: Option<DateTime<Utc>>,
+    max_gap: TimeDelta,
+    untracked: bool,
+    away: bool,
+}
+
+impl Default for Sessionizer {
+    fn default() -> Self {
+        Sessionizer {
+            focused: None,
+            workspace: None,
+            since: None,
+            inactive: false,
+            away_since: None,
+            last: None,
+            max_gap: MAX_EVENT_GAP,
+            untracked: false,
+            away: false,
+        }
+    }
+}
+
+impl Sessionizer {
+    /// Events further apart than `max_gap` mean nothing was tracked between
+    /// them, see [`max_gap`]. Defaults to [`MAX_EVENT_GAP`].
+    pub fn with_max_gap(mut self, max_gap: TimeDelta) -> Self {
+        self.max_gap = max_gap;
+        self
+    }
+
+    /// Also return the gaps between events as sessions of
+    /// [`UNTRACKED_APP`], for reports to show them.
+    pub fn with_untracked(mut self, untracked: bool) -> Self {
+        self.untracked = untracked;
+        self
+    }
+
+    /// Also return the periods of being idle or asleep as sessions of
+    /// [`AWAY_APP`].
+    pub fn with_away(mut self, away: bool) -> Self {
+        self.away = away;
+        self
+    }
+
+    /// Sessionizer in the state after the event at `previous`, e.g. from an
+    /// index entry, a session or away period going on starts there.
+    pub fn resume(
+        previous: Option<DateTime<Utc>>,
+        focused: Option<&Focused>,
+        workspace: Option<&str>,
+        inactive: bool,
+    ) -> Self {
+        let mut sessionizer = Sessionizer {
+            focused: focused.cloned(),
+            workspace: workspace.map(str::to_string),
+            inactive,
+            last: previous,
+            ..Default::default()
+        };
+
+        if let Some(previous) = previous {
+            if inactive {
+                sessionizer.away_since = Some(previous);
+            } else {
+                sessionizer.start(previous);
+            }
+        }
+
+        sessionizer
+    }
+
+    /// Returns the session the event ended, or the period of a backfilled
+    /// event, and the gap before the event and the away period it ended
+    /// when they are kept.
+    pub fn push(
+        &mut self,
+        event: &TimedEvent,
+    ) -> impl Iterator<Item = FocusSession> + use<> {
+        let at = event.timestamp;
+
+        // Backfilled periods stand on their own and are no sign of life.
+        if let Event::Backfilled(backfilled) = &event.event {
+            let period = (backfilled.end > at).then(|| FocusSession {
+                app: backfilled.app.clone(),
+                title: backfilled.title.clone(),
+                detail: None,
+                workspace: None,
+                start: at,
+                end: backfilled.end,
+            });
+            return [period, None, None].into_iter().flatten();
+        }
+
+        let mut ended = None;
+        let mut gap = None;
+        let mut away = None;
+
+        if let Some(last) = self.last
+            && at - last > self.max_gap
+        {
+            ended = self.end(last);
+            away = self.come_back(last);
+            gap = self.untracked.then(|| FocusSession {
+                app: UNTRACKED_APP.to_string(),
+                title: String::new(),
+                detail: None,
+                workspace: None,
+                start: last,
+                end: at,
+            });
+        }
+
+        // Still idle or asleep after a gap, away again from here.
+        if self.inactive {
+            self.away_since.get_or_insert(at);
+        }
+
+        match &event.event {
+            Event::Focused(focused) => {
+                ended = ended.or_else(|| self.end(at));
+                self.focused = Some(Focused::clone(focused));
+                self.start(at);
+            }
+            Event::WorkspaceFocused(workspace)
+                if self.workspace.as_ref() != Some(&workspace.name) =>
+            {
+                ended = ended.or_else(|| self.end(at));
+                self.workspace = Some(workspace.name.clone());
+                self.start(at);
+            }
+            Event::Alive | Event::Present(_) => self.start(at),
+            Event::Idle | Event::Sleep => {
+                ended = ended.or_else(|| self.end(at));
+                self.away_since.get_or_insert(at);
+                self.inactive = true;
+            }
+            Event::Active | Event::Awake => {
+                away = away.or_else(|| self.come_back(at));
+                self.inactive = false;
+                self.start(at);
+            }
+            Event::TrackingPaused | Event::Shutdown => {
+                ended = ended.or_else(|| self.end(at));
+                self.focused = None;
+            }
+            Event::Started(_) => {
+                ended = ended.or_else(|| self.end(at));
+                away = away.or_else(|| self.come_back(at));
+                self.focused = None;
+                self.inactive = false;
+            }
+            Event::Backfilled(_)
+            | Event::StorageDegraded(_)
+            | Event::WorkspaceFocused(_)
+            | Event::OutputChanged
+            | Event::Annotation(_)
+            | Event::TimerStarted(_)
+            | Event::TimerEnded(_)
+            | Event::TrackingResumed
+            | Event::Unknown(_) => {}
+        }
+
+        // Compacted heartbeats are a sign of life up to their end.
+        self.last = Some(match &event.event {
+            Event::Present(present) => present.end,
+            _ => at,
+        });
+
+        [ended, gap, away].into_iter().flatten()
+    }
+
+    /// The session still going on at `now`. It ends at the last event when
+    /// that is more than the max gap before `now`.
+    pub fn current(&self, now: DateTime<Utc>) -> Option<FocusSession> {
+        self.session(self.end_at(now)?)
+    }
+
+    /// The session and the away period, when it is kept, still going on at
+    /// `now`. Like [`Sessionizer::current`] they end at the last event when
+    /// that is more than the max gap before `now`.
+    pub fn ongoing(
+        &self,
+        now: DateTime<Utc>,
+    ) -> impl Iterator<Item = FocusSession> + use<> {
+        let end = self.end_at(now);
+        let away = end.and_then(|end| self.away_session(end));
+
+        [end.and_then(|end| self.session(end)), away]
+            .into_iter()
+            .flatten()
+    }
+
+    /// Focused window, if any.
+    pub fn focused(&self) -> Option<&Focused> {
+        self.focused.as_ref()
+    }
+
+    /// Start of the session going on, it may still be empty.
+    pub fn since(&self) -> Option<DateTime<Utc>> {
+        self.since
+    }
+
+    /// Time of the last event, the end of compacted heartbeats.
+    pub fn last(&self) -> Option<DateTime<Utc>> {
+        self.last
+    }
+
+    fn end_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let last = self.last?;
+
+        Some(if now - last <= self.max_gap {
+            now
+        } else {
+            last
+        })
+    }
+
+    fn start(&mut self, at: DateTime<Utc>) {
+        if !self.inactive && self.focused.is_some() {
+            self.since.get_or_insert(at);
+        }
+    }
+
+    fn end(&mut self, at: DateTime<Utc>) -> Option<FocusSession> {
+        let session = self.session(at);
+        self.since = None;
+        session
+    }
+
+    fn come_back(&mut self, at: DateTime<Utc>) -> Option<FocusSession> {
+        let session = self.away_session(at);
+        self.away_since = None;
+        session
+    }
+
+    fn away_session(&self, end: DateTime<Utc>) -> Option<FocusSession> {
+        let start = self.away_since.filter(|_| self.away)?;
+
+        (end > start).then(|| FocusSession {
+            app: AWAY_APP.to_string(),
+            title: String::new(),
+            detail: None,
+            workspace: None,
+            start,
+            end,
+        })
+    }
+
+    fn session(&self, end: DateTime<Utc>) -> Option<FocusSession> {
+        let start = self.since?;
+        let focused = self.focused.as_ref()?;
+
+        (end > start).then(|| FocusSession {
+            app: focused.id.clone(),
+            title: focused.title.clone(),
+            detail: focused.detail.clone(),
+            workspace: self.workspace.clone(),
+            start,
+            end,
+        })
+    }
+}
+
+/// Sessions of `events` in the order they ended, the one still going on
+/// last, as of `now`.
+pub fn sessionize<'a>(
+    events: impl IntoIterator<Item = &'a TimedEvent>,
+    now: DateTime<Utc>,
+) -> Vec<FocusSession> {
+    sessionize_with(Sessionizer::default(), events, now)
+}
+
+/// `sessionize` with the settings of `sessionizer`.
+pub fn sessionize_with<'a>(
+    mut sessionizer: Sessionizer,
+    events: impl IntoIterator<Item = &'a TimedEvent>,
+    now: DateTime<Utc>,
+) -> Vec<FocusSession> {
+    let mut sessions: Vec<FocusSession> = events
+        .into_iter()
+        .flat_map(|event| sessionizer.push(event))
+        .collect();
+
+    sessions.extend(sessionizer.ongoing(now));
+    sessions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{
+        Backfilled, Focused, Present, Started, WorkspaceFocused,
+    };
+    use chrono::{FixedOffset, Timelike};
+
+    fn at(min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, 10, min, 0).unwrap()
+    }
+
+    fn focused(id: &str) -> Event {
+        Event::Focused(Box::new(Focused {
+            title: format!("{} title", id),
+            id: id.to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }))
+    }
+
+    fn sessions(
+        events: Vec<(u32, Event)>,
+        now: u32,
+    ) -> Vec<(String, u32, u32)> {
+        let events: Vec<TimedEvent> = events
+            .into_iter()
+            .map(|(min, event)| TimedEvent {
+                timestamp: at(min),
+                event,
+            })
+            .collect();
+
+        sessionize(&events, at(now))
+            .into_iter()
+            .map(|s| (s.app, s.start.minute(), s.end.minute()))
+            .collect()
+    }
+
+    fn session(app: &str, start: u32, end: u32) -> (String, u32, u32) {
+        (app.to_string(), start, end)
+    }
+
+    #[test]
+    fn sessionize_focus_and_idle() {
+        let sessions = sessions(
+            vec![
+                (0, Event::Awake),
+                (1, focused("a")),
+                (2, Event::Alive),
+                (3, focused("b")),
+                (4, Event::Idle),
+                (5, Event::Alive),
+                (6, Event::Active),
+                (8, focused("c")),
+                (9, Event::Alive),
+            ],
+            10,
+        );
+
+        assert_eq!(
+            sessions,
+            [
+                session("a", 1, 3),
+                session("b", 3, 4),
+                session("b", 6, 8),
+                session("c", 8, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn sessionize_paused_and_shutdown() {
+        let sessions = sessions(
+            vec![
+                (0, focused("a")),
+                (2, Event::TrackingPaused),
+                (5, Event::TrackingResumed),
+                (6, Event::Alive),
+                (7, focused("b")),
+                (8, Event::Shutdown),
+            ],
+            9,
+        );
+
+        assert_eq!(sessions, [session("a", 0, 2), session("b", 7, 8)]);
+    }
+
+    #[test]
+    fn sessionize_started() {
+        let started = || {
+            Event::Started(Box::new(Started {
+                version: "1.0.0".into(),
+                hostname: None,
+                boot_id: None,
+                compositor: "sway".into(),
+            }))
+        };
+        let sessions = sessions(
+            vec![
+                (0, focused("a")),
+                (1, Event::Idle),
+                (2, started()),
+                (2, focused("b")),
+                (4, started()),
+                (5, Event::Alive),
+            ],
+            6,
+        );
+
+        assert_eq!(sessions, [session("a", 0, 1), session("b", 2, 4)]);
+    }
+
+    #[test]
+    fn sessionize_workspaces() {
+        let workspace = |name: &str| {
+            Event::WorkspaceFocused(Box::new(WorkspaceFocused {
+                name: name.to_string(),
+                output: None,
+            }))
+        };
+        let events: Vec<TimedEvent> = [
+            (0, focused("a")),
+            (1, workspace("1")),
+            (2, workspace("1")),
+            (3, workspace("2")),
+            (3, focused("b")),
+        ]
+        .into_iter()
+        .map(|(min, event)| TimedEvent {
+            timestamp: at(min),
+            event,
+        })
+        .collect();
+
+        let sessions: Vec<_> = sessionize(&events, at(4))
+            .into_iter()
+            .map(|s| (s.app, s.workspace, s.start.minute(), s.end.minute()))
+            .collect();
+        let workspace = |name: &str| Some(name.to_string());
+
+        assert_eq!(
+            sessions,
+            [
+                ("a".to_string(), None, 0, 1),
+                ("a".to_string(), workspace("1"), 1, 3),
+                ("b".to_string(), workspace("2"), 3, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn sessionize_missing_heartbeats() {
+        let present = Event::Present(Present { end: at(20) });
+        let backfilled = Event::Backfilled(Box::new(Backfilled {
+            app: "calendar".to_string(),
+            title: "Meeting".to_string(),
+            source: "test".to_string(),
+            end: at(40),
+        }));
+
+        let sessions = sessions(
+            vec![
+                (0, focused("a")),
+                (1, Event::Alive),
+                // The daemon was not running.
+                (10, present),
+                (21, Event::Alive),
+                (30, backfilled),
+                (50, Event::Alive),
+            ],
+            59,
+        );
+
+        // The gap after 21 ends the session with the event after it, the
+        // one after 50 leaves nothing going on.
+        assert_eq!(
+            sessions,
+            [
+                session("a", 0, 1),
+                session("calendar", 30, 40),
+                session("a", 10, 21),
+            ]
+        );
+    }
+
+    #[test]
+    fn sessionize_untracked_gaps() {
+        let events: Vec<TimedEvent> = [
+            (0, focused("a")),
+            (1, Event::Alive),
+            // Suspended without a sleep event.
+            (30, Event::Alive),
+            (31, Event::Alive),
+            (33, Event::Alive),
+        ]
+        .into_iter()
+        .map(|(min, event)| TimedEvent {
+            timestamp: at(min),
+            event,
+        })
+        .collect();
+
+        let sessionizer = Sessionizer::default()
+            .with_max_gap(TimeDelta::seconds(90))
+            .with_untracked(true);
+        let sessions: Vec<_> = sessionize_with(sessionizer, &events, at(34))
+            .into_iter()
+            .map(|s| (s.is_untracked(), s.start.minute(), s.end.minute()))
+            .collect();
+
+        assert_eq!(
+            sessions,
+            [
+                (false, 0, 1),
+                (true, 1, 30),
+                (false, 30, 31),
+                (true, 31, 33),
+                (false, 33, 34),
+            ]
+        );
+
+        // By default only the suspend is a gap, and it is left out.
+        let sessions: Vec<_> = sessionize(&events, at(34))
+            .into_iter()
+            .map(|s| (s.start.minute(), s.end.minute()))
+            .collect();
+        assert_eq!(sessions, [(0, 1), (30, 34)]);
+    }
+
+    #[test]
+    fn sessionize_away() {
+        let events: Vec<TimedEvent> = [
+            (0, focused("a")),
+            (1, Event::Idle),
+            (2, Event::Alive),
+            // The daemon was not running, still idle after.
+            (10, Event::Alive),
+            (12, Event::Active),
+            (13, Event::Sleep),
+        ]
+        .into_iter()
+        .map(|(min, event)| TimedEvent {
+            timestamp: at(min),
+            event,
+        })
+        .collect();
+
+        let sessionizer = Sessionizer::default().with_away(true);
+        let sessions: Vec<_> = sessionize_with(sessionizer, &events, at(14))
+            .into_iter()
+            .map(|s| (s.is_away(), s.start.minute(), s.end.minute()))
+            .collect();
+
+        assert_eq!(
+            sessions,
+            [
+                (false, 0, 1),
+                (true, 1, 2),
+                (true, 10, 12),
+                (false, 12, 13),
+                (true, 13, 14),
+            ]
+        );
+    }
+
+    #[test]
+    fn max_gap_test() {
+        assert_eq!(max_gap(Duration::from_secs(60)), MAX_EVENT_GAP);
+        assert_eq!(max_gap(Duration::from_secs(20)), TimeDelta::minutes(1));
+        assert_eq!(max_gap(Duration::MAX), TimeDelta::MAX);
+    }
+
+    #[test]
+    fn split_days_test() {
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        let time = |d, h| Utc.with_ymd_and_hms(2026, 1, d, h, 0, 0).unwrap();
+        let session = FocusSession {
+            app: "a".to_string(),
+            title: String::new(),
+            detail: None,
+            workspace: None,
+            start: time(1, 20),
+            end: time(3, 1),
+        };
+
+        let parts: Vec<_> = session
+            .split_days(&tz)
+            .into_iter()
+            .map(|part| (part.start, part.end))
+            .collect();
+
+        assert_eq!(
+            parts,
+            [
+                (time(1, 20), time(1, 22)),
+                (time(1, 22), time(2, 22)),
+                (time(2, 22), time(3, 1)),
+            ]
+        );
+        assert_eq!(
+            parts.iter().map(|(s, e)| *e - *s).sum::<TimeDelta>(),
+            session.duration()
+        );
+    }
+}
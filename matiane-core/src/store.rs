@@ -1,16 +1,95 @@
+#[cfg(feature = "async")]
+mod backend;
+#[cfg(feature = "async")]
+mod compact;
+pub mod crypt;
+pub mod dayfile;
 mod filepath;
+mod format;
+#[cfg(feature = "async")]
+mod index;
+#[cfg(feature = "async")]
+mod insert;
 mod lock;
+#[cfg(feature = "async")]
+mod merge;
 mod read;
+#[cfg(feature = "async")]
+mod retention;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "async")]
+mod summary;
+#[cfg(feature = "async")]
+mod verify;
 mod write;
+mod zone;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod readline;
 
+#[cfg(feature = "async")]
+pub use backend::{DirStore, Store};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+#[cfg(feature = "async")]
 pub use write::EventWriter;
-pub use write::StoreWriteError;
+pub use write::{DEFAULT_MAX_PENDING, Durability, StoreWriteError};
+pub use zone::{DayZone, ZONE_FILE_NAME};
 
+#[cfg(feature = "async")]
+pub use compact::compact;
+#[cfg(feature = "async")]
+pub use index::{INDEX_EXTENSION, Index, IndexEntry, build_indexes};
+#[cfg(feature = "async")]
+pub use insert::{annotate, insert_events};
+#[cfg(feature = "async")]
+pub use merge::{HostEvent, MergeReport, merge};
+
+pub use filepath::COMPRESSED_EXTENSION;
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub use filepath::StorePath;
+pub use format::{
+    CBOR_HEADER, ENCRYPTED_HEADER, JSON_CRC32_HEADER, StoreFormat,
+};
+
+pub use lock::LOCK_FILE_NAME;
 pub use lock::LOCK_FILE_TIME_SEC;
 pub use lock::LockFile;
 pub use lock::LockFileError;
+pub use lock::LockHolder;
+pub use lock::MaintenanceLock;
+pub use lock::READ_LOCK_FILE_NAME;
+#[cfg(feature = "async")]
 pub use lock::acquire_lock_file;
+#[cfg(feature = "async")]
+pub use lock::acquire_lock_file_with;
+#[cfg(feature = "async")]
+pub use lock::acquire_maintenance_lock;
+#[cfg(feature = "async")]
+pub use lock::acquire_read_lock;
+#[cfg(feature = "blocking")]
+pub use lock::acquire_read_lock_blocking;
+#[cfg(feature = "async")]
+pub use lock::has_readers;
+#[cfg(feature = "async")]
+pub use lock::try_exclude_readers;
 
+#[cfg(feature = "async")]
 pub use read::EventReader;
+#[cfg(feature = "async")]
+pub use read::ReverseEventReader;
+pub use read::{
+    DecodeError, EventReaderResult, OnDecodeError, OpenAt, OpenOptions,
+    StoreReadError,
+};
+
+#[cfg(feature = "async")]
+pub use retention::Retention;
+
+#[cfg(feature = "async")]
+pub use summary::{DaySummary, SUMMARY_EXTENSION, read_summaries};
+
+#[cfg(feature = "async")]
+pub use verify::{Problem, ProblemKind, VerifyReport, verify};
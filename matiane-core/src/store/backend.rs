@@ -0,0 +1,77 @@
+//! Stores events can be kept in, the directory of day files or a database.
+
+use super::read::{EventReader, EventReaderResult, StoreReadError};
+use super::write::{EventWriter, StoreWriteError};
+use crate::events::TimedEvent;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use std::path::PathBuf;
+
+pub trait Store {
+    /// Append `event`, events come in time order.
+    fn write(
+        &mut self,
+        event: &TimedEvent,
+    ) -> impl Future<Output = Result<(), StoreWriteError>>;
+
+    fn flush(&mut self) -> impl Future<Output = Result<(), StoreWriteError>>;
+
+    /// Events in `[from, to)`, in time order.
+    fn read_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> impl Future<Output = EventReaderResult<Vec<TimedEvent>>>;
+}
+
+/// Day files of `dir`, written by an `EventWriter`.
+pub struct DirStore {
+    dir: PathBuf,
+    writer: Option<EventWriter>,
+}
+
+impl DirStore {
+    pub fn new(dir: PathBuf) -> Self {
+        DirStore { dir, writer: None }
+    }
+}
+
+impl Store for DirStore {
+    async fn write(
+        &mut self,
+        event: &TimedEvent,
+    ) -> Result<(), StoreWriteError> {
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => {
+                let writer =
+                    EventWriter::open(self.dir.clone(), event.timestamp)
+                        .await?;
+                self.writer.insert(writer)
+            }
+        };
+
+        writer.write(event).await
+    }
+
+    async fn flush(&mut self) -> Result<(), StoreWriteError> {
+        match &mut self.writer {
+            Some(writer) => writer.flush().await,
+            None => Ok(()),
+        }
+    }
+
+    async fn read_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> EventReaderResult<Vec<TimedEvent>> {
+        let dir = self.dir.clone();
+
+        match EventReader::open_range(dir, &from.fixed_offset(), to).await {
+            Ok(reader) => reader.into_stream().try_collect().await,
+            Err(StoreReadError::NoFilesToOpen) => Ok(vec![]),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,479 @@
+//! Blocking readers of the store, for tools without an async runtime.
+
+use super::crypt::StoreKey;
+use super::dayfile;
+use super::filepath::{Filepath, StorePath};
+use super::lock::{LockFile, LockFileError, acquire_read_lock_blocking};
+use super::read::{
+    DecodeError, EventReaderResult, OnDecodeError, StoreDirectory,
+    StoreReadError, checksum_failed, decode_failed,
+};
+use super::readline::{
+    Buffer, BufferRef, DEFAULT_BUF_SIZE, DEFAULT_REV_BUF_SIZE, ReaderResult,
+    concat_slices,
+};
+use super::zone::DayZone;
+use crate::diagnostic::Report;
+use crate::events::{EventHead, TimedEvent};
+use crate::util::{memchr, memrchr};
+use chrono::{DateTime, FixedOffset};
+use std::fs::{File, TryLockError};
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+pub trait LineReader {
+    /// Next line borrowed from the reader, valid until the next call.
+    fn next_line_ref(&mut self) -> ReaderResult<Option<&str>>;
+
+    fn next_line(&mut self) -> ReaderResult<Option<String>> {
+        Ok(self.next_line_ref()?.map(str::to_owned))
+    }
+
+    fn rewind(&mut self) -> ReaderResult<u64>;
+
+    fn seek(&mut self, pos: SeekFrom) -> ReaderResult<u64>;
+
+    fn lines(self) -> impl Iterator<Item = ReaderResult<String>>
+    where
+        Self: Sized,
+    {
+        let mut reader = self;
+        std::iter::from_fn(move || reader.next_line().transpose())
+    }
+}
+
+/// Blocking `AsyncLineReader`.
+pub struct ForwardLineReader<'a, R>
+where
+    R: Read + Seek,
+{
+    file: R,
+    buffer: BufferRef<'a>,
+    line_buf: Vec<u8>,
+    /// Last line read over a chunk boundary.
+    line: Vec<u8>,
+    eof: bool,
+}
+
+pub type FileLineReaderOwned = ForwardLineReader<'static, File>;
+
+impl<'a, R> ForwardLineReader<'a, R>
+where
+    R: Read + Seek,
+{
+    pub fn new(file: R) -> Self {
+        Self::with_buffer_size(file, DEFAULT_BUF_SIZE)
+    }
+
+    pub fn with_buffer_size(file: R, buffer_size: NonZeroUsize) -> Self {
+        Self {
+            file,
+            buffer: BufferRef::Owned(Buffer::new(buffer_size)),
+            line_buf: Vec::new(),
+            line: Vec::new(),
+            eof: false,
+        }
+    }
+
+    pub fn with_buffer(file: R, buffer: &'a mut Buffer) -> Self {
+        Self {
+            file,
+            buffer: BufferRef::Borrowed(buffer),
+            line_buf: Vec::new(),
+            line: Vec::new(),
+            eof: false,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.line_buf.clear();
+        self.buffer.reset();
+        self.eof = false;
+    }
+
+    fn read_to_buffer(&mut self) -> ReaderResult<()> {
+        let buf = self.buffer.unfilled_mut();
+        let read_bytes = self.file.read(buf)?;
+
+        self.buffer.advance_filled(read_bytes);
+
+        if read_bytes == 0 {
+            self.eof = true;
+        }
+
+        Ok(())
+    }
+}
+
+impl<R> LineReader for ForwardLineReader<'_, R>
+where
+    R: Read + Seek,
+{
+    fn rewind(&mut self) -> ReaderResult<u64> {
+        self.seek(SeekFrom::Start(0))
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> ReaderResult<u64> {
+        self.reset();
+        Ok(self.file.seek(pos)?)
+    }
+
+    fn next_line_ref(&mut self) -> ReaderResult<Option<&str>> {
+        while !self.eof {
+            if self.buffer.unprocessed_len() == 0 {
+                self.buffer.reset();
+                self.read_to_buffer()?;
+            }
+
+            let found = memchr(b'\n', self.buffer.unprocessed_forward());
+
+            if let Some(n) = found {
+                let line = &self.buffer.consume_forward(n + 1)[..n];
+
+                if self.line_buf.is_empty() {
+                    return Ok(Some(std::str::from_utf8(line)?));
+                }
+
+                // Only lines over a chunk boundary are copied.
+                self.line_buf.extend_from_slice(line);
+                std::mem::swap(&mut self.line, &mut self.line_buf);
+                self.line_buf.clear();
+
+                return Ok(Some(std::str::from_utf8(&self.line)?));
+            }
+
+            self.line_buf
+                .extend_from_slice(self.buffer.unprocessed_forward());
+            self.buffer.reset();
+
+            if self.eof {
+                std::mem::swap(&mut self.line, &mut self.line_buf);
+                self.line_buf.clear();
+
+                return Ok(Some(std::str::from_utf8(&self.line)?));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Blocking `AsyncLineReverseReader`.
+#[derive(Debug)]
+pub struct ReverseLineReader<'a, R>
+where
+    R: Read + Seek,
+{
+    file: R,
+    buffer: BufferRef<'a>,
+    line_buf: Vec<u8>,
+    /// Last line read over a chunk boundary.
+    line: Vec<u8>,
+    done: bool,
+    pos: u64,
+}
+
+pub type FileLineReverseReaderOwned = ReverseLineReader<'static, File>;
+
+impl<'a, R> ReverseLineReader<'a, R>
+where
+    R: Read + Seek,
+{
+    pub fn new(file: R) -> Self {
+        Self::with_buffer_size(file, DEFAULT_REV_BUF_SIZE)
+    }
+
+    pub fn with_buffer_size(file: R, buffer_size: NonZeroUsize) -> Self {
+        Self {
+            file,
+            buffer: BufferRef::Owned(Buffer::new(buffer_size)),
+            line_buf: Vec::new(),
+            line: Vec::new(),
+            done: false,
+            pos: 0,
+        }
+    }
+
+    pub fn with_buffer(file: R, buffer: &'a mut Buffer) -> Self {
+        Self {
+            file,
+            buffer: BufferRef::Borrowed(buffer),
+            line_buf: Vec::new(),
+            line: Vec::new(),
+            done: false,
+            pos: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.line_buf.clear();
+        self.buffer.reset();
+        self.done = false;
+    }
+
+    fn fill_buffer(&mut self) -> ReaderResult<()> {
+        self.buffer.reset();
+
+        let read_size = self.pos.min(self.buffer.capacity() as u64);
+
+        if read_size == 0 {
+            self.done = true;
+            return Ok(());
+        }
+
+        self.pos -= read_size;
+        self.file.seek(SeekFrom::Start(self.pos))?;
+
+        let buf = &mut self.buffer.unfilled_mut()[..read_size as usize];
+        self.file.read_exact(buf)?;
+        self.buffer.advance_filled(read_size as usize);
+
+        Ok(())
+    }
+}
+
+impl<R> LineReader for ReverseLineReader<'_, R>
+where
+    R: Read + Seek,
+{
+    fn rewind(&mut self) -> ReaderResult<u64> {
+        self.seek(SeekFrom::End(0))
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> ReaderResult<u64> {
+        self.reset();
+        self.pos = self.file.seek(pos)?;
+
+        Ok(self.pos)
+    }
+
+    fn next_line_ref(&mut self) -> ReaderResult<Option<&str>> {
+        loop {
+            let process = self.buffer.unprocessed_backward();
+
+            if self.done && process.is_empty() {
+                if self.line_buf.is_empty() {
+                    return Ok(None);
+                }
+
+                std::mem::swap(&mut self.line, &mut self.line_buf);
+                self.line_buf.clear();
+
+                return Ok(Some(std::str::from_utf8(&self.line)?));
+            }
+
+            if let Some(n) = memrchr(b'\n', process) {
+                // The newline and the line after it.
+                let len = process.len() - n;
+                let line = &self.buffer.consume_backward(len)[1..];
+
+                if self.line_buf.is_empty() {
+                    return Ok(Some(std::str::from_utf8(line)?));
+                }
+
+                // Only lines over a chunk boundary are copied.
+                self.line.clear();
+                self.line.extend_from_slice(line);
+                self.line.extend_from_slice(&self.line_buf);
+                self.line_buf.clear();
+
+                return Ok(Some(std::str::from_utf8(&self.line)?));
+            }
+
+            self.line_buf = concat_slices(process, &self.line_buf);
+            self.fill_buffer()?;
+        }
+    }
+}
+
+/// Blocking `store::dayfile::DayFile`.
+#[derive(Debug)]
+pub enum DayFile {
+    Plain(File),
+    Decoded(Cursor<Vec<u8>>),
+}
+
+impl DayFile {
+    pub fn open(path: &Path, key: Option<&StoreKey>) -> io::Result<Self> {
+        if !dayfile::is_compressed(path) {
+            let mut file = File::open(path)?;
+
+            if !dayfile::is_encoded(&mut file)? {
+                file.rewind()?;
+                return Ok(DayFile::Plain(file));
+            }
+        }
+
+        Ok(DayFile::Decoded(Cursor::new(dayfile::read(path, key)?)))
+    }
+}
+
+impl Read for DayFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            DayFile::Plain(file) => file.read(buf),
+            DayFile::Decoded(cursor) => cursor.read(buf),
+        }
+    }
+}
+
+impl Seek for DayFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            DayFile::Plain(file) => file.seek(pos),
+            DayFile::Decoded(cursor) => cursor.seek(pos),
+        }
+    }
+}
+
+/// Blocking `store::EventReader`.
+pub struct EventReader {
+    file_path: Filepath,
+    line_reader: ForwardLineReader<'static, DayFile>,
+    key: Option<StoreKey>,
+    on_decode_error: OnDecodeError,
+    errors: Vec<DecodeError>,
+    _read_lock: Option<LockFile>,
+}
+
+impl EventReader {
+    pub fn open(
+        store: impl Into<StorePath>,
+        open_at: &DateTime<FixedOffset>,
+    ) -> EventReaderResult<Self> {
+        let store = store.into();
+        let dir = store.dir();
+        let read_lock = match acquire_read_lock_blocking(dir) {
+            Ok(lock) => Some(lock),
+            Err(LockFileError::TryLockError(TryLockError::WouldBlock)) => {
+                return Err(StoreReadError::Maintenance);
+            }
+            Err(e) => {
+                log::debug!("Reading without a read lock: {}", Report(&e));
+                None
+            }
+        };
+
+        let date = DayZone::load_blocking(dir)?.date_of(open_at.to_utc());
+        let from_path =
+            Into::<Filepath>::into(date).with_path(dir.to_path_buf());
+
+        let first = Self::list_files(dir)?
+            .range(&from_path..)
+            .next()
+            .cloned()
+            .ok_or(StoreReadError::NoFilesToOpen)?;
+
+        log::debug!("Opening file: {:?}", first.to_path_buf());
+        let file = DayFile::open(&first.to_path_buf(), store.key())?;
+
+        Ok(Self {
+            file_path: first,
+            line_reader: ForwardLineReader::new(file),
+            key: store.key().cloned(),
+            on_decode_error: OnDecodeError::default(),
+            errors: vec![],
+            _read_lock: read_lock,
+        })
+    }
+
+    pub fn on_decode_error(mut self, policy: OnDecodeError) -> Self {
+        self.on_decode_error = policy;
+        self
+    }
+
+    /// Lines skipped with `OnDecodeError::Collect` so far.
+    pub fn take_errors(&mut self) -> Vec<DecodeError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    pub fn list_files(dir: &Path) -> EventReaderResult<StoreDirectory> {
+        let mut files = StoreDirectory::default();
+
+        for entry in std::fs::read_dir(dir)? {
+            // Files that are not part of the store are skipped.
+            if let Ok(filepath) = entry?.path().try_into() {
+                files.items.insert(filepath);
+            }
+        }
+
+        Ok(files)
+    }
+
+    pub fn next_event(&mut self) -> EventReaderResult<Option<TimedEvent>> {
+        self.next_parsed(|line| Ok(serde_json::from_str(line)?))
+    }
+
+    /// Time and type of the next event, without decoding the rest.
+    pub fn next_head(&mut self) -> EventReaderResult<Option<EventHead>> {
+        self.next_parsed(|line| Ok(EventHead::parse(line)?))
+    }
+
+    /// Next line parsed while it is borrowed from the line reader.
+    fn next_parsed<T>(
+        &mut self,
+        parse: impl Fn(&str) -> EventReaderResult<T>,
+    ) -> EventReaderResult<Option<T>> {
+        loop {
+            match self.line_reader.next_line_ref()? {
+                // Every event is terminated with a newline, so the last
+                // line of the file is empty.
+                Some("") => continue,
+                Some(line)
+                    if checksum_failed(
+                        self.on_decode_error,
+                        &mut self.errors,
+                        &self.file_path,
+                        line,
+                    )? => {}
+                Some(line) => match parse(line) {
+                    Ok(parsed) => return Ok(Some(parsed)),
+                    Err(e @ StoreReadError::EncodeError(_)) => decode_failed(
+                        self.on_decode_error,
+                        &mut self.errors,
+                        &self.file_path,
+                        line,
+                        e,
+                    )?,
+                    Err(e) => return Err(e),
+                },
+                None => {
+                    if !self.open_next_file()? {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn open_next_file(&mut self) -> EventReaderResult<bool> {
+        let mut next_file = self.file_path.clone();
+        next_file.increment_date();
+
+        let next_fp = Self::list_files(self.file_path.path())?
+            .range(&next_file..)
+            .next()
+            .cloned();
+
+        match next_fp {
+            Some(fp) => {
+                log::debug!("Opening next file: {:?}", fp.to_path_buf());
+                let file = DayFile::open(&fp.to_path_buf(), self.key.as_ref())?;
+
+                self.line_reader = ForwardLineReader::new(file);
+                self.file_path = fp;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+impl Iterator for EventReader {
+    type Item = EventReaderResult<TimedEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
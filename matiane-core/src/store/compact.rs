@@ -0,0 +1,225 @@
+//! Collapsing the heartbeats of finished days.
+//!
+//! Runs of `Alive` events without a gap the daemon could have been down in
+//! become a single `Present` event. Compacted days are written as JSON
+//! lines, compressed and encrypted again if they were.
+
+use super::crypt::{self, StoreKey};
+use super::dayfile;
+use super::filepath::{Filepath, StorePath};
+use super::format::{self, StoreFormat};
+use super::index::Index;
+use super::lock::acquire_maintenance_lock;
+use super::read::EventReader;
+use crate::activity::MAX_EVENT_GAP;
+use crate::events::{Event, EventHead, Present, TimedEvent};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Compact the day files of `store` before `older_than`. The store must not
+/// be in use. Returns how many files were rewritten.
+pub async fn compact(
+    store: impl Into<StorePath>,
+    older_than: NaiveDate,
+) -> Result<usize> {
+    let store = store.into();
+    let _lock = acquire_maintenance_lock(store.dir().to_path_buf()).await?;
+
+    let files: Vec<Filepath> = EventReader::list_files(store.dir())
+        .await?
+        .items
+        .into_iter()
+        .filter(|filepath| *filepath.date() < older_than)
+        .collect();
+
+    let mut compacted = 0;
+    for filepath in files {
+        if compact_file(filepath, store.key()).await? {
+            compacted += 1;
+        }
+    }
+
+    Ok(compacted)
+}
+
+async fn compact_file(
+    filepath: Filepath,
+    key: Option<&StoreKey>,
+) -> Result<bool> {
+    let path = filepath.to_path_buf();
+    let plain_path = filepath.clone().with_compressed(false).to_path_buf();
+
+    let (encrypted, content) = {
+        let path = path.clone();
+        let key = key.cloned();
+        tokio::task::spawn_blocking(move || {
+            let encrypted = dayfile::format(&path)? == StoreFormat::Encrypted;
+            Ok::<_, std::io::Error>((
+                encrypted,
+                dayfile::read_to_string(&path, key.as_ref())?,
+            ))
+        })
+        .await??
+    };
+
+    let Some(mut lines) = collapse(&content)? else {
+        return Ok(false);
+    };
+
+    if encrypted {
+        let key = key.ok_or_else(crypt::no_key)?;
+        lines = format::encrypt_lines(&lines, key)?;
+    }
+
+    log::debug!("Compacting {:?}", path);
+
+    let tmp_path = plain_path.with_added_extension("tmp");
+    tokio::fs::write(&tmp_path, lines).await?;
+    tokio::fs::rename(&tmp_path, &plain_path).await?;
+
+    if filepath.is_compressed() {
+        tokio::task::spawn_blocking(move || dayfile::compress(&plain_path))
+            .await??;
+    }
+
+    // Offsets of the old index point into the old file.
+    Index::build(&path).await?.write(&path).await?;
+
+    Ok(true)
+}
+
+/// Lines of a day file with its runs of heartbeats collapsed, none when
+/// there are none.
+fn collapse(content: &str) -> Result<Option<Vec<u8>>> {
+    let mut out = Vec::with_capacity(content.len());
+    let mut run = vec![];
+    let mut collapsed = false;
+
+    for line in content.lines().filter(|line| !line.is_empty()) {
+        // Damaged lines are kept as they are.
+        let alive = EventHead::parse(line)
+            .ok()
+            .filter(|head| head.kind == "alive")
+            .map(|head| head.timestamp);
+
+        if let Some(ts) = alive {
+            if run
+                .last()
+                .is_some_and(|(_, last)| ts - *last > MAX_EVENT_GAP)
+            {
+                collapsed |= flush(&mut run, &mut out)?;
+            }
+
+            run.push((line, ts));
+            continue;
+        }
+
+        collapsed |= flush(&mut run, &mut out)?;
+        out.extend_from_slice(line.as_bytes());
+        out.push(b'\n');
+    }
+    collapsed |= flush(&mut run, &mut out)?;
+
+    Ok(collapsed.then_some(out))
+}
+
+/// Write the heartbeats of `run` to `out`, as one event when there are more
+/// than one. Returns whether they were collapsed.
+fn flush(
+    run: &mut Vec<(&str, DateTime<Utc>)>,
+    out: &mut Vec<u8>,
+) -> Result<bool> {
+    let collapsed = match run.as_slice() {
+        [] => false,
+        [(line, _)] => {
+            out.extend_from_slice(line.as_bytes());
+            out.push(b'\n');
+            false
+        }
+        [(_, start), .., (_, end)] => {
+            let event = TimedEvent {
+                timestamp: *start,
+                event: Event::Present(Present { end: *end }),
+            };
+            serde_json::to_writer(&mut *out, &event)?;
+            out.push(b'\n');
+            true
+        }
+    };
+
+    run.clear();
+    Ok(collapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::MAX_EVENT_GAP;
+    use crate::activity::read_activity;
+    use chrono::{TimeDelta, TimeZone};
+
+    #[tokio::test]
+    async fn compact_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = |d, m| {
+            Utc.with_ymd_and_hms(2026, 1, d, 10, 0, 0).unwrap()
+                + TimeDelta::minutes(m)
+        };
+        let focused = Event::Focused(Box::new(crate::events::Focused {
+            title: "a title".to_string(),
+            id: "a".to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }));
+
+        let mut events = vec![(at(1, 0), focused.clone())];
+        // The daemon was down between 4 and 20.
+        events
+            .extend([1, 2, 3, 4, 20, 21, 22].map(|m| (at(1, m), Event::Alive)));
+        events.push((at(1, 23), Event::Idle));
+        events.extend([(at(2, 0), focused), (at(2, 1), Event::Alive)]);
+
+        for day in events.chunk_by(|a, b| a.0.date_naive() == b.0.date_naive())
+        {
+            let lines: Vec<String> = day
+                .iter()
+                .map(|(timestamp, event)| {
+                    serde_json::to_string(&TimedEvent {
+                        timestamp: *timestamp,
+                        event: event.clone(),
+                    })
+                    .unwrap()
+                })
+                .collect();
+            let name = day[0].0.format("%Y%m%d.log").to_string();
+            std::fs::write(dir.path().join(name), lines.join("\n") + "\n")
+                .unwrap();
+        }
+        dayfile::compress(&dir.path().join("20260101.log")).unwrap();
+
+        let path = dir.path().to_path_buf();
+        let (from, to) = (at(1, -60).fixed_offset(), at(3, 0));
+        let before = read_activity(path.clone(), from, to, MAX_EVENT_GAP)
+            .await
+            .unwrap();
+
+        let date = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        assert_eq!(compact(path.clone(), date(3)).await.unwrap(), 1);
+        assert_eq!(compact(path.clone(), date(3)).await.unwrap(), 0);
+
+        let content =
+            dayfile::read_to_string(&dir.path().join("20260101.log.zst"), None)
+                .unwrap();
+        let kinds: Vec<_> = content
+            .lines()
+            .map(|line| EventHead::parse(line).unwrap().kind)
+            .collect();
+        assert_eq!(kinds, ["focused", "present", "present", "idle"]);
+
+        let after = read_activity(path, from, to, MAX_EVENT_GAP).await.unwrap();
+        assert_eq!(after.spans, before.spans);
+        assert_eq!(after.away, before.away);
+    }
+}
@@ -0,0 +1,104 @@
+//! Encryption of day files with XChaCha20-Poly1305, as window titles are
+//! sensitive.
+//!
+//! The key is loaded from the `key-file` of the config and kept by the
+//! readers and writers of the store. Every event is encrypted on its own
+//! with a random nonce, so day files can still be appended to.
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use std::fmt;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+pub const KEY_LEN: usize = 32;
+
+const NONCE_LEN: usize = 24;
+
+/// Key the day files of a store are encrypted with.
+#[derive(Clone)]
+pub struct StoreKey(XChaCha20Poly1305);
+
+impl StoreKey {
+    pub fn new(key: [u8; KEY_LEN]) -> Self {
+        StoreKey(XChaCha20Poly1305::new(&Key::from(key)))
+    }
+
+    /// Key in the file at `path`, `KEY_LEN` bytes in hex.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let key = parse_key(content.trim()).ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("the key must be {} bytes in hex", KEY_LEN),
+            )
+        })?;
+
+        Ok(StoreKey::new(key))
+    }
+
+    /// `plaintext` encrypted, after the nonce it was encrypted with.
+    pub(super) fn encrypt(&self, plaintext: &[u8]) -> io::Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .0
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| io::Error::other("failed to encrypt an event"))?;
+
+        let mut encrypted = nonce.to_vec();
+        encrypted.extend_from_slice(&ciphertext);
+        Ok(encrypted)
+    }
+
+    /// Plaintext of what `encrypt` returned, none when it was changed or
+    /// encrypted with another key.
+    pub(super) fn decrypt(&self, encrypted: &[u8]) -> Option<Vec<u8>> {
+        let (nonce, ciphertext) = encrypted.split_at_checked(NONCE_LEN)?;
+        let nonce: [u8; NONCE_LEN] = nonce.try_into().ok()?;
+
+        self.0.decrypt(&XNonce::from(nonce), ciphertext).ok()
+    }
+}
+
+impl fmt::Debug for StoreKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StoreKey(..)")
+    }
+}
+
+/// Error of reading an encrypted day file without a key.
+pub(super) fn no_key() -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidInput,
+        "the day file is encrypted and no key is set",
+    )
+}
+
+fn parse_key(hex: &str) -> Option<[u8; KEY_LEN]> {
+    if hex.len() != KEY_LEN * 2 || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut key = [0; KEY_LEN];
+    for (byte, digits) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).ok()?;
+        *byte = u8::from_str_radix(digits, 16).ok()?;
+    }
+
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_key_test() {
+        let hex = "00ff".repeat(KEY_LEN / 2);
+        let key = parse_key(&hex).unwrap();
+        assert_eq!(key[..2], [0x00, 0xff]);
+
+        assert!(parse_key(&hex[2..]).is_none());
+        assert!(parse_key(&hex.replace("ff", "fg")).is_none());
+    }
+}
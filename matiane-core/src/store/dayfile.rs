@@ -0,0 +1,223 @@
+//! Day files read as JSON lines whether compressed with zstd or not, and
+//! in any `StoreFormat`.
+//!
+//! Compressed and binary files are decoded into memory when opened, so
+//! readers can still seek in them.
+
+use super::crypt::StoreKey;
+use super::filepath::COMPRESSED_EXTENSION;
+use super::format::{self, MAX_HEADER_LEN, StoreFormat};
+use std::io::{self, ErrorKind, Write};
+
+#[cfg(feature = "blocking")]
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "async")]
+use std::io::Cursor;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, ReadBuf};
+
+pub(super) fn is_compressed(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == COMPRESSED_EXTENSION)
+}
+
+/// Whether a plain day file needs decoding, it is left at an unknown
+/// position.
+#[cfg(feature = "blocking")]
+pub(super) fn is_encoded(file: &mut impl Read) -> io::Result<bool> {
+    let mut start = Vec::with_capacity(MAX_HEADER_LEN);
+    file.take(MAX_HEADER_LEN as u64).read_to_end(&mut start)?;
+
+    Ok(StoreFormat::detect(&start) != StoreFormat::Json)
+}
+
+/// Content of the day file at `path`, as JSON lines. Encrypted files are
+/// decrypted with `key`.
+pub fn read(path: &Path, key: Option<&StoreKey>) -> io::Result<Vec<u8>> {
+    let content = std::fs::read(path)?;
+
+    let content = match is_compressed(path) {
+        true => zstd::decode_all(content.as_slice())?,
+        false => content,
+    };
+
+    format::decode(content, key)
+}
+
+/// Format of the day file at `path`.
+pub fn format(path: &Path) -> io::Result<StoreFormat> {
+    use std::io::Read as _;
+
+    let start = match is_compressed(path) {
+        true => zstd::decode_all(std::fs::File::open(path)?)?,
+        false => {
+            let mut start = Vec::with_capacity(MAX_HEADER_LEN);
+            std::fs::File::open(path)?
+                .take(MAX_HEADER_LEN as u64)
+                .read_to_end(&mut start)?;
+            start
+        }
+    };
+
+    Ok(StoreFormat::detect(&start))
+}
+
+pub fn read_to_string(
+    path: &Path,
+    key: Option<&StoreKey>,
+) -> io::Result<String> {
+    String::from_utf8(read(path, key)?)
+        .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Compress the day file at `path` into `path.zst` and remove it.
+pub fn compress(path: &Path) -> io::Result<PathBuf> {
+    let content = std::fs::read(path)?;
+    let compressed =
+        zstd::encode_all(content.as_slice(), zstd::DEFAULT_COMPRESSION_LEVEL)?;
+
+    let target = path.with_added_extension(COMPRESSED_EXTENSION);
+    write_file(&target, &compressed)?;
+    std::fs::remove_file(path)?;
+
+    Ok(target)
+}
+
+/// Move the day file at `path` into `archive_dir`, compressed.
+pub fn archive(path: &Path, archive_dir: &Path) -> io::Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::from(ErrorKind::InvalidInput))?;
+    let mut content = std::fs::read(path)?;
+    let mut target = archive_dir.join(name);
+
+    if !is_compressed(path) {
+        content = zstd::encode_all(
+            content.as_slice(),
+            zstd::DEFAULT_COMPRESSION_LEVEL,
+        )?;
+        target = target.with_added_extension(COMPRESSED_EXTENSION);
+    }
+
+    write_file(&target, &content)?;
+    std::fs::remove_file(path)?;
+
+    Ok(target)
+}
+
+/// Write through a temporary file, so `target` is never left half written.
+fn write_file(target: &Path, content: &[u8]) -> io::Result<()> {
+    let tmp_path = target.with_added_extension("tmp");
+
+    let mut tmp = std::fs::File::create(&tmp_path)?;
+    tmp.write_all(content)?;
+    tmp.sync_all()?;
+    drop(tmp);
+
+    std::fs::rename(&tmp_path, target)
+}
+
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub enum DayFile {
+    Plain(tokio::fs::File),
+    /// Content of a compressed or binary file, as JSON lines.
+    Decoded(Cursor<Vec<u8>>),
+}
+
+#[cfg(feature = "async")]
+impl DayFile {
+    pub async fn open(path: &Path, key: Option<&StoreKey>) -> io::Result<Self> {
+        if !is_compressed(path) {
+            let mut file = tokio::fs::File::open(path).await?;
+
+            let mut start = Vec::with_capacity(MAX_HEADER_LEN);
+            (&mut file)
+                .take(MAX_HEADER_LEN as u64)
+                .read_to_end(&mut start)
+                .await?;
+
+            if StoreFormat::detect(&start) == StoreFormat::Json {
+                file.rewind().await?;
+                return Ok(DayFile::Plain(file));
+            }
+        }
+
+        let path = path.to_path_buf();
+        let key = key.cloned();
+        let content =
+            tokio::task::spawn_blocking(move || read(&path, key.as_ref()))
+                .await
+                .map_err(io::Error::other)??;
+
+        Ok(DayFile::Decoded(Cursor::new(content)))
+    }
+
+    /// Length of the content, decoded.
+    pub async fn len(&self) -> io::Result<u64> {
+        match self {
+            DayFile::Plain(file) => Ok(file.metadata().await?.len()),
+            DayFile::Decoded(cursor) => Ok(cursor.get_ref().len() as u64),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncRead for DayFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            DayFile::Plain(file) => Pin::new(file).poll_read(cx, buf),
+            DayFile::Decoded(cursor) => Pin::new(cursor).poll_read(cx, buf),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncSeek for DayFile {
+    fn start_seek(self: Pin<&mut Self>, pos: io::SeekFrom) -> io::Result<()> {
+        match self.get_mut() {
+            DayFile::Plain(file) => Pin::new(file).start_seek(pos),
+            DayFile::Decoded(cursor) => Pin::new(cursor).start_seek(pos),
+        }
+    }
+
+    fn poll_complete(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<u64>> {
+        match self.get_mut() {
+            DayFile::Plain(file) => Pin::new(file).poll_complete(cx),
+            DayFile::Decoded(cursor) => Pin::new(cursor).poll_complete(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("20260101.log");
+        std::fs::write(&path, "line 1\nline 2\n").unwrap();
+
+        let compressed = compress(&path).unwrap();
+        assert_eq!(compressed, dir.path().join("20260101.log.zst"));
+        assert!(!path.exists());
+        assert_eq!(
+            read_to_string(&compressed, None).unwrap(),
+            "line 1\nline 2\n"
+        );
+    }
+}
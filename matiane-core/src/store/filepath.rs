@@ -1,10 +1,18 @@
+#[cfg(any(feature = "async", feature = "blocking"))]
+use super::crypt::StoreKey;
+#[cfg(any(feature = "async", feature = "blocking"))]
 use chrono::{DateTime, NaiveDate, Utc};
+#[cfg(any(feature = "async", feature = "blocking"))]
+use std::cmp::Ordering;
 use std::error::Error;
 use std::fmt;
+#[cfg(any(feature = "async", feature = "blocking"))]
 use std::path::{Path, PathBuf};
 
-const DATE_FORMAT: &str = "%Y%m%d";
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(super) const DATE_FORMAT: &str = "%Y%m%d";
 const EXTENSION: &str = "log";
+pub const COMPRESSED_EXTENSION: &str = "zst";
 
 #[derive(Debug, PartialEq)]
 pub enum TryIntoFilenameError {
@@ -35,12 +43,60 @@ impl fmt::Display for TryIntoFilenameError {
 
 impl Error for TryIntoFilenameError {}
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// Directory of a store, with the key its day files are encrypted with.
+#[cfg(any(feature = "async", feature = "blocking"))]
+#[derive(Debug, Clone)]
+pub struct StorePath {
+    dir: PathBuf,
+    key: Option<StoreKey>,
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+impl StorePath {
+    pub fn new(dir: PathBuf) -> Self {
+        StorePath { dir, key: None }
+    }
+
+    pub fn with_key(mut self, key: Option<StoreKey>) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn key(&self) -> Option<&StoreKey> {
+        self.key.as_ref()
+    }
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+impl From<PathBuf> for StorePath {
+    fn from(dir: PathBuf) -> Self {
+        StorePath::new(dir)
+    }
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+impl<P: AsRef<Path> + ?Sized> From<&P> for StorePath {
+    fn from(dir: &P) -> Self {
+        StorePath::new(dir.as_ref().to_path_buf())
+    }
+}
+
+/// Day file of a store. Files are ordered and equal by directory and date,
+/// whether compressed or not.
+#[cfg(any(feature = "async", feature = "blocking"))]
+#[derive(Debug, Clone)]
 pub struct Filepath {
     path: PathBuf,
     date: NaiveDate,
+    /// Compressed with zstd, `20260101.log.zst`.
+    compressed: bool,
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 impl Filepath {
     pub fn date(&self) -> &NaiveDate {
         &self.date
@@ -70,18 +126,66 @@ impl Filepath {
         self
     }
 
+    pub fn is_compressed(&self) -> bool {
+        self.compressed
+    }
+
+    pub fn with_compressed(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
     pub fn to_path_buf(&self) -> PathBuf {
         let formatted = self.date.format(DATE_FORMAT);
-        self.path
+        let path = self
+            .path
             .join(formatted.to_string())
-            .with_extension(EXTENSION)
+            .with_extension(EXTENSION);
+
+        match self.compressed {
+            true => path.with_added_extension(COMPRESSED_EXTENSION),
+            false => path,
+        }
+    }
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+impl PartialEq for Filepath {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+impl Eq for Filepath {}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+impl PartialOrd for Filepath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(any(feature = "async", feature = "blocking"))]
+impl Ord for Filepath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.path, self.date).cmp(&(&other.path, other.date))
     }
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 impl TryFrom<PathBuf> for Filepath {
     type Error = TryIntoFilenameError;
 
     fn try_from(path: PathBuf) -> Result<Filepath, TryIntoFilenameError> {
+        let compressed = path
+            .extension()
+            .is_some_and(|ext| ext == COMPRESSED_EXTENSION);
+        let path = match compressed {
+            true => path.with_extension(""),
+            false => path,
+        };
+
         match path.extension() {
             Some(ext) if ext == EXTENSION => {}
             Some(_) => return Err(TryIntoFilenameError::IncorrectExtension),
@@ -100,26 +204,31 @@ impl TryFrom<PathBuf> for Filepath {
         Ok(Self {
             path: path.with_file_name(""),
             date,
+            compressed,
         })
     }
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 impl From<NaiveDate> for Filepath {
     fn from(date: NaiveDate) -> Self {
         Self {
             path: PathBuf::default(),
             date,
+            compressed: false,
         }
     }
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 impl From<DateTime<Utc>> for Filepath {
     fn from(datetime: DateTime<Utc>) -> Self {
         datetime.date_naive().into()
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, any(feature = "async", feature = "blocking")))]
+#[allow(clippy::zero_prefixed_literal)]
 mod tests {
     use super::*;
     use anyhow::Result;
@@ -200,8 +309,13 @@ mod tests {
                 expected: Ok(Filepath {
                     path: "path/is/".into(),
                     date: NaiveDate::from_ymd_opt(2026, 01, 23).unwrap(),
+                    compressed: false,
                 }),
             },
+            TestCase {
+                source: "path/is/20260123.idx.zst".into(),
+                expected: Err(TryIntoFilenameError::IncorrectExtension),
+            },
         ];
 
         for test in tests {
@@ -237,6 +351,7 @@ mod tests {
             "path/to/../other/20251231.log".into(),
             "path//double/20251231.log".into(),
             "path/./dot/20251231.log".into(),
+            "path/to/20251231.log.zst".into(),
         ];
 
         for test in tests {
@@ -246,6 +361,11 @@ mod tests {
             assert_eq!(back_path, test, "{:?} != {:?}", back_path, test);
         }
 
+        let compressed: Filepath =
+            PathBuf::from("path/20251231.log.zst").try_into()?;
+        assert!(compressed.is_compressed());
+        assert_eq!(compressed, PathBuf::from("path/20251231.log").try_into()?);
+
         Ok(())
     }
 }
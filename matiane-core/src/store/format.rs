@@ -0,0 +1,399 @@
+//! Encodings of day files. JSON lines have no header, other formats start
+//! with one naming them, so a store can mix them.
+//!
+//! CBOR files hold a frame per event after the header, its length as a big
+//! endian u32 followed by the event. Readers decode them into JSON lines
+//! when opening, keeping fields written next to the event, like the host of
+//! merged stores.
+//!
+//! JSON CRC32 files hold JSON lines followed by a tab and the CRC32 of the
+//! line in hex. Readers check and remove them when opening, lines that do
+//! not match are marked with `CHECKSUM_MISMATCH`.
+//!
+//! Encrypted files are framed like CBOR ones, every frame holding a JSON
+//! line encrypted with the `StoreKey` of the store. Frames that do not
+//! decrypt are marked like lines that do not match their checksum.
+
+use super::crypt::{self, StoreKey};
+use super::write::StoreWriteError;
+use crate::events::TimedEvent;
+use serde::{Deserialize, Serialize};
+use std::io::{self, ErrorKind};
+
+pub const CBOR_HEADER: &[u8] = b"matiane-cbor 1\n";
+pub const JSON_CRC32_HEADER: &[u8] = b"matiane-json-crc32 1\n";
+pub const ENCRYPTED_HEADER: &[u8] = b"matiane-xchacha20 1\n";
+
+/// Length of the longest header, enough to detect the format from.
+pub(super) const MAX_HEADER_LEN: usize = JSON_CRC32_HEADER.len();
+const _: () = assert!(ENCRYPTED_HEADER.len() <= MAX_HEADER_LEN);
+
+/// Start of decoded lines that did not match their checksum.
+pub(super) const CHECKSUM_MISMATCH: &str = "!checksum-mismatch\t";
+
+const FRAME_LENGTH_SIZE: usize = 4;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StoreFormat {
+    #[default]
+    Json,
+    /// JSON lines with a checksum each.
+    JsonCrc32,
+    Cbor,
+    /// Used for new files of stores with a key, see `crypt`.
+    #[serde(skip)]
+    Encrypted,
+}
+
+impl StoreFormat {
+    /// Format of a day file starting with `start`.
+    pub fn detect(start: &[u8]) -> Self {
+        if start.starts_with(CBOR_HEADER) {
+            StoreFormat::Cbor
+        } else if start.starts_with(JSON_CRC32_HEADER) {
+            StoreFormat::JsonCrc32
+        } else if start.starts_with(ENCRYPTED_HEADER) {
+            StoreFormat::Encrypted
+        } else {
+            StoreFormat::Json
+        }
+    }
+
+    /// Written at the start of new files.
+    pub fn header(self) -> &'static [u8] {
+        match self {
+            StoreFormat::Json => b"",
+            StoreFormat::JsonCrc32 => JSON_CRC32_HEADER,
+            StoreFormat::Cbor => CBOR_HEADER,
+            StoreFormat::Encrypted => ENCRYPTED_HEADER,
+        }
+    }
+
+    /// `event` as appended to a file of this format.
+    pub fn encode(
+        self,
+        event: &TimedEvent,
+    ) -> Result<Vec<u8>, StoreWriteError> {
+        self.encode_with(event, None)
+    }
+
+    /// `encode` with the key encrypted files are written with, of any line
+    /// that is an event.
+    pub fn encode_with(
+        self,
+        event: &impl Serialize,
+        key: Option<&StoreKey>,
+    ) -> Result<Vec<u8>, StoreWriteError> {
+        match self {
+            StoreFormat::Json => {
+                let mut encoded = serde_json::to_vec(event)?;
+                encoded.push(b'\n');
+                Ok(encoded)
+            }
+            StoreFormat::JsonCrc32 => {
+                let mut encoded = serde_json::to_vec(event)?;
+                let checksum = crc32fast::hash(&encoded);
+                encoded.extend_from_slice(
+                    format!("\t{:08x}\n", checksum).as_bytes(),
+                );
+                Ok(encoded)
+            }
+            StoreFormat::Cbor => {
+                let mut encoded = vec![0; FRAME_LENGTH_SIZE];
+                ciborium::into_writer(event, &mut encoded)?;
+
+                let length = (encoded.len() - FRAME_LENGTH_SIZE) as u32;
+                encoded[..FRAME_LENGTH_SIZE]
+                    .copy_from_slice(&length.to_be_bytes());
+                Ok(encoded)
+            }
+            StoreFormat::Encrypted => {
+                let key = key.ok_or_else(crypt::no_key)?;
+                let encrypted = key.encrypt(&serde_json::to_vec(event)?)?;
+                Ok(frame(&encrypted))
+            }
+        }
+    }
+}
+
+/// A decoded CBOR frame, the event and the fields written next to it.
+#[derive(Serialize, Deserialize)]
+struct CborLine {
+    #[serde(flatten)]
+    event: TimedEvent,
+    #[serde(flatten)]
+    rest: serde_json::Map<String, serde_json::Value>,
+}
+
+fn frame(payload: &[u8]) -> Vec<u8> {
+    let length = payload.len() as u32;
+    [&length.to_be_bytes(), payload].concat()
+}
+
+/// Day file `content` as JSON lines, encrypted files decrypted with `key`.
+pub(super) fn decode(
+    content: Vec<u8>,
+    key: Option<&StoreKey>,
+) -> io::Result<Vec<u8>> {
+    let format = StoreFormat::detect(&content);
+    let body = &content[format.header().len()..];
+
+    match format {
+        StoreFormat::Json => Ok(content),
+        StoreFormat::JsonCrc32 => Ok(checked_json_lines(body)),
+        StoreFormat::Cbor => Ok(cbor_to_json_lines(body)),
+        StoreFormat::Encrypted => {
+            decrypt_to_json_lines(body, key.ok_or_else(crypt::no_key)?)
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+/// JSON `lines` as a day file encrypted with `key`.
+pub(super) fn encrypt_lines(
+    lines: &[u8],
+    key: &StoreKey,
+) -> io::Result<Vec<u8>> {
+    let mut encrypted = ENCRYPTED_HEADER.to_vec();
+
+    for line in lines.split(|byte| *byte == b'\n') {
+        if !line.is_empty() {
+            encrypted.extend_from_slice(&frame(&key.encrypt(line)?));
+        }
+    }
+
+    Ok(encrypted)
+}
+
+/// Whether a decoded line did not match its checksum.
+#[cfg(any(test, feature = "async", feature = "blocking"))]
+pub(super) fn is_mismatched(line: impl AsRef<[u8]>) -> bool {
+    line.as_ref().starts_with(CHECKSUM_MISMATCH.as_bytes())
+}
+
+#[cfg(feature = "async")]
+/// Length of the whole lines or frames at the start of day file `content`,
+/// the rest was cut short.
+pub(super) fn complete_len(content: &[u8]) -> usize {
+    match StoreFormat::detect(content) {
+        StoreFormat::Json | StoreFormat::JsonCrc32 => content
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |i| i + 1),
+        format @ (StoreFormat::Cbor | StoreFormat::Encrypted) => {
+            let mut frames = &content[format.header().len()..];
+            while let Some((_, rest)) = split_frame(frames) {
+                frames = rest;
+            }
+
+            content.len() - frames.len()
+        }
+    }
+}
+
+/// The first frame of `frames` and the ones after it.
+fn split_frame(frames: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (length, rest) = frames.split_at_checked(FRAME_LENGTH_SIZE)?;
+    let length = u32::from_be_bytes(length.try_into().ok()?);
+    rest.split_at_checked(length as usize)
+}
+
+/// Lines with their checksum removed, the ones that do not match it are
+/// kept whole after `CHECKSUM_MISMATCH`.
+fn checked_json_lines(lines: &[u8]) -> Vec<u8> {
+    let mut checked = Vec::with_capacity(lines.len());
+
+    for line in lines.split(|byte| *byte == b'\n') {
+        if line.is_empty() {
+            continue;
+        }
+
+        let json = line
+            .iter()
+            .rposition(|byte| *byte == b'\t')
+            .map(|i| line.split_at(i))
+            .filter(|(json, checksum)| {
+                std::str::from_utf8(&checksum[1..])
+                    .ok()
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .is_some_and(|checksum| checksum == crc32fast::hash(json))
+            });
+
+        match json {
+            Some((json, _)) => checked.extend_from_slice(json),
+            None => {
+                checked.extend_from_slice(CHECKSUM_MISMATCH.as_bytes());
+                checked.extend_from_slice(line);
+            }
+        }
+        checked.push(b'\n');
+    }
+
+    checked
+}
+
+/// Fails when no frame decrypts, the key is not the one the file was
+/// encrypted with.
+fn decrypt_to_json_lines(
+    mut frames: &[u8],
+    key: &StoreKey,
+) -> io::Result<Vec<u8>> {
+    let mut lines = vec![];
+    let mut decrypted = 0;
+    let mut mismatched = 0;
+
+    while !frames.is_empty() {
+        let Some((frame, rest)) = split_frame(frames) else {
+            lines.extend_from_slice(b"\"truncated encrypted frame\"\n");
+            break;
+        };
+        frames = rest;
+
+        match key.decrypt(frame) {
+            Some(line) => {
+                lines.extend_from_slice(&line);
+                decrypted += 1;
+            }
+            None => {
+                lines.extend_from_slice(CHECKSUM_MISMATCH.as_bytes());
+                lines.extend_from_slice(b"encrypted frame");
+                mismatched += 1;
+            }
+        }
+        lines.push(b'\n');
+    }
+
+    if decrypted == 0 && mismatched > 0 {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "the day file is encrypted with another key",
+        ));
+    }
+
+    Ok(lines)
+}
+
+/// Frames that can not be decoded become lines that are not events, so
+/// readers treat them like damaged JSON lines.
+fn cbor_to_json_lines(mut frames: &[u8]) -> Vec<u8> {
+    let mut lines = vec![];
+
+    while !frames.is_empty() {
+        // A frame cut short, usually by a crash while writing it.
+        let Some((frame, rest)) = split_frame(frames) else {
+            lines.extend_from_slice(b"\"truncated CBOR frame\"\n");
+            break;
+        };
+        frames = rest;
+
+        // Written the way JSON files are, so heads parse fast.
+        let line = ciborium::from_reader::<CborLine, _>(frame)
+            .map_err(|e| format!("damaged CBOR frame: {}", e))
+            .and_then(|event| {
+                serde_json::to_string(&event).map_err(|e| e.to_string())
+            })
+            .unwrap_or_else(|e| serde_json::Value::from(e).to_string());
+
+        lines.extend_from_slice(line.as_bytes());
+        lines.push(b'\n');
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Event;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn cbor_test() {
+        let event = TimedEvent {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+            event: Event::Alive,
+        };
+        let frame = StoreFormat::Cbor.encode(&event).unwrap();
+
+        let mut content = CBOR_HEADER.to_vec();
+        content.extend_from_slice(&frame);
+        content.extend_from_slice(&frame);
+        assert_eq!(StoreFormat::detect(&content), StoreFormat::Cbor);
+
+        let json = StoreFormat::Json.encode(&event).unwrap();
+        let decoded = decode(content.clone(), None).unwrap();
+        assert_eq!(decoded, [json.clone(), json.clone()].concat());
+
+        // What was written of the last frame before a crash.
+        content.truncate(content.len() - 3);
+        let decoded =
+            String::from_utf8(decode(content, None).unwrap()).unwrap();
+        let lines: Vec<_> = decoded.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(serde_json::from_str::<TimedEvent>(lines[1]).is_err());
+
+        assert_eq!(decode(json.clone(), None).unwrap(), json);
+    }
+
+    #[test]
+    fn cbor_extra_fields_test() {
+        let line = serde_json::json!({
+            "timestamp": "2026-01-01T00:00:00Z",
+            "event": { "type": "alive" },
+            "host": "laptop",
+        });
+        let frame = StoreFormat::Cbor.encode_with(&line, None).unwrap();
+
+        let decoded = decode([CBOR_HEADER, &frame].concat(), None).unwrap();
+        let decoded: serde_json::Value =
+            serde_json::from_slice(&decoded).unwrap();
+        assert_eq!(decoded["host"], "laptop");
+    }
+
+    #[test]
+    fn json_crc32_test() {
+        let event = TimedEvent {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+            event: Event::Alive,
+        };
+        let line = StoreFormat::JsonCrc32.encode(&event).unwrap();
+        let json = StoreFormat::Json.encode(&event).unwrap();
+
+        // A bit flipped on the way to a network filesystem.
+        let mut flipped = line.clone();
+        flipped[30] ^= 1;
+
+        let content = [JSON_CRC32_HEADER, &line, &flipped, &line].concat();
+        assert_eq!(StoreFormat::detect(&content), StoreFormat::JsonCrc32);
+
+        let decoded =
+            String::from_utf8(decode(content, None).unwrap()).unwrap();
+        let lines: Vec<_> = decoded.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].as_bytes(), json.trim_ascii_end());
+        assert!(is_mismatched(lines[1]));
+        assert_eq!(lines[2].as_bytes(), json.trim_ascii_end());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn complete_len_test() {
+        let event = TimedEvent {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+            event: Event::Alive,
+        };
+
+        let json = StoreFormat::Json.encode(&event).unwrap();
+        let content = [json.clone(), json[..10].to_vec()].concat();
+        assert_eq!(complete_len(&content), json.len());
+        assert_eq!(complete_len(&json[..10]), 0);
+        assert_eq!(complete_len(b""), 0);
+
+        let frame = StoreFormat::Cbor.encode(&event).unwrap();
+        let content = [CBOR_HEADER, &frame, &frame[..3]].concat();
+        assert_eq!(complete_len(&content), CBOR_HEADER.len() + frame.len());
+        assert_eq!(complete_len(CBOR_HEADER), CBOR_HEADER.len());
+        assert_eq!(complete_len(&CBOR_HEADER[..5]), 0);
+    }
+}
@@ -0,0 +1,253 @@
+//! Sidecar indexes of day files, `20260101.idx` next to `20260101.log`.
+//!
+//! Every line is an entry for the first event of an hour, with the offset
+//! of its line and what was focused before it, so reading can start there
+//! instead of at midnight.
+
+use super::dayfile::{self, DayFile};
+use super::filepath::{COMPRESSED_EXTENSION, Filepath};
+use super::format::StoreFormat;
+use super::read::{EventReaderResult, StoreReadError};
+use super::readline::{AsyncLineReader, LineReader};
+use crate::events::{Event, EventHead, Focused, TimedEvent};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+pub const INDEX_EXTENSION: &str = "idx";
+
+const SECONDS_PER_ENTRY: i64 = 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Time of the event the entry points to.
+    pub timestamp: DateTime<Utc>,
+    /// Offset of its line in the day file.
+    pub offset: u64,
+    /// Time of the event before it.
+    pub previous: Option<DateTime<Utc>>,
+    /// Window focused before it.
+    pub focused: Option<Focused>,
+    /// Workspace focused before it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+    /// Whether idle or asleep before it.
+    pub inactive: bool,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Index {
+    pub entries: Vec<IndexEntry>,
+}
+
+impl Index {
+    pub fn path(log_path: &Path) -> PathBuf {
+        let log_path = match log_path.extension() {
+            Some(ext) if ext == COMPRESSED_EXTENSION => {
+                log_path.with_extension("")
+            }
+            _ => log_path.to_path_buf(),
+        };
+
+        log_path.with_extension(INDEX_EXTENSION)
+    }
+
+    /// Index every hour of the day file at `log_path`. Encrypted files get
+    /// an empty index, entries would hold window titles in the clear.
+    pub async fn build(log_path: &Path) -> EventReaderResult<Self> {
+        let format = {
+            let path = log_path.to_path_buf();
+            tokio::task::spawn_blocking(move || dayfile::format(&path))
+                .await
+                .map_err(std::io::Error::other)??
+        };
+
+        if format == StoreFormat::Encrypted {
+            return Ok(Index::default());
+        }
+
+        let file = DayFile::open(log_path, None).await?;
+        let mut reader = AsyncLineReader::new(file);
+
+        let mut index = Index::default();
+        let mut offset = 0;
+        let mut last_slot = None;
+        let mut previous = None;
+        let mut focused = None;
+        let mut workspace = None;
+        let mut inactive = false;
+
+        while let Some(line) = reader.next_line_ref().await? {
+            let line_offset = offset;
+            offset += line.len() as u64 + 1;
+
+            if line.is_empty() {
+                continue;
+            }
+
+            // Only focus changes need decoding, most events are heartbeats.
+            let Ok(head) = EventHead::parse(line) else {
+                log::warn!("Skipping a damaged line of {:?}", log_path);
+                continue;
+            };
+            let slot = head.timestamp.timestamp().div_euclid(SECONDS_PER_ENTRY);
+            // Time of the last sign of life of the event.
+            let mut last = head.timestamp;
+
+            if last_slot.is_none_or(|last_slot| last_slot < slot) {
+                last_slot = Some(slot);
+                index.entries.push(IndexEntry {
+                    timestamp: head.timestamp,
+                    offset: line_offset,
+                    previous,
+                    focused: focused.clone(),
+                    workspace: workspace.clone(),
+                    inactive,
+                });
+            }
+
+            match head.kind {
+                "focused" => match serde_json::from_str::<TimedEvent>(line) {
+                    Ok(TimedEvent {
+                        event: Event::Focused(event),
+                        ..
+                    }) => focused = Some(*event),
+                    Ok(_) => {}
+                    Err(_) => {
+                        log::warn!("Skipping a damaged line of {:?}", log_path)
+                    }
+                },
+                "workspace_focused" => {
+                    match serde_json::from_str::<TimedEvent>(line) {
+                        Ok(TimedEvent {
+                            event: Event::WorkspaceFocused(event),
+                            ..
+                        }) => workspace = Some(event.name),
+                        Ok(_) => {}
+                        Err(_) => {
+                            log::warn!(
+                                "Skipping a damaged line of {:?}",
+                                log_path
+                            )
+                        }
+                    }
+                }
+                "present" => match serde_json::from_str::<TimedEvent>(line) {
+                    Ok(TimedEvent {
+                        event: Event::Present(present),
+                        ..
+                    }) => last = present.end,
+                    Ok(_) => {}
+                    Err(_) => {
+                        log::warn!("Skipping a damaged line of {:?}", log_path)
+                    }
+                },
+                "idle" | "sleep" => inactive = true,
+                "active" | "awake" => inactive = false,
+                "tracking_paused" | "shutdown" => focused = None,
+                "started" => {
+                    focused = None;
+                    inactive = false;
+                }
+                _ => {}
+            }
+
+            // Backfilled periods are no sign of life.
+            if head.kind != "backfilled" {
+                previous = Some(last);
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// Index of the day file at `log_path`, none when it is missing or
+    /// older than the file.
+    pub async fn read(log_path: &Path) -> EventReaderResult<Option<Self>> {
+        let index_path = Self::path(log_path);
+
+        let index_meta = match tokio::fs::metadata(&index_path).await {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let log_meta = tokio::fs::metadata(log_path).await?;
+
+        if index_meta.modified()? < log_meta.modified()? {
+            return Ok(None);
+        }
+
+        let content = tokio::fs::read_to_string(&index_path).await?;
+        let entries = content
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(Index { entries }))
+    }
+
+    pub async fn write(&self, log_path: &Path) -> EventReaderResult<()> {
+        let index_path = Self::path(log_path);
+        let tmp_path = index_path.with_extension("idx.tmp");
+
+        let mut content = vec![];
+        for entry in &self.entries {
+            serde_json::to_writer(&mut content, entry)?;
+            content.push(b'\n');
+        }
+
+        let mut tmp = tokio::fs::File::create(&tmp_path).await?;
+        tmp.write_all(&content).await?;
+        tmp.sync_all().await?;
+        tokio::fs::rename(&tmp_path, &index_path).await?;
+
+        Ok(())
+    }
+
+    /// Last entry at or before `at`, reading from it reaches every event
+    /// from `at` on.
+    pub fn entry_before(&self, at: DateTime<Utc>) -> Option<&IndexEntry> {
+        let n = self.entries.partition_point(|entry| entry.timestamp <= at);
+        n.checked_sub(1).map(|n| &self.entries[n])
+    }
+}
+
+/// Index the day files of `dir` without an up to date index. The newest
+/// file is still written to and is left alone. Returns how many were
+/// indexed.
+pub async fn build_indexes(dir: &Path) -> EventReaderResult<usize> {
+    let files = super::read::EventReader::list_files(dir).await?;
+    let mut built = 0;
+
+    for filepath in files.items.iter().rev().skip(1) {
+        if index_file(filepath).await? {
+            built += 1;
+        }
+    }
+
+    Ok(built)
+}
+
+async fn index_file(filepath: &Filepath) -> EventReaderResult<bool> {
+    let log_path = filepath.to_path_buf();
+
+    if Index::read(&log_path).await.ok().flatten().is_some() {
+        return Ok(false);
+    }
+
+    log::debug!("Indexing {:?}", log_path);
+
+    match Index::build(&log_path).await {
+        Ok(index) => {
+            index.write(&log_path).await?;
+            Ok(true)
+        }
+        Err(StoreReadError::EncodeError(e)) => {
+            log::warn!("Not indexing {:?}: {}", log_path, e);
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,188 @@
+use super::crypt::{self, StoreKey};
+use super::dayfile;
+use super::filepath::{Filepath, StorePath};
+use super::format::{self, StoreFormat};
+use super::write::StoreWriteError;
+use super::zone::DayZone;
+use crate::events::{Annotation, Event, TimedEvent};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::io::ErrorKind;
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+
+const TMP_EXTENSION: &str = "log.tmp";
+
+#[derive(Deserialize)]
+struct LineTimestamp {
+    timestamp: DateTime<Utc>,
+}
+
+/// Insert events into the day files they belong to, keeping every file
+/// ordered by timestamp.
+///
+/// Unlike [`EventWriter`](super::EventWriter) this rewrites whole files, it
+/// must not be used on a file that is being appended to. Files keep their
+/// `StoreFormat`, new ones are encrypted when the store has a key.
+pub async fn insert_events(
+    store: impl Into<StorePath>,
+    mut events: Vec<TimedEvent>,
+) -> Result<(), StoreWriteError> {
+    let store = store.into();
+    let (dir, key) = (store.dir(), store.key());
+
+    if !tokio::fs::try_exists(dir).await? {
+        tokio::fs::create_dir(dir).await?;
+    }
+
+    events.sort_by_key(|e| e.timestamp);
+    let zone = DayZone::load(dir).await?;
+
+    for day in events
+        .chunk_by(|a, b| zone.date_of(a.timestamp) == zone.date_of(b.timestamp))
+    {
+        let filepath = Filepath::from(zone.date_of(day[0].timestamp))
+            .with_path(dir.to_path_buf());
+        let path = filepath.to_path_buf();
+        // Compressed days are written back uncompressed.
+        let compressed_path = filepath.with_compressed(true).to_path_buf();
+
+        let existing = match read_existing(&compressed_path, key).await? {
+            Some(content) => Some(content),
+            None => read_existing(&path, key).await?,
+        };
+        let (format, existing) = existing.unwrap_or_else(|| {
+            let format = match key {
+                Some(_) => StoreFormat::Encrypted,
+                None => StoreFormat::Json,
+            };
+            (format, String::new())
+        });
+
+        let merged = merge(&existing, day, format != StoreFormat::Json)?;
+        let merged = encode(format, merged, key)?;
+
+        let tmp_path = path.with_extension(TMP_EXTENSION);
+        let mut tmp = tokio::fs::File::create(&tmp_path).await?;
+        tmp.write_all(&merged).await?;
+        tmp.sync_all().await?;
+        drop(tmp);
+
+        tokio::fs::rename(&tmp_path, &path).await?;
+
+        match tokio::fs::remove_file(&compressed_path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Annotate `timestamp` with `text` and `tags`, in its day file wherever
+/// the time is. Same as [`insert_events`], the day must not be the one the
+/// daemon is appending to.
+pub async fn annotate(
+    store: impl Into<StorePath>,
+    timestamp: DateTime<Utc>,
+    text: String,
+    tags: Vec<String>,
+) -> Result<(), StoreWriteError> {
+    let event = TimedEvent {
+        timestamp,
+        event: Event::Annotation(Box::new(Annotation { text, tags })),
+    };
+
+    insert_events(store, vec![event]).await
+}
+
+/// Format and JSON lines of the day file at `path`.
+async fn read_existing(
+    path: &Path,
+    key: Option<&StoreKey>,
+) -> Result<Option<(StoreFormat, String)>, StoreWriteError> {
+    let path = path.to_path_buf();
+    let key = key.cloned();
+    let content = tokio::task::spawn_blocking(move || {
+        Ok::<_, std::io::Error>((
+            dayfile::format(&path)?,
+            dayfile::read_to_string(&path, key.as_ref())?,
+        ))
+    })
+    .await
+    .map_err(std::io::Error::other)?;
+
+    match content {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Merge sorted `events` into the lines of a day file. Existing lines are
+/// kept as they are, new events go after existing ones with the same time.
+///
+/// Decoded files mark what they could not decode with lines that are not
+/// events, with `drop_damaged` those are left out instead of being written
+/// back as content.
+fn merge(
+    existing: &str,
+    events: &[TimedEvent],
+    drop_damaged: bool,
+) -> Result<Vec<u8>, StoreWriteError> {
+    let mut out = Vec::with_capacity(existing.len() + events.len() * 128);
+    let mut events = events.iter().peekable();
+    let mut last_ts = DateTime::<Utc>::MIN_UTC;
+
+    for line in existing.lines().filter(|l| !l.is_empty()) {
+        // Lines that can not be parsed stay next to their neighbours.
+        match serde_json::from_str::<LineTimestamp>(line) {
+            Ok(parsed) => last_ts = parsed.timestamp,
+            Err(_) if drop_damaged => {
+                log::warn!("Dropping a damaged line: {}", line);
+                continue;
+            }
+            Err(_) => {}
+        }
+
+        while let Some(event) = events.next_if(|e| e.timestamp < last_ts) {
+            serde_json::to_writer(&mut out, event)?;
+            out.push(b'\n');
+        }
+
+        out.extend_from_slice(line.as_bytes());
+        out.push(b'\n');
+    }
+
+    for event in events {
+        serde_json::to_writer(&mut out, event)?;
+        out.push(b'\n');
+    }
+
+    Ok(out)
+}
+
+/// JSON `lines` as a day file of `format`.
+fn encode(
+    format: StoreFormat,
+    lines: Vec<u8>,
+    key: Option<&StoreKey>,
+) -> Result<Vec<u8>, StoreWriteError> {
+    match format {
+        StoreFormat::Json => Ok(lines),
+        StoreFormat::Encrypted => {
+            let key = key.ok_or_else(crypt::no_key)?;
+            Ok(format::encrypt_lines(&lines, key)?)
+        }
+        StoreFormat::Cbor | StoreFormat::JsonCrc32 => {
+            let mut encoded = format.header().to_vec();
+            for line in lines.split(|byte| *byte == b'\n') {
+                if !line.is_empty() {
+                    let event: TimedEvent = serde_json::from_slice(line)?;
+                    encoded.extend_from_slice(&format.encode(&event)?);
+                }
+            }
+            Ok(encoded)
+        }
+    }
+}
@@ -1,9 +1,21 @@
 use log::error;
+#[cfg(feature = "async")]
+use log::warn;
+use std::fmt;
+use std::fs::TryLockError;
+#[cfg(feature = "async")]
+use std::io::Write;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use std::path::Path;
+#[cfg(feature = "async")]
 use std::path::PathBuf;
 use std::time::Duration;
 use thiserror::Error;
 
 pub const LOCK_FILE_TIME_SEC: Duration = Duration::from_secs(60);
+pub const LOCK_FILE_NAME: &str = "LOCK";
+/// Readers hold a shared lock of it, maintenance an exclusive one.
+pub const READ_LOCK_FILE_NAME: &str = "READ.LOCK";
 
 #[derive(Debug)]
 pub struct LockFile(std::fs::File);
@@ -16,30 +28,219 @@ impl Drop for LockFile {
     }
 }
 
+/// Process holding the lock, written into the lock file as `pid start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockHolder {
+    pub pid: u32,
+    /// Start time in clock ticks after boot, tells reused pids apart.
+    pub start_time: u64,
+}
+
+impl LockHolder {
+    pub fn current() -> Option<Self> {
+        let pid = std::process::id();
+
+        Some(LockHolder {
+            pid,
+            start_time: start_time(pid)?,
+        })
+    }
+
+    pub fn parse(content: &str) -> Option<Self> {
+        let (pid, start_time) = content.trim().split_once(' ')?;
+
+        Some(LockHolder {
+            pid: pid.parse().ok()?,
+            start_time: start_time.parse().ok()?,
+        })
+    }
+
+    /// Whether the process is still running, and not a new one with the
+    /// same pid.
+    pub fn is_alive(&self) -> bool {
+        start_time(self.pid) == Some(self.start_time)
+    }
+}
+
+impl fmt::Display for LockHolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.pid, self.start_time)
+    }
+}
+
+/// Field 22 of `/proc/<pid>/stat`.
+fn start_time(pid: u32) -> Option<u64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name in field 2 may contain spaces, it ends at the last
+    // parenthesis.
+    let (_, fields) = stat.rsplit_once(')')?;
+
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
 #[derive(Debug, Error)]
 pub enum LockFileError {
     #[error("LockFile IO Error: {0}")]
     Io(#[from] std::io::Error),
     #[error("LockFile failed to acquire lock.")]
-    TryLockError(#[from] std::fs::TryLockError),
+    TryLockError(#[from] TryLockError),
+    #[error("The store is locked by the running process {}.", .0.pid)]
+    Held(LockHolder),
 }
 
+#[cfg(feature = "async")]
 pub async fn acquire_lock_file(
     filepath: PathBuf,
 ) -> Result<LockFile, LockFileError> {
-    let filename = filepath.join("LOCK");
+    acquire_lock_file_with(filepath, false).await
+}
+
+#[cfg(feature = "async")]
+/// Lock the store. A lock of a process that is no longer running is taken
+/// over, with `force` also the lock of a running one.
+pub async fn acquire_lock_file_with(
+    filepath: PathBuf,
+    force: bool,
+) -> Result<LockFile, LockFileError> {
+    let filename = filepath.join(LOCK_FILE_NAME);
+
+    let result = try_lock(&filename).await;
+
+    if !matches!(
+        result,
+        Err(LockFileError::TryLockError(TryLockError::WouldBlock))
+    ) {
+        return result;
+    }
+
+    let holder = tokio::fs::read_to_string(&filename)
+        .await
+        .ok()
+        .and_then(|content| LockHolder::parse(&content));
+
+    match holder {
+        Some(holder) if !holder.is_alive() => {
+            warn!("Taking over the lock of stopped process {}.", holder.pid);
+        }
+        Some(holder) if force => {
+            warn!("Taking over the lock of process {}.", holder.pid);
+        }
+        Some(holder) => return Err(LockFileError::Held(holder)),
+        // Locks without a holder are taken by older versions.
+        None if force => warn!("Taking over the lock."),
+        None => {
+            return Err(TryLockError::WouldBlock.into());
+        }
+    }
+
+    // The old holder keeps its lock on the removed file.
+    tokio::fs::remove_file(&filename).await?;
+    try_lock(&filename).await
+}
 
+#[cfg(feature = "async")]
+async fn try_lock(filename: &Path) -> Result<LockFile, LockFileError> {
+    // Not truncated before it is locked, the content tells who holds it.
     let file = tokio::fs::OpenOptions::new()
         .create(true)
+        .truncate(false)
         .write(true)
-        .truncate(true)
         .open(filename)
         .await
         .map_err(LockFileError::Io)?;
 
-    let stdfile = file.into_std().await;
+    let mut stdfile = file.into_std().await;
     stdfile.try_lock()?;
-    let lock = LockFile(stdfile);
 
-    Ok(lock)
+    if let Some(holder) = LockHolder::current() {
+        stdfile.set_len(0)?;
+        writeln!(stdfile, "{}", holder)?;
+    }
+
+    Ok(LockFile(stdfile))
+}
+
+#[cfg(feature = "async")]
+/// Shared lock held while reading, maintenance waits for the readers to be
+/// done. Readers do not block the writer or each other.
+pub async fn acquire_read_lock(
+    filepath: &Path,
+) -> Result<LockFile, LockFileError> {
+    let file = open_read_lock(filepath).await?;
+    file.try_lock_shared()?;
+
+    Ok(LockFile(file))
+}
+
+/// Blocking `acquire_read_lock`.
+#[cfg(feature = "blocking")]
+pub fn acquire_read_lock_blocking(
+    filepath: &Path,
+) -> Result<LockFile, LockFileError> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(filepath.join(READ_LOCK_FILE_NAME))?;
+    file.try_lock_shared()?;
+
+    Ok(LockFile(file))
+}
+
+#[cfg(feature = "async")]
+/// Exclusive lock of the readers for the writer to remove or rewrite files,
+/// none while the store is read. Readers can not start while it is held.
+pub async fn try_exclude_readers(
+    filepath: &Path,
+) -> Result<Option<LockFile>, LockFileError> {
+    let file = open_read_lock(filepath).await?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(LockFile(file))),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(feature = "async")]
+/// Whether the store is read, by a reader holding `READ_LOCK_FILE_NAME`.
+pub async fn has_readers(filepath: &Path) -> Result<bool, LockFileError> {
+    Ok(try_exclude_readers(filepath).await?.is_none())
+}
+
+/// Locks of maintenance rewriting store files, held while nothing else
+/// writes or reads the store.
+#[derive(Debug)]
+pub struct MaintenanceLock {
+    _write: LockFile,
+    _read: LockFile,
+}
+
+#[cfg(feature = "async")]
+/// Lock the store for maintenance, fails while it is written or read.
+pub async fn acquire_maintenance_lock(
+    filepath: PathBuf,
+) -> Result<MaintenanceLock, LockFileError> {
+    let write = acquire_lock_file(filepath.clone()).await?;
+    let file = open_read_lock(&filepath).await?;
+    file.try_lock()?;
+
+    Ok(MaintenanceLock {
+        _write: write,
+        _read: LockFile(file),
+    })
+}
+
+#[cfg(feature = "async")]
+async fn open_read_lock(
+    filepath: &Path,
+) -> Result<std::fs::File, LockFileError> {
+    let file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(filepath.join(READ_LOCK_FILE_NAME))
+        .await?;
+
+    Ok(file.into_std().await)
 }
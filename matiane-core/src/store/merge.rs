@@ -0,0 +1,340 @@
+//! Merging the stores of several machines into one history.
+//!
+//! Events are interleaved by time and tagged with the host they were
+//! recorded on. The same event at the same time in more than one store is
+//! written once, with the host of the first store it is in.
+
+use super::crypt::StoreKey;
+use super::dayfile;
+use super::filepath::{Filepath, StorePath};
+use super::format::StoreFormat;
+use super::read::{EventReader, OnDecodeError, OpenAt, OpenOptions};
+use super::zone::DayZone;
+use crate::events::TimedEvent;
+use anyhow::{Result, bail};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tokio::fs::File;
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+/// A line of a merged store. Readers that do not know about hosts read it
+/// as the `TimedEvent` it is.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HostEvent {
+    #[serde(flatten)]
+    pub event: TimedEvent,
+    pub host: String,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeReport {
+    pub written: usize,
+    pub duplicates: usize,
+}
+
+/// Merge the stores in `dirs`, each with the name of its host, into `out`,
+/// which must not have day files yet. Encrypted stores are read with the key
+/// of `out`.
+///
+/// Day files are written encrypted when `out` has a key, otherwise in the
+/// format of the newest day file of the first store.
+pub async fn merge(
+    dirs: &[(String, PathBuf)],
+    out: impl Into<StorePath>,
+) -> Result<MergeReport> {
+    let out = out.into();
+    let out_dir = out.dir().to_path_buf();
+    tokio::fs::create_dir_all(&out_dir).await?;
+    if !EventReader::list_files(&out_dir).await?.items.is_empty() {
+        bail!("{:?} already has day files", out_dir);
+    }
+
+    let options = OpenOptions {
+        at: OpenAt::Earliest,
+        or_empty: true,
+        mmap: true,
+    };
+    let mut readers = Vec::with_capacity(dirs.len());
+    for (host, dir) in dirs {
+        let from = DateTime::UNIX_EPOCH.fixed_offset();
+        let store = StorePath::new(dir.clone()).with_key(out.key().cloned());
+        let reader = EventReader::open_with(store, &from, options).await?;
+        readers.push((host, reader.on_decode_error(OnDecodeError::Skip)));
+    }
+
+    // The next event of every reader, none once it is done.
+    let mut next = Vec::with_capacity(readers.len());
+    for (_, reader) in &mut readers {
+        next.push(reader.next_event().await?);
+    }
+
+    let format = match out.key() {
+        Some(_) => StoreFormat::Encrypted,
+        None => store_format(dirs).await?,
+    };
+    let zone = DayZone::load(&out_dir).await?;
+    let mut out = DayFiles::new(out_dir, zone, format, out.key().cloned());
+    let mut report = MergeReport::default();
+    // Events written at the time of the last one, to find duplicates.
+    let mut seen = HashSet::new();
+
+    // Earliest first, the first store on ties.
+    while let Some(i) = (0..next.len())
+        .filter(|&i| next[i].is_some())
+        .min_by_key(|&i| next[i].as_ref().map(|event| event.timestamp))
+    {
+        let event = next[i].take().unwrap();
+        let (host, reader) = &mut readers[i];
+        next[i] = reader.next_event().await?;
+
+        if out.last.is_some_and(|last| last != event.timestamp) {
+            seen.clear();
+        }
+
+        if !seen.insert(serde_json::to_string(&event.event)?) {
+            report.duplicates += 1;
+            continue;
+        }
+
+        out.write(&HostEvent {
+            event,
+            host: host.to_string(),
+        })
+        .await?;
+        report.written += 1;
+    }
+
+    out.flush().await?;
+    Ok(report)
+}
+
+/// Format of the newest day file of the first store with one, JSON for
+/// stores without any.
+async fn store_format(dirs: &[(String, PathBuf)]) -> Result<StoreFormat> {
+    for (_, dir) in dirs {
+        let files = EventReader::list_files(dir).await?;
+        if let Some(newest) = files.items.last() {
+            let path = newest.to_path_buf();
+            let format =
+                tokio::task::spawn_blocking(move || dayfile::format(&path))
+                    .await??;
+            return Ok(format);
+        }
+    }
+
+    Ok(StoreFormat::Json)
+}
+
+/// Lines written to the day file of their date.
+struct DayFiles {
+    dir: PathBuf,
+    zone: DayZone,
+    format: StoreFormat,
+    key: Option<StoreKey>,
+    file: Option<(NaiveDate, BufWriter<File>)>,
+    last: Option<DateTime<Utc>>,
+}
+
+impl DayFiles {
+    fn new(
+        dir: PathBuf,
+        zone: DayZone,
+        format: StoreFormat,
+        key: Option<StoreKey>,
+    ) -> Self {
+        Self {
+            dir,
+            zone,
+            format,
+            key,
+            file: None,
+            last: None,
+        }
+    }
+
+    async fn write(&mut self, event: &HostEvent) -> Result<()> {
+        let date = self.zone.date_of(event.event.timestamp);
+
+        if self.file.as_ref().is_none_or(|(day, _)| *day != date) {
+            self.flush().await?;
+
+            let path = Into::<Filepath>::into(date)
+                .with_path(self.dir.clone())
+                .to_path_buf();
+            let mut file = BufWriter::new(File::create_new(&path).await?);
+            file.write_all(self.format.header()).await?;
+            self.file = Some((date, file));
+        }
+
+        let (_, file) = self.file.as_mut().unwrap();
+        let line = self.format.encode_with(event, self.key.as_ref())?;
+        file.write_all(&line).await?;
+
+        self.last = Some(event.event.timestamp);
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if let Some((_, mut file)) = self.file.take() {
+            file.flush().await?;
+            file.get_ref().sync_all().await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Event, EventHead, Focused};
+    use crate::store::crypt;
+    use crate::store::{ENCRYPTED_HEADER, insert_events};
+    use chrono::TimeZone;
+
+    async fn write_store(dir: &std::path::Path, events: &[TimedEvent]) {
+        for day in events.chunk_by(|a, b| {
+            a.timestamp.date_naive() == b.timestamp.date_naive()
+        }) {
+            let lines: Vec<String> = day
+                .iter()
+                .map(|event| serde_json::to_string(event).unwrap())
+                .collect();
+            let name = day[0].timestamp.format("%Y%m%d.log").to_string();
+            tokio::fs::write(dir.join(name), lines.join("\n") + "\n")
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn merge_test() {
+        let at = |d, h| Utc.with_ymd_and_hms(2026, 1, d, h, 0, 0).unwrap();
+        let event = |timestamp, event| TimedEvent { timestamp, event };
+        let focused = Event::Focused(Box::new(Focused {
+            title: "a title".to_string(),
+            id: "a".to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }));
+
+        let laptop = tempfile::tempdir().unwrap();
+        let desktop = tempfile::tempdir().unwrap();
+        let empty = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+
+        write_store(
+            laptop.path(),
+            &[
+                event(at(1, 1), focused.clone()),
+                event(at(1, 3), Event::Idle),
+                event(at(2, 1), Event::Alive),
+            ],
+        )
+        .await;
+        // The first event was copied over from the laptop.
+        write_store(
+            desktop.path(),
+            &[
+                event(at(1, 1), focused),
+                event(at(1, 1), Event::Alive),
+                event(at(1, 2), Event::Alive),
+                event(at(3, 1), Event::Idle),
+            ],
+        )
+        .await;
+
+        let dirs = [
+            ("laptop".to_string(), laptop.path().to_path_buf()),
+            ("desktop".to_string(), desktop.path().to_path_buf()),
+            ("empty".to_string(), empty.path().to_path_buf()),
+        ];
+        let report = merge(&dirs, out.path().to_path_buf()).await.unwrap();
+        assert_eq!(
+            report,
+            MergeReport {
+                written: 6,
+                duplicates: 1
+            }
+        );
+
+        let day = tokio::fs::read_to_string(out.path().join("20260101.log"))
+            .await
+            .unwrap();
+        let lines: Vec<(String, &'static str)> = day
+            .lines()
+            .map(|line| {
+                let event: HostEvent = serde_json::from_str(line).unwrap();
+                (event.host, EventHead::parse(line).unwrap().kind)
+            })
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                ("laptop".to_string(), "focused"),
+                ("desktop".to_string(), "alive"),
+                ("desktop".to_string(), "alive"),
+                ("laptop".to_string(), "idle"),
+            ]
+        );
+
+        let from = at(1, 0).fixed_offset();
+        let mut reader = EventReader::open(out.path().to_path_buf(), &from)
+            .await
+            .unwrap();
+        let mut read = 0;
+        while reader.next_event().await.unwrap().is_some() {
+            read += 1;
+        }
+        assert_eq!(read, 6);
+
+        assert!(merge(&dirs, out.path().to_path_buf()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn merge_encrypted_test() {
+        let at = |h| Utc.with_ymd_and_hms(2026, 1, 1, h, 0, 0).unwrap();
+        let event = |timestamp| TimedEvent {
+            timestamp,
+            event: Event::Alive,
+        };
+        let key = StoreKey::new([7; crypt::KEY_LEN]);
+
+        let laptop = tempfile::tempdir().unwrap();
+        let desktop = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let store = |dir: &tempfile::TempDir| {
+            StorePath::new(dir.path().to_path_buf()).with_key(Some(key.clone()))
+        };
+
+        insert_events(store(&laptop), vec![event(at(1)), event(at(3))])
+            .await
+            .unwrap();
+        insert_events(store(&desktop), vec![event(at(2))])
+            .await
+            .unwrap();
+
+        let dirs = [
+            ("laptop".to_string(), laptop.path().to_path_buf()),
+            ("desktop".to_string(), desktop.path().to_path_buf()),
+        ];
+        let report = merge(&dirs, store(&out)).await.unwrap();
+        assert_eq!(report.written, 3);
+
+        let path = out.path().join("20260101.log");
+        let content = tokio::fs::read(&path).await.unwrap();
+        assert!(content.starts_with(ENCRYPTED_HEADER));
+        assert!(!content.windows(6).any(|w| w == b"laptop"));
+
+        let day = dayfile::read_to_string(&path, Some(&key)).unwrap();
+        let hosts: Vec<String> = day
+            .lines()
+            .map(|line| serde_json::from_str::<HostEvent>(line).unwrap().host)
+            .collect();
+        assert_eq!(hosts, ["laptop", "desktop", "laptop"]);
+    }
+}
@@ -1,16 +1,59 @@
-use super::filepath::{Filepath, TryIntoFilenameError};
-use super::readline::{AsyncLineReader, FileLineReaderOwned, LineReaderError};
-use crate::events::TimedEvent;
-use crate::store::readline::LineReader;
-use chrono::{DateTime, FixedOffset};
-use futures::stream::{self, Stream};
-use futures::{StreamExt, TryStreamExt};
+#[cfg(feature = "async")]
+use super::crypt::StoreKey;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use super::filepath::Filepath;
+#[cfg(feature = "async")]
+use super::filepath::StorePath;
+use super::filepath::TryIntoFilenameError;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use super::format::is_mismatched;
+use super::readline::LineReaderError;
+#[cfg(feature = "async")]
+use super::zone::DayZone;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use chrono::NaiveDate;
 use serde_json;
+#[cfg(any(feature = "async", feature = "blocking"))]
 use std::collections::BTreeSet;
-use std::path::Path;
+#[cfg(any(feature = "async", feature = "blocking"))]
+use std::ops::{Bound, RangeBounds};
 use std::path::PathBuf;
 use thiserror::Error;
-use tokio::fs::{self, File};
+
+#[cfg(feature = "async")]
+use super::dayfile::DayFile;
+#[cfg(feature = "async")]
+use super::index::{Index, IndexEntry};
+#[cfg(feature = "async")]
+use super::lock::{LockFile, LockFileError, acquire_read_lock};
+#[cfg(feature = "async")]
+use super::readline::{
+    AsyncLineReader, AsyncLineReverseReader, BinarySearch, LineReader,
+    MmapLineReader, ReaderResult, SeekLineReader,
+};
+#[cfg(feature = "async")]
+use crate::diagnostic::Report;
+#[cfg(feature = "async")]
+use crate::events::{EventHead, TimedEvent};
+#[cfg(feature = "async")]
+use chrono::{DateTime, FixedOffset, Utc};
+#[cfg(feature = "async")]
+use futures::stream::{self, Stream};
+#[cfg(feature = "async")]
+use futures::{StreamExt, TryStreamExt};
+#[cfg(feature = "async")]
+use std::cmp::Ordering;
+#[cfg(feature = "async")]
+use std::fs::TryLockError;
+#[cfg(feature = "async")]
+use std::io::{Cursor, SeekFrom};
+#[cfg(feature = "async")]
+use std::path::Path;
+#[cfg(feature = "async")]
+use tokio::fs;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+#[cfg(feature = "async")]
 use tokio_stream::wrappers::ReadDirStream;
 
 #[derive(Debug, Error)]
@@ -25,41 +68,311 @@ pub enum StoreReadError {
     NoFilesToOpen,
     #[error("Failed to read line: {0}")]
     LineReaderError(#[from] LineReaderError),
+    #[error("The store is locked for maintenance")]
+    Maintenance,
+    #[cfg(feature = "sqlite")]
+    #[error("Store database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("A line of {0:?} does not match its checksum")]
+    ChecksumMismatch(PathBuf),
 }
 
 pub type EventReaderResult<T> = Result<T, StoreReadError>;
 
+/// What readers do with lines that are not events.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OnDecodeError {
+    /// Return the error, the reader can not go on.
+    #[default]
+    Fail,
+    /// Skip the line.
+    Skip,
+    /// Skip the line and keep the error, see `take_errors`.
+    Collect,
+}
+
+/// Day file an `EventReader` opens for a time.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OpenAt {
+    /// The file of the day, or the first one after it.
+    #[default]
+    AtOrAfter,
+    /// The file of the day, or the last one before it.
+    AtOrBefore,
+    /// The first file of the store, whatever the time.
+    Earliest,
+}
+
+/// How `EventReader::open_with` opens the store.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OpenOptions {
+    pub at: OpenAt,
+    /// Without a file to open, open a reader without events instead of
+    /// failing with `NoFilesToOpen`.
+    pub or_empty: bool,
+    /// Read finished days through a memory map, faster for reading a lot
+    /// of history. The file of today is still written to and is read
+    /// buffered.
+    pub mmap: bool,
+}
+
+/// Line that could not be decoded.
+#[derive(Debug)]
+pub struct DecodeError {
+    pub path: PathBuf,
+    /// Empty for a line that was too long to read.
+    pub line: String,
+    /// `EncodeError`, `ChecksumMismatch` or a `LineTooLong`
+    /// `LineReaderError`.
+    pub error: StoreReadError,
+}
+
+/// Fail or skip a line that could not be decoded, by `policy`.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(super) fn decode_failed(
+    policy: OnDecodeError,
+    errors: &mut Vec<DecodeError>,
+    path: &Filepath,
+    line: impl AsRef<[u8]>,
+    error: StoreReadError,
+) -> EventReaderResult<()> {
+    match policy {
+        OnDecodeError::Fail => return Err(error),
+        OnDecodeError::Skip => {}
+        OnDecodeError::Collect => errors.push(DecodeError {
+            path: path.to_path_buf(),
+            line: String::from_utf8_lossy(line.as_ref()).into_owned(),
+            error,
+        }),
+    }
+
+    log::warn!("Skipping a damaged line of {:?}", path.to_path_buf());
+    Ok(())
+}
+
+/// Fail or skip `line` by `policy` when it did not match its checksum.
+/// Returns whether it did not.
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(super) fn checksum_failed(
+    policy: OnDecodeError,
+    errors: &mut Vec<DecodeError>,
+    path: &Filepath,
+    line: impl AsRef<[u8]>,
+) -> EventReaderResult<bool> {
+    if !is_mismatched(&line) {
+        return Ok(false);
+    }
+
+    let error = StoreReadError::ChecksumMismatch(path.to_path_buf());
+    decode_failed(policy, errors, path, line, error)?;
+    Ok(true)
+}
+
+#[cfg(feature = "async")]
 pub struct EventReader {
     file_path: Filepath,
-    line_reader: FileLineReaderOwned,
+    line_reader: DayLines,
+    key: Option<StoreKey>,
+    mmap: bool,
+    /// Zone of the days of the store.
+    zone: DayZone,
+    /// Events before it are skipped.
+    skip_before: Option<DateTime<Utc>>,
+    /// Reading ends at the first event at or after it.
+    until: Option<DateTime<Utc>>,
+    done: bool,
+    on_decode_error: OnDecodeError,
+    errors: Vec<DecodeError>,
+    /// Shared lock keeping maintenance away while reading, missing when
+    /// the store can not be written to.
+    _read_lock: Option<LockFile>,
 }
 
+#[cfg(feature = "async")]
 impl EventReader {
     pub async fn open(
-        dir: PathBuf,
+        store: impl Into<StorePath>,
+        open_at: &DateTime<FixedOffset>,
+    ) -> EventReaderResult<Self> {
+        Self::open_with(store, open_at, OpenOptions::default()).await
+    }
+
+    /// Open at the start of the day file picked by `options` for
+    /// `open_at`.
+    pub async fn open_with(
+        store: impl Into<StorePath>,
         open_at: &DateTime<FixedOffset>,
+        options: OpenOptions,
     ) -> EventReaderResult<Self> {
-        let utc_naive = open_at.to_utc().date_naive();
+        let store = store.into();
+        let dir = store.dir();
+        let key = store.key().cloned();
+        let read_lock = Self::read_lock(dir).await?;
+        let zone = DayZone::load(dir).await?;
 
+        let date = zone.date_of(open_at.to_utc());
         let from_path =
-            Into::<Filepath>::into(utc_naive).with_path(dir.clone());
+            Into::<Filepath>::into(date).with_path(dir.to_path_buf());
 
         let first = {
-            let entries = Self::list_files(&dir).await?;
-            entries.range(&from_path..).next().cloned()
-        }
-        .ok_or(StoreReadError::NoFilesToOpen)?;
+            let entries = Self::list_files(dir).await?;
+            entries.find(&from_path, options.at).cloned()
+        };
 
-        let path = first.to_path_buf();
-        log::debug!("Opening file: {:?}", &path);
-        let file = open_read_file(&path).await?;
+        let (file_path, line_reader) = match first {
+            Some(first) => {
+                log::debug!("Opening file: {:?}", first.to_path_buf());
+                let lines =
+                    DayLines::open(&first, options.mmap, zone, key.as_ref())
+                        .await?;
+                (first, lines)
+            }
+            // Files written after it are still read.
+            None if options.or_empty => (from_path, DayLines::empty()),
+            None => return Err(StoreReadError::NoFilesToOpen),
+        };
 
         Ok(Self {
-            file_path: first,
-            line_reader: AsyncLineReader::new(file),
+            file_path,
+            line_reader,
+            key,
+            mmap: options.mmap,
+            zone,
+            skip_before: None,
+            until: None,
+            done: false,
+            on_decode_error: OnDecodeError::default(),
+            errors: vec![],
+            _read_lock: read_lock,
         })
     }
 
+    /// Open at the first event at or after `from`, found with the index of
+    /// its file or by binary search without one.
+    pub async fn open_from(
+        store: impl Into<StorePath>,
+        from: &DateTime<FixedOffset>,
+    ) -> EventReaderResult<Self> {
+        let mut reader = Self::open(store, from).await?;
+        reader.seek_to(from.to_utc()).await?;
+
+        Ok(reader)
+    }
+
+    /// Move to the first event at or after `timestamp`, in the file being
+    /// read or another one. Reading goes on from there, up to the end of
+    /// the range the reader was opened for.
+    pub async fn seek_to(
+        &mut self,
+        timestamp: DateTime<Utc>,
+    ) -> EventReaderResult<()> {
+        let date = self.zone.date_of(timestamp);
+
+        if *self.file_path.date() != date {
+            let dir = self.file_path.path().to_path_buf();
+            let from = Into::<Filepath>::into(date).with_path(dir.clone());
+            let found = Self::list_files(&dir)
+                .await?
+                .find(&from, OpenAt::AtOrAfter)
+                .cloned();
+
+            // Past the last file, files written after it are still read.
+            let (file_path, line_reader) = match found {
+                Some(found) => {
+                    log::debug!("Seeking to file: {:?}", found.to_path_buf());
+                    let lines = self.open_lines(&found).await?;
+                    (found, lines)
+                }
+                None => (from, DayLines::empty()),
+            };
+
+            self.line_reader = line_reader;
+            self.file_path = file_path;
+        }
+
+        let offset = if *self.file_path.date() == date {
+            let path = self.file_path.to_path_buf();
+            match Index::read(&path).await? {
+                Some(index) => {
+                    index.entry_before(timestamp).map_or(0, |e| e.offset)
+                }
+                None => self.line_reader.seek_first_from(timestamp).await?,
+            }
+        } else {
+            0
+        };
+
+        self.line_reader.seek(SeekFrom::Start(offset)).await?;
+        self.skip_before = Some(timestamp);
+        self.done = false;
+
+        Ok(())
+    }
+
+    /// Open for the events in `[from, to)`.
+    pub async fn open_range(
+        store: impl Into<StorePath>,
+        from: &DateTime<FixedOffset>,
+        to: DateTime<Utc>,
+    ) -> EventReaderResult<Self> {
+        let mut reader = Self::open_from(store, from).await?;
+        reader.until = Some(to);
+
+        Ok(reader)
+    }
+
+    /// Open at the index entry before `from` and return it, so what was
+    /// going on at `from` can be picked up from the entry. Opens at the
+    /// start of the file without an index.
+    pub async fn open_indexed(
+        store: impl Into<StorePath>,
+        from: &DateTime<FixedOffset>,
+    ) -> EventReaderResult<(Self, Option<IndexEntry>)> {
+        let mut reader = Self::open(store, from).await?;
+        let from = from.to_utc();
+
+        if *reader.file_path.date() != reader.zone.date_of(from) {
+            return Ok((reader, None));
+        }
+
+        let index = Index::read(&reader.file_path.to_path_buf()).await?;
+        let entry = index.and_then(|index| index.entry_before(from).cloned());
+
+        if let Some(entry) = &entry {
+            reader
+                .line_reader
+                .seek(SeekFrom::Start(entry.offset))
+                .await?;
+        }
+
+        Ok((reader, entry))
+    }
+
+    pub fn on_decode_error(mut self, policy: OnDecodeError) -> Self {
+        self.on_decode_error = policy;
+        self
+    }
+
+    /// Lines skipped with `OnDecodeError::Collect` so far.
+    pub fn take_errors(&mut self) -> Vec<DecodeError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Shared lock keeping maintenance away, none when the store can not
+    /// be written to.
+    pub async fn read_lock(dir: &Path) -> EventReaderResult<Option<LockFile>> {
+        match acquire_read_lock(dir).await {
+            Ok(lock) => Ok(Some(lock)),
+            Err(LockFileError::TryLockError(TryLockError::WouldBlock)) => {
+                Err(StoreReadError::Maintenance)
+            }
+            Err(e) => {
+                log::debug!("Reading without a read lock: {}", Report(&e));
+                Ok(None)
+            }
+        }
+    }
+
     pub async fn list_files(dir: &Path) -> EventReaderResult<StoreDirectory> {
         ReadDirStream::new(fs::read_dir(dir).await?)
             .map_err(StoreReadError::Io)
@@ -75,17 +388,103 @@ impl EventReader {
     pub async fn next_event(
         &mut self,
     ) -> EventReaderResult<Option<TimedEvent>> {
-        let line = loop {
-            if let Some(l) = self.line_reader.next_line().await? {
-                break l;
-            }
+        self.next_parsed(|line| Ok(serde_json::from_slice(line)?))
+            .await
+    }
 
-            if !self.open_next_file().await? {
-                return Ok(None);
-            }
-        };
+    /// Time and type of the next event, without decoding the rest.
+    pub async fn next_head(&mut self) -> EventReaderResult<Option<EventHead>> {
+        self.next_parsed(|line| Ok(EventHead::parse_slice(line)?))
+            .await
+    }
 
-        Ok(serde_json::from_str(&line)?)
+    /// Next line parsed while it is borrowed from the line reader. Lines
+    /// are checked to be UTF-8 by the parser, as they are parsed.
+    async fn next_parsed<T>(
+        &mut self,
+        parse: impl Fn(&[u8]) -> EventReaderResult<T>,
+    ) -> EventReaderResult<Option<T>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        loop {
+            let line = match self.line_reader.next_line_bytes().await {
+                Err(e @ LineReaderError::LineTooLong(_)) => {
+                    decode_failed(
+                        self.on_decode_error,
+                        &mut self.errors,
+                        &self.file_path,
+                        b"",
+                        e.into(),
+                    )?;
+                    continue;
+                }
+                line => line?,
+            };
+
+            match line {
+                // Every event is terminated with a newline, so the last
+                // line of the file is empty.
+                Some([]) => continue,
+                Some(line) => {
+                    if checksum_failed(
+                        self.on_decode_error,
+                        &mut self.errors,
+                        &self.file_path,
+                        line,
+                    )? {
+                        continue;
+                    }
+
+                    if self.skip_before.is_some() || self.until.is_some() {
+                        let timestamp = match EventHead::parse_slice(line) {
+                            Ok(head) => head.timestamp,
+                            Err(e) => {
+                                decode_failed(
+                                    self.on_decode_error,
+                                    &mut self.errors,
+                                    &self.file_path,
+                                    line,
+                                    e.into(),
+                                )?;
+                                continue;
+                            }
+                        };
+
+                        if self.skip_before.is_some_and(|from| timestamp < from)
+                        {
+                            continue;
+                        }
+                        self.skip_before = None;
+
+                        if self.until.is_some_and(|to| timestamp >= to) {
+                            self.done = true;
+                            return Ok(None);
+                        }
+                    }
+
+                    match parse(line) {
+                        Ok(parsed) => return Ok(Some(parsed)),
+                        Err(e @ StoreReadError::EncodeError(_)) => {
+                            decode_failed(
+                                self.on_decode_error,
+                                &mut self.errors,
+                                &self.file_path,
+                                line,
+                                e,
+                            )?
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                None => {
+                    if !self.open_next_file().await? {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
     }
 
     pub async fn open_next_file(&mut self) -> EventReaderResult<bool> {
@@ -98,12 +497,210 @@ impl EventReader {
         };
 
         match next_fp {
+            Some(fp) => {
+                log::debug!("Opening next file: {:?}", fp.to_path_buf());
+
+                self.line_reader = self.open_lines(&fp).await?;
+                self.file_path = fp;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn open_lines(
+        &self,
+        filepath: &Filepath,
+    ) -> EventReaderResult<DayLines> {
+        DayLines::open(filepath, self.mmap, self.zone, self.key.as_ref()).await
+    }
+
+    pub fn into_stream(
+        self,
+    ) -> impl Stream<Item = EventReaderResult<TimedEvent>>
+    where
+        Self: Sized,
+    {
+        stream::unfold(self, |mut reader| async {
+            match reader.next_event().await {
+                Ok(Some(line)) => Some((Ok(line), reader)),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), reader)),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+/// Reads events newest first, from the end of the day files back.
+pub struct ReverseEventReader {
+    file_path: Filepath,
+    line_reader: AsyncLineReverseReader<'static, DayFile>,
+    key: Option<StoreKey>,
+    on_decode_error: OnDecodeError,
+    errors: Vec<DecodeError>,
+    _read_lock: Option<LockFile>,
+}
+
+#[cfg(feature = "async")]
+impl ReverseEventReader {
+    /// Open at the last event before `before`.
+    pub async fn open(
+        store: impl Into<StorePath>,
+        before: &DateTime<FixedOffset>,
+    ) -> EventReaderResult<Self> {
+        let store = store.into();
+        let dir = store.dir();
+        let read_lock = EventReader::read_lock(dir).await?;
+        let zone = DayZone::load(dir).await?;
+        let before = before.to_utc();
+        let date = zone.date_of(before);
+
+        let before_path =
+            Into::<Filepath>::into(date).with_path(dir.to_path_buf());
+
+        let last = {
+            let entries = EventReader::list_files(dir).await?;
+            entries.range(..=&before_path).next_back().cloned()
+        }
+        .ok_or(StoreReadError::NoFilesToOpen)?;
+
+        let until = (*last.date() == date).then_some(before);
+
+        Self::open_file(last, until, store.key().cloned(), read_lock).await
+    }
+
+    /// Open at the newest event of the store.
+    pub async fn open_latest(
+        store: impl Into<StorePath>,
+    ) -> EventReaderResult<Self> {
+        let store = store.into();
+        let read_lock = EventReader::read_lock(store.dir()).await?;
+        let last = EventReader::list_files(store.dir())
+            .await?
+            .items
+            .last()
+            .cloned()
+            .ok_or(StoreReadError::NoFilesToOpen)?;
+
+        Self::open_file(last, None, store.key().cloned(), read_lock).await
+    }
+
+    /// Open `filepath` at the end, or at the first event at or after
+    /// `until`.
+    async fn open_file(
+        filepath: Filepath,
+        until: Option<DateTime<Utc>>,
+        key: Option<StoreKey>,
+        read_lock: Option<LockFile>,
+    ) -> EventReaderResult<Self> {
+        let path = filepath.to_path_buf();
+        log::debug!("Opening file backwards: {:?}", &path);
+        let mut file = open_read_file(&path, key.as_ref()).await?;
+
+        let end = match until {
+            Some(until) => seek_first_from(&mut file, until).await?,
+            None => file.len().await?,
+        };
+
+        let mut line_reader = AsyncLineReverseReader::new(file);
+        line_reader.seek(SeekFrom::Start(end)).await?;
+
+        Ok(Self {
+            file_path: filepath,
+            line_reader,
+            key,
+            on_decode_error: OnDecodeError::default(),
+            errors: vec![],
+            _read_lock: read_lock,
+        })
+    }
+
+    pub fn on_decode_error(mut self, policy: OnDecodeError) -> Self {
+        self.on_decode_error = policy;
+        self
+    }
+
+    /// Lines skipped with `OnDecodeError::Collect` so far.
+    pub fn take_errors(&mut self) -> Vec<DecodeError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    pub async fn next_event(
+        &mut self,
+    ) -> EventReaderResult<Option<TimedEvent>> {
+        self.next_parsed(|line| Ok(serde_json::from_str(line)?))
+            .await
+    }
+
+    /// Time and type of the next event, without decoding the rest.
+    pub async fn next_head(&mut self) -> EventReaderResult<Option<EventHead>> {
+        self.next_parsed(|line| Ok(EventHead::parse(line)?)).await
+    }
+
+    async fn next_parsed<T>(
+        &mut self,
+        parse: impl Fn(&str) -> EventReaderResult<T>,
+    ) -> EventReaderResult<Option<T>> {
+        loop {
+            let line = match self.line_reader.next_line_ref().await {
+                Err(e @ LineReaderError::LineTooLong(_)) => {
+                    decode_failed(
+                        self.on_decode_error,
+                        &mut self.errors,
+                        &self.file_path,
+                        b"",
+                        e.into(),
+                    )?;
+                    continue;
+                }
+                line => line?,
+            };
+
+            match line {
+                Some("") => continue,
+                Some(line)
+                    if checksum_failed(
+                        self.on_decode_error,
+                        &mut self.errors,
+                        &self.file_path,
+                        line,
+                    )? => {}
+                Some(line) => match parse(line) {
+                    Ok(parsed) => return Ok(Some(parsed)),
+                    Err(e @ StoreReadError::EncodeError(_)) => decode_failed(
+                        self.on_decode_error,
+                        &mut self.errors,
+                        &self.file_path,
+                        line,
+                        e,
+                    )?,
+                    Err(e) => return Err(e),
+                },
+                None => {
+                    if !self.open_previous_file().await? {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn open_previous_file(&mut self) -> EventReaderResult<bool> {
+        let previous_fp = EventReader::list_files(self.file_path.path())
+            .await?
+            .range(..&self.file_path)
+            .next_back()
+            .cloned();
+
+        match previous_fp {
             Some(fp) => {
                 let path = fp.to_path_buf();
-                log::debug!("Opening next file: {:?}", path);
-                let file = open_read_file(&path).await?;
+                log::debug!("Opening previous file: {:?}", path);
+                let file = open_read_file(&path, self.key.as_ref()).await?;
 
-                self.line_reader = AsyncLineReader::new(file);
+                self.line_reader = AsyncLineReverseReader::new(file);
+                self.line_reader.rewind().await?;
                 self.file_path = fp;
                 Ok(true)
             }
@@ -127,11 +724,13 @@ impl EventReader {
     }
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 #[derive(Debug, Default)]
 pub struct StoreDirectory {
     pub items: BTreeSet<Filepath>,
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 impl StoreDirectory {
     pub fn len(&self) -> usize {
         self.items.len()
@@ -147,8 +746,54 @@ impl StoreDirectory {
     ) -> std::collections::btree_set::Range<'_, Filepath> {
         self.items.range(range)
     }
+
+    /// Days in `range` without a day file, when the daemon was not
+    /// running. Unbounded ends are the days of the first and last files.
+    pub fn missing_days(
+        &self,
+        range: impl RangeBounds<NaiveDate>,
+    ) -> Vec<NaiveDate> {
+        let dates: BTreeSet<NaiveDate> =
+            self.items.iter().map(|filepath| *filepath.date()).collect();
+
+        let start = match range.start_bound() {
+            Bound::Included(date) => Some(*date),
+            Bound::Excluded(date) => date.succ_opt(),
+            Bound::Unbounded => dates.first().copied(),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(date) => Some(*date),
+            Bound::Excluded(date) => date.pred_opt(),
+            Bound::Unbounded => dates.last().copied(),
+        };
+
+        let (Some(start), Some(end)) = (start, end) else {
+            return vec![];
+        };
+
+        start
+            .iter_days()
+            .take_while(|date| *date <= end)
+            .filter(|date| !dates.contains(date))
+            .collect()
+    }
+
+    /// File to open for the date of `from`, by `at`.
+    #[cfg(feature = "async")]
+    pub(super) fn find(
+        &self,
+        from: &Filepath,
+        at: OpenAt,
+    ) -> Option<&Filepath> {
+        match at {
+            OpenAt::AtOrAfter => self.range(from..).next(),
+            OpenAt::AtOrBefore => self.range(..=from).next_back(),
+            OpenAt::Earliest => self.items.first(),
+        }
+    }
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 impl std::iter::Extend<Filepath> for StoreDirectory {
     #[inline]
     fn extend<Iter: IntoIterator<Item = Filepath>>(&mut self, iter: Iter) {
@@ -156,9 +801,107 @@ impl std::iter::Extend<Filepath> for StoreDirectory {
     }
 }
 
-async fn open_read_file(filepath: &PathBuf) -> EventReaderResult<File> {
-    Ok(tokio::fs::OpenOptions::new()
-        .read(true)
-        .open(filepath)
-        .await?)
+#[cfg(feature = "async")]
+/// Lines of the day file an `EventReader` reads.
+enum DayLines {
+    Buffered(AsyncLineReader<'static, DayFile>),
+    Mapped(MmapLineReader),
+}
+
+#[cfg(feature = "async")]
+impl DayLines {
+    async fn open(
+        filepath: &Filepath,
+        mmap: bool,
+        zone: DayZone,
+        key: Option<&StoreKey>,
+    ) -> EventReaderResult<Self> {
+        let file = open_read_file(&filepath.to_path_buf(), key).await?;
+        let finished = zone.is_finished(*filepath.date(), Utc::now());
+
+        // Only plain files are mapped, the others are decoded to memory.
+        match file {
+            DayFile::Plain(file) if mmap && finished => {
+                Ok(DayLines::Mapped(MmapLineReader::new(&file)?))
+            }
+            file => Ok(DayLines::Buffered(AsyncLineReader::new(file))),
+        }
+    }
+
+    fn empty() -> Self {
+        let file = DayFile::Decoded(Cursor::new(vec![]));
+        DayLines::Buffered(AsyncLineReader::new(file))
+    }
+
+    async fn seek_first_from(
+        &mut self,
+        from: DateTime<Utc>,
+    ) -> EventReaderResult<u64> {
+        match self {
+            DayLines::Buffered(reader) => {
+                seek_first_from(reader.get_mut(), from).await
+            }
+            DayLines::Mapped(reader) => {
+                let bytes = reader.as_bytes();
+                seek_first_from(&mut Cursor::new(bytes), from).await
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl LineReader for DayLines {
+    async fn next_line_bytes(&mut self) -> ReaderResult<Option<&[u8]>> {
+        match self {
+            DayLines::Buffered(reader) => reader.next_line_bytes().await,
+            DayLines::Mapped(reader) => reader.next_line_bytes().await,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl SeekLineReader for DayLines {
+    async fn rewind(&mut self) -> ReaderResult<u64> {
+        match self {
+            DayLines::Buffered(reader) => reader.rewind().await,
+            DayLines::Mapped(reader) => reader.rewind().await,
+        }
+    }
+
+    async fn seek(&mut self, pos: SeekFrom) -> ReaderResult<u64> {
+        match self {
+            DayLines::Buffered(reader) => reader.seek(pos).await,
+            DayLines::Mapped(reader) => reader.seek(pos).await,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+/// Offset of the first line of `file` at or after `from`, its length
+/// without one.
+async fn seek_first_from<F>(
+    file: &mut F,
+    from: DateTime<Utc>,
+) -> EventReaderResult<u64>
+where
+    F: AsyncReadExt + AsyncSeekExt + Unpin,
+{
+    let cmp = |line: &str| {
+        if line.is_empty() {
+            return Ok(Ordering::Less);
+        }
+
+        let head = EventHead::parse(line).map_err(LineReaderError::compare)?;
+        Ok(head.timestamp.cmp(&from))
+    };
+
+    Ok(BinarySearch::new(file, cmp).find_first_ge().await?)
+}
+
+#[cfg(feature = "async")]
+async fn open_read_file(
+    filepath: &Path,
+    key: Option<&StoreKey>,
+) -> EventReaderResult<DayFile> {
+    Ok(DayFile::open(filepath, key).await?)
 }
@@ -1,30 +1,49 @@
+#[cfg(feature = "async")]
 use crate::util::{memchr, memrchr};
+#[cfg(feature = "async")]
 use futures::stream::{self, Stream};
+#[cfg(feature = "async")]
+use memmap2::Mmap;
+#[cfg(feature = "async")]
 use std::cmp::Ordering;
+#[cfg(any(feature = "async", feature = "blocking"))]
 use std::num::NonZeroUsize;
 use thiserror::Error;
+#[cfg(feature = "async")]
 use tokio::fs::File;
+#[cfg(feature = "async")]
 use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
 
 // 512 KiB.
-const DEFAULT_BUF_SIZE: NonZeroUsize = NonZeroUsize::new(512 * 1024).unwrap();
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(super) const DEFAULT_BUF_SIZE: NonZeroUsize =
+    NonZeroUsize::new(512 * 1024).unwrap();
 
 // 64 KiB.
-const DEFAULT_REV_BUF_SIZE: NonZeroUsize =
+#[cfg(any(feature = "async", feature = "blocking"))]
+pub(super) const DEFAULT_REV_BUF_SIZE: NonZeroUsize =
     NonZeroUsize::new(64 * 1024).unwrap();
 
 // 4 KiB.
-const DEFAULT_SEEK_BUF_SIZE: NonZeroUsize =
+#[cfg(feature = "async")]
+pub(super) const DEFAULT_SEEK_BUF_SIZE: NonZeroUsize =
     NonZeroUsize::new(4 * 1024).unwrap();
 
+/// Longest line readers return by default, 16 MiB. Events are far shorter,
+/// a longer line is a damaged file without newlines.
+#[cfg(feature = "async")]
+pub const DEFAULT_MAX_LINE_LEN: usize = 16 * 1024 * 1024;
+
 #[derive(Debug, Error)]
 pub enum LineReaderError {
     #[error("Store IO Error: {0}")]
     Io(#[from] std::io::Error),
     #[error("String UTF Error: {0}")]
-    Utf8(#[from] std::string::FromUtf8Error),
+    Utf8(#[from] std::str::Utf8Error),
     #[error("Compare Error: {0}")]
     Compare(#[from] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Line is longer than {0} bytes")]
+    LineTooLong(usize),
 }
 
 impl LineReaderError {
@@ -35,17 +54,31 @@ impl LineReaderError {
 
 pub type ReaderResult<T> = Result<T, LineReaderError>;
 
+#[cfg(feature = "async")]
 pub trait LineReader {
-    fn next_line(
+    /// Next line borrowed from the reader, valid until the next call. It
+    /// is not checked to be UTF-8, for parsers that check it themselves.
+    fn next_line_bytes(
         &mut self,
-    ) -> impl Future<Output = ReaderResult<Option<String>>>;
+    ) -> impl Future<Output = ReaderResult<Option<&[u8]>>>;
 
-    fn rewind(&mut self) -> impl Future<Output = ReaderResult<u64>>;
+    /// Next line borrowed from the reader, valid until the next call.
+    fn next_line_ref(
+        &mut self,
+    ) -> impl Future<Output = ReaderResult<Option<&str>>> {
+        async {
+            match self.next_line_bytes().await? {
+                Some(line) => Ok(Some(std::str::from_utf8(line)?)),
+                None => Ok(None),
+            }
+        }
+    }
 
-    fn seek(
+    fn next_line(
         &mut self,
-        pos: SeekFrom,
-    ) -> impl Future<Output = ReaderResult<u64>>;
+    ) -> impl Future<Output = ReaderResult<Option<String>>> {
+        async { Ok(self.next_line_ref().await?.map(str::to_owned)) }
+    }
 
     fn into_stream(self) -> impl Stream<Item = ReaderResult<String>>
     where
@@ -61,23 +94,45 @@ pub trait LineReader {
     }
 }
 
-/// Reader reads buffer then processes, may not read full buffer.
+#[cfg(feature = "async")]
+/// Line reader of a source that can be seeked, like a file.
+pub trait SeekLineReader: LineReader {
+    fn rewind(&mut self) -> impl Future<Output = ReaderResult<u64>>;
+
+    fn seek(
+        &mut self,
+        pos: SeekFrom,
+    ) -> impl Future<Output = ReaderResult<u64>>;
+}
+
+#[cfg(feature = "async")]
+/// Reader reads buffer then processes, may not read full buffer. Reads any
+/// `AsyncRead`, like a decoder or a socket, and seeks the ones that are
+/// `AsyncSeek`.
 pub struct AsyncLineReader<'a, F>
 where
-    F: AsyncReadExt + AsyncSeekExt + Unpin,
+    F: AsyncReadExt + Unpin,
 {
     file: F,
     buffer: BufferRef<'a>,
     line_buf: Vec<u8>,
+    /// Last line read over a chunk boundary.
+    line: Vec<u8>,
     eof: bool,
+    max_line_len: usize,
+    /// Dropping the rest of a line that was too long.
+    skipping: bool,
 }
 
+#[cfg(feature = "async")]
 pub type FileLineReaderRef<'a> = AsyncLineReader<'a, &'a mut File>;
+#[cfg(feature = "async")]
 pub type FileLineReaderOwned = AsyncLineReader<'static, File>;
 
+#[cfg(feature = "async")]
 impl<'a, F> AsyncLineReader<'a, F>
 where
-    F: AsyncReadExt + AsyncSeekExt + Unpin,
+    F: AsyncReadExt + Unpin,
 {
     pub fn new(file: F) -> Self {
         Self::with_buffer_size(file, DEFAULT_BUF_SIZE)
@@ -88,7 +143,10 @@ where
             file,
             buffer: BufferRef::Owned(Buffer::new(buffer_size)),
             line_buf: Vec::new(),
+            line: Vec::new(),
             eof: false,
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+            skipping: false,
         }
     }
 
@@ -97,14 +155,30 @@ where
             file,
             buffer: BufferRef::Borrowed(buffer),
             line_buf: Vec::new(),
+            line: Vec::new(),
             eof: false,
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+            skipping: false,
         }
     }
 
+    /// Lines longer than `len` are not read, they fail with `LineTooLong`
+    /// and reading goes on after them.
+    pub fn max_line_len(mut self, len: usize) -> Self {
+        self.max_line_len = len;
+        self
+    }
+
+    /// The file read. Seek the reader after moving it.
+    pub fn get_mut(&mut self) -> &mut F {
+        &mut self.file
+    }
+
     fn reset(&mut self) {
         self.line_buf.clear();
         self.buffer.reset();
         self.eof = false;
+        self.skipping = false;
     }
 
     async fn read_to_buffer(&mut self) -> ReaderResult<()> {
@@ -121,7 +195,8 @@ where
     }
 }
 
-impl<F> LineReader for AsyncLineReader<'_, F>
+#[cfg(feature = "async")]
+impl<F> SeekLineReader for AsyncLineReader<'_, F>
 where
     F: AsyncReadExt + AsyncSeekExt + Unpin,
 {
@@ -133,36 +208,70 @@ where
         self.reset();
         Ok(self.file.seek(pos).await?)
     }
+}
 
-    async fn next_line(&mut self) -> ReaderResult<Option<String>> {
+#[cfg(feature = "async")]
+impl<F> LineReader for AsyncLineReader<'_, F>
+where
+    F: AsyncReadExt + Unpin,
+{
+    async fn next_line_bytes(&mut self) -> ReaderResult<Option<&[u8]>> {
         while !self.eof {
             if self.buffer.unprocessed_len() == 0 {
+                self.buffer.reset();
                 self.read_to_buffer().await?;
             }
 
-            let unprocessed = self.buffer.unprocessed_forward();
+            let found = memchr(b'\n', self.buffer.unprocessed_forward());
 
-            if let Some(n) = memchr(b'\n', unprocessed) {
-                self.line_buf.extend_from_slice(&unprocessed[0..n]);
+            if let Some(n) = found {
+                if std::mem::take(&mut self.skipping) {
+                    self.buffer.consume_forward(n + 1);
+                    continue;
+                }
+
+                if self.line_buf.len() + n > self.max_line_len {
+                    self.buffer.consume_forward(n + 1);
+                    self.line_buf.clear();
+                    return Err(LineReaderError::LineTooLong(
+                        self.max_line_len,
+                    ));
+                }
 
-                let raw_line = std::mem::take(&mut self.line_buf);
-                let line = String::from_utf8(raw_line)?;
-                self.buffer.advance_processed(n + 1);
+                let line = &self.buffer.consume_forward(n + 1)[..n];
 
-                if self.buffer.unprocessed_len() == 0 {
-                    self.buffer.reset()
+                if self.line_buf.is_empty() {
+                    return Ok(Some(line));
                 }
 
-                return Ok(Some(line));
+                // Only lines over a chunk boundary are copied.
+                self.line_buf.extend_from_slice(line);
+                std::mem::swap(&mut self.line, &mut self.line_buf);
+                self.line_buf.clear();
+
+                return Ok(Some(&self.line));
+            }
+
+            if self.skipping {
+                self.buffer.reset();
+                continue;
             }
 
-            self.line_buf.extend_from_slice(unprocessed);
+            self.line_buf
+                .extend_from_slice(self.buffer.unprocessed_forward());
             self.buffer.reset();
 
+            if self.line_buf.len() > self.max_line_len {
+                self.line_buf.clear();
+                self.skipping = !self.eof;
+                return Err(LineReaderError::LineTooLong(self.max_line_len));
+            }
+
             if self.eof {
-                let raw_line = std::mem::take(&mut self.line_buf);
-                let line = String::from_utf8(raw_line)?;
-                return Ok(Some(line));
+                std::mem::swap(&mut self.line, &mut self.line_buf);
+                self.line_buf.clear();
+
+                return Ok(Some(&self.line));
             }
         }
 
@@ -170,6 +279,7 @@ where
     }
 }
 
+#[cfg(feature = "async")]
 #[derive(Debug)]
 pub struct AsyncLineReverseReader<'a, F>
 where
@@ -178,14 +288,22 @@ where
     file: F,
     buffer: BufferRef<'a>,
     line_buf: Vec<u8>,
+    /// Last line read over a chunk boundary.
+    line: Vec<u8>,
     done: bool,
     pos: u64,
+    max_line_len: usize,
+    /// Dropping the start of a line that was too long.
+    skipping: bool,
 }
 
+#[cfg(feature = "async")]
 pub type FileLineReverseReaderRef<'a> =
     AsyncLineReverseReader<'a, &'a mut File>;
+#[cfg(feature = "async")]
 pub type FileLineReverseReaderOwned = AsyncLineReverseReader<'static, File>;
 
+#[cfg(feature = "async")]
 impl<'a, F> AsyncLineReverseReader<'a, F>
 where
     F: AsyncReadExt + AsyncSeekExt + Unpin,
@@ -199,8 +317,11 @@ where
             file,
             buffer: BufferRef::Owned(Buffer::new(buffer_size)),
             line_buf: Vec::new(),
+            line: Vec::new(),
             done: false,
             pos: 0,
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+            skipping: false,
         }
     }
 
@@ -209,15 +330,26 @@ where
             file,
             buffer: BufferRef::Borrowed(buffer),
             line_buf: Vec::new(),
+            line: Vec::new(),
             done: false,
             pos: 0,
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+            skipping: false,
         }
     }
 
+    /// Lines longer than `len` are not read, they fail with `LineTooLong`
+    /// and reading goes on before them.
+    pub fn max_line_len(mut self, len: usize) -> Self {
+        self.max_line_len = len;
+        self
+    }
+
     fn reset(&mut self) {
         self.line_buf.clear();
         self.buffer.reset();
         self.done = false;
+        self.skipping = false;
     }
 
     pub async fn fill_buffer(&mut self) -> ReaderResult<()> {
@@ -246,7 +378,8 @@ where
     }
 }
 
-impl<F> LineReader for AsyncLineReverseReader<'_, F>
+#[cfg(feature = "async")]
+impl<F> SeekLineReader for AsyncLineReverseReader<'_, F>
 where
     F: AsyncReadExt + AsyncSeekExt + Unpin,
 {
@@ -260,65 +393,201 @@ where
 
         Ok(self.pos)
     }
+}
 
-    async fn next_line(&mut self) -> ReaderResult<Option<String>> {
+#[cfg(feature = "async")]
+impl<F> LineReader for AsyncLineReverseReader<'_, F>
+where
+    F: AsyncReadExt + AsyncSeekExt + Unpin,
+{
+    async fn next_line_bytes(&mut self) -> ReaderResult<Option<&[u8]>> {
         loop {
             let process = self.buffer.unprocessed_backward();
 
             if self.done && process.is_empty() {
-                if !self.line_buf.is_empty() {
-                    let line =
-                        String::from_utf8(std::mem::take(&mut self.line_buf))?;
-                    return Ok(Some(line));
+                if self.line_buf.is_empty() {
+                    return Ok(None);
                 }
 
-                return Ok(None);
+                std::mem::swap(&mut self.line, &mut self.line_buf);
+                self.line_buf.clear();
+
+                return Ok(Some(&self.line));
             }
 
             if let Some(n) = memrchr(b'\n', process) {
-                let prefix = &process[n + 1..];
-                let line = String::from_utf8(concat_slices(
-                    prefix,
-                    &std::mem::take(&mut self.line_buf),
-                ))?;
+                // The newline and the line after it.
+                let len = process.len() - n;
+                if std::mem::take(&mut self.skipping) {
+                    self.buffer.consume_backward(len);
+                    continue;
+                }
 
-                let prefix_len = prefix.len();
+                if self.line_buf.len() + len - 1 > self.max_line_len {
+                    self.buffer.consume_backward(len);
+                    self.line_buf.clear();
+                    return Err(LineReaderError::LineTooLong(
+                        self.max_line_len,
+                    ));
+                }
 
-                self.buffer.advance_processed(prefix_len + 1);
+                let line = &self.buffer.consume_backward(len)[1..];
 
-                return Ok(Some(line));
-            } else {
-                self.line_buf = concat_slices(process, &self.line_buf);
+                if self.line_buf.is_empty() {
+                    return Ok(Some(line));
+                }
+
+                // Only lines over a chunk boundary are copied.
+                self.line.clear();
+                self.line.extend_from_slice(line);
+                self.line.extend_from_slice(&self.line_buf);
+                self.line_buf.clear();
+
+                return Ok(Some(&self.line));
+            }
+
+            if self.skipping {
                 self.fill_buffer().await?;
+                continue;
             }
+
+            self.line_buf = concat_slices(process, &self.line_buf);
+
+            if self.line_buf.len() > self.max_line_len {
+                self.line_buf.clear();
+                self.skipping = true;
+                return Err(LineReaderError::LineTooLong(self.max_line_len));
+            }
+
+            self.fill_buffer().await?;
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+/// Reads lines of a file mapped to memory, without copying them. The
+/// mapping is the length of the file when it was mapped.
+pub struct MmapLineReader {
+    map: Mmap,
+    /// Past the end once the last line was read.
+    pos: usize,
+    max_line_len: usize,
+}
+
+#[cfg(feature = "async")]
+impl MmapLineReader {
+    /// Map `file`. It must not be cut short while mapped, reading a part
+    /// that was cut off kills the process with `SIGBUS`.
+    pub fn new(file: &File) -> ReaderResult<Self> {
+        // SAFETY: the caller keeps the file from being truncated, see
+        // above.
+        let map = unsafe { Mmap::map(file)? };
+
+        Ok(Self {
+            map,
+            pos: 0,
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+        })
+    }
+
+    /// Lines longer than `len` fail with `LineTooLong`, like the buffered
+    /// readers, and reading goes on after them.
+    pub fn max_line_len(mut self, len: usize) -> Self {
+        self.max_line_len = len;
+        self
+    }
+
+    /// The mapped file.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+#[cfg(feature = "async")]
+impl SeekLineReader for MmapLineReader {
+    async fn rewind(&mut self) -> ReaderResult<u64> {
+        self.seek(SeekFrom::Start(0)).await
+    }
+
+    async fn seek(&mut self, pos: SeekFrom) -> ReaderResult<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(pos) => (0, i64::try_from(pos).ok()),
+            SeekFrom::End(offset) => (self.map.len(), Some(offset)),
+            SeekFrom::Current(offset) => (self.pos, Some(offset)),
+        };
+
+        let pos = offset
+            .and_then(|offset| (base as u64).checked_add_signed(offset))
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "invalid seek to a negative or overflowing position",
+                )
+            })?;
+
+        self.pos = usize::try_from(pos).unwrap_or(usize::MAX);
+        Ok(pos)
+    }
+}
+
+#[cfg(feature = "async")]
+impl LineReader for MmapLineReader {
+    async fn next_line_bytes(&mut self) -> ReaderResult<Option<&[u8]>> {
+        // Like the buffered reader, the end of the file ends a last line,
+        // an empty one after a newline.
+        let Some(rest) = self.map.get(self.pos..) else {
+            return Ok(None);
+        };
+
+        let line = match memchr(b'\n', rest) {
+            Some(n) => {
+                self.pos += n + 1;
+                &rest[..n]
+            }
+            None => {
+                self.pos = self.map.len() + 1;
+                rest
+            }
+        };
+
+        if line.len() > self.max_line_len {
+            return Err(LineReaderError::LineTooLong(self.max_line_len));
         }
+
+        Ok(Some(line))
     }
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 #[inline]
-fn concat_slices(pre: &[u8], post: &[u8]) -> Vec<u8> {
+pub(super) fn concat_slices(pre: &[u8], post: &[u8]) -> Vec<u8> {
     let mut concatted = Vec::with_capacity(pre.len() + post.len());
     concatted.extend_from_slice(pre);
     concatted.extend_from_slice(post);
     concatted
 }
 
-/// Binary search line in the file with custom comparator.
-pub struct BinarySearch<'a, F>
+#[cfg(feature = "async")]
+/// Binary search line in the file with custom comparator. The comparator
+/// orders a line against what is searched for.
+pub struct BinarySearch<'a, F, R = File>
 where
     F: Fn(&str) -> ReaderResult<Ordering>,
+    R: AsyncReadExt + AsyncSeekExt + Unpin,
 {
-    file: &'a mut File,
+    file: &'a mut R,
     cmp: F,
     buffer_size: NonZeroUsize,
     buffer: Buffer,
 }
 
-impl<'a, F> BinarySearch<'a, F>
+#[cfg(feature = "async")]
+impl<'a, F, R> BinarySearch<'a, F, R>
 where
     F: Fn(&str) -> ReaderResult<Ordering>,
+    R: AsyncReadExt + AsyncSeekExt + Unpin,
 {
-    pub fn new(file: &'a mut File, cmp: F) -> Self {
+    pub fn new(file: &'a mut R, cmp: F) -> Self {
         Self {
             file,
             cmp,
@@ -333,17 +602,18 @@ where
         self
     }
 
+    /// Offset of a line the comparator finds `Equal`. Without one, the
+    /// offset returned is only near where it would be, use `find_first_ge`
+    /// or `find_first_gt` for a boundary.
     pub async fn seek(mut self) -> ReaderResult<Option<u64>> {
-        let fmeta = self.file.metadata().await?;
-
-        let file_len = fmeta.len();
+        let file_len = self.file.seek(SeekFrom::End(0)).await?;
 
         if file_len == 0 {
             return Ok(None);
         }
 
         let mut left: u64 = 0;
-        let mut right: u64 = fmeta.len();
+        let mut right: u64 = file_len;
 
         loop {
             let mid = (right + left) / 2;
@@ -356,19 +626,7 @@ where
                 return Ok(None);
             };
 
-            let line = {
-                self.buffer.reset();
-                let mut forwards: FileLineReaderRef =
-                    AsyncLineReader::with_buffer(
-                        &mut self.file,
-                        &mut self.buffer,
-                    );
-
-                forwards.seek(SeekFrom::Start(line_start)).await?;
-                forwards.next_line().await?
-            };
-
-            let Some(line) = line else {
+            let Some(line) = self.line_at(line_start).await? else {
                 return Ok(None);
             };
 
@@ -394,14 +652,61 @@ where
         }
     }
 
+    /// Offset of the first line the comparator finds `Equal` or `Greater`,
+    /// the length of the file when there is none. Lines must be in order.
+    pub async fn find_first_ge(mut self) -> ReaderResult<u64> {
+        self.partition_point(|ordering| ordering == Ordering::Less)
+            .await
+    }
+
+    /// Offset of the first line the comparator finds `Greater`, the length
+    /// of the file when there is none. Lines must be in order.
+    pub async fn find_first_gt(mut self) -> ReaderResult<u64> {
+        self.partition_point(|ordering| ordering != Ordering::Greater)
+            .await
+    }
+
+    /// Offset of the first line that is not `before`, which must hold for
+    /// every line up to it and none after.
+    async fn partition_point(
+        &mut self,
+        before: impl Fn(Ordering) -> bool,
+    ) -> ReaderResult<u64> {
+        let len = self.file.seek(SeekFrom::End(0)).await?;
+        let (mut left, mut right) = (0, len);
+
+        while left < right {
+            let mid = left + (right - left) / 2;
+            let start = self.line_start(mid).await?.unwrap_or(mid);
+            let line = self.line_at(start).await?.unwrap_or_default();
+
+            if before((self.cmp)(&line)?) {
+                left = start + line.len() as u64 + 1;
+            } else {
+                right = start;
+            }
+        }
+
+        Ok(left.min(len))
+    }
+
+    async fn line_at(&mut self, pos: u64) -> ReaderResult<Option<String>> {
+        self.buffer.reset();
+
+        let mut forwards =
+            AsyncLineReader::with_buffer(&mut *self.file, &mut self.buffer);
+
+        forwards.seek(SeekFrom::Start(pos)).await?;
+        forwards.next_line().await
+    }
+
     async fn line_start(&mut self, pos: u64) -> ReaderResult<Option<u64>> {
         self.buffer.reset();
 
-        let mut backwards: FileLineReverseReaderRef =
-            AsyncLineReverseReader::with_buffer(
-                &mut self.file,
-                &mut self.buffer,
-            );
+        let mut backwards = AsyncLineReverseReader::with_buffer(
+            &mut *self.file,
+            &mut self.buffer,
+        );
 
         backwards.seek(SeekFrom::Start(pos)).await?;
 
@@ -413,6 +718,7 @@ where
     }
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 #[derive(Debug)]
 pub struct Buffer {
     processed: usize,
@@ -421,8 +727,9 @@ pub struct Buffer {
     data: Vec<u8>,
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 impl Buffer {
-    fn new(size: NonZeroUsize) -> Self {
+    pub(super) fn new(size: NonZeroUsize) -> Self {
         Self {
             filled: 0,
             processed: 0,
@@ -431,46 +738,58 @@ impl Buffer {
         }
     }
 
-    fn capacity(&self) -> usize {
+    pub(super) fn capacity(&self) -> usize {
         self.size
     }
 
-    fn advance_filled(&mut self, n: usize) {
+    pub(super) fn advance_filled(&mut self, n: usize) {
         self.filled += n;
     }
 
-    fn unfilled_mut(&mut self) -> &mut [u8] {
+    pub(super) fn unfilled_mut(&mut self) -> &mut [u8] {
         &mut self.data[self.filled..]
     }
 
-    fn advance_processed(&mut self, n: usize) {
-        self.processed += n;
-    }
-
-    fn unprocessed_len(&self) -> usize {
+    pub(super) fn unprocessed_len(&self) -> usize {
         self.filled - self.processed
     }
 
-    fn unprocessed_forward(&self) -> &[u8] {
+    pub(super) fn unprocessed_forward(&self) -> &[u8] {
         &self.data[self.processed..self.filled]
     }
 
-    fn unprocessed_backward(&self) -> &[u8] {
+    pub(super) fn unprocessed_backward(&self) -> &[u8] {
         &self.data[0..(self.filled - self.processed)]
     }
 
-    fn reset(&mut self) {
+    /// Next `n` unprocessed bytes going forward, marked processed.
+    pub(super) fn consume_forward(&mut self, n: usize) -> &[u8] {
+        let start = self.processed;
+        self.processed += n;
+        &self.data[start..start + n]
+    }
+
+    /// Last `n` unprocessed bytes going backward, marked processed.
+    pub(super) fn consume_backward(&mut self, n: usize) -> &[u8] {
+        let end = self.filled - self.processed;
+        self.processed += n;
+        &self.data[end - n..end]
+    }
+
+    pub(super) fn reset(&mut self) {
         self.filled = 0;
         self.processed = 0;
     }
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 #[derive(Debug)]
 pub enum BufferRef<'a> {
     Owned(Buffer),
     Borrowed(&'a mut Buffer),
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 impl std::ops::DerefMut for BufferRef<'_> {
     fn deref_mut(&mut self) -> &mut Buffer {
         match self {
@@ -480,6 +799,7 @@ impl std::ops::DerefMut for BufferRef<'_> {
     }
 }
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 impl std::ops::Deref for BufferRef<'_> {
     type Target = Buffer;
 
@@ -0,0 +1,142 @@
+//! Removing or archiving day files once they are older than the store
+//! keeps them. Summaries of downsampled days are kept.
+
+use super::dayfile;
+use super::filepath::Filepath;
+use super::index::Index;
+use super::write::StoreWriteError;
+use chrono::{Days, NaiveDate};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retention {
+    /// Days kept, counting the current one.
+    pub days: NonZeroU32,
+    /// Older day files are moved here compressed, instead of removed.
+    pub archive_dir: Option<PathBuf>,
+}
+
+impl Retention {
+    /// First day kept when `today` is the current one.
+    pub fn first_kept(&self, today: NaiveDate) -> NaiveDate {
+        today
+            .checked_sub_days(Days::new(u64::from(self.days.get()) - 1))
+            .unwrap_or(NaiveDate::MIN)
+    }
+
+    /// Remove or archive the day files of `dir` older than it keeps.
+    /// Returns how many there were.
+    pub async fn apply(
+        &self,
+        dir: &Path,
+        today: NaiveDate,
+    ) -> Result<usize, StoreWriteError> {
+        let first_kept = self.first_kept(today);
+        let mut entries = tokio::fs::read_dir(dir).await?;
+        let mut paths = vec![];
+
+        while let Some(entry) = entries.next_entry().await? {
+            let Ok(filepath) = Filepath::try_from(entry.path()) else {
+                continue;
+            };
+
+            if *filepath.date() < first_kept {
+                paths.push(filepath.to_path_buf());
+            }
+        }
+
+        if let Some(archive_dir) = &self.archive_dir
+            && !paths.is_empty()
+        {
+            tokio::fs::create_dir_all(archive_dir).await?;
+        }
+
+        for path in &paths {
+            remove_if_exists(&Index::path(path)).await?;
+
+            match &self.archive_dir {
+                Some(archive_dir) => {
+                    log::debug!("Archiving {:?} to {:?}", path, archive_dir);
+
+                    let (path, archive_dir) =
+                        (path.clone(), archive_dir.clone());
+                    tokio::task::spawn_blocking(move || {
+                        dayfile::archive(&path, &archive_dir)
+                    })
+                    .await
+                    .map_err(std::io::Error::other)??;
+                }
+                None => {
+                    log::debug!("Removing {:?}", path);
+                    tokio::fs::remove_file(path).await?;
+                }
+            }
+        }
+
+        Ok(paths.len())
+    }
+}
+
+async fn remove_if_exists(path: &Path) -> std::io::Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn retention_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("store");
+        let archive = dir.path().join("archive");
+        std::fs::create_dir(&store).unwrap();
+
+        for name in [
+            "20260101.log.zst",
+            "20260102.log",
+            "20260102.idx",
+            "20260103.log",
+            "20260101.sum",
+        ] {
+            std::fs::write(store.join(name), "{}\n").unwrap();
+        }
+        dayfile::compress(&store.join("20260103.log")).unwrap();
+        std::fs::write(store.join("20260104.log"), "{}\n").unwrap();
+
+        let date = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        let mut retention = Retention {
+            days: NonZeroU32::new(2).unwrap(),
+            archive_dir: Some(archive.clone()),
+        };
+        assert_eq!(retention.first_kept(date(4)), date(3));
+
+        assert_eq!(retention.apply(&store, date(4)).await.unwrap(), 2);
+        let names = |dir: &Path| {
+            let mut names: Vec<_> = std::fs::read_dir(dir)
+                .unwrap()
+                .map(|e| e.unwrap().file_name().into_string().unwrap())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&archive), ["20260101.log.zst", "20260102.log.zst"]);
+        assert_eq!(
+            names(&store),
+            ["20260101.sum", "20260103.log.zst", "20260104.log"]
+        );
+        assert_eq!(
+            dayfile::read_to_string(&archive.join("20260102.log.zst"), None)
+                .unwrap(),
+            "{}\n"
+        );
+
+        retention.archive_dir = None;
+        assert_eq!(retention.apply(&store, date(5)).await.unwrap(), 1);
+        assert_eq!(names(&store), ["20260101.sum", "20260104.log"]);
+    }
+}
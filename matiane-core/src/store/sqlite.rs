@@ -0,0 +1,234 @@
+//! Events in a SQLite database, for stores with years of history.
+//!
+//! Every event is a row of its time in microseconds, its type and the JSON
+//! line the day files would hold, indexed by time.
+
+use super::backend::Store;
+use super::filepath::StorePath;
+use super::read::{EventReader, EventReaderResult, StoreReadError};
+use super::write::StoreWriteError;
+use crate::events::TimedEvent;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Events imported in one transaction.
+const IMPORT_BATCH: usize = 10_000;
+
+/// Time in microseconds, type and JSON line of an event.
+type Row = (i64, &'static str, String);
+
+const SCHEMA: &str = "
+    PRAGMA journal_mode = WAL;
+    CREATE TABLE IF NOT EXISTS events (
+        timestamp INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        line TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS events_timestamp ON events (timestamp);
+";
+
+const INSERT: &str =
+    "INSERT INTO events (timestamp, kind, line) VALUES (?1, ?2, ?3)";
+
+#[derive(Clone)]
+pub struct SqliteStore {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Database at `path`, created if missing.
+    pub async fn open(path: PathBuf) -> EventReaderResult<Self> {
+        let connection = blocking(move || {
+            let connection = Connection::open(path)?;
+            connection.execute_batch(SCHEMA)?;
+            Ok(connection)
+        })
+        .await?;
+
+        Ok(SqliteStore {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Copy every event of the day files of `store` in. Returns how many
+    /// were copied.
+    pub async fn import(
+        &self,
+        store: impl Into<StorePath>,
+    ) -> EventReaderResult<usize> {
+        let from = DateTime::UNIX_EPOCH.fixed_offset();
+        let mut reader = match EventReader::open(store, &from).await {
+            Ok(reader) => reader,
+            Err(StoreReadError::NoFilesToOpen) => return Ok(0),
+            Err(e) => return Err(e),
+        };
+
+        let mut imported = 0;
+        let mut rows = Vec::with_capacity(IMPORT_BATCH);
+
+        loop {
+            let event = reader.next_event().await?;
+            if let Some(event) = &event {
+                rows.push(row(event)?);
+            }
+
+            if rows.len() == IMPORT_BATCH
+                || (event.is_none() && !rows.is_empty())
+            {
+                imported += rows.len();
+                self.insert(std::mem::take(&mut rows)).await?;
+            }
+
+            if event.is_none() {
+                return Ok(imported);
+            }
+        }
+    }
+
+    async fn insert(&self, rows: Vec<Row>) -> EventReaderResult<()> {
+        let connection = self.connection.clone();
+
+        blocking(move || {
+            let mut connection = lock(&connection);
+            let transaction = connection.transaction()?;
+
+            for (timestamp, kind, line) in &rows {
+                transaction.execute(INSERT, params![timestamp, kind, line])?;
+            }
+
+            transaction.commit()
+        })
+        .await
+    }
+}
+
+impl Store for SqliteStore {
+    async fn write(
+        &mut self,
+        event: &TimedEvent,
+    ) -> Result<(), StoreWriteError> {
+        let (timestamp, kind, line) = row(event)?;
+        let connection = self.connection.clone();
+
+        tokio::task::spawn_blocking(move || {
+            lock(&connection).execute(INSERT, params![timestamp, kind, line])
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        Ok(())
+    }
+
+    /// Every write is committed on its own.
+    async fn flush(&mut self) -> Result<(), StoreWriteError> {
+        Ok(())
+    }
+
+    async fn read_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> EventReaderResult<Vec<TimedEvent>> {
+        let connection = self.connection.clone();
+
+        let lines = blocking(move || {
+            let connection = lock(&connection);
+            let mut statement = connection.prepare(
+                "SELECT line FROM events
+                 WHERE timestamp >= ?1 AND timestamp < ?2
+                 ORDER BY timestamp, rowid",
+            )?;
+
+            statement
+                .query_map(
+                    params![from.timestamp_micros(), to.timestamp_micros()],
+                    |row| row.get::<_, String>(0),
+                )?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
+
+        lines
+            .iter()
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+}
+
+fn row(event: &TimedEvent) -> serde_json::Result<Row> {
+    Ok((
+        event.timestamp.timestamp_micros(),
+        event.event.kind(),
+        serde_json::to_string(event)?,
+    ))
+}
+
+/// A panic while holding the connection leaves it usable, SQLite rolls
+/// back what was not committed.
+fn lock(connection: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
+    connection.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> rusqlite::Result<T> + Send + 'static,
+) -> EventReaderResult<T> {
+    Ok(tokio::task::spawn_blocking(f)
+        .await
+        .map_err(std::io::Error::other)??)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Event;
+    use crate::store::DirStore;
+    use chrono::{TimeDelta, TimeZone};
+
+    #[tokio::test]
+    async fn sqlite_store_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let midnight = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let at = |h| midnight + TimeDelta::hours(h);
+        let events: Vec<_> = [(1, Event::Active), (2, Event::Alive)]
+            .into_iter()
+            .chain([(25, Event::Idle)])
+            .map(|(h, event)| TimedEvent {
+                timestamp: at(h),
+                event,
+            })
+            .collect();
+
+        let mut files = DirStore::new(dir.path().join("files"));
+        for event in &events {
+            files.write(event).await.unwrap();
+        }
+        files.flush().await.unwrap();
+
+        let db = dir.path().join("events.db");
+        let mut sqlite = SqliteStore::open(db.clone()).await.unwrap();
+        let imported = sqlite.import(&dir.path().join("files")).await.unwrap();
+        assert_eq!(imported, 3);
+
+        let timestamps = |events: Vec<TimedEvent>| -> Vec<_> {
+            events.iter().map(|event| event.timestamp).collect()
+        };
+        let read = sqlite.read_range(at(2), at(26)).await.unwrap();
+        assert_eq!(timestamps(read), [at(2), at(25)]);
+        let read = files.read_range(at(2), at(26)).await.unwrap();
+        assert_eq!(timestamps(read), [at(2), at(25)]);
+
+        sqlite
+            .write(&TimedEvent {
+                timestamp: at(26),
+                event: Event::Active,
+            })
+            .await
+            .unwrap();
+
+        let reopened = SqliteStore::open(db).await.unwrap();
+        let read = reopened.read_range(at(0), at(48)).await.unwrap();
+        assert_eq!(read.len(), 4);
+    }
+}
@@ -0,0 +1,104 @@
+//! Totals of downsampled days, `20260101.sum` in place of `20260101.log`.
+
+use super::filepath::DATE_FORMAT;
+use super::read::EventReaderResult;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+pub const SUMMARY_EXTENSION: &str = "sum";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaySummary {
+    /// UTC day, like the day files.
+    pub date: NaiveDate,
+    /// Seconds focused per app.
+    pub apps: BTreeMap<String, i64>,
+}
+
+impl DaySummary {
+    pub fn new(date: NaiveDate) -> Self {
+        DaySummary {
+            date,
+            apps: BTreeMap::new(),
+        }
+    }
+
+    pub fn path(dir: &Path, date: NaiveDate) -> PathBuf {
+        dir.join(date.format(DATE_FORMAT).to_string())
+            .with_extension(SUMMARY_EXTENSION)
+    }
+
+    /// Summary of `date`, none when the day was not downsampled.
+    pub async fn read(
+        dir: &Path,
+        date: NaiveDate,
+    ) -> EventReaderResult<Option<Self>> {
+        match tokio::fs::read_to_string(Self::path(dir, date)).await {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn write(&self, dir: &Path) -> EventReaderResult<()> {
+        let path = Self::path(dir, self.date);
+        let tmp_path = path.with_extension("sum.tmp");
+
+        let mut content = serde_json::to_vec(self)?;
+        content.push(b'\n');
+
+        let mut tmp = tokio::fs::File::create(&tmp_path).await?;
+        tmp.write_all(&content).await?;
+        tmp.sync_all().await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+
+        Ok(())
+    }
+
+    /// Add the totals of `other`.
+    pub fn merge(&mut self, other: &DaySummary) {
+        for (app, seconds) in &other.apps {
+            *self.apps.entry(app.clone()).or_default() += seconds;
+        }
+    }
+}
+
+/// Summaries of the days in `days`, in order.
+pub async fn read_summaries(
+    dir: &Path,
+    days: RangeInclusive<NaiveDate>,
+) -> EventReaderResult<Vec<DaySummary>> {
+    let mut dates = vec![];
+    let mut entries = tokio::fs::read_dir(dir).await?;
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+
+        if path.extension().is_none_or(|ext| ext != SUMMARY_EXTENSION) {
+            continue;
+        }
+
+        let date = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| NaiveDate::parse_from_str(stem, DATE_FORMAT).ok());
+
+        if let Some(date) = date.filter(|date| days.contains(date)) {
+            dates.push(date);
+        }
+    }
+
+    dates.sort();
+
+    let mut summaries = vec![];
+    for date in dates {
+        summaries.extend(DaySummary::read(dir, date).await?);
+    }
+
+    Ok(summaries)
+}
@@ -0,0 +1,237 @@
+//! Checking the day files of a store for problems readers would trip on.
+
+use super::dayfile;
+use super::filepath::StorePath;
+use super::format::is_mismatched;
+use super::read::{EventReader, EventReaderResult};
+use super::zone::DayZone;
+use crate::events::TimedEvent;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProblemKind {
+    /// The file could not be read or decompressed.
+    Unreadable(String),
+    /// The line is not an event.
+    Damaged(String),
+    /// The line changed after it was written.
+    ChecksumMismatch,
+    /// The event is older than the one before it.
+    OutOfOrder {
+        previous: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
+    },
+    /// The event belongs to another day file.
+    WrongDay {
+        date: NaiveDate,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    pub path: PathBuf,
+    /// Line number, starting at 1.
+    pub line: Option<usize>,
+    pub kind: ProblemKind,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+
+        match &self.kind {
+            ProblemKind::Unreadable(e) => write!(f, ": can not read: {}", e),
+            ProblemKind::Damaged(e) => write!(f, ": not an event: {}", e),
+            ProblemKind::ChecksumMismatch => {
+                write!(f, ": does not match its checksum")
+            }
+            ProblemKind::OutOfOrder {
+                previous,
+                timestamp,
+            } => write!(
+                f,
+                ": {} is before the previous event at {}",
+                timestamp.to_rfc3339(),
+                previous.to_rfc3339()
+            ),
+            ProblemKind::WrongDay { date, timestamp } => write!(
+                f,
+                ": {} does not belong to {}",
+                timestamp.to_rfc3339(),
+                date
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct VerifyReport {
+    pub files: usize,
+    pub events: usize,
+    pub problems: Vec<Problem>,
+    /// Days between the first and last files without one. Not a problem,
+    /// the daemon was not running.
+    pub missing_days: Vec<NaiveDate>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Check every day file of `store`.
+pub async fn verify(
+    store: impl Into<StorePath>,
+) -> EventReaderResult<VerifyReport> {
+    let store = store.into();
+    let dir = store.dir();
+    let _lock = EventReader::read_lock(dir).await?;
+    let files = EventReader::list_files(dir).await?;
+    let zone = DayZone::load(dir).await?;
+    let mut report = VerifyReport {
+        missing_days: files.missing_days(..),
+        ..Default::default()
+    };
+
+    for filepath in files.items.iter() {
+        let path = filepath.to_path_buf();
+        let date = *filepath.date();
+        report.files += 1;
+
+        let read_path = path.clone();
+        let key = store.key().cloned();
+        let content = tokio::task::spawn_blocking(move || {
+            dayfile::read_to_string(&read_path, key.as_ref())
+        })
+        .await
+        .map_err(std::io::Error::other)?;
+
+        match content {
+            Ok(content) => {
+                verify_file(&path, date, zone, &content, &mut report)
+            }
+            Err(e) => report.problems.push(Problem {
+                path,
+                line: None,
+                kind: ProblemKind::Unreadable(e.to_string()),
+            }),
+        }
+    }
+
+    Ok(report)
+}
+
+fn verify_file(
+    path: &Path,
+    date: NaiveDate,
+    zone: DayZone,
+    content: &str,
+    report: &mut VerifyReport,
+) {
+    let mut previous = None;
+
+    for (n, line) in content.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let problem = |kind| Problem {
+            path: path.to_path_buf(),
+            line: Some(n + 1),
+            kind,
+        };
+
+        if is_mismatched(line) {
+            report.problems.push(problem(ProblemKind::ChecksumMismatch));
+            continue;
+        }
+
+        let timestamp = match serde_json::from_str::<TimedEvent>(line) {
+            Ok(event) => event.timestamp,
+            Err(e) => {
+                report
+                    .problems
+                    .push(problem(ProblemKind::Damaged(e.to_string())));
+                continue;
+            }
+        };
+        report.events += 1;
+
+        // Files written before the zone of the store was set have UTC days.
+        if zone.date_of(timestamp) != date && timestamp.date_naive() != date {
+            report
+                .problems
+                .push(problem(ProblemKind::WrongDay { date, timestamp }));
+        }
+
+        if let Some(previous) = previous
+            && timestamp < previous
+        {
+            report.problems.push(problem(ProblemKind::OutOfOrder {
+                previous,
+                timestamp,
+            }));
+        }
+
+        previous = Some(previous.map_or(timestamp, |p| p.max(timestamp)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::ZONE_FILE_NAME;
+
+    #[tokio::test]
+    async fn verify_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let lines = [
+            r#"{"timestamp":"2026-01-01T20:00:00Z","event":{"type":"alive"}}"#,
+            r#"{"timestamp":"2026-01-01T19:00:00Z","event":{"type":"alive"}}"#,
+            r#"{"timestamp":"2026-01-01T21:00:00Z","event":{"ty"#,
+            r#"{"timestamp":"2026-01-02T01:00:00Z","event":{"type":"idle"}}"#,
+        ];
+        std::fs::write(dir.path().join("20260101.log"), lines.join("\n"))
+            .unwrap();
+
+        let report = verify(dir.path()).await.unwrap();
+        assert_eq!(report.files, 1);
+        assert_eq!(report.events, 3);
+
+        let found: Vec<_> = report
+            .problems
+            .iter()
+            .map(|problem| (problem.line, &problem.kind))
+            .collect();
+        assert!(matches!(
+            found.as_slice(),
+            [
+                (Some(2), ProblemKind::OutOfOrder { .. }),
+                (Some(3), ProblemKind::Damaged(_)),
+                (Some(4), ProblemKind::WrongDay { .. }),
+            ]
+        ));
+
+        std::fs::write(dir.path().join("20260101.log"), lines[0]).unwrap();
+        assert!(verify(dir.path()).await.unwrap().is_ok());
+
+        std::fs::write(dir.path().join("20260104.log"), "").unwrap();
+        let report = verify(dir.path()).await.unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        assert_eq!(report.missing_days, [date(2), date(3)]);
+        assert!(report.is_ok());
+
+        std::fs::write(dir.path().join("20260101.log"), lines[3]).unwrap();
+        assert!(!verify(dir.path()).await.unwrap().is_ok());
+        std::fs::write(dir.path().join(ZONE_FILE_NAME), "-02:00").unwrap();
+        assert!(verify(dir.path()).await.unwrap().is_ok());
+    }
+}
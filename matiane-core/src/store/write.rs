@@ -1,12 +1,38 @@
 use thiserror::Error;
 
+#[cfg(feature = "async")]
+use super::crypt::StoreKey;
+#[cfg(feature = "async")]
+use super::dayfile;
+#[cfg(feature = "async")]
 use super::filepath::Filepath;
-use crate::events::TimedEvent;
+#[cfg(feature = "async")]
+use super::format::{MAX_HEADER_LEN, StoreFormat, complete_len};
+#[cfg(feature = "async")]
+use super::index::build_indexes;
+#[cfg(feature = "async")]
+use super::lock::try_exclude_readers;
+#[cfg(feature = "async")]
+use super::retention::Retention;
+#[cfg(feature = "async")]
+use super::zone::DayZone;
+#[cfg(feature = "async")]
+use crate::events::{Event, StorageDegraded, TimedEvent};
+#[cfg(feature = "async")]
 use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
 use serde_json;
-use std::path::PathBuf;
+#[cfg(feature = "async")]
+use std::collections::VecDeque;
+#[cfg(feature = "async")]
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+#[cfg(feature = "async")]
+use std::time::Instant;
+#[cfg(feature = "async")]
 use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 #[derive(Debug, Error)]
 pub enum StoreWriteError {
@@ -14,13 +40,101 @@ pub enum StoreWriteError {
     Io(#[from] std::io::Error),
     #[error("Store failed to encode event")]
     EncodeError(#[from] serde_json::Error),
+    #[error("Store failed to encode event as CBOR: {0}")]
+    CborEncodeError(#[from] ciborium::ser::Error<std::io::Error>),
+    #[cfg(feature = "sqlite")]
+    #[error("Store database error: {0}")]
+    Database(#[from] rusqlite::Error),
 }
 
+impl StoreWriteError {
+    /// Whether the disk or the quota of the user is full.
+    pub fn is_disk_full(&self) -> bool {
+        use std::io::ErrorKind;
+
+        matches!(
+            self,
+            StoreWriteError::Io(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::StorageFull | ErrorKind::QuotaExceeded
+                )
+        )
+    }
+}
+
+/// Events an `EventWriter` keeps in memory by default while the disk is
+/// full, about three days of heartbeats.
+pub const DEFAULT_MAX_PENDING: usize = 10_000;
+
+#[cfg(feature = "async")]
+const MIN_RETRY: Duration = Duration::from_secs(5);
+#[cfg(feature = "async")]
+const MAX_RETRY: Duration = Duration::from_secs(5 * 60);
+
+/// When written events are flushed and synced to disk.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Durability {
+    /// After every event.
+    EveryEvent,
+    /// Events are kept in memory and written every this many seconds.
+    Interval(u64),
+    /// Events are written as they come and synced when the day is over.
+    #[default]
+    OnRotate,
+}
+
+impl Durability {
+    /// Time events are kept in memory for.
+    pub fn interval(self) -> Option<Duration> {
+        match self {
+            Durability::Interval(secs) => Some(Duration::from_secs(secs)),
+            Durability::EveryEvent | Durability::OnRotate => None,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
 pub struct EventWriter {
     file: File,
     file_path: Filepath,
+    /// Zone of the days of the store, files rotate at its midnight.
+    zone: DayZone,
+    /// Compress the day files before the current one on rotation.
+    compress: bool,
+    /// Format of new day files.
+    format: StoreFormat,
+    /// Format of the current file, known once written to.
+    file_format: Option<StoreFormat>,
+    /// Key of encrypted day files.
+    key: Option<StoreKey>,
+    /// Applied to the store on rotation.
+    retention: Option<Retention>,
+    durability: Durability,
+    /// Events not written yet, with an interval.
+    buffer: Vec<u8>,
+    synced: Instant,
+    /// Length of the complete events of the current file.
+    len: u64,
+    /// Set while the disk is full.
+    degraded: Option<Degraded>,
+    /// Events that came while the disk is full, oldest first.
+    pending: VecDeque<TimedEvent>,
+    max_pending: usize,
 }
 
+#[cfg(feature = "async")]
+/// Writing failed for a full disk, events wait in memory.
+struct Degraded {
+    since: DateTime<Utc>,
+    /// Events dropped for a full queue.
+    dropped: usize,
+    retry_in: Duration,
+    retry_at: Instant,
+}
+
+#[cfg(feature = "async")]
 impl EventWriter {
     pub async fn open(
         dir: PathBuf,
@@ -32,36 +146,287 @@ impl EventWriter {
             tokio::fs::create_dir(&dir).await?;
         }
 
-        let filepath = Into::<Filepath>::into(date).with_path(dir);
+        let zone = DayZone::load(&dir).await?;
+        let filepath =
+            Into::<Filepath>::into(zone.date_of(date)).with_path(dir);
 
         log::debug!("opening log file: {:?}", filepath);
 
         let file = open_write_file(filepath.to_path_buf()).await?;
+        let len = file.metadata().await?.len();
 
         let store = EventWriter {
             file,
             file_path: filepath,
+            zone,
+            compress: false,
+            format: StoreFormat::default(),
+            file_format: None,
+            key: None,
+            retention: None,
+            durability: Durability::default(),
+            buffer: vec![],
+            synced: Instant::now(),
+            len,
+            degraded: None,
+            pending: VecDeque::new(),
+            max_pending: DEFAULT_MAX_PENDING,
         };
 
         Ok(store)
     }
 
+    /// Compress finished day files with zstd on rotation.
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Remove or archive old day files on rotation.
+    pub fn with_retention(mut self, retention: Option<Retention>) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Format of new day files, existing ones keep theirs.
+    pub fn with_format(mut self, format: StoreFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Key encrypted day files are written with.
+    pub fn with_key(mut self, key: Option<StoreKey>) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// When events are flushed and synced to disk.
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Events kept in memory while the disk is full, the oldest are
+    /// dropped past it.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending;
+        self
+    }
+
+    /// Write `event`. While the disk is full, events are kept in memory and
+    /// written with a `StorageDegraded` event once there is space again.
     pub async fn write(
         &mut self,
         event: &TimedEvent,
     ) -> Result<(), StoreWriteError> {
-        self.maybe_rotate(event.timestamp.date_naive()).await?;
+        if let Some(degraded) = &mut self.degraded {
+            if self.pending.len() >= self.max_pending {
+                self.pending.pop_front();
+                degraded.dropped += 1;
+            }
+            self.pending.push_back(event.clone());
+
+            if Instant::now() < degraded.retry_at {
+                return Ok(());
+            }
+
+            return match self.write_pending().await {
+                Err(e) if e.is_disk_full() => {
+                    self.retry_later();
+                    Ok(())
+                }
+                result => result,
+            };
+        }
+
+        let encoded = match self.encode(event).await {
+            Ok(encoded) => encoded,
+            Err(e) => return self.degrade(e, Some(event)),
+        };
 
-        let mut encoded = serde_json::to_vec(&event)?;
-        encoded.push(b'\n');
+        match self.durability.interval() {
+            Some(interval) => {
+                self.buffer.extend_from_slice(&encoded);
 
-        self.file.write_all(&encoded).await?;
+                if self.synced.elapsed() >= interval
+                    && let Err(e) = self.sync().await
+                {
+                    return self.degrade(e, None);
+                }
+            }
+            None => {
+                if let Err(e) = self.append(&encoded).await {
+                    return self.degrade(e, Some(event));
+                }
 
+                if self.durability == Durability::EveryEvent
+                    && let Err(e) = self.sync().await
+                {
+                    return self.degrade(e, None);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `event` encoded for the current file, rotated to its day.
+    async fn encode(
+        &mut self,
+        event: &TimedEvent,
+    ) -> Result<Vec<u8>, StoreWriteError> {
+        self.maybe_rotate(self.zone.date_of(event.timestamp))
+            .await?;
+        let format = self.file_format().await?;
+        format.encode_with(event, self.key.as_ref())
+    }
+
+    /// Append `bytes` to the file, none of them when it fails.
+    async fn append(&mut self, bytes: &[u8]) -> Result<(), StoreWriteError> {
+        let written = async {
+            self.file.write_all(bytes).await?;
+            // Waits for the write, it fails here and not with the next one.
+            self.file.flush().await
+        }
+        .await;
+
+        if let Err(e) = written {
+            // A part that was written would damage the next event.
+            if let Err(e) = self.file.set_len(self.len).await {
+                log::warn!("Failed to remove a part of an event: {}", e);
+            }
+
+            return Err(e.into());
+        }
+
+        self.len += bytes.len() as u64;
         Ok(())
     }
 
+    /// Keep `event`, and the ones after it, in memory when the disk is
+    /// full. Other errors are returned.
+    fn degrade(
+        &mut self,
+        e: StoreWriteError,
+        event: Option<&TimedEvent>,
+    ) -> Result<(), StoreWriteError> {
+        if !e.is_disk_full() {
+            return Err(e);
+        }
+
+        log::warn!("The disk is full, keeping events in memory: {}", e);
+        self.pending.extend(event.cloned());
+        self.degraded = Some(Degraded {
+            since: Utc::now(),
+            dropped: 0,
+            retry_in: MIN_RETRY,
+            retry_at: Instant::now() + MIN_RETRY,
+        });
+
+        Ok(())
+    }
+
+    /// Wait twice as long as the last time, up to `MAX_RETRY`.
+    fn retry_later(&mut self) {
+        if let Some(degraded) = &mut self.degraded {
+            degraded.retry_in = (degraded.retry_in * 2).min(MAX_RETRY);
+            degraded.retry_at = Instant::now() + degraded.retry_in;
+            log::debug!(
+                "The disk is still full, retrying in {:?}",
+                degraded.retry_in
+            );
+        }
+    }
+
+    /// Write the events kept while the disk was full and a
+    /// `StorageDegraded` event after them.
+    async fn write_pending(&mut self) -> Result<(), StoreWriteError> {
+        // Events of an interval that failed to be written.
+        if !self.buffer.is_empty() {
+            self.sync().await?;
+        }
+
+        // At the last event kept, or now without one.
+        let mut timestamp = Utc::now();
+        while let Some(event) = self.pending.front().cloned() {
+            let encoded = self.encode(&event).await?;
+            self.append(&encoded).await?;
+            self.pending.pop_front();
+            timestamp = event.timestamp;
+        }
+
+        let Some(degraded) = &self.degraded else {
+            return Ok(());
+        };
+
+        let event = TimedEvent {
+            timestamp,
+            event: Event::StorageDegraded(StorageDegraded {
+                since: degraded.since,
+                dropped: degraded.dropped,
+            }),
+        };
+        let encoded = self.encode(&event).await?;
+        self.append(&encoded).await?;
+        self.sync().await?;
+
+        log::info!("The disk has space again, events are written");
+        self.degraded = None;
+
+        Ok(())
+    }
+
+    /// Format of the current file, the header is written to new ones.
+    async fn file_format(&mut self) -> Result<StoreFormat, StoreWriteError> {
+        if let Some(format) = self.file_format {
+            return Ok(format);
+        }
+
+        let format = match self.file.metadata().await?.len() {
+            0 => {
+                self.append(self.format.header()).await?;
+                self.format
+            }
+            _ => {
+                let file = File::open(self.file_path.to_path_buf()).await?;
+                let mut start = Vec::with_capacity(MAX_HEADER_LEN);
+                file.take(MAX_HEADER_LEN as u64)
+                    .read_to_end(&mut start)
+                    .await?;
+
+                StoreFormat::detect(&start)
+            }
+        };
+
+        self.file_format = Some(format);
+        Ok(format)
+    }
+
+    /// Write the events kept in memory and sync the file to disk. Fails
+    /// while the disk is full.
     pub async fn flush(&mut self) -> Result<(), StoreWriteError> {
-        Ok(self.file.flush().await?)
+        match self.degraded {
+            Some(_) => self.write_pending().await,
+            None => self.sync().await,
+        }
+    }
+
+    /// Write the events of the interval and sync the file to disk.
+    async fn sync(&mut self) -> Result<(), StoreWriteError> {
+        if !self.buffer.is_empty() {
+            let buffer = std::mem::take(&mut self.buffer);
+
+            if let Err(e) = self.append(&buffer).await {
+                self.buffer = buffer;
+                return Err(e);
+            }
+        }
+
+        self.file.flush().await?;
+        self.file.sync_data().await?;
+        self.synced = Instant::now();
+
+        Ok(())
     }
 
     pub async fn maybe_rotate(
@@ -72,23 +437,187 @@ impl EventWriter {
             return Ok(());
         }
 
-        self.file_path.set_date(date);
+        let mut file_path = self.file_path.clone();
+        file_path.set_date(date);
 
-        log::debug!("Rotating file: {:?}", self.file_path);
-        let file = open_write_file(self.file_path.to_path_buf()).await?;
+        log::debug!("Rotating file: {:?}", file_path);
+        let file = open_write_file(file_path.to_path_buf()).await?;
+        let len = file.metadata().await?.len();
 
-        self.flush().await?;
+        self.sync().await?;
 
         self.file = file;
+        self.file_path = file_path;
+        self.file_format = None;
+        self.len = len;
+
+        if let Some(retention) = &self.retention
+            && let Err(e) =
+                apply_retention(retention, self.file_path.path(), date).await
+        {
+            log::warn!("Failed to apply the retention: {}", e);
+        }
+
+        if self.compress
+            && let Err(e) = compress_before(self.file_path.path(), date).await
+        {
+            log::warn!("Failed to compress day files: {}", e);
+        }
+
+        // After compressing, indexes older than their file are not used.
+        if let Err(e) = build_indexes(self.file_path.path()).await {
+            log::warn!("Failed to index day files: {}", e);
+        }
 
         Ok(())
     }
 }
 
+#[cfg(feature = "async")]
+/// Apply `retention` to `dir`, unless the store is read. Then it waits for
+/// the next rotation.
+async fn apply_retention(
+    retention: &Retention,
+    dir: &Path,
+    date: NaiveDate,
+) -> Result<(), StoreWriteError> {
+    let readers = try_exclude_readers(dir)
+        .await
+        .map_err(std::io::Error::other)?;
+    let Some(_readers) = readers else {
+        log::info!("The store is read, the retention waits for tomorrow");
+        return Ok(());
+    };
+
+    retention.apply(dir, date).await?;
+    Ok(())
+}
+
+#[cfg(feature = "async")]
+/// Compress the uncompressed day files of `dir` before `date`.
+async fn compress_before(
+    dir: &Path,
+    date: NaiveDate,
+) -> Result<(), StoreWriteError> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    let mut paths = vec![];
+
+    while let Some(entry) = entries.next_entry().await? {
+        let Ok(filepath) = Filepath::try_from(entry.path()) else {
+            continue;
+        };
+
+        if !filepath.is_compressed() && *filepath.date() < date {
+            paths.push(filepath.to_path_buf());
+        }
+    }
+
+    for path in paths {
+        log::debug!("Compressing {:?}", path);
+        tokio::task::spawn_blocking(move || dayfile::compress(&path))
+            .await
+            .map_err(std::io::Error::other)??;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "async")]
 async fn open_write_file(filepath: PathBuf) -> Result<File, StoreWriteError> {
+    repair_tail(&filepath).await?;
+
     Ok(tokio::fs::OpenOptions::new()
         .create(true)
         .append(true)
         .open(filepath)
         .await?)
 }
+
+#[cfg(feature = "async")]
+/// Remove the line or frame cut short at the end of `path`, when the daemon
+/// was killed while writing it. Appending to it would damage the next event
+/// too.
+async fn repair_tail(path: &Path) -> Result<(), StoreWriteError> {
+    let content = match tokio::fs::read(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let complete = complete_len(&content);
+    if complete == content.len() {
+        return Ok(());
+    }
+
+    log::warn!(
+        "Removing {} bytes of an event cut short from the end of {:?}",
+        content.len() - complete,
+        path
+    );
+
+    let file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+    file.set_len(complete as u64).await?;
+    file.sync_data().await?;
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "async"))]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn write_disk_full_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = |m| Utc.with_ymd_and_hms(2026, 1, 1, 10, m, 0).unwrap();
+        let alive = |m| TimedEvent {
+            timestamp: at(m),
+            event: Event::Alive,
+        };
+
+        let mut writer = EventWriter::open(dir.path().to_path_buf(), at(0))
+            .await
+            .unwrap()
+            .with_max_pending(3);
+        writer.write(&alive(0)).await.unwrap();
+
+        // Writing to it fails with ENOSPC.
+        let full = File::options().write(true).open("/dev/full").await.unwrap();
+        let file = std::mem::replace(&mut writer.file, full);
+
+        for m in 1..4 {
+            writer.write(&alive(m)).await.unwrap();
+        }
+        assert_eq!(writer.pending.len(), 3);
+
+        // Still full, it waits longer.
+        writer.degraded.as_mut().unwrap().retry_at = Instant::now();
+        writer.write(&alive(4)).await.unwrap();
+        let degraded = writer.degraded.as_ref().unwrap();
+        assert_eq!(degraded.retry_in, MIN_RETRY * 2);
+        assert_eq!(degraded.dropped, 1);
+
+        writer.file = file;
+        writer.degraded.as_mut().unwrap().retry_at = Instant::now();
+        writer.write(&alive(5)).await.unwrap();
+        assert!(writer.degraded.is_none());
+
+        let content =
+            std::fs::read_to_string(dir.path().join("20260101.log")).unwrap();
+        let events: Vec<TimedEvent> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let minutes: Vec<_> = events
+            .iter()
+            .map(|event| (event.timestamp - at(0)).num_minutes())
+            .collect();
+        assert_eq!(minutes, [0, 3, 4, 5, 5]);
+
+        let Event::StorageDegraded(degraded) = &events[4].event else {
+            panic!("Not a storage_degraded event: {:?}", events[4]);
+        };
+        assert_eq!(degraded.dropped, 2);
+    }
+}
@@ -0,0 +1,172 @@
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone, Utc,
+};
+use serde::Deserialize;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// File in the store directory with the zone of its days, UTC without it.
+pub const ZONE_FILE_NAME: &str = "day-zone";
+
+/// Time zone the days of a store start at midnight of. Day files are named
+/// by the date in it, and readers map the times they are asked for onto
+/// the files with it.
+///
+/// The zone is kept in the store, so every reader uses the one the files
+/// were written with. Changing it only changes the days of new files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum DayZone {
+    #[default]
+    Utc,
+    /// The local time zone of the machine, days are 23 or 25 hours long
+    /// with DST changes.
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl DayZone {
+    /// Date of the day `at` is in.
+    pub fn date_of(&self, at: DateTime<Utc>) -> NaiveDate {
+        match self {
+            DayZone::Utc => at.date_naive(),
+            DayZone::Local => at.with_timezone(&Local).date_naive(),
+            DayZone::Fixed(offset) => at.with_timezone(offset).date_naive(),
+        }
+    }
+
+    /// Time the day of `date` starts at.
+    pub fn start_of(&self, date: NaiveDate) -> DateTime<Utc> {
+        match self {
+            DayZone::Utc => date.and_time(NaiveTime::MIN).and_utc(),
+            DayZone::Local => start_in(&Local, date),
+            DayZone::Fixed(offset) => start_in(offset, date),
+        }
+    }
+
+    /// Whether the day of `date` is over at `now`.
+    pub fn is_finished(&self, date: NaiveDate, now: DateTime<Utc>) -> bool {
+        date < self.date_of(now)
+    }
+
+    #[cfg(feature = "async")]
+    /// Zone of the store in `dir`.
+    pub async fn load(dir: &Path) -> io::Result<Self> {
+        match tokio::fs::read_to_string(dir.join(ZONE_FILE_NAME)).await {
+            Ok(raw) => parse_file(&raw),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(DayZone::Utc),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Zone of the store in `dir`, blocking.
+    pub fn load_blocking(dir: &Path) -> io::Result<Self> {
+        match std::fs::read_to_string(dir.join(ZONE_FILE_NAME)) {
+            Ok(raw) => parse_file(&raw),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(DayZone::Utc),
+            Err(e) => Err(e),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    /// Keep the zone in the store in `dir`, for the files written after.
+    pub async fn save(&self, dir: &Path) -> io::Result<()> {
+        let path = dir.join(ZONE_FILE_NAME);
+
+        match self {
+            DayZone::Utc => match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            },
+            zone => tokio::fs::write(&path, format!("{}\n", zone)).await,
+        }
+    }
+}
+
+/// Start of `date` in `tz`. Midnight may not exist on DST change, the UTC
+/// one is used then.
+fn start_in<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_time(NaiveTime::MIN);
+
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.to_utc())
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+fn parse_file(raw: &str) -> io::Result<DayZone> {
+    raw.trim()
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+impl FromStr for DayZone {
+    type Err = String;
+
+    /// "utc", "local" or an offset like "+02:00".
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "utc" => Ok(DayZone::Utc),
+            "local" => Ok(DayZone::Local),
+            offset => offset.parse().map(DayZone::Fixed).map_err(|_| {
+                format!(
+                    "invalid day zone `{}`, expected \"utc\", \"local\" or \
+                     an offset like \"+02:00\"",
+                    offset
+                )
+            }),
+        }
+    }
+}
+
+impl TryFrom<String> for DayZone {
+    type Error = String;
+
+    fn try_from(raw: String) -> Result<Self, String> {
+        raw.parse()
+    }
+}
+
+impl fmt::Display for DayZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DayZone::Utc => write!(f, "utc"),
+            DayZone::Local => write!(f, "local"),
+            DayZone::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn zone_test() {
+        let zone: DayZone = "+02:00".parse().unwrap();
+        let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(zone, DayZone::Fixed(offset));
+        assert_eq!(zone.to_string(), "+02:00");
+        assert_eq!("utc".parse(), Ok(DayZone::Utc));
+        assert_eq!("local".parse(), Ok(DayZone::Local));
+        assert!("Europe/Berlin".parse::<DayZone>().is_err());
+
+        let date = NaiveDate::from_ymd_opt(2026, 1, 2).unwrap();
+        let at = Utc.with_ymd_and_hms(2026, 1, 1, 22, 30, 0).unwrap();
+        assert_eq!(zone.date_of(at), date);
+        assert_eq!(DayZone::Utc.date_of(at), date.pred_opt().unwrap());
+        assert_eq!(
+            zone.start_of(date),
+            Utc.with_ymd_and_hms(2026, 1, 1, 22, 0, 0).unwrap()
+        );
+        assert_eq!(
+            DayZone::Utc.start_of(date),
+            Utc.with_ymd_and_hms(2026, 1, 2, 0, 0, 0).unwrap()
+        );
+        assert!(!zone.is_finished(date, at));
+        assert!(zone.is_finished(date, at + TimeDelta::days(1)));
+    }
+}
@@ -0,0 +1,259 @@
+//! Timers of the tasks the user intends to work on, e.g. pomodoros, and
+//! the intentions read back from their events.
+//!
+//! The daemon keeps a [`Timer`] and writes its events, reports compare the
+//! [`Intention`]s against the focus sessions of the same time.
+
+use crate::events::{Event, TimedEvent, TimerEnded, TimerStarted};
+use crate::sessions::FocusSession;
+use chrono::{DateTime, TimeDelta, Utc};
+
+#[derive(Debug, Clone)]
+struct Running {
+    task: String,
+    end: DateTime<Utc>,
+}
+
+/// A timer running one task at a time.
+#[derive(Debug, Default)]
+pub struct Timer {
+    running: Option<Running>,
+}
+
+impl Timer {
+    /// Start a timer of `task` for `duration`. A timer that is running is
+    /// stopped first.
+    pub fn start(
+        &mut self,
+        task: String,
+        duration: TimeDelta,
+        now: DateTime<Utc>,
+    ) -> Vec<Event> {
+        let stopped = self.stop(now);
+        let started = Event::TimerStarted(Box::new(TimerStarted {
+            task: task.clone(),
+            duration: duration.num_seconds().max(0) as u64,
+        }));
+
+        self.running = Some(Running {
+            task,
+            end: now
+                .checked_add_signed(duration)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+        });
+
+        stopped.into_iter().chain([started]).collect()
+    }
+
+    /// Stop the running timer before its time is up.
+    pub fn stop(&mut self, now: DateTime<Utc>) -> Option<Event> {
+        let running = self.running.take()?;
+
+        Some(Event::TimerEnded(Box::new(TimerEnded {
+            task: running.task,
+            completed: now >= running.end,
+        })))
+    }
+
+    /// End the running timer when its time is up at `now`.
+    pub fn expire(&mut self, now: DateTime<Utc>) -> Option<Event> {
+        match self.deadline() {
+            Some(end) if now >= end => self.stop(now),
+            _ => None,
+        }
+    }
+
+    /// When the running timer is up.
+    pub fn deadline(&self) -> Option<DateTime<Utc>> {
+        self.running.as_ref().map(|running| running.end)
+    }
+
+    /// Task of the running timer.
+    pub fn task(&self) -> Option<&str> {
+        self.running.as_ref().map(|running| running.task.as_str())
+    }
+}
+
+/// The time a timer of `task` ran.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Intention {
+    pub task: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub completed: bool,
+}
+
+impl Intention {
+    /// Time of `sessions` in the intention, by the apps that were focused,
+    /// most first. Untracked gaps are left out.
+    pub fn focus<'a>(
+        &self,
+        sessions: impl IntoIterator<Item = &'a FocusSession>,
+    ) -> Vec<(String, TimeDelta)> {
+        let mut by_app: Vec<(String, TimeDelta)> = vec![];
+
+        for session in sessions {
+            let start = session.start.max(self.start);
+            let end = session.end.min(self.end);
+
+            if end <= start || session.is_untracked() {
+                continue;
+            }
+
+            match by_app.iter_mut().find(|(app, _)| *app == session.app) {
+                Some((_, total)) => *total += end - start,
+                None => by_app.push((session.app.clone(), end - start)),
+            }
+        }
+
+        by_app.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        by_app
+    }
+}
+
+/// Intentions of the timer events in `events`. A timer without its end,
+/// e.g. when the daemon stopped, ends with its duration.
+pub fn intentions<'a>(
+    events: impl IntoIterator<Item = &'a TimedEvent>,
+) -> Vec<Intention> {
+    let mut intentions = vec![];
+    let mut running: Option<Intention> = None;
+
+    for event in events {
+        match &event.event {
+            Event::TimerStarted(started) => {
+                let duration = i64::try_from(started.duration)
+                    .ok()
+                    .and_then(TimeDelta::try_seconds)
+                    .unwrap_or(TimeDelta::MAX);
+                let end = event
+                    .timestamp
+                    .checked_add_signed(duration)
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC);
+
+                intentions.extend(running.replace(Intention {
+                    task: started.task.clone(),
+                    start: event.timestamp,
+                    end,
+                    completed: false,
+                }));
+            }
+            Event::TimerEnded(ended) => {
+                let Some(mut intention) = running.take() else {
+                    continue;
+                };
+
+                intention.end = event.timestamp.min(intention.end);
+                intention.completed = ended.completed;
+                intentions.push(intention);
+            }
+            _ => {}
+        }
+    }
+
+    intentions.extend(running);
+    intentions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, 10, min, 0).unwrap()
+    }
+
+    fn timed(min: u32, event: Event) -> TimedEvent {
+        TimedEvent {
+            timestamp: at(min),
+            event,
+        }
+    }
+
+    #[test]
+    fn timer_test() {
+        let mut timer = Timer::default();
+        let events = timer.start("write".into(), TimeDelta::minutes(25), at(0));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(timer.task(), Some("write"));
+        assert_eq!(timer.deadline(), Some(at(25)));
+        assert!(timer.expire(at(24)).is_none());
+
+        let Some(Event::TimerEnded(ended)) = timer.expire(at(25)) else {
+            panic!("Must be a TimerEnded event.");
+        };
+        assert!(ended.completed);
+        assert_eq!(timer.task(), None);
+
+        timer.start("read".into(), TimeDelta::minutes(25), at(30));
+        let events = timer.start("write".into(), TimeDelta::minutes(5), at(35));
+        let [Event::TimerEnded(ended), Event::TimerStarted(started)] =
+            &events[..]
+        else {
+            panic!("Must end the running timer and start the new one.");
+        };
+        assert!(!ended.completed);
+        assert_eq!(ended.task, "read");
+        assert_eq!(started.duration, 300);
+    }
+
+    #[test]
+    fn intentions_test() {
+        let mut timer = Timer::default();
+        let mut events = vec![];
+
+        for event in timer.start("write".into(), TimeDelta::minutes(25), at(0))
+        {
+            events.push(timed(0, event));
+        }
+        events.push(timed(5, Event::Alive));
+        events.push(timed(10, timer.stop(at(10)).unwrap()));
+        for event in timer.start("read".into(), TimeDelta::minutes(25), at(20))
+        {
+            events.push(timed(20, event));
+        }
+
+        let intentions = intentions(&events);
+        assert_eq!(
+            intentions,
+            [
+                Intention {
+                    task: "write".to_string(),
+                    start: at(0),
+                    end: at(10),
+                    completed: false,
+                },
+                Intention {
+                    task: "read".to_string(),
+                    start: at(20),
+                    end: at(45),
+                    completed: false,
+                },
+            ]
+        );
+
+        let session = |app: &str, start, end| FocusSession {
+            app: app.to_string(),
+            title: String::new(),
+            detail: None,
+            workspace: None,
+            start: at(start),
+            end: at(end),
+        };
+        let sessions = [
+            session("code", 0, 4),
+            session("firefox", 4, 6),
+            session("code", 6, 12),
+        ];
+
+        assert_eq!(
+            intentions[0].focus(&sessions),
+            [
+                ("code".to_string(), TimeDelta::minutes(8)),
+                ("firefox".to_string(), TimeDelta::minutes(2)),
+            ]
+        );
+    }
+}
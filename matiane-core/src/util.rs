@@ -1,5 +1,9 @@
+#[cfg(any(feature = "async", feature = "blocking"))]
 mod memchr;
+#[cfg(any(feature = "async", feature = "blocking"))]
 mod memrchr;
 
+#[cfg(any(feature = "async", feature = "blocking"))]
 pub(crate) use memchr::memchr;
+#[cfg(any(feature = "async", feature = "blocking"))]
 pub(crate) use memrchr::memrchr;
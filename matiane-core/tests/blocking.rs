@@ -0,0 +1,100 @@
+#![cfg(feature = "blocking")]
+
+use anyhow::Result;
+use chrono::{FixedOffset, TimeZone, Utc};
+use matiane_core::events::TimedEvent;
+use matiane_core::store::blocking::{
+    EventReader, ForwardLineReader, LineReader, ReverseLineReader,
+};
+use std::fs;
+use std::io::Cursor;
+use std::num::NonZeroUsize;
+
+mod util;
+use util::tmpdir;
+
+fn forward(content: &str, buffer_size: usize) -> Result<Vec<String>> {
+    let reader = ForwardLineReader::with_buffer_size(
+        Cursor::new(content.as_bytes()),
+        NonZeroUsize::new(buffer_size).unwrap(),
+    );
+
+    Ok(reader.lines().collect::<Result<_, _>>()?)
+}
+
+fn backward(content: &str, buffer_size: usize) -> Result<Vec<String>> {
+    let mut reader = ReverseLineReader::with_buffer_size(
+        Cursor::new(content.as_bytes()),
+        NonZeroUsize::new(buffer_size).unwrap(),
+    );
+    reader.rewind()?;
+
+    Ok(reader.lines().collect::<Result<_, _>>()?)
+}
+
+#[test]
+fn blocking_readline() -> Result<()> {
+    let lines = vec!["Line 1", "Line 2", "Line 3", ""];
+    let content = lines.join("\n");
+    let mut reversed = lines.clone();
+    reversed.reverse();
+
+    for buffer_size in [1, 4, 100] {
+        assert_eq!(forward(&content, buffer_size)?, lines);
+        assert_eq!(backward(&content, buffer_size)?, reversed);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn blocking_store_read() -> Result<()> {
+    let dir = tmpdir("blocking-store-read");
+
+    fs::write(
+        dir.path().join("20260101.log"),
+        json_lines![
+            {
+                "timestamp": "2026-01-01T20:00:00Z",
+                "event": {
+                    "type": "alive"
+                }
+            },
+        ] + "\n",
+    )?;
+    fs::write(
+        dir.path().join("20260103.log"),
+        json_lines![
+            {
+                "timestamp": "2026-01-03T05:00:00Z",
+                "event": {
+                    "type": "awake"
+                }
+            },
+            {
+                "timestamp": "2026-01-03T05:01:00Z",
+                "event": {
+                    "type": "alive"
+                }
+            },
+        ],
+    )?;
+    fs::write(dir.path().join("LOCK"), "")?;
+
+    assert_eq!(EventReader::list_files(dir.path())?.len(), 2);
+
+    let time = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    let time_tz = time.with_timezone(&FixedOffset::east_opt(0).unwrap());
+
+    let reader = EventReader::open(dir.path(), &time_tz)?;
+    let events: Vec<TimedEvent> = reader.collect::<Result<_, _>>()?;
+    assert_eq!(events.len(), 3);
+
+    let time = Utc.with_ymd_and_hms(2026, 1, 2, 0, 0, 0).unwrap();
+    let time_tz = time.with_timezone(&FixedOffset::east_opt(0).unwrap());
+
+    let reader = EventReader::open(dir.path(), &time_tz)?;
+    assert_eq!(reader.count(), 2);
+
+    Ok(())
+}
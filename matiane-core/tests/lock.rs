@@ -0,0 +1,116 @@
+#![cfg(feature = "async")]
+
+use chrono::Utc;
+use matiane_core::store::{
+    EventReader, LOCK_FILE_NAME, LockFileError, LockHolder, StoreReadError,
+    acquire_lock_file, acquire_lock_file_with, acquire_maintenance_lock,
+    acquire_read_lock, has_readers, try_exclude_readers,
+};
+use std::fs;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn lock_writes_holder() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    let _lock = acquire_lock_file(dir.path().into()).await?;
+
+    let content = fs::read_to_string(dir.path().join(LOCK_FILE_NAME))?;
+    let holder = LockHolder::parse(&content).unwrap();
+
+    assert_eq!(Some(holder), LockHolder::current());
+    assert_eq!(holder.pid, std::process::id());
+    assert!(holder.is_alive());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_held() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    let _lock = acquire_lock_file(dir.path().into()).await?;
+
+    let err = acquire_lock_file(dir.path().into()).await.unwrap_err();
+    assert!(matches!(err, LockFileError::Held(_)));
+
+    // --force-lock
+    let _forced = acquire_lock_file_with(dir.path().into(), true).await?;
+    let err = acquire_lock_file(dir.path().into()).await.unwrap_err();
+    assert!(matches!(err, LockFileError::Held(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_stale_takeover() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join(LOCK_FILE_NAME);
+    let _lock = acquire_lock_file(dir.path().into()).await?;
+
+    // A process that reused the pid of the holder.
+    let mut holder = LockHolder::current().unwrap();
+    holder.start_time += 1;
+    fs::write(&path, format!("{}\n", holder))?;
+    assert!(!holder.is_alive());
+
+    let _taken = acquire_lock_file(dir.path().into()).await?;
+    let content = fs::read_to_string(&path)?;
+    assert_eq!(LockHolder::parse(&content), LockHolder::current());
+
+    // Locks of older versions have no holder.
+    fs::write(&path, "")?;
+    let err = acquire_lock_file(dir.path().into()).await.unwrap_err();
+    assert!(matches!(err, LockFileError::TryLockError(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_readers_and_maintenance() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().to_path_buf();
+
+    let first = acquire_read_lock(&path).await?;
+    let second = acquire_read_lock(&path).await?;
+    // Readers do not keep the writer away.
+    let write = acquire_lock_file(path.clone()).await?;
+
+    assert!(acquire_maintenance_lock(path.clone()).await.is_err());
+    drop((first, second, write));
+
+    let maintenance = acquire_maintenance_lock(path.clone()).await?;
+    assert!(acquire_read_lock(&path).await.is_err());
+    assert!(matches!(
+        EventReader::open(path.clone(), &Utc::now().fixed_offset()).await,
+        Err(StoreReadError::Maintenance)
+    ));
+    assert!(acquire_lock_file(path.clone()).await.is_err());
+
+    drop(maintenance);
+    acquire_read_lock(&path).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn lock_exclude_readers() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().to_path_buf();
+    // The writer does not count as a reader.
+    let _write = acquire_lock_file(path.clone()).await?;
+    assert!(!has_readers(&path).await?);
+
+    let reader = acquire_read_lock(&path).await?;
+    assert!(has_readers(&path).await?);
+    assert!(try_exclude_readers(&path).await?.is_none());
+    drop(reader);
+
+    let excluded = try_exclude_readers(&path).await?;
+    assert!(excluded.is_some());
+    assert!(acquire_read_lock(&path).await.is_err());
+
+    drop(excluded);
+    assert!(!has_readers(&path).await?);
+    acquire_read_lock(&path).await?;
+
+    Ok(())
+}
@@ -1,6 +1,10 @@
-#![cfg(target_os = "linux")]
+#![cfg(all(target_os = "linux", feature = "async"))]
+#![allow(clippy::redundant_closure)]
 use anyhow::Result;
-use matiane_core::process::{AlwaysCommandOptions, run_always_command};
+use matiane_core::process::{
+    AlwaysCommandOptions, RestartPolicy, Supervisor, SupervisorOptions,
+    run_always_command,
+};
 use std::path::PathBuf;
 use std::time::Duration;
 use tokio::process::Command;
@@ -183,3 +187,74 @@ async fn process_restart_done() {
         );
     }
 }
+
+fn shell_options(script: &str, restart: RestartPolicy) -> SupervisorOptions {
+    SupervisorOptions {
+        name: String::from("sh"),
+        args: vec!["-c".to_string(), script.to_string()],
+        restart,
+        restart_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(4),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn supervisor_on_failure() -> Result<()> {
+    let cancel = CancellationToken::new();
+    let opts = shell_options("exit 0", RestartPolicy::OnFailure);
+    let mut running = Supervisor::new(opts).spawn(cancel.clone());
+
+    (&mut running.handle).await??;
+    assert_eq!(running.status.lock().await.restarts, 0);
+
+    // No run is long enough to count as stable, however slow the machine.
+    let opts = SupervisorOptions {
+        max_restarts: Some(3),
+        reset_after: Duration::MAX,
+        ..shell_options("exit 1", RestartPolicy::OnFailure)
+    };
+    let mut running = Supervisor::new(opts).spawn(cancel);
+
+    assert!((&mut running.handle).await?.is_err());
+    assert_eq!(running.status.lock().await.restarts, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn supervisor_stable_runs() -> Result<()> {
+    let cancel = CancellationToken::new();
+    // Every run is stable, the restarts in a row never reach the limit.
+    let opts = SupervisorOptions {
+        max_restarts: Some(1),
+        reset_after: Duration::ZERO,
+        ..shell_options("exit 1", RestartPolicy::OnFailure)
+    };
+    let mut running = Supervisor::new(opts).spawn(cancel.clone());
+
+    while running.status.lock().await.restarts < 3 {
+        assert!(!running.handle.is_finished());
+        sleep_a_ms().await;
+    }
+
+    cancel.cancel();
+    (&mut running.handle).await??;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn supervisor_never() -> Result<()> {
+    let opts = SupervisorOptions {
+        capture_output: true,
+        ..shell_options("echo out; echo err >&2; exit 3", RestartPolicy::Never)
+    };
+    let mut running = Supervisor::new(opts).spawn(CancellationToken::new());
+
+    (&mut running.handle).await??;
+    assert_eq!(running.status.lock().await.restarts, 0);
+    assert_eq!(running.status.lock().await.pid, 0);
+
+    Ok(())
+}
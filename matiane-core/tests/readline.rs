@@ -1,10 +1,16 @@
+#![cfg(feature = "async")]
+
 use anyhow::Result;
+use futures::TryStreamExt;
 use matiane_core::store::readline::{
-    self, AsyncLineReader, AsyncLineReverseReader, LineReader,
+    self, AsyncLineReader, AsyncLineReverseReader, LineReader, MmapLineReader,
+    SeekLineReader,
 };
+use std::io::SeekFrom;
 use std::num::NonZeroUsize;
 use tempfile::{Builder, TempDir};
 use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
 
 fn tmpdir(name: &str) -> TempDir {
     Builder::new()
@@ -217,6 +223,37 @@ async fn readline_rewind_backward() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn readline_ref() -> Result<()> {
+    let lines = vec!["Line 1", "Łine ☃", "", "Line 4"];
+    let content = lines.join("\n");
+    let (_dir, mut file) = setup_file(&content).await?;
+
+    for buffer_size in 1..=content.len() + 1 {
+        let buffer_size = NonZeroUsize::new(buffer_size).unwrap();
+
+        let mut reader =
+            AsyncLineReader::with_buffer_size(&mut file, buffer_size);
+        reader.rewind().await?;
+        let mut forward = vec![];
+        while let Some(line) = reader.next_line_ref().await? {
+            forward.push(line.to_string());
+        }
+        assert_eq!(forward, lines, "buffer size {}", buffer_size);
+
+        let mut reader =
+            AsyncLineReverseReader::with_buffer_size(&mut file, buffer_size);
+        reader.rewind().await?;
+        let mut backward = vec![];
+        while let Some(line) = reader.next_line_ref().await? {
+            backward.insert(0, line.to_string());
+        }
+        assert_eq!(backward, lines, "buffer size {}", buffer_size);
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn readline_bin_seek() -> Result<()> {
     use readline::{BinarySearch, LineReaderError, ReaderResult};
@@ -302,3 +339,168 @@ async fn readline_bin_seek() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn readline_bin_find_first() -> Result<()> {
+    use readline::{BinarySearch, LineReaderError};
+    use std::io::Cursor;
+
+    // 8 bytes per line, the offset of a line is its index times 8.
+    let numbers = [10, 20, 20, 20, 50, 70];
+    let lines: Vec<String> =
+        numbers.iter().map(|x| format!("Line {}", x)).collect();
+    let content = lines.join("\n") + "\n";
+    let (_dir, mut file) = setup_file(&content).await?;
+    let len = content.len() as u64;
+
+    let against = |target: u32| {
+        move |line: &str| {
+            let num: u32 =
+                line[5..].parse().map_err(LineReaderError::compare)?;
+            Ok(num.cmp(&target))
+        }
+    };
+
+    for buffer_size in [4, 128] {
+        let size = NonZeroUsize::new(buffer_size).unwrap();
+
+        for (target, ge, gt) in [
+            (0, 0, 0),
+            (10, 0, 8),
+            (20, 8, 32),
+            (30, 32, 32),
+            (70, 40, len),
+            (80, len, len),
+        ] {
+            let pos = BinarySearch::new(&mut file, against(target))
+                .buffer_size(size)
+                .find_first_ge()
+                .await?;
+            assert_eq!(pos, ge, "first >= {}", target);
+
+            let pos = BinarySearch::new(&mut file, against(target))
+                .buffer_size(size)
+                .find_first_gt()
+                .await?;
+            assert_eq!(pos, gt, "first > {}", target);
+        }
+    }
+
+    let mut empty = Cursor::new(vec![]);
+    let pos = BinarySearch::new(&mut empty, against(10))
+        .find_first_ge()
+        .await?;
+    assert_eq!(pos, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn readline_mmap_like_buffered() -> Result<()> {
+    let contents = ["", "\n", "Line 1", "Line 1\nLine 2\n", "Line 1\n\nLine 3"];
+
+    for content in contents {
+        let (_dir, mut file) = setup_file(content).await?;
+
+        let mut mapped = MmapLineReader::new(&file)?;
+        let mut buffered = AsyncLineReader::new(&mut file);
+
+        loop {
+            let expected = buffered.next_line().await?;
+            assert_eq!(mapped.next_line().await?, expected, "{:?}", content);
+
+            if expected.is_none() {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn readline_mmap_seek() -> Result<()> {
+    let (_dir, file) = setup_file("Line 1\nLine 2\nLine 3").await?;
+    let mut reader = MmapLineReader::new(&file)?;
+
+    assert_eq!(reader.seek(SeekFrom::Start(7)).await?, 7);
+    assert_eq!(reader.next_line_ref().await?, Some("Line 2"));
+    assert_eq!(reader.seek(SeekFrom::Current(-3)).await?, 11);
+    assert_eq!(reader.next_line_ref().await?, Some(" 2"));
+    assert_eq!(reader.seek(SeekFrom::End(-6)).await?, 14);
+    assert_eq!(reader.next_line_ref().await?, Some("Line 3"));
+    assert_eq!(reader.next_line_ref().await?, None);
+
+    assert!(reader.seek(SeekFrom::End(-30)).await.is_err());
+    assert_eq!(reader.rewind().await?, 0);
+    assert_eq!(reader.next_line_ref().await?, Some("Line 1"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn readline_max_line_len() -> Result<()> {
+    let content =
+        format!("Line 1\n{}\nLine 3\n{}", "x".repeat(40), "y".repeat(40));
+
+    for buffer_size in [4, 16, 100] {
+        let (_dir, mut file) = setup_file(&content).await?;
+        let size = NonZeroUsize::new(buffer_size).unwrap();
+
+        let mut reader =
+            AsyncLineReader::with_buffer_size(&mut file, size).max_line_len(10);
+        assert_eq!(reader.next_line().await?, Some("Line 1".into()));
+        assert!(matches!(
+            reader.next_line().await,
+            Err(readline::LineReaderError::LineTooLong(10))
+        ));
+        assert_eq!(reader.next_line().await?, Some("Line 3".into()));
+        assert!(reader.next_line().await.is_err());
+        assert_eq!(reader.next_line().await?, None);
+
+        let mut reader =
+            AsyncLineReverseReader::with_buffer_size(&mut file, size)
+                .max_line_len(10);
+        reader.rewind().await?;
+        assert!(reader.next_line().await.is_err());
+        assert_eq!(reader.next_line().await?, Some("Line 3".into()));
+        assert!(reader.next_line().await.is_err());
+        assert_eq!(reader.next_line().await?, Some("Line 1".into()));
+        assert_eq!(reader.next_line().await?, None);
+    }
+
+    let (_dir, file) = setup_file(&content).await?;
+    let mut reader = MmapLineReader::new(&file)?.max_line_len(10);
+    assert_eq!(reader.next_line().await?, Some("Line 1".into()));
+    assert!(reader.next_line().await.is_err());
+    assert_eq!(reader.next_line().await?, Some("Line 3".into()));
+    assert!(reader.next_line().await.is_err());
+    assert_eq!(reader.next_line().await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn readline_async_read() -> Result<()> {
+    // A stream that can not be seeked, written a few bytes at a time.
+    let (mut tx, rx) = tokio::io::duplex(8);
+    let writer = tokio::spawn(async move {
+        tx.write_all(b"Line 1\nLine 2\n\nLine 4").await?;
+        Ok::<_, std::io::Error>(())
+    });
+
+    let size = NonZeroUsize::new(4).unwrap();
+    let lines: Vec<String> = AsyncLineReader::with_buffer_size(rx, size)
+        .into_stream()
+        .try_collect()
+        .await?;
+    assert_eq!(lines, ["Line 1", "Line 2", "", "Line 4"]);
+    writer.await??;
+
+    let mut reader = AsyncLineReader::new(&b"Line 1\nLine 2"[..]);
+    assert_eq!(reader.next_line().await?, Some("Line 1".into()));
+    assert_eq!(reader.next_line().await?, Some("Line 2".into()));
+    assert_eq!(reader.next_line().await?, None);
+
+    Ok(())
+}
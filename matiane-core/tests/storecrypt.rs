@@ -0,0 +1,155 @@
+#![cfg(feature = "async")]
+
+use anyhow::Result;
+use chrono::{TimeZone, Utc};
+use futures::TryStreamExt;
+use matiane_core::events::{Event, Focused, TimedEvent};
+use matiane_core::store::crypt::{KEY_LEN, StoreKey};
+use matiane_core::store::{
+    ENCRYPTED_HEADER, EventReader, EventWriter, Index, OnDecodeError,
+    StoreFormat, StorePath, StoreReadError,
+};
+use std::fs;
+
+mod util;
+use util::tmpdir;
+
+#[tokio::test]
+async fn store_encrypted() -> Result<()> {
+    let key = StoreKey::new([7; KEY_LEN]);
+
+    let dir = tmpdir("store-encrypted");
+    let pathbuf = dir.path().to_path_buf();
+    let store = StorePath::new(pathbuf.clone()).with_key(Some(key.clone()));
+    let at = |d, h| Utc.with_ymd_and_hms(2026, 1, d, h, 0, 0).unwrap();
+    let focused = Event::Focused(Box::new(Focused {
+        title: "a secret title".to_string(),
+        id: "a".to_string(),
+        pid: 1,
+        process: None,
+        raw_title: None,
+        detail: None,
+    }));
+
+    let mut writer = EventWriter::open(pathbuf.clone(), at(1, 0))
+        .await?
+        .with_format(StoreFormat::Encrypted)
+        .with_key(Some(key.clone()));
+    for (timestamp, event) in [
+        (at(1, 1), focused),
+        (at(1, 2), Event::Alive),
+        (at(2, 1), Event::Idle),
+    ] {
+        writer.write(&TimedEvent { timestamp, event }).await?;
+    }
+    writer.flush().await?;
+
+    let path = dir.path().join("20260101.log");
+    let content = fs::read(&path)?;
+    assert!(content.starts_with(ENCRYPTED_HEADER));
+    assert!(!String::from_utf8_lossy(&content).contains("secret"));
+
+    let from = at(1, 0).into();
+    assert!(EventReader::open(pathbuf.clone(), &from).await.is_err());
+    let other = StorePath::new(pathbuf.clone())
+        .with_key(Some(StoreKey::new([8; KEY_LEN])));
+    assert!(EventReader::open(other, &from).await.is_err());
+
+    let read: Vec<TimedEvent> = EventReader::open(store.clone(), &from)
+        .await?
+        .into_stream()
+        .try_collect()
+        .await?;
+    let timestamps: Vec<_> = read.iter().map(|e| e.timestamp).collect();
+    assert_eq!(timestamps, [at(1, 1), at(1, 2), at(2, 1)]);
+
+    // Window titles would be in the clear in an index.
+    assert!(Index::build(&path).await?.entries.is_empty());
+
+    // A byte of the last event of the first day changed on disk.
+    let mut changed = content.clone();
+    *changed.last_mut().unwrap() ^= 1;
+    fs::write(&path, changed)?;
+
+    let mut reader = EventReader::open(store, &from)
+        .await?
+        .on_decode_error(OnDecodeError::Collect);
+    let mut read = 0;
+    while reader.next_event().await?.is_some() {
+        read += 1;
+    }
+    assert_eq!(read, 2);
+
+    let errors = reader.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0].error,
+        StoreReadError::ChecksumMismatch(_)
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_annotate_encrypted() -> Result<()> {
+    use matiane_core::store::annotate;
+
+    let key = StoreKey::new([7; KEY_LEN]);
+
+    let dir = tmpdir("store-annotate-encrypted");
+    let pathbuf = dir.path().to_path_buf();
+    let store = StorePath::new(pathbuf.clone()).with_key(Some(key.clone()));
+    let at = |d, h| Utc.with_ymd_and_hms(2026, 1, d, h, 0, 0).unwrap();
+
+    let mut writer = EventWriter::open(pathbuf, at(1, 0))
+        .await?
+        .with_format(StoreFormat::Encrypted)
+        .with_key(Some(key));
+    for hour in [8, 12] {
+        writer
+            .write(&TimedEvent {
+                timestamp: at(1, hour),
+                event: Event::Alive,
+            })
+            .await?;
+    }
+    writer.flush().await?;
+    drop(writer);
+
+    // A frame cut short by a crash, decoded as a line that is not an event.
+    let path = dir.path().join("20260101.log");
+    let mut content = fs::read(&path)?;
+    content.extend_from_slice(&[0, 0, 0, 9, 1]);
+    fs::write(&path, content)?;
+
+    let tags = vec!["client-x".to_string()];
+    annotate(
+        store.clone(),
+        at(1, 10),
+        "a secret meeting".to_string(),
+        tags,
+    )
+    .await?;
+
+    let content = fs::read(&path)?;
+    assert!(content.starts_with(ENCRYPTED_HEADER));
+    assert!(!String::from_utf8_lossy(&content).contains("secret"));
+
+    let mut reader = EventReader::open(store, &at(1, 0).into())
+        .await?
+        .on_decode_error(OnDecodeError::Collect);
+    let mut read = vec![];
+    while let Some(event) = reader.next_event().await? {
+        read.push(event);
+    }
+    assert!(reader.take_errors().is_empty());
+
+    let timestamps: Vec<_> = read.iter().map(|e| e.timestamp).collect();
+    assert_eq!(timestamps, [at(1, 8), at(1, 10), at(1, 12)]);
+    let Event::Annotation(annotation) = &read[1].event else {
+        panic!("Must be an Annotation event.");
+    };
+    assert_eq!(annotation.text, "a secret meeting");
+
+    Ok(())
+}
@@ -1,7 +1,14 @@
+#![cfg(feature = "async")]
+#![allow(
+    clippy::redundant_pattern_matching,
+    clippy::vec_init_then_push,
+    clippy::zero_prefixed_literal
+)]
+
 use anyhow::Result;
 use futures::{StreamExt, TryStreamExt};
 use matiane_core::events::TimedEvent;
-use matiane_core::store::EventReader;
+use matiane_core::store::{EventReader, OnDecodeError};
 use std::path::Path;
 use tokio::fs;
 
@@ -118,6 +125,39 @@ async fn store_read_all_files() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn store_read_trailing_newline() -> Result<()> {
+    use chrono::*;
+
+    let dir = tmpdir("store-read-trailing-newline");
+    let mut lines = json_lines![
+        {
+            "timestamp": "2026-01-01T20:00:00Z",
+            "event": {
+                "type": "alive"
+            }
+        },
+        {
+            "timestamp": "2026-01-01T22:00:00Z",
+            "event": {
+                "type": "sleep"
+            }
+        },
+    ];
+    lines.push('\n');
+    fs::write(dir.path().join("20260101.log"), lines).await?;
+
+    let time = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    let reader =
+        EventReader::open(dir.path().to_path_buf(), &time.fixed_offset())
+            .await?;
+    let collected: Vec<TimedEvent> = reader.into_stream().try_collect().await?;
+
+    assert_eq!(collected.len(), 2);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn store_read_all_stream() -> Result<()> {
     use chrono::*;
@@ -174,3 +214,523 @@ async fn store_read_all_stream_one_by_one() -> Result<()> {
 
     Ok(())
 }
+
+/// Events every 2 minutes of 2026-01-01 from 08:00 to 18:00, focus
+/// alternating between `a` and `b` with an idle period every 5th.
+async fn prepare_day(dir: &Path) -> Result<Vec<TimedEvent>> {
+    use chrono::*;
+    use matiane_core::events::{Event, Focused};
+
+    let start = Utc.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap();
+    let events: Vec<TimedEvent> = (0..300)
+        .map(|i| TimedEvent {
+            timestamp: start + TimeDelta::minutes(2 * i),
+            event: match i % 5 {
+                3 => Event::Idle,
+                4 => Event::Active,
+                _ => Event::Focused(Box::new(Focused {
+                    title: format!("title {}", i),
+                    id: if i % 2 == 0 { "a" } else { "b" }.to_string(),
+                    pid: 1,
+                    process: None,
+                    raw_title: None,
+                    detail: None,
+                })),
+            },
+        })
+        .collect();
+
+    let mut content = String::new();
+    for event in &events {
+        content += &serde_json::to_string(event)?;
+        content += "\n";
+    }
+
+    fs::write(dir.join("20260101.log"), content).await?;
+    fs::write(dir.join("20260102.log"), "").await?;
+
+    Ok(events)
+}
+
+#[tokio::test]
+async fn store_read_open_from() -> Result<()> {
+    use chrono::*;
+    use matiane_core::store::{Index, build_indexes};
+
+    let dir = tmpdir("store-read-open-from");
+    let events = prepare_day(dir.path()).await?;
+
+    let check = async |from: DateTime<Utc>| -> Result<()> {
+        let expected = events
+            .iter()
+            .find(|e| e.timestamp >= from)
+            .map(|e| e.timestamp);
+        let mut reader =
+            EventReader::open_from(dir.path().to_path_buf(), &from.into())
+                .await?;
+
+        let first = reader.next_event().await?.map(|e| e.timestamp);
+        assert_eq!(first, expected, "open from {}", from);
+        Ok(())
+    };
+
+    let times = [
+        Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2026, 1, 1, 9, 10, 0).unwrap(),
+        Utc.with_ymd_and_hms(2026, 1, 1, 12, 54, 0).unwrap(),
+        Utc.with_ymd_and_hms(2026, 1, 1, 17, 59, 0).unwrap(),
+        Utc.with_ymd_and_hms(2026, 1, 1, 23, 0, 0).unwrap(),
+    ];
+
+    for from in times {
+        check(from).await?;
+    }
+
+    let log_path = dir.path().join("20260101.log");
+    assert!(Index::read(&log_path).await?.is_none());
+    assert_eq!(build_indexes(dir.path()).await?, 1);
+    assert_eq!(build_indexes(dir.path()).await?, 0);
+
+    let index = Index::read(&log_path).await?.expect("index is written");
+    assert_eq!(index.entries.len(), 10);
+    assert_eq!(index.entries[0].offset, 0);
+    assert_eq!(EventReader::list_files(dir.path()).await?.len(), 2);
+
+    for from in times {
+        check(from).await?;
+    }
+
+    // Writing to the day again makes the index stale.
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    fs::OpenOptions::new()
+        .append(true)
+        .open(&log_path)
+        .await?
+        .set_len(0)
+        .await?;
+    assert!(Index::read(&log_path).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_activity_indexed() -> Result<()> {
+    use chrono::*;
+    use matiane_core::activity::{MAX_EVENT_GAP, read_activity};
+    use matiane_core::store::build_indexes;
+
+    let dir = tmpdir("store-read-activity-indexed");
+    prepare_day(dir.path()).await?;
+
+    let ranges = [(9, 17), (10, 11), (12, 18)];
+    let at = |h| Utc.with_ymd_and_hms(2026, 1, 1, h, 30, 0).unwrap();
+
+    let mut unindexed = vec![];
+    for (from, to) in ranges {
+        let dir = dir.path().to_path_buf();
+        unindexed.push(
+            read_activity(dir, at(from).into(), at(to), MAX_EVENT_GAP).await?,
+        );
+    }
+
+    build_indexes(dir.path()).await?;
+
+    for ((from, to), expected) in ranges.into_iter().zip(unindexed) {
+        let dir = dir.path().to_path_buf();
+        let activity =
+            read_activity(dir, at(from).into(), at(to), MAX_EVENT_GAP).await?;
+
+        assert!(!activity.spans.is_empty());
+        assert_eq!(activity.spans, expected.spans);
+        assert_eq!(activity.away, expected.away);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_reverse() -> Result<()> {
+    use chrono::*;
+    use matiane_core::store::ReverseEventReader;
+
+    let dir = tmpdir("store-read-reverse");
+    prepare_files(dir.path()).await?;
+
+    let start = Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap();
+    let mut forward: Vec<TimedEvent> =
+        EventReader::open(dir.path().to_path_buf(), &start.into())
+            .await?
+            .into_stream()
+            .try_collect()
+            .await?;
+    forward.reverse();
+
+    let backward: Vec<TimedEvent> =
+        ReverseEventReader::open_latest(dir.path().to_path_buf())
+            .await?
+            .into_stream()
+            .try_collect()
+            .await?;
+    assert_eq!(
+        backward.iter().map(|e| e.timestamp).collect::<Vec<_>>(),
+        forward.iter().map(|e| e.timestamp).collect::<Vec<_>>()
+    );
+
+    let dir = tmpdir("store-read-reverse-before");
+    let events = prepare_day(dir.path()).await?;
+
+    let times = [
+        Utc.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap(),
+        Utc.with_ymd_and_hms(2026, 1, 1, 9, 10, 0).unwrap(),
+        Utc.with_ymd_and_hms(2026, 1, 1, 12, 55, 0).unwrap(),
+        Utc.with_ymd_and_hms(2026, 1, 2, 1, 0, 0).unwrap(),
+    ];
+
+    for before in times {
+        let expected = events
+            .iter()
+            .rev()
+            .find(|e| e.timestamp < before)
+            .map(|e| e.timestamp);
+        let mut reader =
+            ReverseEventReader::open(dir.path().to_path_buf(), &before.into())
+                .await?;
+
+        let last = reader.next_event().await?.map(|e| e.timestamp);
+        assert_eq!(last, expected, "open before {}", before);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_open_range() -> Result<()> {
+    use chrono::*;
+
+    let dir = tmpdir("store-read-open-range");
+    let events = prepare_day(dir.path()).await?;
+    let at = |h, m| Utc.with_ymd_and_hms(2026, 1, 1, h, m, 0).unwrap();
+
+    let ranges = [
+        (at(0, 0), at(8, 0)),
+        (at(9, 10), at(9, 20)),
+        (at(12, 55), at(17, 1)),
+        (at(17, 0), at(23, 0)),
+    ];
+
+    for (from, to) in ranges {
+        let expected: Vec<_> = events
+            .iter()
+            .filter(|e| e.timestamp >= from && e.timestamp < to)
+            .map(|e| e.timestamp)
+            .collect();
+        let reader =
+            EventReader::open_range(dir.path().to_path_buf(), &from.into(), to)
+                .await?;
+        let read: Vec<TimedEvent> = reader.into_stream().try_collect().await?;
+
+        assert_eq!(
+            read.iter().map(|e| e.timestamp).collect::<Vec<_>>(),
+            expected,
+            "range {} - {}",
+            from,
+            to
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_missing_days() -> Result<()> {
+    use chrono::NaiveDate;
+
+    let dir = tmpdir("store-read-missing-days");
+    let date = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+
+    let files = EventReader::list_files(dir.path()).await?;
+    assert!(files.missing_days(..).is_empty());
+    assert_eq!(files.missing_days(date(1)..date(3)), [date(1), date(2)]);
+
+    prepare_files(dir.path()).await?;
+    let files = EventReader::list_files(dir.path()).await?;
+
+    assert_eq!(files.missing_days(..), [date(2)]);
+    assert_eq!(files.missing_days(date(2)..), [date(2)]);
+    assert_eq!(
+        files.missing_days(date(1)..=date(5)),
+        [date(2), date(4), date(5)]
+    );
+    assert!(files.missing_days(date(3)..date(3)).is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_open_options() -> Result<()> {
+    use chrono::*;
+    use matiane_core::store::{OpenAt, OpenOptions, StoreReadError};
+
+    let dir = tmpdir("store-read-open-options");
+    prepare_files(dir.path()).await?;
+
+    let day = |d| Utc.with_ymd_and_hms(2026, 1, d, 12, 0, 0).unwrap();
+    let first = async |d, at, or_empty| {
+        let options = OpenOptions {
+            at,
+            or_empty,
+            ..Default::default()
+        };
+        let open_at = day(d).fixed_offset();
+        let mut reader =
+            EventReader::open_with(dir.path().to_path_buf(), &open_at, options)
+                .await?;
+        let event = reader.next_event().await?;
+        Ok::<_, StoreReadError>(event.map(|e| e.timestamp))
+    };
+
+    let jan1 = Utc.with_ymd_and_hms(2026, 1, 1, 20, 0, 0).unwrap();
+    let jan3 = Utc.with_ymd_and_hms(2026, 1, 3, 5, 0, 0).unwrap();
+
+    assert_eq!(first(2, OpenAt::AtOrAfter, false).await?, Some(jan3));
+    assert_eq!(first(2, OpenAt::AtOrBefore, false).await?, Some(jan1));
+    assert_eq!(first(3, OpenAt::AtOrBefore, false).await?, Some(jan3));
+    assert_eq!(first(5, OpenAt::Earliest, false).await?, Some(jan1));
+
+    assert!(matches!(
+        first(5, OpenAt::AtOrAfter, false).await,
+        Err(StoreReadError::NoFilesToOpen)
+    ));
+    assert_eq!(first(5, OpenAt::AtOrAfter, true).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_seek_to() -> Result<()> {
+    use chrono::*;
+
+    let dir = tmpdir("store-read-seek-to");
+    prepare_files(dir.path()).await?;
+
+    let at = |d, h, m| Utc.with_ymd_and_hms(2026, 1, d, h, m, 0).unwrap();
+    let mut reader =
+        EventReader::open(dir.path().to_path_buf(), &at(1, 0, 0).into())
+            .await?;
+    let mut next = async |seek_to| {
+        reader.seek_to(seek_to).await?;
+        let event = reader.next_event().await?;
+        Ok::<_, anyhow::Error>(event.map(|e| e.timestamp))
+    };
+
+    assert_eq!(next(at(3, 5, 1)).await?, Some(at(3, 5, 1)));
+    assert_eq!(next(at(1, 21, 0)).await?, Some(at(1, 22, 0)));
+    assert_eq!(next(at(2, 0, 0)).await?, Some(at(3, 5, 0)));
+    assert_eq!(next(at(1, 0, 0)).await?, Some(at(1, 20, 0)));
+    assert_eq!(next(at(5, 0, 0)).await?, None);
+    assert_eq!(next(at(3, 5, 0)).await?, Some(at(3, 5, 0)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_mmap() -> Result<()> {
+    use chrono::*;
+    use matiane_core::store::{OpenAt, OpenOptions};
+
+    let dir = tmpdir("store-read-mmap");
+    prepare_files(dir.path()).await?;
+
+    let at = |d, h, m| Utc.with_ymd_and_hms(2026, 1, d, h, m, 0).unwrap();
+    let open = async |mmap| {
+        let options = OpenOptions {
+            at: OpenAt::AtOrAfter,
+            or_empty: false,
+            mmap,
+        };
+        let open_at = at(1, 0, 0).fixed_offset();
+        EventReader::open_with(dir.path().to_path_buf(), &open_at, options)
+            .await
+    };
+
+    let buffered: Vec<TimedEvent> =
+        open(false).await?.into_stream().try_collect().await?;
+    let mapped: Vec<TimedEvent> =
+        open(true).await?.into_stream().try_collect().await?;
+    assert_eq!(
+        serde_json::to_string(&mapped)?,
+        serde_json::to_string(&buffered)?
+    );
+    assert_eq!(mapped.len(), 4);
+
+    let mut reader = open(true).await?;
+    reader.seek_to(at(1, 21, 0)).await?;
+    let next = reader.next_event().await?.map(|e| e.timestamp);
+    assert_eq!(next, Some(at(1, 22, 0)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_damaged_lines() -> Result<()> {
+    use chrono::*;
+
+    let dir = tmpdir("store-read-damaged-lines");
+    let lines = [
+        r#"{"timestamp":"2026-01-01T20:00:00Z","event":{"type":"alive"}}"#,
+        r#"{"timestamp":"2026-01-01T20:30:00Z","event":{"type":"focused"}}"#,
+        r#"{"timestamp":"2026-01-01T21:00:00Z","event":{"ty"#,
+        r#"{"timestamp":"2026-01-01T22:00:00Z","event":{"type":"sleep"}}"#,
+    ];
+    fs::write(dir.path().join("20260101.log"), lines.join("\n")).await?;
+
+    let from = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap().into();
+    let open = || EventReader::open(dir.path().to_path_buf(), &from);
+
+    let mut reader = open().await?;
+    assert!(reader.next_event().await?.is_some());
+    assert!(reader.next_event().await.is_err());
+
+    let reader = open().await?.on_decode_error(OnDecodeError::Skip);
+    let read: Vec<TimedEvent> = reader.into_stream().try_collect().await?;
+    assert_eq!(read.len(), 2);
+
+    let mut reader = open().await?.on_decode_error(OnDecodeError::Collect);
+    let mut read = 0;
+    while reader.next_event().await?.is_some() {
+        read += 1;
+    }
+    assert_eq!(read, 2);
+
+    let errors = reader.take_errors();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[1].line, lines[2]);
+    assert!(errors[1].path.ends_with("20260101.log"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_line_too_long() -> Result<()> {
+    use chrono::*;
+    use matiane_core::store::StoreReadError;
+    use matiane_core::store::readline::{
+        DEFAULT_MAX_LINE_LEN, LineReaderError,
+    };
+
+    let dir = tmpdir("store-read-line-too-long");
+    let lines = [
+        r#"{"timestamp":"2026-01-01T20:00:00Z","event":{"type":"alive"}}"#,
+        &"x".repeat(DEFAULT_MAX_LINE_LEN + 1),
+        r#"{"timestamp":"2026-01-01T22:00:00Z","event":{"type":"sleep"}}"#,
+    ];
+    fs::write(dir.path().join("20260101.log"), lines.join("\n")).await?;
+
+    let from = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap().into();
+    let mut reader = EventReader::open(dir.path().to_path_buf(), &from)
+        .await?
+        .on_decode_error(OnDecodeError::Collect);
+
+    let mut read = 0;
+    while reader.next_event().await?.is_some() {
+        read += 1;
+    }
+    assert_eq!(read, 2);
+
+    let errors = reader.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0].error,
+        StoreReadError::LineReaderError(LineReaderError::LineTooLong(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_invalid_utf8() -> Result<()> {
+    use chrono::*;
+
+    let dir = tmpdir("store-read-invalid-utf8");
+    let mut content = Vec::new();
+    content.extend_from_slice(
+        br#"{"timestamp":"2026-01-01T20:00:00Z","event":{"type":"alive"}}"#,
+    );
+    content.extend_from_slice(b"\n{\"timestamp\":\"\xff\"}\n");
+    content.extend_from_slice(
+        br#"{"timestamp":"2026-01-01T22:00:00Z","event":{"type":"sleep"}}"#,
+    );
+    fs::write(dir.path().join("20260101.log"), content).await?;
+
+    let from = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap().into();
+    let mut reader = EventReader::open(dir.path().to_path_buf(), &from)
+        .await?
+        .on_decode_error(OnDecodeError::Collect);
+
+    let mut read = 0;
+    while reader.next_event().await?.is_some() {
+        read += 1;
+    }
+    assert_eq!(read, 2);
+
+    let errors = reader.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].line, "{\"timestamp\":\"\u{fffd}\"}");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_read_checksums() -> Result<()> {
+    use chrono::*;
+    use matiane_core::events::Event;
+    use matiane_core::store::{EventWriter, StoreFormat, StoreReadError};
+
+    let dir = tmpdir("store-read-checksums");
+    let at = |h| Utc.with_ymd_and_hms(2026, 1, 1, h, 0, 0).unwrap();
+
+    let mut writer = EventWriter::open(dir.path().to_path_buf(), at(0))
+        .await?
+        .with_format(StoreFormat::JsonCrc32);
+    for h in [1, 2, 3] {
+        let event = TimedEvent {
+            timestamp: at(h),
+            event: Event::Alive,
+        };
+        writer.write(&event).await?;
+    }
+    writer.flush().await?;
+
+    // The hour of the second event changed on disk.
+    let path = dir.path().join("20260101.log");
+    let content = fs::read_to_string(&path).await?;
+    let content = content.replacen("T02:", "T04:", 1);
+    fs::write(&path, content).await?;
+
+    let from = at(0).into();
+    let open = || EventReader::open(dir.path().to_path_buf(), &from);
+
+    let mut reader = open().await?;
+    assert_eq!(reader.next_event().await?.unwrap().timestamp, at(1));
+    assert!(matches!(
+        reader.next_event().await,
+        Err(StoreReadError::ChecksumMismatch(_))
+    ));
+
+    let mut reader = open().await?.on_decode_error(OnDecodeError::Collect);
+    let mut read = vec![];
+    while let Some(event) = reader.next_event().await? {
+        read.push(event.timestamp);
+    }
+    assert_eq!(read, [at(1), at(3)]);
+
+    let errors = reader.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0].error,
+        StoreReadError::ChecksumMismatch(_)
+    ));
+
+    Ok(())
+}
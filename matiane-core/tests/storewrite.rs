@@ -1,7 +1,10 @@
+#![cfg(feature = "async")]
+#![allow(clippy::zero_prefixed_literal)]
+
 use anyhow::Result;
 use chrono::{TimeZone, Utc};
 use matiane_core::events::{Event, Focused, TimedEvent};
-use matiane_core::store::EventWriter;
+use matiane_core::store::{Durability, EventWriter, Index};
 use std::fs;
 
 mod util;
@@ -124,6 +127,9 @@ async fn store_write_several_events() -> Result<()> {
                     title: "This-is-title".to_string(),
                     id: "Program".to_string(),
                     pid: 111,
+                    process: None,
+                    raw_title: None,
+                    detail: None,
                 })),
             },
             expected: r#"
@@ -195,8 +201,9 @@ async fn store_rotate_on_write() -> Result<()> {
 
     store.flush().await?;
 
+    // The finished day is indexed on rotation.
     let dirs_count = fs::read_dir(dir.path())?.count();
-    assert_eq!(dirs_count, 2);
+    assert_eq!(dirs_count, 3);
 
     let contents_day1 = fs::read_to_string(dir.path().join("20250101.log"))?;
     let contents_day2 = fs::read_to_string(dir.path().join("20250102.log"))?;
@@ -204,5 +211,356 @@ async fn store_rotate_on_write() -> Result<()> {
     assert_eq!(contents_day1.lines().count(), 5);
     assert_eq!(contents_day2.lines().count(), 5);
 
+    let index = Index::read(&dir.path().join("20250101.log")).await?;
+    assert_eq!(index.map(|index| index.entries.len()), Some(1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_insert_events() -> Result<()> {
+    use matiane_core::events::Backfilled;
+    use matiane_core::store::insert_events;
+
+    let dir = tmpdir("store-insert-events");
+    let pathbuf = dir.path().to_path_buf();
+    let at = |d, h| Utc.with_ymd_and_hms(2025, 1, d, h, 0, 0).unwrap();
+
+    let mut store = EventWriter::open(pathbuf.clone(), at(1, 0)).await?;
+    for hour in [8, 12] {
+        store
+            .write(&TimedEvent {
+                timestamp: at(1, hour),
+                event: Event::Alive,
+            })
+            .await?;
+    }
+    store.flush().await?;
+
+    let backfilled = |d, h| TimedEvent {
+        timestamp: at(d, h),
+        event: Event::Backfilled(Box::new(Backfilled {
+            app: "calendar".to_string(),
+            title: "Meeting".to_string(),
+            source: "test".to_string(),
+            end: at(d, h + 1),
+        })),
+    };
+
+    insert_events(
+        pathbuf,
+        vec![backfilled(2, 10), backfilled(1, 12), backfilled(1, 10)],
+    )
+    .await?;
+
+    let first = fs::read_to_string(dir.path().join("20250101.log"))?;
+    let types: Vec<(String, String)> = first
+        .lines()
+        .map(|line| {
+            let event: TimedEvent = serde_json::from_str(line).unwrap();
+            let kind = match event.event {
+                Event::Alive => "alive",
+                Event::Backfilled(_) => "backfilled",
+                _ => "other",
+            };
+            (event.timestamp.format("%H").to_string(), kind.to_string())
+        })
+        .collect();
+
+    let expected = [
+        ("08", "alive"),
+        ("10", "backfilled"),
+        ("12", "alive"),
+        ("12", "backfilled"),
+    ];
+    assert_eq!(types, expected.map(|(h, k)| (h.to_string(), k.to_string())));
+
+    let second = fs::read_to_string(dir.path().join("20250102.log"))?;
+    assert_eq!(second.lines().count(), 1);
+    assert_eq!(fs::read_dir(dir.path())?.count(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_annotate() -> Result<()> {
+    use matiane_core::store::annotate;
+
+    let dir = tmpdir("store-annotate");
+    let pathbuf = dir.path().to_path_buf();
+    let at = |d, h| Utc.with_ymd_and_hms(2025, 1, d, h, 0, 0).unwrap();
+
+    let mut store = EventWriter::open(pathbuf.clone(), at(1, 0)).await?;
+    for hour in [8, 12] {
+        store
+            .write(&TimedEvent {
+                timestamp: at(1, hour),
+                event: Event::Alive,
+            })
+            .await?;
+    }
+    store.flush().await?;
+    drop(store);
+
+    let tags = vec!["client-x".to_string()];
+    annotate(pathbuf, at(1, 10), "Meeting".to_string(), tags).await?;
+
+    let lines: Vec<TimedEvent> =
+        fs::read_to_string(dir.path().join("20250101.log"))?
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[1].timestamp, at(1, 10));
+    let Event::Annotation(annotation) = &lines[1].event else {
+        panic!("Must be an Annotation event.");
+    };
+    assert_eq!(annotation.text, "Meeting");
+    assert_eq!(annotation.tags, ["client-x"]);
+
+    Ok(())
+}
+
+#[cfg(feature = "blocking")]
+#[tokio::test]
+async fn store_compress_on_rotate() -> Result<()> {
+    use futures::TryStreamExt;
+    use matiane_core::store::{EventReader, ReverseEventReader, blocking};
+
+    let dir = tmpdir("store-compress-on-rotate");
+    let pathbuf = dir.path().to_path_buf();
+    let at = |d, s| Utc.with_ymd_and_hms(2025, 1, d, 0, 0, s).unwrap();
+
+    let mut store = EventWriter::open(pathbuf.clone(), at(1, 0))
+        .await?
+        .with_compression(true);
+
+    for (d, s) in [(1, 1), (1, 2), (2, 1), (3, 1)] {
+        store
+            .write(&TimedEvent {
+                timestamp: at(d, s),
+                event: Event::Alive,
+            })
+            .await?;
+    }
+    store.flush().await?;
+
+    let mut names: Vec<_> = fs::read_dir(dir.path())?
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "20250101.idx",
+            "20250101.log.zst",
+            "20250102.idx",
+            "20250102.log.zst",
+            "20250103.log"
+        ]
+    );
+
+    let expected = [at(1, 1), at(1, 2), at(2, 1), at(3, 1)];
+    let timestamps = |events: Vec<TimedEvent>| -> Vec<_> {
+        events.iter().map(|e| e.timestamp).collect()
+    };
+
+    let forward = EventReader::open(pathbuf.clone(), &at(1, 0).into())
+        .await?
+        .into_stream()
+        .try_collect()
+        .await?;
+    assert_eq!(timestamps(forward), expected);
+
+    let mut backward = timestamps(
+        ReverseEventReader::open_latest(pathbuf.clone())
+            .await?
+            .into_stream()
+            .try_collect()
+            .await?,
+    );
+    backward.reverse();
+    assert_eq!(backward, expected);
+
+    let mut reader =
+        EventReader::open_from(pathbuf.clone(), &at(1, 2).into()).await?;
+    let first = reader.next_event().await?.map(|e| e.timestamp);
+    assert_eq!(first, Some(at(1, 2)));
+
+    let blocking = blocking::EventReader::open(dir.path(), &at(1, 0).into())?
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(timestamps(blocking), expected);
+
+    Ok(())
+}
+
+#[cfg(feature = "blocking")]
+#[tokio::test]
+async fn store_write_cbor() -> Result<()> {
+    use futures::TryStreamExt;
+    use matiane_core::store::{
+        CBOR_HEADER, EventReader, ReverseEventReader, StoreFormat, blocking,
+    };
+
+    let dir = tmpdir("store-write-cbor");
+    let pathbuf = dir.path().to_path_buf();
+    let at = |d, s| Utc.with_ymd_and_hms(2025, 1, d, 0, 0, s).unwrap();
+    let write = async |format, events: &[(u32, u32)]| -> Result<()> {
+        let (d, s) = events[0];
+        let mut store = EventWriter::open(pathbuf.clone(), at(d, s))
+            .await?
+            .with_format(format);
+
+        for &(d, s) in events {
+            store
+                .write(&TimedEvent {
+                    timestamp: at(d, s),
+                    event: Event::Alive,
+                })
+                .await?;
+        }
+
+        Ok(store.flush().await?)
+    };
+
+    write(StoreFormat::Json, &[(1, 1)]).await?;
+    write(StoreFormat::Cbor, &[(1, 2), (2, 1), (2, 2)]).await?;
+    // Days already written keep their format.
+    write(StoreFormat::Json, &[(2, 3), (3, 1)]).await?;
+
+    let day = |name| fs::read(dir.path().join(name)).unwrap();
+    assert_eq!(day("20250101.log")[0], b'{');
+    assert!(day("20250102.log").starts_with(CBOR_HEADER));
+    assert_eq!(day("20250103.log")[0], b'{');
+
+    let expected = [at(1, 1), at(1, 2), at(2, 1), at(2, 2), at(2, 3), at(3, 1)];
+    let timestamps = |events: Vec<TimedEvent>| -> Vec<_> {
+        events.iter().map(|e| e.timestamp).collect()
+    };
+
+    let forward = EventReader::open(pathbuf.clone(), &at(1, 0).into())
+        .await?
+        .into_stream()
+        .try_collect()
+        .await?;
+    assert_eq!(timestamps(forward), expected);
+
+    let mut backward = timestamps(
+        ReverseEventReader::open_latest(pathbuf.clone())
+            .await?
+            .into_stream()
+            .try_collect()
+            .await?,
+    );
+    backward.reverse();
+    assert_eq!(backward, expected);
+
+    let mut reader =
+        EventReader::open_from(pathbuf.clone(), &at(2, 2).into()).await?;
+    let first = reader.next_event().await?.map(|e| e.timestamp);
+    assert_eq!(first, Some(at(2, 2)));
+
+    let blocking = blocking::EventReader::open(dir.path(), &at(1, 0).into())?
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(timestamps(blocking), expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_write_durability() -> Result<()> {
+    let dir = tmpdir("store-write-durability");
+    let now = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    let path = dir.path().join("20260101.log");
+    let event = TimedEvent {
+        timestamp: now,
+        event: Event::Alive,
+    };
+
+    let mut store = EventWriter::open(dir.path().to_path_buf(), now)
+        .await?
+        .with_durability(Durability::Interval(3600));
+    store.write(&event).await?;
+    store.write(&event).await?;
+    assert_eq!(fs::read_to_string(&path)?, "");
+
+    store.flush().await?;
+    assert_eq!(fs::read_to_string(&path)?.lines().count(), 2);
+
+    let mut store = EventWriter::open(dir.path().to_path_buf(), now)
+        .await?
+        .with_durability(Durability::EveryEvent);
+    store.write(&event).await?;
+    assert_eq!(fs::read_to_string(&path)?.lines().count(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_write_repair_tail() -> Result<()> {
+    let dir = tmpdir("store-write-repair-tail");
+    let now = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    let path = dir.path().join("20260101.log");
+    let event = TimedEvent {
+        timestamp: now,
+        event: Event::Alive,
+    };
+    let line = serde_json::to_string(&event)?;
+
+    // Killed while writing the second event.
+    fs::write(&path, format!("{}\n{}", line, &line[..20]))?;
+
+    let mut store = EventWriter::open(dir.path().to_path_buf(), now).await?;
+    store.write(&event).await?;
+    store.flush().await?;
+
+    assert_eq!(fs::read_to_string(&path)?, format!("{}\n{}\n", line, line));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn store_day_zone() -> Result<()> {
+    use futures::TryStreamExt;
+    use matiane_core::store::{DayZone, EventReader};
+
+    let dir = tmpdir("store-write-day-zone");
+    let at = |h| Utc.with_ymd_and_hms(2026, 1, 1, h, 0, 0).unwrap();
+    let zone: DayZone = "+02:00".parse().unwrap();
+    zone.save(dir.path()).await?;
+
+    // Midnight of the zone is at 22:00 UTC.
+    let mut store = EventWriter::open(dir.path().to_path_buf(), at(21)).await?;
+    for h in 21..=23 {
+        let event = TimedEvent {
+            timestamp: at(h),
+            event: Event::Alive,
+        };
+        store.write(&event).await?;
+    }
+    store.flush().await?;
+
+    let contents_day1 = fs::read_to_string(dir.path().join("20260101.log"))?;
+    let contents_day2 = fs::read_to_string(dir.path().join("20260102.log"))?;
+    assert_eq!(contents_day1.lines().count(), 1);
+    assert_eq!(contents_day2.lines().count(), 2);
+
+    let read = async |from: chrono::DateTime<Utc>| -> Result<Vec<_>> {
+        let from = from.fixed_offset();
+        let reader =
+            EventReader::open_range(dir.path().to_path_buf(), &from, at(23))
+                .await?;
+        let events: Vec<TimedEvent> =
+            reader.into_stream().try_collect().await?;
+        Ok(events.into_iter().map(|e| e.timestamp).collect())
+    };
+    assert_eq!(read(at(20)).await?, [at(21), at(22)]);
+    assert_eq!(read(at(22)).await?, [at(22)]);
+
+    assert_eq!(DayZone::load(dir.path()).await?, zone);
+    DayZone::Utc.save(dir.path()).await?;
+    assert_eq!(DayZone::load(dir.path()).await?, DayZone::Utc);
+
     Ok(())
 }
@@ -0,0 +1,42 @@
+/* C ABI of the matiane query API.
+ *
+ * Results are JSON strings owned by the caller, freed with
+ * matiane_string_free. Failing calls return NULL, matiane_last_error
+ * tells why. Times are unix seconds, ranges are [from, to).
+ */
+
+#ifndef MATIANE_H
+#define MATIANE_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define MATIANE_GROUP_BY_APP 0
+#define MATIANE_GROUP_BY_TITLE 1
+#define MATIANE_GROUP_BY_DAY 2
+
+typedef struct MatianeClient MatianeClient;
+
+/* Store in state_dir, or of the default config when it is NULL. */
+MatianeClient *matiane_open(const char *state_dir);
+void matiane_free(MatianeClient *client);
+
+/* [{"app", "title", "start", "end"}], start and end in RFC 3339. */
+char *matiane_sessions(const MatianeClient *client, int64_t from, int64_t to);
+
+/* [{"key", "seconds"}], longest first, days in order. */
+char *matiane_summary(const MatianeClient *client, int64_t from, int64_t to,
+                      int group_by);
+
+/* Message of the last failed call on this thread, or NULL. */
+const char *matiane_last_error(void);
+void matiane_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,268 @@
+//! C ABI of `matiane_core::client`, see `include/matiane.h`.
+//!
+//! Results are JSON strings owned by the caller, freed with
+//! `matiane_string_free`. Failing calls return NULL and leave a message for
+//! `matiane_last_error`.
+//!
+//! With the `python` feature, the library is the `matiane` Python module
+//! too, see `python`.
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use matiane_core::client::{GroupBy, Matiane};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::ptr;
+
+#[cfg(feature = "python")]
+mod python;
+
+pub const GROUP_BY_APP: c_int = 0;
+pub const GROUP_BY_TITLE: c_int = 1;
+pub const GROUP_BY_DAY: c_int = 2;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Client with the runtime its queries run on.
+pub struct MatianeClient {
+    client: Matiane,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl MatianeClient {
+    /// The store in `state_dir`, or of the default config without it.
+    fn open(state_dir: Option<PathBuf>) -> Result<Self> {
+        let client = match state_dir {
+            Some(state_dir) => Matiane::new(state_dir),
+            None => Matiane::from_default_config()?,
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+
+        Ok(MatianeClient { client, runtime })
+    }
+}
+
+#[derive(Serialize)]
+struct Total {
+    key: String,
+    seconds: i64,
+}
+
+/// Open the store in `state_dir`, or of the default config when it is
+/// NULL.
+///
+/// # Safety
+///
+/// `state_dir` is NULL or a valid NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn matiane_open(
+    state_dir: *const c_char,
+) -> *mut MatianeClient {
+    let state_dir = (!state_dir.is_null()).then(|| {
+        unsafe { CStr::from_ptr(state_dir) }
+            .to_str()
+            .context("state_dir is not UTF-8")
+            .map(PathBuf::from)
+    });
+    let client = state_dir.transpose().and_then(MatianeClient::open);
+
+    match client {
+        Ok(client) => Box::into_raw(Box::new(client)),
+        Err(e) => fail(e),
+    }
+}
+
+/// # Safety
+///
+/// `client` is NULL or returned by `matiane_open` and not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn matiane_free(client: *mut MatianeClient) {
+    if !client.is_null() {
+        drop(unsafe { Box::from_raw(client) });
+    }
+}
+
+/// Sessions in `[from, to)`, unix seconds, as a JSON array of
+/// `{"app", "title", "start", "end"}`.
+///
+/// # Safety
+///
+/// `client` is returned by `matiane_open` and not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn matiane_sessions(
+    client: *const MatianeClient,
+    from: i64,
+    to: i64,
+) -> *mut c_char {
+    let Some(client) = (unsafe { client.as_ref() }) else {
+        return fail(anyhow!("client is NULL"));
+    };
+
+    respond(range(from, to).and_then(|range| {
+        client.runtime.block_on(client.client.sessions(range))
+    }))
+}
+
+/// Totals in `[from, to)`, unix seconds, grouped by one of `GROUP_BY_*`,
+/// as a JSON array of `{"key", "seconds"}`.
+///
+/// # Safety
+///
+/// `client` is returned by `matiane_open` and not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn matiane_summary(
+    client: *const MatianeClient,
+    from: i64,
+    to: i64,
+    group_by: c_int,
+) -> *mut c_char {
+    let Some(client) = (unsafe { client.as_ref() }) else {
+        return fail(anyhow!("client is NULL"));
+    };
+
+    let group_by = match group_by {
+        GROUP_BY_APP => GroupBy::App,
+        GROUP_BY_TITLE => GroupBy::Title,
+        GROUP_BY_DAY => GroupBy::Day,
+        _ => return fail(anyhow!("unknown group_by {}", group_by)),
+    };
+
+    let totals = range(from, to).and_then(|range| {
+        client
+            .runtime
+            .block_on(client.client.summary(range, group_by))
+    });
+
+    respond(totals.map(|totals| {
+        totals
+            .into_iter()
+            .map(|(key, total)| Total {
+                key,
+                seconds: total.num_seconds(),
+            })
+            .collect::<Vec<_>>()
+    }))
+}
+
+/// Message of the last failed call on this thread, NULL without one. Valid
+/// until the next call.
+#[unsafe(no_mangle)]
+pub extern "C" fn matiane_last_error() -> *const c_char {
+    LAST_ERROR.with_borrow(|error| {
+        error.as_ref().map_or(ptr::null(), |error| error.as_ptr())
+    })
+}
+
+/// # Safety
+///
+/// `string` is NULL or returned by this library and not freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn matiane_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+fn range(from: i64, to: i64) -> Result<Range<DateTime<Utc>>> {
+    let at = |secs| {
+        DateTime::from_timestamp(secs, 0)
+            .with_context(|| format!("timestamp {} is out of range", secs))
+    };
+
+    Ok(at(from)?..at(to)?)
+}
+
+fn respond<T: Serialize>(result: Result<T>) -> *mut c_char {
+    let json = result.and_then(|value| Ok(serde_json::to_string(&value)?));
+
+    match json.and_then(|json| Ok(CString::new(json)?)) {
+        Ok(json) => json.into_raw(),
+        Err(e) => fail(e),
+    }
+}
+
+fn fail<T>(error: anyhow::Error) -> *mut T {
+    // Messages with NUL bytes are cut at the first one.
+    let message = format!("{:#}", error);
+    let message = CString::new(message).unwrap_or_else(|e| {
+        let end = e.nul_position();
+        CString::new(&e.into_vec()[..end]).expect("no NUL before it")
+    });
+
+    LAST_ERROR.set(Some(message));
+    ptr::null_mut()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use matiane_core::events::{Event, Focused, TimedEvent};
+
+    fn take(string: *mut c_char) -> serde_json::Value {
+        assert!(!string.is_null());
+        let json = unsafe { CStr::from_ptr(string) }.to_str().unwrap();
+        let value = serde_json::from_str(json).unwrap();
+        unsafe { matiane_string_free(string) };
+        value
+    }
+
+    #[test]
+    fn ffi_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = |m| Utc.with_ymd_and_hms(2026, 1, 1, 10, m, 0).unwrap();
+        let lines: Vec<String> = [
+            (
+                at(0),
+                Event::Focused(Box::new(Focused {
+                    title: "a title".into(),
+                    id: "a".into(),
+                    pid: 1,
+                    process: None,
+                    raw_title: None,
+                    detail: None,
+                })),
+            ),
+            (at(2), Event::Sleep),
+        ]
+        .into_iter()
+        .map(|(timestamp, event)| {
+            serde_json::to_string(&TimedEvent { timestamp, event }).unwrap()
+        })
+        .collect();
+        std::fs::write(dir.path().join("20260101.log"), lines.join("\n"))
+            .unwrap();
+
+        let state_dir =
+            CString::new(dir.path().to_str().unwrap().as_bytes()).unwrap();
+        let client = unsafe { matiane_open(state_dir.as_ptr()) };
+        assert!(!client.is_null());
+
+        let (from, to) = (at(0).timestamp(), at(59).timestamp());
+
+        let sessions = take(unsafe { matiane_sessions(client, from, to) });
+        assert_eq!(sessions[0]["app"], "a");
+        assert_eq!(sessions[0]["end"], "2026-01-01T10:02:00Z");
+
+        let summary =
+            take(unsafe { matiane_summary(client, from, to, GROUP_BY_APP) });
+        assert_eq!(
+            summary,
+            serde_json::json!([{ "key": "a", "seconds": 120 }])
+        );
+
+        let summary = unsafe { matiane_summary(client, from, to, 7) };
+        assert!(summary.is_null());
+        let error = unsafe { CStr::from_ptr(matiane_last_error()) };
+        assert_eq!(error.to_str().unwrap(), "unknown group_by 7");
+
+        unsafe { matiane_free(client) };
+    }
+}
@@ -0,0 +1,147 @@
+//! The `matiane` Python module, over the same client as the C ABI.
+//!
+//! ```python
+//! import pandas
+//! import matiane
+//!
+//! m = matiane.Matiane()
+//! sessions = pandas.DataFrame(m.sessions(start, end))
+//! ```
+
+use crate::MatianeClient;
+use chrono::{DateTime, FixedOffset, Utc};
+use matiane_core::client::GroupBy;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::ops::Range;
+use std::path::PathBuf;
+
+create_exception!(matiane, MatianeError, PyException);
+
+/// A time, as an aware datetime or unix seconds.
+#[derive(FromPyObject)]
+enum Time {
+    DateTime(DateTime<FixedOffset>),
+    Seconds(f64),
+}
+
+impl Time {
+    fn to_utc(&self) -> PyResult<DateTime<Utc>> {
+        match self {
+            Time::DateTime(at) => Ok(at.to_utc()),
+            Time::Seconds(secs) => {
+                DateTime::from_timestamp_millis((secs * 1000.0) as i64)
+                    .ok_or_else(|| {
+                        PyValueError::new_err(format!(
+                            "timestamp {} is out of range",
+                            secs
+                        ))
+                    })
+            }
+        }
+    }
+}
+
+/// Store in `state_dir`, or of the default config without it.
+#[pyclass(name = "Matiane", module = "matiane")]
+struct PyMatiane {
+    inner: MatianeClient,
+}
+
+#[pymethods]
+impl PyMatiane {
+    #[new]
+    #[pyo3(signature = (state_dir=None))]
+    fn new(state_dir: Option<PathBuf>) -> PyResult<Self> {
+        let inner = MatianeClient::open(state_dir).map_err(error)?;
+        Ok(PyMatiane { inner })
+    }
+
+    /// Sessions in [start, end) as dicts of app, title, start and end.
+    fn sessions<'py>(
+        &self,
+        py: Python<'py>,
+        start: Time,
+        end: Time,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let range = range(&start, &end)?;
+        let sessions = py
+            .detach(|| {
+                self.inner
+                    .runtime
+                    .block_on(self.inner.client.sessions(range))
+            })
+            .map_err(error)?;
+
+        sessions
+            .into_iter()
+            .map(|span| {
+                let dict = PyDict::new(py);
+                dict.set_item("app", span.app)?;
+                dict.set_item("title", span.title)?;
+                dict.set_item("start", span.start)?;
+                dict.set_item("end", span.end)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
+    /// Totals in [start, end) grouped by app, title or day, as dicts of key
+    /// and seconds.
+    #[pyo3(signature = (start, end, group_by="app"))]
+    fn summary<'py>(
+        &self,
+        py: Python<'py>,
+        start: Time,
+        end: Time,
+        group_by: &str,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let group_by = match group_by {
+            "app" => GroupBy::App,
+            "title" => GroupBy::Title,
+            "day" => GroupBy::Day,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown group_by {}",
+                    group_by
+                )));
+            }
+        };
+
+        let range = range(&start, &end)?;
+        let totals = py
+            .detach(|| {
+                self.inner
+                    .runtime
+                    .block_on(self.inner.client.summary(range, group_by))
+            })
+            .map_err(error)?;
+
+        totals
+            .into_iter()
+            .map(|(key, total)| {
+                let dict = PyDict::new(py);
+                dict.set_item("key", key)?;
+                dict.set_item("seconds", total.num_seconds())?;
+                Ok(dict)
+            })
+            .collect()
+    }
+}
+
+fn range(start: &Time, end: &Time) -> PyResult<Range<DateTime<Utc>>> {
+    Ok(start.to_utc()?..end.to_utc()?)
+}
+
+fn error(error: anyhow::Error) -> PyErr {
+    MatianeError::new_err(format!("{:#}", error))
+}
+
+#[pymodule]
+fn matiane(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyMatiane>()?;
+    m.add("MatianeError", m.py().get_type::<MatianeError>())?;
+    Ok(())
+}
@@ -281,6 +281,7 @@ pub(super) struct PostfixTokens {
 }
 
 impl PostfixTokens {
+    #[allow(dead_code)]
     pub fn iter(&self) -> std::slice::Iter<'_, Token> {
         self.tokens.iter()
     }
@@ -4,8 +4,9 @@ mod exec;
 mod lexer;
 mod parser;
 
-pub struct Regex<'a> {
-    src: &'a str,
+#[derive(Debug, Clone)]
+pub struct Regex {
+    src: String,
     nfa: parser::Nfa,
 }
 
@@ -17,18 +18,22 @@ pub enum RegexCompileError {
     ParseError(#[from] parser::ParseError),
 }
 
-impl<'a> Regex<'a> {
-    pub fn compile(raw_regex: &'a str) -> Result<Self, RegexCompileError> {
+impl Regex {
+    pub fn compile(raw_regex: &str) -> Result<Self, RegexCompileError> {
         let tokens = lexer::tokenize(raw_regex.chars())?;
         let postfix_tokens = lexer::to_postfix(tokens)?;
         let nfa = parser::NfaBuilder::build(postfix_tokens)?;
 
         Ok(Self {
-            src: raw_regex,
+            src: raw_regex.to_string(),
             nfa,
         })
     }
 
+    pub fn as_str(&self) -> &str {
+        &self.src
+    }
+
     pub fn is_match(&self, hay: &str) -> bool {
         let exec = exec::ExecRegex::new(&self.nfa);
 
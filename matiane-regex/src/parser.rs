@@ -41,7 +41,7 @@ pub(super) enum NfaState {
     Finish,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(super) struct Nfa {
     pub(super) entry: StateId,
     pub(super) match_start: bool,
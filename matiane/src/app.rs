@@ -6,10 +6,11 @@ use iced::widget::{
 use std::collections::BTreeSet;
 
 use chrono::TimeZone;
-use iced::{Element, Fill, Subscription, Task};
+use iced::{Element, Fill, Subscription, Task, window};
 
 use crate::config;
 use crate::icon;
+use crate::instance::Activations;
 
 const DEFAULT_LIGHT: Theme = Theme::Light;
 const DEFAULT_DARK: Theme = Theme::Nord;
@@ -31,6 +32,8 @@ pub enum State {
 pub enum Message {
     LoadedDates(()),
     ThemeToggle,
+    /// Another launch asked for the window.
+    Activate,
 }
 
 #[derive(Debug)]
@@ -45,7 +48,10 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(cfg: config::MatianeConfig) -> (Self, Task<Message>) {
+    pub fn new(
+        cfg: config::MatianeConfig,
+        activations: Option<Activations>,
+    ) -> (Self, Task<Message>) {
         let tz_offset = *chrono::Local::now().offset();
 
         (
@@ -58,7 +64,12 @@ impl App {
                 tz_offset,
                 loaded_dates: None,
             },
-            Task::none(),
+            match activations {
+                Some(activations) => {
+                    Task::run(activations.into_stream(), |()| Message::Activate)
+                }
+                None => Task::none(),
+            },
         )
     }
 
@@ -78,6 +89,9 @@ impl App {
                     self.theme = DEFAULT_LIGHT
                 }
             }
+            Message::Activate => {
+                return window::latest().and_then(window::gain_focus);
+            }
         }
         Task::none()
     }
@@ -167,6 +181,21 @@ impl App {
     }
 }
 
+pub fn run(
+    cfg: config::MatianeConfig,
+    activations: Activations,
+) -> iced::Result {
+    let activations = std::sync::Mutex::new(Some(activations));
+    let app_init =
+        move || App::new(cfg.clone(), activations.lock().unwrap().take());
+
+    iced::application(app_init, App::update, App::view)
+        .title(App::title)
+        .theme(App::theme)
+        .font(icon::FONT)
+        .run()
+}
+
 pub fn loading<'a, Message: 'a>() -> Element<'a, Message> {
     container("Loading...")
         .width(Fill)
@@ -0,0 +1,77 @@
+use crate::config::MatianeConfig;
+use clap::{ArgMatches, Command};
+
+mod backfill;
+mod compact;
+mod current;
+mod doctor;
+mod downsample;
+mod export;
+mod format;
+mod index;
+mod journal;
+mod merge;
+#[allow(clippy::all)]
+mod proto;
+mod range;
+mod report;
+mod serve;
+mod statusline;
+mod summary;
+mod sync;
+mod top;
+mod waybar;
+mod webhooks;
+
+pub fn subcommands() -> impl IntoIterator<Item = Command> {
+    [
+        backfill::command(),
+        compact::command(),
+        current::command(),
+        doctor::command(),
+        downsample::command(),
+        export::command(),
+        index::command(),
+        journal::command(),
+        merge::command(),
+        report::command(),
+        serve::command(),
+        statusline::command(),
+        summary::command(),
+        sync::command(),
+        top::command(),
+        waybar::command(),
+        webhooks::command(),
+    ]
+}
+
+pub fn run(
+    cfg: MatianeConfig,
+    name: &str,
+    matches: &ArgMatches,
+) -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+
+    runtime.block_on(async move {
+        match name {
+            backfill::NAME => backfill::run(cfg, matches).await,
+            compact::NAME => compact::run(cfg, matches).await,
+            current::NAME => current::run(cfg, matches).await,
+            doctor::NAME => doctor::run(cfg, matches).await,
+            downsample::NAME => downsample::run(cfg, matches).await,
+            export::NAME => export::run(cfg, matches).await,
+            index::NAME => index::run(cfg, matches).await,
+            journal::NAME => journal::run(cfg, matches).await,
+            merge::NAME => merge::run(cfg, matches).await,
+            report::NAME => report::run(cfg, matches).await,
+            serve::NAME => serve::run(cfg, matches).await,
+            statusline::NAME => statusline::run(cfg, matches).await,
+            summary::NAME => summary::run(cfg, matches).await,
+            sync::NAME => sync::run(cfg, matches).await,
+            top::NAME => top::run(cfg, matches).await,
+            waybar::NAME => waybar::run(cfg, matches).await,
+            webhooks::NAME => webhooks::run(cfg, matches).await,
+            _ => unreachable!("clap only accepts registered subcommands"),
+        }
+    })
+}
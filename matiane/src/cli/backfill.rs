@@ -0,0 +1,221 @@
+use crate::config::MatianeConfig;
+use anyhow::Result;
+use chrono::{DateTime, Days, TimeDelta, Utc};
+use clap::{ArgGroup, ArgMatches, Command, arg, value_parser};
+use matiane_core::activity;
+use matiane_core::events::{Backfilled, Event, TimedEvent};
+use matiane_core::store::{
+    DayZone, LockFileError, acquire_lock_file, insert_events,
+};
+use std::path::PathBuf;
+
+mod csv;
+mod ical;
+mod rescuetime;
+mod timing;
+
+pub const NAME: &str = "backfill";
+
+/// Untracked pieces shorter than this are not worth an event.
+const MIN_PERIOD: TimeDelta = TimeDelta::minutes(1);
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about(
+            "Fill untracked periods from a calendar, a CSV file or an export",
+        )
+        .arg(
+            arg!(--ical <FILE> "iCalendar (.ics) file")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--csv <FILE> "CSV file with start,end,app,title lines")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--rescuetime <FILE> "RescueTime activity CSV export")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--timing <FILE> "Timing CSV export")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .group(
+            ArgGroup::new("source")
+                .args(SOURCES.map(|(id, _)| id))
+                .required(true),
+        )
+        .arg(arg!(-n --"dry-run" "Only print what would be written"))
+}
+
+type Parser = fn(&str) -> Result<Vec<Entry>>;
+
+const SOURCES: [(&str, Parser); 4] = [
+    ("ical", ical::parse),
+    ("csv", csv::parse),
+    ("rescuetime", rescuetime::parse),
+    ("timing", timing::parse),
+];
+
+/// A period of activity read from an external source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub app: String,
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let dry_run = matches.get_flag("dry-run");
+    let max_gap = cfg.sway.max_gap();
+    let store = cfg.general.store()?;
+    let state_dir = cfg.general.state_dir;
+
+    let (path, parse) = SOURCES
+        .iter()
+        .find_map(|(id, parse)| {
+            matches.get_one::<PathBuf>(id).map(|path| (path, parse))
+        })
+        .unwrap();
+    let entries = parse(&std::fs::read_to_string(path)?)?;
+
+    let entries: Vec<Entry> =
+        entries.into_iter().filter(|e| e.end > e.start).collect();
+
+    let (Some(first), Some(last)) = (
+        entries.iter().map(|e| e.start).min(),
+        entries.iter().map(|e| e.end).max(),
+    ) else {
+        println!("Nothing to backfill.");
+        return Ok(());
+    };
+
+    tokio::fs::create_dir_all(&state_dir).await?;
+
+    // While the daemon runs it appends to today's file, leave it alone.
+    let (_lock, writable_before) =
+        match acquire_lock_file(state_dir.clone()).await {
+            Ok(lock) => (Some(lock), DateTime::<Utc>::MAX_UTC),
+            Err(LockFileError::TryLockError(_) | LockFileError::Held(_)) => {
+                let zone = DayZone::load(&state_dir).await?;
+                (None, zone.start_of(zone.date_of(Utc::now())))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+    // Read a day earlier to know what was focused when the range started.
+    let from = activity::start_of_day(first.date_naive() - Days::new(1));
+    let tracked = activity::read_activity(store.clone(), from, last, max_gap)
+        .await?
+        .sorted_intervals();
+
+    let source = path.display().to_string();
+    let mut events = vec![];
+    let mut skipped = 0;
+
+    for entry in &entries {
+        for (start, end) in untracked(entry.start, entry.end, &tracked) {
+            if end - start < MIN_PERIOD {
+                continue;
+            }
+
+            if start >= writable_before {
+                skipped += 1;
+                continue;
+            }
+
+            println!(
+                "{} - {}  {}  {}",
+                start.format("%Y-%m-%d %H:%M"),
+                end.format("%H:%M"),
+                entry.app,
+                entry.title
+            );
+
+            events.push(TimedEvent {
+                timestamp: start,
+                event: Event::Backfilled(Box::new(Backfilled {
+                    app: entry.app.clone(),
+                    title: entry.title.clone(),
+                    source: source.clone(),
+                    end,
+                })),
+            });
+        }
+    }
+
+    if skipped > 0 {
+        eprintln!(
+            "Skipped {} period(s) of today, sway-matiane is writing them.",
+            skipped
+        );
+    }
+
+    if dry_run {
+        println!("Dry run, {} period(s) not written.", events.len());
+        return Ok(());
+    }
+
+    let count = events.len();
+    insert_events(store, events).await?;
+    println!("Backfilled {} period(s).", count);
+
+    Ok(())
+}
+
+/// Parts of `start..end` not covered by the sorted `tracked` intervals.
+fn untracked(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    tracked: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut free = vec![];
+    let mut cursor = start;
+
+    for &(t_start, t_end) in tracked {
+        if t_end <= cursor {
+            continue;
+        }
+
+        if t_start >= end {
+            break;
+        }
+
+        if t_start > cursor {
+            free.push((cursor, t_start));
+        }
+
+        cursor = cursor.max(t_end);
+    }
+
+    if cursor < end {
+        free.push((cursor, end));
+    }
+
+    free
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn untracked_test() {
+        let tracked = [(at(1), at(3)), (at(2), at(4)), (at(6), at(7))];
+
+        assert_eq!(untracked(at(0), at(1), &tracked), vec![(at(0), at(1))]);
+        assert_eq!(untracked(at(1), at(4), &tracked), vec![]);
+        assert_eq!(
+            untracked(at(0), at(8), &tracked),
+            vec![(at(0), at(1)), (at(4), at(6)), (at(7), at(8))]
+        );
+        assert_eq!(untracked(at(5), at(6), &tracked), vec![(at(5), at(6))]);
+        assert_eq!(untracked(at(3), at(5), &[]), vec![(at(3), at(5))]);
+    }
+}
@@ -0,0 +1,119 @@
+//! `start,end,app,title` per line. Times are RFC 3339 or `YYYY-MM-DD HH:MM`
+//! with optional seconds in the local timezone, the title is the rest of the
+//! line and may contain commas. Empty lines, `#` comments and a `start,...`
+//! header are skipped.
+
+use super::Entry;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+
+/// Local time formats accepted next to RFC 3339.
+const LOCAL_FORMATS: [&str; 3] =
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"];
+
+pub fn parse(content: &str) -> Result<Vec<Entry>> {
+    let mut entries = vec![];
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if i == 0 && line.starts_with("start,") {
+            continue;
+        }
+
+        let entry =
+            parse_line(line).with_context(|| format!("Line {}", i + 1))?;
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+fn parse_line(line: &str) -> Result<Entry> {
+    let mut fields = line.splitn(4, ',').map(str::trim);
+
+    let (Some(start), Some(end), Some(app), Some(title)) =
+        (fields.next(), fields.next(), fields.next(), fields.next())
+    else {
+        bail!("Expected start,end,app,title");
+    };
+
+    Ok(Entry {
+        app: app.to_string(),
+        title: title.to_string(),
+        start: parse_time(start)?,
+        end: parse_time(end)?,
+    })
+}
+
+/// Parse an RFC 3339 time, a time with a `+HHMM` offset or a local time.
+pub fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z"))
+    {
+        return Ok(datetime.to_utc());
+    }
+
+    LOCAL_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .ok_or_else(|| anyhow!("Bad time: {}", value))?
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|local| local.to_utc())
+        .ok_or_else(|| anyhow!("{} does not exist in local time", value))
+}
+
+/// Index of the first of `names` in the header of an export.
+pub fn column(headers: &::csv::StringRecord, names: &[&str]) -> Result<usize> {
+    headers
+        .iter()
+        .position(|header| names.contains(&header.trim()))
+        .ok_or_else(|| anyhow!("No {} column", names[0]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_test() {
+        let csv = "\
+start,end,app,title
+# lunch is not work
+2026-01-05T09:00:00Z,2026-01-05T10:00:00+01:00,phone,Call, with Bob
+
+2026-01-05T11:00:00Z,2026-01-05T11:30:00Z,zoom,Review
+";
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 1, 5, h, m, 0).unwrap();
+        let entries = parse(csv).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].app, "phone");
+        assert_eq!(entries[0].title, "Call, with Bob");
+        assert_eq!((entries[0].start, entries[0].end), (at(9, 0), at(9, 0)));
+        assert_eq!((entries[1].start, entries[1].end), (at(11, 0), at(11, 30)));
+
+        let err = parse("2026-01-05T09:00:00Z,bad,a,b").unwrap_err();
+        assert_eq!(err.to_string(), "Line 1");
+        assert!(parse("2026-01-05T09:00:00Z,2026-01-05T10:00:00Z").is_err());
+    }
+
+    #[test]
+    fn parse_time_test() {
+        let at = Utc.with_ymd_and_hms(2026, 1, 5, 8, 0, 0).unwrap();
+
+        assert_eq!(parse_time("2026-01-05 09:00:00 +0100").unwrap(), at);
+        assert_eq!(parse_time("2026-01-05T09:00:00+01:00").unwrap(), at);
+        assert_eq!(
+            parse_time("2026-01-05 09:00:00").unwrap(),
+            parse_time("2026-01-05T09:00:00").unwrap()
+        );
+        assert!(parse_time("05.01.2026").is_err());
+    }
+}
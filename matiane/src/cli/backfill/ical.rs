@@ -0,0 +1,224 @@
+//! Just enough of RFC 5545 to read meetings out of a calendar export.
+//!
+//! Recurrence rules are not expanded and TZID parameters are ignored, times
+//! without `Z` are read in the local timezone.
+
+use super::Entry;
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, Utc};
+
+const APP: &str = "calendar";
+
+pub fn parse(content: &str) -> Result<Vec<Entry>> {
+    let mut entries = vec![];
+    let mut event: Option<VEvent> = None;
+
+    for line in unfold(content) {
+        let Some((name, params, value)) = property(&line) else {
+            continue;
+        };
+
+        match (name, value) {
+            ("BEGIN", "VEVENT") => event = Some(VEvent::default()),
+            ("END", "VEVENT") => {
+                if let Some(entry) = event.take().and_then(VEvent::into_entry) {
+                    entries.push(entry);
+                }
+            }
+            _ => {
+                let Some(event) = event.as_mut() else {
+                    continue;
+                };
+
+                let datetime = || {
+                    parse_datetime(params, value)
+                        .with_context(|| format!("Bad {}: {}", name, value))
+                };
+
+                match name {
+                    "SUMMARY" => event.summary = Some(unescape(value)),
+                    "DTSTART" => event.start = datetime()?,
+                    "DTEND" => event.end = datetime()?,
+                    "DURATION" => {
+                        event.duration =
+                            Some(parse_duration(value).ok_or_else(|| {
+                                anyhow!("Bad DURATION: {}", value)
+                            })?)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+#[derive(Default)]
+struct VEvent {
+    summary: Option<String>,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    duration: Option<TimeDelta>,
+}
+
+impl VEvent {
+    /// All-day events have no time and are skipped.
+    fn into_entry(self) -> Option<Entry> {
+        let start = self.start?;
+        let end = self.end.or_else(|| Some(start + self.duration?))?;
+
+        Some(Entry {
+            app: APP.to_string(),
+            title: self.summary.unwrap_or_default(),
+            start,
+            end,
+        })
+    }
+}
+
+/// Join continuation lines, which start with a space or a tab.
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    lines
+}
+
+/// Split `NAME;PARAM=X:VALUE` into its parts.
+fn property(line: &str) -> Option<(&str, &str, &str)> {
+    let (head, value) = line.split_once(':')?;
+    let (name, params) = head.split_once(';').unwrap_or((head, ""));
+
+    Some((name, params, value))
+}
+
+fn parse_datetime(params: &str, value: &str) -> Result<Option<DateTime<Utc>>> {
+    if params.split(';').any(|p| p == "VALUE=DATE") || value.len() == 8 {
+        return Ok(None);
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")?;
+        return Ok(Some(naive.and_utc()));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")?;
+    let local = naive
+        .and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| anyhow!("{} does not exist in local time", value))?;
+
+    Ok(Some(local.to_utc()))
+}
+
+/// `P1W`, `PT1H30M`, `P1DT2H`...
+fn parse_duration(value: &str) -> Option<TimeDelta> {
+    let value = value.strip_prefix('+').unwrap_or(value);
+    let value = value.strip_prefix('P')?;
+
+    let mut total = TimeDelta::zero();
+    let mut number = String::new();
+    let mut time = false;
+
+    for ch in value.chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
+        }
+
+        if ch == 'T' {
+            time = true;
+            continue;
+        }
+
+        let n: i64 = std::mem::take(&mut number).parse().ok()?;
+
+        total += match (ch, time) {
+            ('W', false) => TimeDelta::weeks(n),
+            ('D', false) => TimeDelta::days(n),
+            ('H', true) => TimeDelta::hours(n),
+            ('M', true) => TimeDelta::minutes(n),
+            ('S', true) => TimeDelta::seconds(n),
+            _ => return None,
+        };
+    }
+
+    number.is_empty().then_some(total)
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            out.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') | Some('N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_test() {
+        let ics = "\
+BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+SUMMARY:Standup\\, daily\r
+DTSTART:20260105T090000Z\r
+DTEND:20260105T091500Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Planning with a very long\r
+  title\r
+DTSTART:20260105T130000Z\r
+DURATION:PT1H30M\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:Holiday\r
+DTSTART;VALUE=DATE:20260106\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 1, 5, h, m, 0).unwrap();
+        let entries = parse(ics).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].title, "Standup, daily");
+        assert_eq!((entries[0].start, entries[0].end), (at(9, 0), at(9, 15)));
+        assert_eq!(entries[1].title, "Planning with a very long title");
+        assert_eq!((entries[1].start, entries[1].end), (at(13, 0), at(14, 30)));
+    }
+
+    #[test]
+    fn parse_duration_test() {
+        assert_eq!(parse_duration("PT15M"), Some(TimeDelta::minutes(15)));
+        assert_eq!(
+            parse_duration("P1DT2H"),
+            Some(TimeDelta::days(1) + TimeDelta::hours(2))
+        );
+        assert_eq!(parse_duration("P1W"), Some(TimeDelta::weeks(1)));
+        assert_eq!(parse_duration("PT1H5"), None);
+        assert_eq!(parse_duration("P1H"), None);
+        assert_eq!(parse_duration("1H"), None);
+    }
+}
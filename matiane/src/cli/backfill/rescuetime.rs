@@ -0,0 +1,82 @@
+//! RescueTime activity export: `Date,Time Spent (seconds),Number of
+//! People,Activity,Category,Productivity`. RescueTime only keeps the hour an
+//! activity happened in, so the activities of an hour are laid out one after
+//! another from its start.
+
+use super::Entry;
+use super::csv::{column, parse_time};
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::HashMap;
+
+pub fn parse(content: &str) -> Result<Vec<Entry>> {
+    let mut reader = ::csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+
+    let date = column(&headers, &["Date"])?;
+    let spent = column(&headers, &["Time Spent (seconds)", "Time Spent"])?;
+    let activity = column(&headers, &["Activity"])?;
+    let category = column(&headers, &["Category"]).ok();
+
+    let mut entries = vec![];
+    let mut hours: HashMap<DateTime<Utc>, DateTime<Utc>> = HashMap::new();
+
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        let field = |index: usize| record.get(index).unwrap_or("").trim();
+
+        let (hour, seconds) = parse_row(field(date), field(spent))
+            .with_context(|| format!("Row {}", i + 1))?;
+        let start = *hours.entry(hour).or_insert(hour);
+        let end = start + TimeDelta::seconds(seconds);
+        hours.insert(hour, end);
+
+        entries.push(Entry {
+            app: field(activity).to_string(),
+            title: category.map(field).unwrap_or_default().to_string(),
+            start,
+            end,
+        });
+    }
+
+    Ok(entries)
+}
+
+fn parse_row(date: &str, spent: &str) -> Result<(DateTime<Utc>, i64)> {
+    let seconds = spent
+        .parse()
+        .with_context(|| format!("Bad time spent: {}", spent))?;
+
+    Ok((parse_time(date)?, seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_test() {
+        let csv = "\
+Date,Time Spent (seconds),Number of People,Activity,Category,Productivity
+2026-01-05T09:00:00+00:00,1200,1,firefox,Browsers,0
+2026-01-05T09:00:00+00:00,600,1,\"Slack, Inc.\",Communication,1
+2026-01-05T10:00:00+00:00,60,1,foot,Utilities,2
+";
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 1, 5, h, m, 0).unwrap();
+        let entries = parse(csv).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].app, "firefox");
+        assert_eq!(entries[0].title, "Browsers");
+        assert_eq!((entries[0].start, entries[0].end), (at(9, 0), at(9, 20)));
+        assert_eq!(entries[1].app, "Slack, Inc.");
+        assert_eq!((entries[1].start, entries[1].end), (at(9, 20), at(9, 30)));
+        assert_eq!((entries[2].start, entries[2].end), (at(10, 0), at(10, 1)));
+
+        let err =
+            parse("Date,Time Spent (seconds),Activity\nbad,1,a").unwrap_err();
+        assert_eq!(err.to_string(), "Row 1");
+        assert!(parse("Date,Activity\n").is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! Timing CSV export, either of app usage (`Application`, `Title`, `Start
+//! Date`, `End Date`, ...) or of time entries (`Project`, `Title`, ...).
+//! Time entries use the project as the app.
+
+use super::Entry;
+use super::csv::{column, parse_time};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+
+pub fn parse(content: &str) -> Result<Vec<Entry>> {
+    let mut reader = ::csv::Reader::from_reader(content.as_bytes());
+    let headers = reader.headers()?.clone();
+
+    let start_col = column(&headers, &["Start Date", "Start"])?;
+    let end_col = column(&headers, &["End Date", "End"])?;
+    let app = column(&headers, &["Application", "Project"])?;
+    let title = column(&headers, &["Title"]).ok();
+    let path = column(&headers, &["Path"]).ok();
+
+    let mut entries = vec![];
+
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        let field = |index: usize| record.get(index).unwrap_or("").trim();
+
+        // Documents and websites have a path, often better than no title.
+        let title = [title, path]
+            .into_iter()
+            .flatten()
+            .map(field)
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+
+        let (start, end) = parse_span(field(start_col), field(end_col))
+            .with_context(|| format!("Row {}", i + 1))?;
+
+        entries.push(Entry {
+            app: field(app).to_string(),
+            title: title.to_string(),
+            start,
+            end,
+        });
+    }
+
+    Ok(entries)
+}
+
+fn parse_span(
+    start: &str,
+    end: &str,
+) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    Ok((parse_time(start)?, parse_time(end)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_test() {
+        let apps = "\
+Application,Title,Path,Start Date,End Date,Duration
+Safari,\"News, today\",https://example.com,2026-01-05 09:00:00 +0100,2026-01-05 09:30:00 +0100,00:30:00
+Xcode,,/src/app.swift,2026-01-05 09:30:00 +0100,2026-01-05 10:00:00 +0100,00:30:00
+";
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 1, 5, h, m, 0).unwrap();
+        let entries = parse(apps).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].app, "Safari");
+        assert_eq!(entries[0].title, "News, today");
+        assert_eq!((entries[0].start, entries[0].end), (at(8, 0), at(8, 30)));
+        assert_eq!(entries[1].title, "/src/app.swift");
+
+        let projects = "\
+Project,Title,Notes,Start Date,End Date,Duration
+Matiane,Review,,2026-01-05T11:00:00Z,2026-01-05T12:00:00Z,1:00:00
+";
+        let entries = parse(projects).unwrap();
+        assert_eq!(entries[0].app, "Matiane");
+        assert_eq!((entries[0].start, entries[0].end), (at(11, 0), at(12, 0)));
+
+        let err = parse("Project,Start Date,End Date\na,bad,bad").unwrap_err();
+        assert_eq!(err.to_string(), "Row 1");
+        assert!(parse("Title,Start Date,End Date\n").is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use crate::config::MatianeConfig;
+use anyhow::{Context, Result};
+use chrono::{Days, Utc};
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::store::{DayZone, compact};
+
+pub const NAME: &str = "compact";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about(
+            "Collapse heartbeats of old days into presence events, with \
+             sway-matiane stopped",
+        )
+        .arg(
+            arg!(--"older-than" <DAYS> "Compact days older than this")
+                .value_parser(value_parser!(u32))
+                .required(true),
+        )
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let days = *matches.get_one::<u32>("older-than").unwrap();
+    let zone = DayZone::load(&cfg.general.state_dir).await?;
+    let before = zone
+        .date_of(Utc::now())
+        .checked_sub_days(Days::new(days.into()))
+        .context("--older-than is out of range")?;
+
+    let compacted = compact(cfg.general.store()?, before).await?;
+
+    println!("Compacted {} files.", compacted);
+
+    Ok(())
+}
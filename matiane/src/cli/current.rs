@@ -0,0 +1,116 @@
+use super::format;
+use crate::config::MatianeConfig;
+use anyhow::Result;
+use chrono::TimeDelta;
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity::{self, Activity};
+use matiane_core::store::StorePath;
+use matiane_core::xdg;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+pub const NAME: &str = "current";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Print the focused app and today's total")
+        .arg(arg!(-f --follow "Keep printing a line on every update"))
+        .arg(
+            arg!(--interval <SECONDS> "Update interval for --follow")
+                .value_parser(value_parser!(u64).range(1..))
+                .default_value("5"),
+        )
+        .arg(
+            arg!(--format <FORMAT> "Output format")
+                .value_parser(["text", "json"])
+                .default_value("text"),
+        )
+}
+
+#[derive(Debug, Serialize)]
+struct Current {
+    app: Option<String>,
+    title: Option<String>,
+    /// Seconds the window has been focused.
+    focused: i64,
+    /// Seconds tracked today.
+    today: i64,
+}
+
+impl From<&Activity> for Current {
+    fn from(activity: &Activity) -> Self {
+        let current = activity.current.as_ref();
+
+        Current {
+            app: current.map(|span| span.app.clone()),
+            title: current.map(|span| span.title.clone()),
+            focused: current.map_or(0, |span| span.duration().num_seconds()),
+            today: activity.total().num_seconds(),
+        }
+    }
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let follow = matches.get_flag("follow");
+    let interval = *matches.get_one::<u64>("interval").unwrap();
+    let json = matches.get_one::<String>("format").unwrap() == "json";
+
+    let max_gap = cfg.sway.max_gap();
+    let store = cfg.general.store()?;
+    let runtime_dir = xdg::runtime_dir(Some(matiane_core::NAME));
+    let mut stdout = std::io::stdout();
+
+    loop {
+        let activity = today(store.clone(), &runtime_dir, max_gap).await?;
+        let current = Current::from(&activity);
+
+        let line = match json {
+            true => serde_json::to_string(&current)?,
+            false => text(&current),
+        };
+
+        writeln!(stdout, "{}", line)?;
+        stdout.flush()?;
+
+        if !follow {
+            return Ok(());
+        }
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+/// Today's activity from the `status` of the daemon, read from the store
+/// when it does not answer with one.
+pub(super) async fn today(
+    store: StorePath,
+    runtime_dir: &Path,
+    max_gap: TimeDelta,
+) -> Result<Activity> {
+    let socket =
+        xdg::runtime_dir(None::<&Path>).join(activity::CONTROL_SOCKET_NAME);
+
+    match activity::status_today(&socket, max_gap).await {
+        Ok(Some(activity)) => return Ok(activity),
+        Ok(None) => {}
+        Err(e) => log::debug!("No status from the daemon: {:#}", e),
+    }
+
+    activity::read_today(store, runtime_dir, max_gap).await
+}
+
+fn text(current: &Current) -> String {
+    let duration = |secs| format::duration(TimeDelta::seconds(secs));
+
+    match &current.app {
+        Some(app) => format!(
+            "{} for {}, today {}",
+            app,
+            duration(current.focused),
+            duration(current.today)
+        ),
+        None => format!("idle, today {}", duration(current.today)),
+    }
+}
@@ -0,0 +1,484 @@
+use crate::config::MatianeConfig;
+use anyhow::Result;
+use chrono::{NaiveDate, TimeDelta, Utc};
+use clap::{ArgMatches, Command};
+use matiane_core::events::TimedEvent;
+use matiane_core::store::{
+    LOCK_FILE_NAME, LockHolder, ReverseEventReader, StorePath, StoreReadError,
+    verify,
+};
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+pub const NAME: &str = "doctor";
+
+const SYSTEMD_UNIT: &str = "sway-matiane.service";
+const STALE_AFTER: TimeDelta = TimeDelta::minutes(5);
+
+pub fn command() -> Command {
+    Command::new(NAME).about("Diagnose common problems with the setup")
+}
+
+pub async fn run(cfg: MatianeConfig, _matches: &ArgMatches) -> Result<()> {
+    let store = cfg.general.store()?;
+    let state_dir = store.dir();
+
+    let checks = [
+        check_window_backend(),
+        check_wayland_display(),
+        check_lock(state_dir),
+        check_store_writable(state_dir),
+        check_last_event(&store).await,
+        check_store_integrity(&store).await,
+        check_systemd_unit().await,
+    ];
+
+    for check in &checks {
+        println!("{}", check);
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+
+    if failed > 0 {
+        anyhow::bail!("{} check(s) failed.", failed);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "[ OK ]"),
+            Status::Warn => write!(f, "[WARN]"),
+            Status::Fail => write!(f, "[FAIL]"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(
+        name: &'static str,
+        detail: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Check {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(
+        name: &'static str,
+        detail: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Check {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.status, self.name, self.detail)?;
+
+        if let Some(hint) = &self.hint {
+            write!(f, "\n       {}", hint)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The backend the daemon picks with `backend = "auto"`, by the same
+/// variables.
+fn check_window_backend() -> Check {
+    const NAME: &str = "window backend";
+
+    if let Some(path) = std::env::var_os("SWAYSOCK") {
+        return check_socket(
+            NAME,
+            PathBuf::from(path),
+            "SWAYSOCK is stale, re-export it from `sway --get-socketpath`.",
+        );
+    }
+    if let Some(path) = std::env::var_os("I3SOCK") {
+        return check_socket(
+            NAME,
+            PathBuf::from(path),
+            "I3SOCK is stale, re-export it from `i3 --get-socketpath`.",
+        );
+    }
+    if let Some(display) = std::env::var_os("WAYLAND_DISPLAY") {
+        return Check::ok(
+            NAME,
+            format!("wlr toplevels of {}.", display.to_string_lossy()),
+        );
+    }
+    if let Some(display) = std::env::var_os("DISPLAY") {
+        return Check::ok(
+            NAME,
+            format!("X11 display {}.", display.to_string_lossy()),
+        );
+    }
+
+    Check::warn(
+        NAME,
+        "None of SWAYSOCK, I3SOCK, WAYLAND_DISPLAY or DISPLAY is set.",
+        "Run inside the graphical session, or export the variables of it.",
+    )
+}
+
+fn check_socket(name: &'static str, path: PathBuf, hint: &str) -> Check {
+    match std::os::unix::net::UnixStream::connect(&path) {
+        Ok(_) => Check::ok(name, path.display().to_string()),
+        Err(e) => Check::fail(
+            name,
+            format!("Can not connect to {}: {}", path.display(), e),
+            hint,
+        ),
+    }
+}
+
+fn check_wayland_display() -> Check {
+    const NAME: &str = "wayland socket";
+    const HINT: &str =
+        "Idle detection needs the compositor, D-Bus is used without it.";
+
+    let display = PathBuf::from(
+        std::env::var_os("WAYLAND_DISPLAY").unwrap_or("wayland-0".into()),
+    );
+    let path = match std::env::var_os("XDG_RUNTIME_DIR") {
+        _ if display.is_absolute() => display,
+        Some(runtime_dir) => PathBuf::from(runtime_dir).join(display),
+        None => {
+            return Check::warn(NAME, "XDG_RUNTIME_DIR is not set.", HINT);
+        }
+    };
+
+    match std::os::unix::net::UnixStream::connect(&path) {
+        Ok(_) => Check::ok(NAME, path.display().to_string()),
+        Err(e) => Check::warn(
+            NAME,
+            format!("Can not connect to {}: {}", path.display(), e),
+            HINT,
+        ),
+    }
+}
+
+fn check_lock(state_dir: &Path) -> Check {
+    const NAME: &str = "daemon lock";
+    const START_HINT: &str =
+        "Start sway-matiane, e.g. `exec sway-matiane` in the sway config.";
+
+    let lock_path = state_dir.join(LOCK_FILE_NAME);
+
+    let content = match std::fs::read_to_string(&lock_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Check::warn(
+                NAME,
+                format!("{} does not exist.", lock_path.display()),
+                "sway-matiane has never run with this state dir, start it.",
+            );
+        }
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("Can not access {}: {}", lock_path.display(), e),
+                "Check permissions of the state directory.",
+            );
+        }
+    };
+
+    // The daemon writes itself into the lock file when it takes the lock.
+    match LockHolder::parse(&content) {
+        Some(holder) if holder.is_alive() => {
+            Check::ok(NAME, format!("Held by pid {}.", holder.pid))
+        }
+        Some(holder) => Check::warn(
+            NAME,
+            format!(
+                "Last held by pid {}, which stopped. sway-matiane is not \
+                 running.",
+                holder.pid
+            ),
+            START_HINT,
+        ),
+        None => Check::warn(
+            NAME,
+            "The lock file names no holder, sway-matiane is not running.",
+            START_HINT,
+        ),
+    }
+}
+
+fn check_store_writable(state_dir: &Path) -> Check {
+    const NAME: &str = "store";
+
+    match std::fs::metadata(state_dir) {
+        Ok(meta) if !meta.is_dir() => {
+            return Check::fail(
+                NAME,
+                format!("{} is not a directory.", state_dir.display()),
+                "Point general.state-dir to a directory.",
+            );
+        }
+        Ok(_) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Check::warn(
+                NAME,
+                format!("{} does not exist yet.", state_dir.display()),
+                "sway-matiane creates it on start, its parent must exist.",
+            );
+        }
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("Can not access {}: {}", state_dir.display(), e),
+                "Check permissions of the state directory.",
+            );
+        }
+    }
+
+    let probe = state_dir.join(format!(".doctor-{}", std::process::id()));
+
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Check::ok(NAME, format!("{} is writable.", state_dir.display()))
+        }
+        Err(e) => Check::fail(
+            NAME,
+            format!("{} is not writable: {}", state_dir.display(), e),
+            "Fix ownership or permissions of the state directory.",
+        ),
+    }
+}
+
+async fn check_last_event(store: &StorePath) -> Check {
+    const NAME: &str = "last event";
+
+    let last = match last_event(store).await {
+        Ok(Some(event)) => event,
+        Ok(None) => {
+            return Check::warn(
+                NAME,
+                "The store has no events.",
+                "Start sway-matiane and give it a minute.",
+            );
+        }
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("Failed to read the store: {}", e),
+                "The newest log file may be corrupt, inspect it manually.",
+            );
+        }
+    };
+
+    let age = Utc::now() - last.timestamp;
+    let detail = format!(
+        "{} ({} minutes ago).",
+        last.timestamp.to_rfc3339(),
+        age.num_minutes()
+    );
+
+    if age > STALE_AFTER {
+        Check::warn(
+            NAME,
+            detail,
+            "sway-matiane is not writing events, check that it is running.",
+        )
+    } else {
+        Check::ok(NAME, detail)
+    }
+}
+
+async fn check_store_integrity(store: &StorePath) -> Check {
+    const NAME: &str = "store integrity";
+
+    if !store.dir().is_dir() {
+        return Check::ok(NAME, "The store has no files.");
+    }
+
+    let report = match verify(store.clone()).await {
+        Ok(report) => report,
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("Failed to read the store: {}", e),
+                "Check permissions of the state directory.",
+            );
+        }
+    };
+
+    match report.problems.first() {
+        None => Check::ok(
+            NAME,
+            format!(
+                "{} events in {} files, no problems.{}",
+                report.events,
+                report.files,
+                missing_days(&report.missing_days)
+            ),
+        ),
+        Some(first) => Check::warn(
+            NAME,
+            format!("{} problem(s), first: {}", report.problems.len(), first),
+            "Reports may miss or miscount these events, fix or remove \
+             the lines by hand.",
+        ),
+    }
+}
+
+/// The days sway-matiane did not run, as runs of days.
+fn missing_days(days: &[NaiveDate]) -> String {
+    if days.is_empty() {
+        return String::new();
+    }
+
+    let runs: Vec<String> = days
+        .chunk_by(|a, b| a.succ_opt() == Some(*b))
+        .map(|run| match run {
+            [day] => day.to_string(),
+            [first, .., last] => format!("{} - {}", first, last),
+            [] => unreachable!(),
+        })
+        .collect();
+
+    format!(" No events on {} day(s): {}.", days.len(), runs.join(", "))
+}
+
+async fn check_systemd_unit() -> Check {
+    const NAME: &str = "systemd unit";
+
+    let output = tokio::process::Command::new("systemctl")
+        .args(["--user", "is-active", SYSTEMD_UNIT])
+        .output()
+        .await;
+
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            return Check::warn(
+                NAME,
+                "systemctl was not found.",
+                "Make sure sway starts sway-matiane, e.g. `exec sway-matiane`.",
+            );
+        }
+        Err(e) => {
+            return Check::warn(
+                NAME,
+                format!("Failed to run systemctl: {}", e),
+                "Make sure sway starts sway-matiane, e.g. `exec sway-matiane`.",
+            );
+        }
+    };
+
+    let state = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    match state.as_str() {
+        "" => Check::warn(
+            NAME,
+            format!("{} state is unknown.", SYSTEMD_UNIT),
+            "Is the systemd user instance running?",
+        ),
+        "active" => Check::ok(NAME, format!("{} is active.", SYSTEMD_UNIT)),
+        "failed" => Check::fail(
+            NAME,
+            format!("{} has failed.", SYSTEMD_UNIT),
+            format!("Inspect `journalctl --user -u {}`.", SYSTEMD_UNIT),
+        ),
+        _ => Check::warn(
+            NAME,
+            format!("{} is {}.", SYSTEMD_UNIT, state),
+            format!(
+                "Ignore if sway starts sway-matiane, otherwise run \
+                 `sway-matiane --install-systemd-unit` and \
+                 `systemctl --user enable --now {}`.",
+                SYSTEMD_UNIT
+            ),
+        ),
+    }
+}
+
+async fn last_event(store: &StorePath) -> Result<Option<TimedEvent>> {
+    if !store.dir().is_dir() {
+        return Ok(None);
+    }
+
+    match ReverseEventReader::open_latest(store.clone()).await {
+        Ok(mut reader) => Ok(reader.next_event().await?),
+        Err(StoreReadError::NoFilesToOpen) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_lock_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock_path = dir.path().join(LOCK_FILE_NAME);
+        assert_eq!(check_lock(dir.path()).status, Status::Warn);
+
+        let holder = LockHolder::current().unwrap();
+        std::fs::write(&lock_path, holder.to_string()).unwrap();
+        assert_eq!(check_lock(dir.path()).status, Status::Ok);
+
+        let stopped = LockHolder {
+            start_time: holder.start_time + 1,
+            ..holder
+        };
+        std::fs::write(&lock_path, stopped.to_string()).unwrap();
+        assert_eq!(check_lock(dir.path()).status, Status::Warn);
+
+        std::fs::write(&lock_path, "").unwrap();
+        assert_eq!(check_lock(dir.path()).status, Status::Warn);
+    }
+
+    #[test]
+    fn missing_days_test() {
+        let date = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+
+        assert_eq!(missing_days(&[]), "");
+        assert_eq!(
+            missing_days(&[date(2), date(3), date(4), date(9)]),
+            " No events on 4 day(s): 2026-01-02 - 2026-01-04, 2026-01-09."
+        );
+    }
+}
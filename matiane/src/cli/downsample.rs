@@ -0,0 +1,41 @@
+use crate::config::MatianeConfig;
+use anyhow::{Context, Result};
+use chrono::{Months, Utc};
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity;
+use matiane_core::store::DayZone;
+
+pub const NAME: &str = "downsample";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about(
+            "Replace old days with totals per app, with sway-matiane stopped",
+        )
+        .arg(
+            arg!(--"older-than" <MONTHS> "Downsample days older than this")
+                .value_parser(value_parser!(u32))
+                .required(true),
+        )
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let months = *matches.get_one::<u32>("older-than").unwrap();
+    let zone = DayZone::load(&cfg.general.state_dir).await?;
+    let before = zone
+        .date_of(Utc::now())
+        .checked_sub_months(Months::new(months))
+        .context("--older-than is out of range")?;
+
+    let downsampled = activity::downsample(
+        cfg.general.store()?,
+        before,
+        cfg.sway.max_gap(),
+        cfg.general.threads(),
+    )
+    .await?;
+
+    println!("Downsampled {} files.", downsampled);
+
+    Ok(())
+}
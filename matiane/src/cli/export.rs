@@ -0,0 +1,105 @@
+use super::range::{self, Range};
+use crate::config::MatianeConfig;
+use anyhow::Result;
+use clap::{ArgMatches, Command, arg, value_parser};
+use futures::StreamExt;
+use matiane_core::activity;
+use matiane_core::export::{ExportFormat, export};
+use matiane_core::store::{EventReader, StoreReadError};
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+mod activitywatch;
+mod timewarrior;
+
+pub const NAME: &str = "export";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Export tracked activity for other tools")
+        .arg(
+            arg!(<FORMAT> "Export format, events as rows for csv, json and \
+                           parquet")
+            .value_parser(formats()),
+        )
+        .args(range::args())
+        .arg(
+            arg!(-o --output <FILE> "Write to a file instead of stdout")
+                .value_parser(value_parser!(PathBuf)),
+        )
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let range = range::from_matches(matches);
+    let format = matches.get_one::<String>("FORMAT").unwrap();
+    let output = matches.get_one::<PathBuf>("output");
+
+    if let Some(format) = ExportFormat::from_name(format) {
+        return export_events(cfg, range, format, output).await;
+    }
+
+    let activity = activity::read_activity_parallel(
+        cfg.general.store()?,
+        range.from,
+        range.to,
+        cfg.sway.max_gap(),
+        cfg.general.threads(),
+    )
+    .await?;
+
+    let exported = match format.as_str() {
+        activitywatch::FORMAT => activitywatch::export(&activity, &hostname())?,
+        timewarrior::FORMAT => timewarrior::export(&activity)?,
+        _ => unreachable!("clap only accepts listed formats"),
+    };
+
+    match output {
+        Some(path) => std::fs::write(path, exported)?,
+        None => println!("{}", exported),
+    }
+
+    Ok(())
+}
+
+fn formats() -> Vec<&'static str> {
+    let mut formats = vec![activitywatch::FORMAT, timewarrior::FORMAT];
+    formats.extend(ExportFormat::ALL.iter().map(|format| format.name()));
+    formats
+}
+
+/// Every event of `range`, one per row.
+async fn export_events(
+    cfg: MatianeConfig,
+    range: Range,
+    format: ExportFormat,
+    output: Option<&PathBuf>,
+) -> Result<()> {
+    let store = cfg.general.store()?;
+    let events =
+        match EventReader::open_range(store, &range.from, range.to).await {
+            Ok(reader) => Some(reader.into_stream()),
+            Err(StoreReadError::NoFilesToOpen) => None,
+            Err(e) => return Err(e.into()),
+        };
+    let events = futures::stream::iter(events).flatten();
+
+    let written = match output {
+        Some(path) => {
+            let file = BufWriter::new(std::fs::File::create(path)?);
+            export(events, format, file).await?
+        }
+        None => {
+            export(events, format, BufWriter::new(std::io::stdout())).await?
+        }
+    };
+
+    log::info!("Exported {} events.", written);
+
+    Ok(())
+}
+
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .map(|name| name.trim().to_string())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
@@ -0,0 +1,186 @@
+//! ActivityWatch export format, as produced by `/api/0/export` and read by
+//! `/api/0/import`: one window and one afk bucket.
+
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use matiane_core::activity::Activity;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub const FORMAT: &str = "activitywatch";
+
+#[derive(Debug, Serialize)]
+struct Export {
+    buckets: BTreeMap<String, Bucket>,
+}
+
+#[derive(Debug, Serialize)]
+struct Bucket {
+    id: String,
+    created: DateTime<Utc>,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    client: &'static str,
+    hostname: String,
+    events: Vec<AwEvent>,
+}
+
+#[derive(Debug, Serialize)]
+struct AwEvent {
+    timestamp: DateTime<Utc>,
+    /// Seconds.
+    duration: f64,
+    data: EventData,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum EventData {
+    Window { app: String, title: String },
+    Afk { status: &'static str },
+}
+
+impl AwEvent {
+    fn new(start: DateTime<Utc>, end: DateTime<Utc>, data: EventData) -> Self {
+        AwEvent {
+            timestamp: start,
+            duration: seconds(end - start),
+            data,
+        }
+    }
+}
+
+pub fn export(activity: &Activity, hostname: &str) -> Result<String> {
+    let created = Utc::now();
+
+    let window = activity
+        .all_spans()
+        .map(|span| {
+            AwEvent::new(
+                span.start,
+                span.end,
+                EventData::Window {
+                    app: span.app.clone(),
+                    title: span.title.clone(),
+                },
+            )
+        })
+        .collect();
+
+    let not_afk = merge(activity.sorted_intervals())
+        .into_iter()
+        .map(|(start, end)| (start, end, "not-afk"));
+    let afk = activity
+        .away
+        .iter()
+        .map(|&(start, end)| (start, end, "afk"));
+
+    let mut afk: Vec<AwEvent> = not_afk
+        .chain(afk)
+        .map(|(start, end, status)| {
+            AwEvent::new(start, end, EventData::Afk { status })
+        })
+        .collect();
+    afk.sort_by_key(|event| event.timestamp);
+
+    let bucket = |client: &'static str, kind, events| {
+        let id = format!("{}_{}", client, hostname);
+        let bucket = Bucket {
+            id: id.clone(),
+            created,
+            kind,
+            client,
+            hostname: hostname.to_string(),
+            events,
+        };
+
+        (id, bucket)
+    };
+
+    let export = Export {
+        buckets: BTreeMap::from([
+            bucket("aw-watcher-window", "currentwindow", window),
+            bucket("aw-watcher-afk", "afkstatus", afk),
+        ]),
+    };
+
+    Ok(serde_json::to_string_pretty(&export)?)
+}
+
+/// Join sorted intervals that touch or overlap.
+fn merge(
+    intervals: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = vec![];
+
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+fn seconds(delta: TimeDelta) -> f64 {
+    delta.num_milliseconds() as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use matiane_core::activity::Span;
+
+    fn at(min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, 10, min, 0).unwrap()
+    }
+
+    fn span(app: &str, start: u32, end: u32) -> Span {
+        Span {
+            app: app.to_string(),
+            title: format!("{} title", app),
+            start: at(start),
+            end: at(end),
+        }
+    }
+
+    #[test]
+    fn export_test() {
+        let activity = Activity {
+            spans: vec![span("a", 0, 5), span("b", 5, 10)],
+            current: Some(span("a", 20, 25)),
+            away: vec![(at(10), at(20))],
+            untracked: vec![],
+        };
+
+        let exported = export(&activity, "host").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&exported).unwrap();
+
+        let window = &value["buckets"]["aw-watcher-window_host"];
+        assert_eq!(window["type"], "currentwindow");
+        assert_eq!(window["hostname"], "host");
+        assert_eq!(window["events"].as_array().unwrap().len(), 3);
+        assert_eq!(window["events"][1]["data"]["app"], "b");
+        assert_eq!(window["events"][1]["duration"], 300.0);
+
+        let afk = &value["buckets"]["aw-watcher-afk_host"]["events"];
+        let statuses: Vec<(&str, f64)> = afk
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                (
+                    e["data"]["status"].as_str().unwrap(),
+                    e["duration"].as_f64().unwrap(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            statuses,
+            vec![("not-afk", 600.0), ("afk", 600.0), ("not-afk", 300.0)]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Timewarrior interval JSON, the format of `timew export` that
+//! `timew import` reads back.
+
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use matiane_core::activity::Activity;
+use serde::{Serialize, Serializer};
+
+pub const FORMAT: &str = "timewarrior";
+
+/// Tag added to every interval, so they are easy to find in timew.
+const TAG: &str = "matiane";
+
+/// Spans of the same app closer than this become one interval.
+const JOIN_GAP: TimeDelta = TimeDelta::minutes(1);
+
+#[derive(Debug, Serialize)]
+struct Interval {
+    #[serde(serialize_with = "timew_datetime")]
+    start: DateTime<Utc>,
+    #[serde(serialize_with = "timew_datetime")]
+    end: DateTime<Utc>,
+    tags: [String; 2],
+}
+
+pub fn export(activity: &Activity) -> Result<String> {
+    let intervals: Vec<Interval> = activity
+        .blocks(JOIN_GAP)
+        .into_iter()
+        .map(|block| Interval {
+            start: block.start,
+            end: block.end,
+            tags: [block.app, TAG.to_string()],
+        })
+        .collect();
+
+    Ok(serde_json::to_string_pretty(&intervals)?)
+}
+
+fn timew_datetime<S>(dt: &DateTime<Utc>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_str(&dt.format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use matiane_core::activity::Span;
+
+    fn span(app: &str, start: u32, end: u32) -> Span {
+        let at = |min| Utc.with_ymd_and_hms(2026, 1, 1, 10, min, 0).unwrap();
+
+        Span {
+            app: app.to_string(),
+            title: String::new(),
+            start: at(start),
+            end: at(end),
+        }
+    }
+
+    #[test]
+    fn export_test() {
+        let activity = Activity {
+            spans: vec![span("a", 0, 5), span("a", 5, 10), span("b", 10, 20)],
+            current: Some(span("a", 25, 30)),
+            away: vec![],
+            untracked: vec![],
+        };
+
+        let exported = export(&activity).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&exported).unwrap();
+
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {
+                    "start": "20260101T100000Z",
+                    "end": "20260101T101000Z",
+                    "tags": ["a", "matiane"]
+                },
+                {
+                    "start": "20260101T101000Z",
+                    "end": "20260101T102000Z",
+                    "tags": ["b", "matiane"]
+                },
+                {
+                    "start": "20260101T102500Z",
+                    "end": "20260101T103000Z",
+                    "tags": ["a", "matiane"]
+                }
+            ])
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use chrono::TimeDelta;
+
+/// Short human readable duration, e.g. `1h 05m`, `12m`, `40s`.
+pub fn duration(delta: TimeDelta) -> String {
+    let secs = delta.num_seconds().max(0);
+    let (hours, mins) = (secs / 3600, secs % 3600 / 60);
+
+    if hours > 0 {
+        format!("{}h {:02}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m", mins)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Escape text for pango markup, which waybar renders, and HTML.
+pub fn markup_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            _ => escaped.push(ch),
+        }
+    }
+
+    escaped
+}
+
+/// Cut `text` to at most `max` characters, marking the cut with `…`.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+
+    let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Plain text table, the first column is left aligned, the rest right.
+pub fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> =
+        header.iter().map(|h| h.chars().count()).collect();
+
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    let mut out = String::new();
+
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, width))| {
+                if i == 0 {
+                    format!("{:<width$}", cell, width = width)
+                } else {
+                    format!("{:>width$}", cell, width = width)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Replace `{{name}}` placeholders, unknown names are an error.
+pub fn fill(template: &str, vars: &[(&str, String)]) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+
+        let Some(len) = rest[start..].find("}}") else {
+            anyhow::bail!("Unclosed {{{{ in the template");
+        };
+
+        let name = rest[start + 2..start + len].trim();
+        let Some((_, value)) = vars.iter().find(|(var, _)| *var == name) else {
+            anyhow::bail!("Unknown variable {{{{{}}}}} in the template", name);
+        };
+
+        out.push_str(value);
+        rest = &rest[start + len + 2..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_test() {
+        assert_eq!(duration(TimeDelta::seconds(0)), "0s");
+        assert_eq!(duration(TimeDelta::seconds(59)), "59s");
+        assert_eq!(duration(TimeDelta::seconds(60)), "1m");
+        assert_eq!(duration(TimeDelta::seconds(3599)), "59m");
+        assert_eq!(duration(TimeDelta::seconds(3600 + 5 * 60)), "1h 05m");
+        assert_eq!(duration(TimeDelta::hours(30)), "30h 00m");
+        assert_eq!(duration(TimeDelta::seconds(-5)), "0s");
+    }
+
+    #[test]
+    fn markup_escape_test() {
+        assert_eq!(markup_escape("plain"), "plain");
+        assert_eq!(markup_escape("<b>&</b>"), "&lt;b&gt;&amp;&lt;/b&gt;");
+    }
+
+    #[test]
+    fn truncate_test() {
+        assert_eq!(truncate("short", 5), "short");
+        assert_eq!(truncate("longer", 5), "long…");
+        assert_eq!(truncate("ქართული", 4), "ქარ…");
+    }
+
+    #[test]
+    fn table_test() {
+        let rows = vec![
+            vec!["firefox".to_string(), "1h 05m".to_string()],
+            vec!["foot".to_string(), "5m".to_string()],
+        ];
+
+        assert_eq!(
+            table(&["APP", "TIME"], &rows),
+            "APP        TIME\nfirefox  1h 05m\nfoot         5m\n"
+        );
+    }
+
+    #[test]
+    fn fill_test() {
+        let vars = [("a", "1".to_string()), ("b", "2".to_string())];
+
+        assert_eq!(fill("{{a}} and {{ b }}.", &vars).unwrap(), "1 and 2.");
+        assert_eq!(fill("no vars", &vars).unwrap(), "no vars");
+        assert!(fill("{{c}}", &vars).is_err());
+        assert!(fill("{{a", &vars).is_err());
+    }
+}
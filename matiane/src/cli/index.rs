@@ -0,0 +1,18 @@
+use crate::config::MatianeConfig;
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use matiane_core::store::build_indexes;
+
+pub const NAME: &str = "index";
+
+pub fn command() -> Command {
+    Command::new(NAME).about("Index past days so reading can start at any hour")
+}
+
+pub async fn run(cfg: MatianeConfig, _matches: &ArgMatches) -> Result<()> {
+    let built = build_indexes(&cfg.general.state_dir).await?;
+
+    println!("Indexed {} files.", built);
+
+    Ok(())
+}
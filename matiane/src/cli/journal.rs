@@ -0,0 +1,177 @@
+use super::format;
+use super::range::{self, Range};
+use crate::config::MatianeConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, TimeDelta, Utc};
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity::{self, Activity};
+use std::path::PathBuf;
+
+pub const NAME: &str = "journal";
+
+const MAX_TITLE_LEN: usize = 80;
+
+/// Built-in template. `{{name}}` is replaced by the variable `name`:
+/// `date`, `weekday`, `total`, `start`, `end`, `sessions` and `apps`.
+const TEMPLATE: &str = include_str!("journal/template.md");
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Print the sessions of a day through a template, for notes")
+        .arg(
+            arg!(--date <DAY> "Day: today, yesterday or YYYY-MM-DD")
+                .value_parser(range::parse_day)
+                .default_value("today"),
+        )
+        .arg(
+            arg!(--template <FILE> "Template [default: [journal] template]")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"min-session" <MINUTES> "Leave out shorter sessions")
+                .value_parser(value_parser!(i64).range(0..))
+                .default_value("5"),
+        )
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let day = *matches.get_one::<NaiveDate>("date").unwrap();
+    let min_session =
+        TimeDelta::minutes(*matches.get_one::<i64>("min-session").unwrap());
+
+    let template = matches
+        .get_one::<PathBuf>("template")
+        .or(cfg.journal.template.as_ref());
+    let template = match template {
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        None => TEMPLATE.to_string(),
+    };
+
+    let today = Local::now().date_naive();
+    let range = Range::days(day, (day < today).then_some(day));
+    let activity = activity::read_activity(
+        cfg.general.store()?,
+        range.from,
+        range.to,
+        cfg.sway.max_gap(),
+    )
+    .await?;
+
+    print!("{}", render(&activity, day, min_session, &template)?);
+
+    Ok(())
+}
+
+fn render(
+    activity: &Activity,
+    day: NaiveDate,
+    min_session: TimeDelta,
+    template: &str,
+) -> Result<String> {
+    // Sessions are the blocks of consecutive spans of an app.
+    let sessions: Vec<_> = activity
+        .blocks(TimeDelta::minutes(1))
+        .into_iter()
+        .filter(|block| block.duration() >= min_session)
+        .collect();
+
+    let time = |at: Option<DateTime<Utc>>| {
+        at.map(|at| at.with_timezone(&Local).format("%H:%M").to_string())
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let start = activity.all_spans().map(|span| span.start).min();
+    let end = activity.all_spans().map(|span| span.end).max();
+
+    let session_lines = sessions
+        .iter()
+        .map(|session| {
+            format!(
+                "- {}–{} {}: {} ({})",
+                time(Some(session.start)),
+                time(Some(session.end)),
+                session.app,
+                format::truncate(&session.title, MAX_TITLE_LEN),
+                format::duration(session.duration())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let app_lines = activity
+        .totals_by(|span| &span.app)
+        .into_iter()
+        .map(|(app, spent)| format!("- {}: {}", app, format::duration(spent)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let vars = [
+        ("date", day.format("%Y-%m-%d").to_string()),
+        ("weekday", day.format("%A").to_string()),
+        ("total", format::duration(activity.total())),
+        ("start", time(start)),
+        ("end", time(end)),
+        ("sessions", session_lines),
+        ("apps", app_lines),
+    ];
+
+    format::fill(template, &vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use matiane_core::activity::Span;
+
+    fn span(app: &str, title: &str, start: u32, end: u32) -> Span {
+        let at = |min: u32| {
+            Local
+                .with_ymd_and_hms(2026, 3, 2, 9, 0, 0)
+                .unwrap()
+                .to_utc()
+                + TimeDelta::minutes(min.into())
+        };
+
+        Span {
+            app: app.to_string(),
+            title: title.to_string(),
+            start: at(start),
+            end: at(end),
+        }
+    }
+
+    #[test]
+    fn render_test() {
+        let activity = Activity {
+            spans: vec![
+                span("code", "main.rs", 0, 50),
+                span("firefox", "docs", 50, 52),
+                span("code", "lib.rs", 52, 60),
+                span("foot", "cargo", 60, 90),
+            ],
+            current: None,
+            away: vec![],
+            untracked: vec![],
+        };
+        let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+        assert_eq!(
+            render(&activity, day, TimeDelta::minutes(5), TEMPLATE).unwrap(),
+            "## Time tracked\n\
+             \n\
+             **Total:** 1h 30m, 09:00 – 10:30\n\
+             \n\
+             - 09:00–09:50 code: main.rs (50m)\n\
+             - 09:52–10:00 code: lib.rs (8m)\n\
+             - 10:00–10:30 foot: cargo (30m)\n"
+        );
+
+        assert_eq!(
+            render(&activity, day, TimeDelta::zero(), "{{weekday}} {{apps}}")
+                .unwrap(),
+            "Monday - code: 58m\n- foot: 30m\n- firefox: 2m"
+        );
+        assert!(render(&activity, day, TimeDelta::zero(), "{{x}}").is_err());
+    }
+}
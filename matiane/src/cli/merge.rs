@@ -0,0 +1,55 @@
+use crate::config::MatianeConfig;
+use anyhow::{Context, Result};
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::store::{StorePath, merge};
+use std::path::PathBuf;
+
+pub const NAME: &str = "merge";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Merge the stores of several machines into one")
+        .arg(
+            arg!(<OUT> "Directory of the merged store")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(<STORE> ... "State directory as HOST=DIR, or DIR named \
+                              after its host")
+            .value_parser(parse_store),
+        )
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let out = matches.get_one::<PathBuf>("OUT").unwrap().clone();
+    let stores: Vec<(String, PathBuf)> = matches
+        .get_many::<(String, PathBuf)>("STORE")
+        .unwrap()
+        .cloned()
+        .collect();
+
+    let out = StorePath::new(out).with_key(cfg.general.load_key()?);
+    let report = merge(&stores, out).await?;
+
+    println!(
+        "Merged {} events, {} duplicates left out.",
+        report.written, report.duplicates
+    );
+
+    Ok(())
+}
+
+fn parse_store(arg: &str) -> Result<(String, PathBuf)> {
+    if let Some((host, dir)) = arg.split_once('=') {
+        return Ok((host.to_string(), dir.into()));
+    }
+
+    let dir = PathBuf::from(arg);
+    let host = dir
+        .file_name()
+        .context("name the host of the store as HOST=DIR")?
+        .to_string_lossy()
+        .into_owned();
+
+    Ok((host, dir))
+}
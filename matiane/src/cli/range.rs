@@ -0,0 +1,137 @@
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Local, NaiveDate, Utc, Weekday,
+};
+use clap::{Arg, ArgMatches, arg};
+use matiane_core::activity;
+
+/// Time range shared by the reporting commands.
+#[derive(Debug, Clone, Copy)]
+pub struct Range {
+    pub from: DateTime<FixedOffset>,
+    pub to: DateTime<Utc>,
+}
+
+const NAMED_RANGES: [&str; 6] = [
+    "today",
+    "yesterday",
+    "this-week",
+    "last-week",
+    "this-month",
+    "last-month",
+];
+
+pub fn args() -> [Arg; 3] {
+    [
+        arg!(--from <DAY> "First day: today, yesterday or YYYY-MM-DD")
+            .value_parser(parse_day)
+            .default_value("today"),
+        arg!(--to <DAY> "Last day, inclusive [default: now]")
+            .value_parser(parse_day),
+        arg!(--range <RANGE> "Named range instead of --from/--to")
+            .value_parser(NAMED_RANGES)
+            .conflicts_with_all(["from", "to"]),
+    ]
+}
+
+impl Range {
+    /// From the start of `from` to the end of `to`, or now without it.
+    pub fn days(from: NaiveDate, to: Option<NaiveDate>) -> Self {
+        let to = to
+            .map(|day| activity::start_of_day(day + Days::new(1)).to_utc())
+            .unwrap_or_else(Utc::now);
+
+        Range {
+            from: activity::start_of_day(from),
+            to,
+        }
+    }
+
+    /// One of the named ranges, e.g. `last-week`, relative to `today`.
+    pub fn named(name: &str, today: NaiveDate) -> Self {
+        let (from, to) = named_range(name, today);
+        Range::days(from, to)
+    }
+}
+
+pub fn from_matches(matches: &ArgMatches) -> Range {
+    match matches.get_one::<String>("range") {
+        Some(name) => Range::named(name, Local::now().date_naive()),
+        None => Range::days(
+            *matches.get_one::<NaiveDate>("from").unwrap(),
+            matches.get_one::<NaiveDate>("to").copied(),
+        ),
+    }
+}
+
+/// First and last (inclusive) day of the range, no last day means now.
+fn named_range(name: &str, today: NaiveDate) -> (NaiveDate, Option<NaiveDate>) {
+    let day = |n: u64| Days::new(n);
+    let monday = today.week(Weekday::Mon).first_day();
+    let first_of_month = today.with_day(1).expect("first day exists");
+
+    match name {
+        "yesterday" => (today - day(1), Some(today - day(1))),
+        "this-week" => (monday, None),
+        "last-week" => (monday - day(7), Some(monday - day(1))),
+        "this-month" => (first_of_month, None),
+        "last-month" => {
+            let last = first_of_month - day(1);
+            (last.with_day(1).expect("first day exists"), Some(last))
+        }
+        _ => (today, None),
+    }
+}
+
+/// `today`, `yesterday` or `YYYY-MM-DD`.
+pub fn parse_day(value: &str) -> Result<NaiveDate, String> {
+    let today = Local::now().date_naive();
+
+    match value {
+        "today" => Ok(today),
+        "yesterday" => Ok(today - Days::new(1)),
+        _ => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|e| format!("{} (expected YYYY-MM-DD)", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_day_test() {
+        let today = Local::now().date_naive();
+
+        assert_eq!(parse_day("today"), Ok(today));
+        assert_eq!(parse_day("yesterday"), Ok(today - Days::new(1)));
+        assert_eq!(
+            parse_day("2026-01-31"),
+            Ok(NaiveDate::from_ymd_opt(2026, 1, 31).unwrap())
+        );
+        assert!(parse_day("2026-02-31").is_err());
+        assert!(parse_day("tomorrow").is_err());
+    }
+
+    #[test]
+    fn named_range_test() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        // Wednesday.
+        let today = date(3, 4);
+
+        assert_eq!(named_range("today", today), (today, None));
+        assert_eq!(
+            named_range("yesterday", today),
+            (date(3, 3), Some(date(3, 3)))
+        );
+        assert_eq!(named_range("this-week", today), (date(3, 2), None));
+        assert_eq!(
+            named_range("last-week", today),
+            (date(2, 23), Some(date(3, 1)))
+        );
+        assert_eq!(named_range("this-month", today), (date(3, 1), None));
+        assert_eq!(
+            named_range("last-month", today),
+            (date(2, 1), Some(date(2, 28)))
+        );
+    }
+}
@@ -0,0 +1,471 @@
+use super::format;
+use super::range::Range;
+use crate::config::{GitConfig, MatianeConfig};
+use anyhow::{Context, Result};
+use chrono::{
+    DateTime, Datelike, Days, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone,
+    Utc, Weekday,
+};
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity::{self, Activity};
+use matiane_core::config::GeneralConfig;
+use std::path::PathBuf;
+
+mod email;
+mod git;
+mod markdown;
+
+pub const NAME: &str = "report";
+
+const TOP_APPS: usize = 10;
+const MAX_NAME_LEN: usize = 60;
+
+/// Away periods at least this long are listed as gaps.
+const NOTABLE_GAP: TimeDelta = TimeDelta::minutes(30);
+
+/// Coding spans of an app closer than this are one block for git.
+const CODING_GAP: TimeDelta = TimeDelta::minutes(5);
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Print or email a report of a day, week or month")
+        .arg(
+            arg!([PERIOD] "Period to report")
+                .value_parser(["day", "week", "month"])
+                .default_value("week"),
+        )
+        .arg(arg!(--previous "Report the previous, complete period"))
+        .arg(
+            arg!(--format <FORMAT> "Output format")
+                .value_parser(["text", "markdown"])
+                .default_value("text"),
+        )
+        .arg(
+            arg!(--template <FILE> "Markdown template [default: [report] template]")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--email "Send the report to [report.email] instead")
+                .conflicts_with("format"),
+        )
+        .arg(
+            arg!(--schedule "Keep running and email every period after it ends")
+                .requires("email"),
+        )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    fn parse(name: &str) -> Self {
+        match name {
+            "day" => Period::Day,
+            "month" => Period::Month,
+            _ => Period::Week,
+        }
+    }
+
+    fn range(self, previous: bool, today: NaiveDate) -> Range {
+        let name = match (self, previous) {
+            (Period::Day, false) => "today",
+            (Period::Day, true) => "yesterday",
+            (Period::Week, false) => "this-week",
+            (Period::Week, true) => "last-week",
+            (Period::Month, false) => "this-month",
+            (Period::Month, true) => "last-month",
+        };
+
+        Range::named(name, today)
+    }
+
+    fn starts_on(self, day: NaiveDate) -> bool {
+        match self {
+            Period::Day => true,
+            Period::Week => day.weekday() == Weekday::Mon,
+            Period::Month => day.day() == 1,
+        }
+    }
+
+    fn title(self, first_day: NaiveDate) -> String {
+        match self {
+            Period::Day => format!("Day {}", first_day.format("%Y-%m-%d")),
+            Period::Week => {
+                format!("Week of {}", first_day.format("%Y-%m-%d"))
+            }
+            Period::Month => format!("Month {}", first_day.format("%Y-%m")),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Report {
+    title: String,
+    total: TimeDelta,
+    /// Time with no events, not part of `total`.
+    untracked: TimeDelta,
+    days: Vec<(NaiveDate, TimeDelta)>,
+    apps: Vec<(String, TimeDelta)>,
+    /// Notable away periods.
+    gaps: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Coding time per repository and branch, with `[report.git]`.
+    repos: Vec<(String, TimeDelta)>,
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let period = Period::parse(matches.get_one::<String>("PERIOD").unwrap());
+    let previous = matches.get_flag("previous");
+    let general = cfg.general;
+    let max_gap = cfg.sway.max_gap();
+    let git = cfg.report.git;
+
+    if !matches.get_flag("email") {
+        let report =
+            build(&general, max_gap, period, previous, git.as_ref()).await?;
+
+        match matches.get_one::<String>("format").unwrap().as_str() {
+            "markdown" => {
+                let template = matches
+                    .get_one::<PathBuf>("template")
+                    .or(cfg.report.template.as_ref());
+                let template = match template {
+                    Some(path) => {
+                        std::fs::read_to_string(path).with_context(|| {
+                            format!("Failed to read {}", path.display())
+                        })?
+                    }
+                    None => markdown::TEMPLATE.to_string(),
+                };
+
+                println!(
+                    "{}",
+                    markdown::render(&report, &template)?.trim_end()
+                );
+            }
+            _ => print!("{}", text(&report)),
+        }
+
+        return Ok(());
+    }
+
+    let Some(email) = cfg.report.email else {
+        anyhow::bail!("No email configured, add a [report.email] section.");
+    };
+
+    if !matches.get_flag("schedule") {
+        let report =
+            build(&general, max_gap, period, previous, git.as_ref()).await?;
+        return email::send(
+            &email,
+            &report.title,
+            text(&report),
+            html(&report),
+        )
+        .await;
+    }
+
+    loop {
+        let at = next_send(period, email.send_at, Local::now());
+        log::info!("Next report at {}", at.with_timezone(&Local));
+
+        let wait = (at - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        let report = build(&general, max_gap, period, true, git.as_ref());
+
+        let sent = match report.await {
+            Ok(report) => {
+                let (text, html) = (text(&report), html(&report));
+                email::send(&email, &report.title, text, html).await
+            }
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = sent {
+            log::error!("Failed to send the report: {:#}", e);
+        }
+    }
+}
+
+async fn build(
+    general: &GeneralConfig,
+    max_gap: TimeDelta,
+    period: Period,
+    previous: bool,
+    git: Option<&GitConfig>,
+) -> Result<Report> {
+    let range = period.range(previous, Local::now().date_naive());
+    let activity = activity::read_activity_parallel(
+        general.store()?,
+        range.from,
+        range.to,
+        max_gap,
+        general.threads(),
+    )
+    .await?;
+
+    let mut apps = activity.totals_by(|span| &span.app);
+    apps.truncate(TOP_APPS);
+
+    let gaps = activity
+        .away
+        .iter()
+        .filter(|(start, end)| *end - *start >= NOTABLE_GAP)
+        .copied()
+        .collect();
+
+    let repos = match git {
+        Some(git) => repos(git, &activity, range.to).await,
+        None => vec![],
+    };
+
+    Ok(Report {
+        title: period.title(range.from.date_naive()),
+        total: activity.total(),
+        untracked: activity.untracked_total(),
+        days: activity.totals_by_day(&Local),
+        apps,
+        gaps,
+        repos,
+    })
+}
+
+/// Coding time of `activity` per repository and branch, longest first.
+pub(super) async fn repos(
+    config: &GitConfig,
+    activity: &Activity,
+    to: DateTime<Utc>,
+) -> Vec<(String, TimeDelta)> {
+    let mut blocks = activity.blocks(CODING_GAP);
+    blocks.retain(|block| {
+        config.apps.is_empty() || config.apps.contains(&block.app)
+    });
+
+    let (Some(first), Some(last)) = (blocks.first(), blocks.last()) else {
+        return vec![];
+    };
+
+    let window = TimeDelta::minutes(config.window as i64);
+    let commits =
+        git::commits(config, first.start, (last.end + window).min(to)).await;
+
+    git::attribute(&blocks, &commits, window)
+}
+
+/// When the report of the period that ends before it is sent: `at` on the
+/// first day of a period, after `now`.
+fn next_send<Tz: TimeZone>(
+    period: Period,
+    at: NaiveTime,
+    now: DateTime<Tz>,
+) -> DateTime<Utc> {
+    let tz = now.timezone();
+    let mut day = now.date_naive();
+
+    loop {
+        if period.starts_on(day)
+            && let Some(send) =
+                tz.from_local_datetime(&day.and_time(at)).earliest()
+            && send > now
+        {
+            return send.to_utc();
+        }
+
+        day = day + Days::new(1);
+    }
+}
+
+fn percent(spent: TimeDelta, total: TimeDelta) -> String {
+    match total.num_seconds() {
+        0 => "0%".to_string(),
+        total => format!("{}%", spent.num_seconds() * 100 / total),
+    }
+}
+
+fn day_rows(report: &Report) -> Vec<Vec<String>> {
+    report
+        .days
+        .iter()
+        .map(|(day, spent)| {
+            vec![
+                day.format("%a %Y-%m-%d").to_string(),
+                format::duration(*spent),
+            ]
+        })
+        .collect()
+}
+
+fn repo_rows(report: &Report) -> Vec<Vec<String>> {
+    report
+        .repos
+        .iter()
+        .map(|(repo, spent)| {
+            vec![
+                format::truncate(repo, MAX_NAME_LEN),
+                format::duration(*spent),
+            ]
+        })
+        .collect()
+}
+
+fn app_rows(report: &Report) -> Vec<Vec<String>> {
+    report
+        .apps
+        .iter()
+        .map(|(app, spent)| {
+            vec![
+                format::truncate(app, MAX_NAME_LEN),
+                format::duration(*spent),
+                percent(*spent, report.total),
+            ]
+        })
+        .collect()
+}
+
+fn text(report: &Report) -> String {
+    let mut text = format!(
+        "{}\n\nTotal: {}\nUntracked: {}\n\n{}\n{}",
+        report.title,
+        format::duration(report.total),
+        format::duration(report.untracked),
+        format::table(&["DAY", "TIME"], &day_rows(report)),
+        format::table(&["APP", "TIME", "%"], &app_rows(report)),
+    );
+
+    if !report.repos.is_empty() {
+        text.push('\n');
+        text.push_str(&format::table(&["REPO", "TIME"], &repo_rows(report)));
+    }
+
+    text
+}
+
+fn html_row<'a>(tag: &str, cells: impl Iterator<Item = &'a str>) -> String {
+    let cells: String = cells
+        .map(|cell| format!("<{tag}>{}</{tag}>", format::markup_escape(cell)))
+        .collect();
+
+    format!("<tr>{}</tr>\n", cells)
+}
+
+fn html_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::from("<table>\n");
+    out.push_str(&html_row("th", header.iter().copied()));
+
+    for row in rows {
+        out.push_str(&html_row("td", row.iter().map(String::as_str)));
+    }
+
+    out.push_str("</table>\n");
+    out
+}
+
+fn html(report: &Report) -> String {
+    let title = format::markup_escape(&report.title);
+    let repos = if report.repos.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<h2>Repositories</h2>\n{}",
+            html_table(&["Repository", "Time"], &repo_rows(report))
+        )
+    };
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\">\
+         <title>{title}</title></head>\n<body>\n<h1>{title}</h1>\n\
+         <p>Total: {}</p>\n<p>Untracked: {}</p>\n<h2>Days</h2>\n{}\
+         <h2>Apps</h2>\n{}{}</body>\n</html>\n",
+        format::duration(report.total),
+        format::duration(report.untracked),
+        html_table(&["Day", "Time"], &day_rows(report)),
+        html_table(&["App", "Time", "%"], &app_rows(report)),
+        repos,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn next_send_test() {
+        let tz = FixedOffset::east_opt(4 * 3600).unwrap();
+        let at = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        // Wednesday.
+        let local = |m, d, h| tz.with_ymd_and_hms(2026, m, d, h, 0, 0).unwrap();
+
+        assert_eq!(
+            next_send(Period::Day, at, local(3, 4, 8)),
+            local(3, 4, 9).to_utc()
+        );
+        assert_eq!(
+            next_send(Period::Day, at, local(3, 4, 9)),
+            local(3, 5, 9).to_utc()
+        );
+        assert_eq!(
+            next_send(Period::Week, at, local(3, 4, 8)),
+            local(3, 9, 9).to_utc()
+        );
+        assert_eq!(
+            next_send(Period::Month, at, local(3, 4, 8)),
+            local(4, 1, 9).to_utc()
+        );
+    }
+
+    #[test]
+    fn render_test() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let report = Report {
+            title: "Week of 2026-03-02".to_string(),
+            total: TimeDelta::hours(3),
+            untracked: TimeDelta::minutes(30),
+            days: vec![
+                (day(2), TimeDelta::hours(1)),
+                (day(3), TimeDelta::hours(2)),
+            ],
+            apps: vec![
+                ("firefox".to_string(), TimeDelta::hours(2)),
+                ("<foot>".to_string(), TimeDelta::hours(1)),
+            ],
+            gaps: vec![],
+            repos: vec![],
+        };
+
+        assert_eq!(
+            text(&report),
+            "Week of 2026-03-02\n\
+             \n\
+             Total: 3h 00m\n\
+             Untracked: 30m\n\
+             \n\
+             DAY               TIME\n\
+             Mon 2026-03-02  1h 00m\n\
+             Tue 2026-03-03  2h 00m\n\
+             \n\
+             APP        TIME    %\n\
+             firefox  2h 00m  66%\n\
+             <foot>   1h 00m  33%\n"
+        );
+
+        let html = html(&report);
+        assert!(html.contains("<h1>Week of 2026-03-02</h1>"));
+        assert!(html.contains("<td>&lt;foot&gt;</td><td>1h 00m</td>"));
+        assert!(!html.contains("Repositories"));
+
+        let report = Report {
+            repos: vec![("matiane (main)".to_string(), TimeDelta::hours(2))],
+            ..report
+        };
+        assert!(text(&report).ends_with(
+            "\nREPO              TIME\n\
+             matiane (main)  2h 00m\n"
+        ));
+        assert!(super::html(&report).contains("<h2>Repositories</h2>"));
+    }
+}
@@ -0,0 +1,57 @@
+use crate::config::{EmailConfig, SmtpSecurity};
+use anyhow::{Context, Result};
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+/// Send the report as a plain text and HTML alternative.
+pub async fn send(
+    config: &EmailConfig,
+    subject: &str,
+    text: String,
+    html: String,
+) -> Result<()> {
+    let from: Mailbox = config
+        .from
+        .parse()
+        .with_context(|| format!("Bad from address {:?}", config.from))?;
+    let mut builder = Message::builder()
+        .from(from)
+        .subject(format!("matiane: {}", subject));
+
+    for to in &config.to {
+        let to: Mailbox = to
+            .parse()
+            .with_context(|| format!("Bad to address {:?}", to))?;
+        builder = builder.to(to);
+    }
+
+    let message =
+        builder.multipart(MultiPart::alternative_plain_html(text, html))?;
+
+    let host = config.smtp_host.as_str();
+    let mut transport = match config.security {
+        SmtpSecurity::Starttls => {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+        }
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        SmtpSecurity::None => {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+        }
+    };
+
+    if let Some(port) = config.smtp_port {
+        transport = transport.port(port);
+    }
+
+    if let Some(username) = &config.username {
+        let password = config.password.clone().unwrap_or_default();
+        transport =
+            transport.credentials(Credentials::new(username.clone(), password));
+    }
+
+    transport.build().send(message).await?;
+    log::info!("Sent {} to {}", subject, config.to.join(", "));
+
+    Ok(())
+}
@@ -0,0 +1,199 @@
+//! Attributes coding time to repositories and branches by the commits made
+//! during or shortly after it.
+
+use crate::config::GitConfig;
+use anyhow::{Result, bail};
+use chrono::{DateTime, TimeDelta, Utc};
+use matiane_core::activity::Span;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    /// Repository and branch, e.g. `matiane (main)`.
+    pub repo: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Commits of the configured repositories in `[from, to)`, repositories
+/// that can not be read are skipped with a warning.
+pub async fn commits(
+    config: &GitConfig,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<Commit> {
+    let mut commits = vec![];
+
+    for repo in &config.repos {
+        match repo_commits(repo, from, to).await {
+            Ok(repo_commits) => commits.extend(repo_commits),
+            Err(e) => log::warn!("Skipping {}: {:#}", repo.display(), e),
+        }
+    }
+
+    commits.sort_by_key(|commit| commit.at);
+    commits
+}
+
+async fn repo_commits(
+    repo: &Path,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Commit>> {
+    let name = repo
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| repo.display().to_string());
+
+    // %S is the ref a commit was reached from, the branch it was made on
+    // for most of them.
+    let output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["log", "--all", "--source", "--format=%ct%x09%S"])
+        .arg(format!("--since=@{}", from.timestamp()))
+        .arg(format!("--until=@{}", to.timestamp()))
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(parse_log(&name, &String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_log(name: &str, log: &str) -> Vec<Commit> {
+    log.lines()
+        .filter_map(|line| {
+            let (time, source) = line.split_once('\t')?;
+            let at = DateTime::from_timestamp(time.parse().ok()?, 0)?;
+            let branch = source
+                .strip_prefix("refs/heads/")
+                .or_else(|| source.strip_prefix("refs/remotes/"))
+                .unwrap_or(source);
+
+            Some(Commit {
+                repo: format!("{} ({})", name, branch),
+                at,
+            })
+        })
+        .collect()
+}
+
+/// Time per repository, longest first. Each commit counts for the last of
+/// the sorted `blocks` that started before it, if it was made at most
+/// `window` after that block ended. A block goes to the repository with the
+/// most commits, blocks without commits are left out.
+pub fn attribute(
+    blocks: &[Span],
+    commits: &[Commit],
+    window: TimeDelta,
+) -> Vec<(String, TimeDelta)> {
+    let mut counts: Vec<HashMap<&str, usize>> =
+        vec![HashMap::new(); blocks.len()];
+
+    for commit in commits {
+        let i = blocks.partition_point(|block| block.start <= commit.at);
+
+        if i > 0 && commit.at <= blocks[i - 1].end + window {
+            *counts[i - 1].entry(&commit.repo).or_default() += 1;
+        }
+    }
+
+    let mut totals: HashMap<&str, TimeDelta> = HashMap::new();
+
+    for (block, counts) in blocks.iter().zip(counts) {
+        let repo = counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)));
+
+        if let Some((repo, _)) = repo {
+            *totals.entry(repo).or_default() += block.duration();
+        }
+    }
+
+    let mut totals: Vec<(String, TimeDelta)> = totals
+        .into_iter()
+        .map(|(repo, spent)| (repo.to_string(), spent))
+        .collect();
+
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap()
+            + TimeDelta::minutes(min.into())
+    }
+
+    fn commit(repo: &str, min: u32) -> Commit {
+        Commit {
+            repo: repo.to_string(),
+            at: at(min),
+        }
+    }
+
+    fn block(start: u32, end: u32) -> Span {
+        Span {
+            app: "code".to_string(),
+            title: String::new(),
+            start: at(start),
+            end: at(end),
+        }
+    }
+
+    #[test]
+    fn parse_log_test() {
+        let log = "1767261600\trefs/heads/main\n\
+                   1767261000\trefs/remotes/origin/fix\n\
+                   garbage\n";
+
+        assert_eq!(
+            parse_log("matiane", log),
+            vec![
+                Commit {
+                    repo: "matiane (main)".to_string(),
+                    at: at(0),
+                },
+                Commit {
+                    repo: "matiane (origin/fix)".to_string(),
+                    at: at(0) - TimeDelta::minutes(10),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn attribute_test() {
+        let commits = [
+            commit("a (main)", 20),
+            commit("b (main)", 25),
+            commit("b (main)", 70),
+            commit("b (main)", 80),
+            commit("a (main)", 150),
+        ];
+        let blocks = [block(0, 30), block(60, 90), block(100, 110)];
+
+        assert_eq!(
+            attribute(&blocks, &commits, TimeDelta::minutes(30)),
+            vec![
+                ("a (main)".to_string(), TimeDelta::minutes(30)),
+                ("b (main)".to_string(), TimeDelta::minutes(30)),
+            ]
+        );
+
+        assert_eq!(
+            attribute(&blocks, &commits, TimeDelta::minutes(60)),
+            vec![
+                ("a (main)".to_string(), TimeDelta::minutes(40)),
+                ("b (main)".to_string(), TimeDelta::minutes(30)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use super::{Report, app_rows, day_rows, repo_rows};
+use crate::cli::format;
+use anyhow::Result;
+use chrono::Local;
+
+/// Built-in template. `{{name}}` is replaced by the variable `name`:
+/// `title`, `total`, `untracked`, `days`, `apps`, `gaps` and `repos`, a
+/// repositories section that is empty without `[report.git]`.
+pub const TEMPLATE: &str = include_str!("template.md");
+
+pub fn render(report: &Report, template: &str) -> Result<String> {
+    let vars = [
+        ("title", report.title.clone()),
+        ("total", format::duration(report.total)),
+        ("untracked", format::duration(report.untracked)),
+        ("days", table(&["Day", "Time"], &day_rows(report))),
+        ("apps", table(&["App", "Time", "%"], &app_rows(report))),
+        ("gaps", gaps(report)),
+        ("repos", repos(report)),
+    ];
+
+    format::fill(template, &vars)
+}
+
+fn escape(cell: &str) -> String {
+    cell.replace('|', "\\|")
+}
+
+/// Markdown table, the first column is left aligned, the rest right.
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let row = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let align = (0..header.len())
+        .map(|i| if i == 0 { ":--".into() } else { "--:".into() })
+        .collect();
+
+    let mut out = row(header.iter().map(|h| escape(h)).collect());
+    out.push_str(&row(align));
+
+    for cells in rows {
+        out.push_str(&row(cells.iter().map(|c| escape(c)).collect()));
+    }
+
+    out.pop();
+    out
+}
+
+fn repos(report: &Report) -> String {
+    if report.repos.is_empty() {
+        return String::new();
+    }
+
+    format!(
+        "## Repositories\n\n{}",
+        table(&["Repository", "Time"], &repo_rows(report))
+    )
+}
+
+fn gaps(report: &Report) -> String {
+    if report.gaps.is_empty() {
+        return "_None_".to_string();
+    }
+
+    report
+        .gaps
+        .iter()
+        .map(|(start, end)| {
+            let (start, end) =
+                (start.with_timezone(&Local), end.with_timezone(&Local));
+            let end_format = if start.date_naive() == end.date_naive() {
+                "%H:%M"
+            } else {
+                "%a %Y-%m-%d %H:%M"
+            };
+
+            format!(
+                "- {} – {} ({})",
+                start.format("%a %Y-%m-%d %H:%M"),
+                end.format(end_format),
+                format::duration(end - start),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeDelta, TimeZone, Utc};
+
+    #[test]
+    fn render_default_template() {
+        let at = |h| Utc.with_ymd_and_hms(2026, 3, 2, h, 0, 0).unwrap();
+        let report = Report {
+            title: "Day 2026-03-02".to_string(),
+            total: TimeDelta::hours(1),
+            untracked: TimeDelta::zero(),
+            days: vec![(
+                NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(),
+                TimeDelta::hours(1),
+            )],
+            apps: vec![("firefox".to_string(), TimeDelta::hours(1))],
+            gaps: vec![(at(12), at(13))],
+            repos: vec![],
+        };
+
+        let rendered = render(&report, TEMPLATE).unwrap();
+
+        assert!(rendered.starts_with(
+            "# Day 2026-03-02\n\n**Total:** 1h 00m\n\n\
+             **Untracked:** 0s\n"
+        ));
+        assert!(rendered.contains("| firefox | 1h 00m | 100% |"));
+        assert!(rendered.contains("## Notable gaps\n\n- "));
+        assert!(rendered.trim_end().ends_with("(1h 00m)"));
+    }
+
+    #[test]
+    fn table_test() {
+        let rows = vec![vec!["a|b".to_string(), "1m".to_string()]];
+
+        assert_eq!(
+            table(&["App", "Time"], &rows),
+            "| App | Time |\n| :-- | --: |\n| a\\|b | 1m |"
+        );
+    }
+}
@@ -0,0 +1,604 @@
+use super::proto::{
+    self, matiane_server::Matiane, matiane_server::MatianeServer,
+};
+use crate::config::{MatianeConfig, ServeConfig};
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity;
+use matiane_core::events::{
+    Annotation, Backfilled, Event, Focused, Present, Process, Started,
+    StorageDegraded, TimedEvent, TimerEnded, TimerStarted, Unknown,
+    WorkspaceFocused,
+};
+use matiane_core::store::{self, EventReader, StorePath, StoreReadError};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+use tokio::sync::{Mutex, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::{Identity, ServerTlsConfig};
+use tonic::{Request, Response, Status};
+
+pub const NAME: &str = "serve";
+
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+const CHANNEL_SIZE: usize = 64;
+
+/// Pushed events are stored in `<state_dir>/hosts/<host>`.
+const HOSTS_DIR: &str = "hosts";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Serve the store over gRPC, see matiane/proto/matiane.proto")
+        .arg(
+            arg!(--grpc <ADDR> "Address to listen on")
+                .value_parser(value_parser!(SocketAddr))
+                .default_value("127.0.0.1:7479"),
+        )
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let addr = *matches.get_one::<SocketAddr>("grpc").unwrap();
+    let serve = cfg.serve;
+
+    for host in serve.hosts.keys() {
+        if !valid_host(host) {
+            anyhow::bail!("Invalid host name in [serve.hosts]: {:?}", host);
+        }
+    }
+
+    let mut builder = tonic::transport::Server::builder();
+
+    if let Some(tls) = tls_config(&serve)? {
+        builder = builder.tls_config(tls)?;
+    }
+
+    let service = Service {
+        store: cfg.general.store()?,
+        max_gap: cfg.sway.max_gap(),
+        hosts: serve.hosts,
+        read_tokens: serve.read_tokens,
+        push_lock: Mutex::new(()),
+    };
+
+    log::info!("gRPC listening on {}", addr);
+
+    builder
+        .add_service(MatianeServer::new(service))
+        .serve_with_shutdown(addr, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+
+    Ok(())
+}
+
+fn tls_config(serve: &ServeConfig) -> Result<Option<ServerTlsConfig>> {
+    let (cert, key) = match (&serve.tls_cert, &serve.tls_key) {
+        (Some(cert), Some(key)) => (cert, key),
+        (None, None) => return Ok(None),
+        _ => anyhow::bail!("Both tls-cert and tls-key must be set for TLS."),
+    };
+
+    let cert = std::fs::read(cert)
+        .with_context(|| format!("Failed to read {}", cert.display()))?;
+    let key = std::fs::read(key)
+        .with_context(|| format!("Failed to read {}", key.display()))?;
+
+    Ok(Some(
+        ServerTlsConfig::new().identity(Identity::from_pem(cert, key)),
+    ))
+}
+
+/// Host names are used as directory names.
+fn valid_host(host: &str) -> bool {
+    !host.is_empty()
+        && !host.starts_with('.')
+        && host
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "-_.".contains(ch))
+}
+
+struct Service {
+    store: StorePath,
+    max_gap: TimeDelta,
+    /// Host name to its push token.
+    hosts: BTreeMap<String, String>,
+    read_tokens: Vec<String>,
+    /// Pushes rewrite day files, one at a time.
+    push_lock: Mutex<()>,
+}
+
+impl Service {
+    /// Host of the push token of `request`.
+    fn authorize<T>(&self, request: &Request<T>) -> Result<&str, Status> {
+        let token = bearer_token(request)?;
+
+        self.hosts
+            .iter()
+            .find(|(_, host_token)| same_token(host_token, token))
+            .map(|(host, _)| host.as_str())
+            .ok_or_else(|| Status::unauthenticated("Unknown token"))
+    }
+
+    /// Reads take a read token or the push token of a host.
+    fn authorize_read<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let token = bearer_token(request)?;
+
+        match self
+            .read_tokens
+            .iter()
+            .chain(self.hosts.values())
+            .any(|known| same_token(known, token))
+        {
+            true => Ok(()),
+            false => Err(Status::unauthenticated("Unknown token")),
+        }
+    }
+}
+
+fn bearer_token<T>(request: &Request<T>) -> Result<&str, Status> {
+    request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(|| Status::unauthenticated("Missing bearer token"))
+}
+
+/// Compared in constant time, so answer times do not tell how much of a
+/// token was right.
+fn same_token(known: &str, token: &str) -> bool {
+    known.as_bytes().ct_eq(token.as_bytes()).into()
+}
+
+#[tonic::async_trait]
+impl Matiane for Service {
+    type EventsStream = ReceiverStream<Result<proto::Event, Status>>;
+    type SessionsStream =
+        tokio_stream::Iter<std::vec::IntoIter<Result<proto::Session, Status>>>;
+
+    async fn events(
+        &self,
+        request: Request<proto::EventsRequest>,
+    ) -> Result<Response<Self::EventsStream>, Status> {
+        self.authorize_read(&request)?;
+        let request = request.into_inner();
+        let from = datetime(request.from_ms)?;
+        let to = request.to_ms.map(datetime).transpose()?;
+
+        let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+        let store = self.store.clone();
+
+        tokio::spawn(async move {
+            let follow = request.follow && to.is_none();
+
+            if let Err(e) = send_events(store, from, to, follow, &tx).await {
+                let _ = tx.send(Err(Status::internal(e.to_string()))).await;
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn sessions(
+        &self,
+        request: Request<proto::SessionsRequest>,
+    ) -> Result<Response<Self::SessionsStream>, Status> {
+        self.authorize_read(&request)?;
+        let request = request.into_inner();
+        let from = datetime(request.from_ms)?;
+        let to = request.to_ms.map(datetime).transpose()?;
+
+        let activity = activity::read_activity(
+            self.store.clone(),
+            from.fixed_offset(),
+            to.unwrap_or_else(Utc::now),
+            self.max_gap,
+        )
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+        let current = activity.current.iter().map(|span| (span, true));
+        let mut sessions: Vec<_> = activity
+            .spans
+            .iter()
+            .map(|span| (span, false))
+            .chain(current)
+            .map(|(span, current)| proto::Session {
+                app: span.app.clone(),
+                title: span.title.clone(),
+                start_ms: span.start.timestamp_millis(),
+                end_ms: span.end.timestamp_millis(),
+                current,
+            })
+            .collect();
+        sessions.sort_by_key(|session| session.start_ms);
+
+        let sessions: Vec<_> = sessions.into_iter().map(Ok).collect();
+
+        Ok(Response::new(tokio_stream::iter(sessions)))
+    }
+
+    async fn push(
+        &self,
+        request: Request<proto::PushRequest>,
+    ) -> Result<Response<proto::PushReply>, Status> {
+        let host = self.authorize(&request)?.to_string();
+        let events = request
+            .into_inner()
+            .events
+            .into_iter()
+            .map(TimedEvent::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let stored = events.len() as u64;
+
+        let hosts_dir = self.store.dir().join(HOSTS_DIR);
+        let _guard = self.push_lock.lock().await;
+
+        tokio::fs::create_dir_all(&hosts_dir)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let host_store = StorePath::new(hosts_dir.join(&host))
+            .with_key(self.store.key().cloned());
+        store::insert_events(host_store, events)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        log::debug!("Stored {} events of {}", stored, host);
+
+        Ok(Response::new(proto::PushReply { stored }))
+    }
+}
+
+/// Send the stored events in `from..to`, then new ones while following.
+async fn send_events(
+    store: StorePath,
+    from: DateTime<Utc>,
+    to: Option<DateTime<Utc>>,
+    follow: bool,
+    tx: &mpsc::Sender<Result<proto::Event, Status>>,
+) -> Result<()> {
+    let in_range = |e: &TimedEvent| {
+        e.timestamp >= from && to.is_none_or(|to| e.timestamp < to)
+    };
+    let mut last_seen = from - TimeDelta::milliseconds(1);
+
+    let open_at = from.fixed_offset();
+    let opened = match to {
+        Some(to) => EventReader::open_range(store.clone(), &open_at, to).await,
+        None => EventReader::open_from(store.clone(), &open_at).await,
+    };
+
+    match opened {
+        Ok(mut reader) => {
+            while let Some(event) = reader.next_event().await? {
+                if !in_range(&event) {
+                    continue;
+                }
+
+                last_seen = event.timestamp;
+
+                if tx.send(Ok(event.into())).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Err(StoreReadError::NoFilesToOpen) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    while follow && !tx.is_closed() {
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+
+        for event in activity::events_after(store.clone(), last_seen).await? {
+            last_seen = event.timestamp;
+
+            if tx.send(Ok(event.into())).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn datetime(ms: i64) -> Result<DateTime<Utc>, Status> {
+    DateTime::from_timestamp_millis(ms)
+        .ok_or_else(|| Status::invalid_argument("Time is out of range"))
+}
+
+impl From<TimedEvent> for proto::Event {
+    fn from(event: TimedEvent) -> Self {
+        use proto::event::Kind;
+
+        let marker = proto::Marker {};
+        let kind = match event.event {
+            Event::Focused(focused) => Kind::Focused(proto::Focused {
+                title: focused.title,
+                id: focused.id,
+                pid: focused.pid,
+                process: focused.process.map(|process| proto::Process {
+                    exe: process.exe,
+                    cmdline: process.cmdline,
+                    cgroup: process.cgroup,
+                }),
+                raw_title: focused.raw_title,
+                detail: focused.detail,
+            }),
+            Event::Alive => Kind::Alive(marker),
+            Event::Sleep => Kind::Sleep(marker),
+            Event::Awake => Kind::Awake(marker),
+            Event::Idle => Kind::Idle(marker),
+            Event::Active => Kind::Active(marker),
+            Event::Backfilled(backfilled) => {
+                Kind::Backfilled(proto::Backfilled {
+                    app: backfilled.app,
+                    title: backfilled.title,
+                    source: backfilled.source,
+                    end_ms: backfilled.end.timestamp_millis(),
+                })
+            }
+            Event::Present(present) => Kind::Present(proto::Present {
+                end_ms: present.end.timestamp_millis(),
+            }),
+            Event::StorageDegraded(degraded) => {
+                Kind::StorageDegraded(proto::StorageDegraded {
+                    since_ms: degraded.since.timestamp_millis(),
+                    dropped: degraded.dropped as u64,
+                })
+            }
+            Event::WorkspaceFocused(workspace) => {
+                Kind::WorkspaceFocused(proto::WorkspaceFocused {
+                    name: workspace.name,
+                    output: workspace.output,
+                })
+            }
+            Event::OutputChanged => Kind::OutputChanged(marker),
+            Event::Annotation(annotation) => {
+                Kind::Annotation(proto::Annotation {
+                    text: annotation.text,
+                    tags: annotation.tags,
+                })
+            }
+            Event::TimerStarted(timer) => {
+                Kind::TimerStarted(proto::TimerStarted {
+                    task: timer.task,
+                    duration_secs: timer.duration,
+                })
+            }
+            Event::TimerEnded(timer) => Kind::TimerEnded(proto::TimerEnded {
+                task: timer.task,
+                completed: timer.completed,
+            }),
+            Event::TrackingPaused => Kind::TrackingPaused(marker),
+            Event::TrackingResumed => Kind::TrackingResumed(marker),
+            Event::Shutdown => Kind::Shutdown(marker),
+            Event::Started(started) => Kind::Started(proto::Started {
+                version: started.version,
+                hostname: started.hostname,
+                boot_id: started.boot_id,
+                compositor: started.compositor,
+            }),
+            Event::Unknown(unknown) => Kind::Unknown(proto::Unknown {
+                r#type: unknown.kind,
+                data: unknown.payload.map(|payload| payload.to_string()),
+            }),
+        };
+
+        proto::Event {
+            timestamp_ms: event.timestamp.timestamp_millis(),
+            kind: Some(kind),
+        }
+    }
+}
+
+impl TryFrom<proto::Event> for TimedEvent {
+    type Error = Status;
+
+    fn try_from(event: proto::Event) -> Result<Self, Status> {
+        use proto::event::Kind;
+
+        let timestamp = datetime(event.timestamp_ms)?;
+        let kind = event
+            .kind
+            .ok_or_else(|| Status::invalid_argument("Event without kind"))?;
+        let event = match kind {
+            Kind::Focused(focused) => Event::Focused(Box::new(Focused {
+                title: focused.title,
+                id: focused.id,
+                pid: focused.pid,
+                process: focused.process.map(|process| Process {
+                    exe: process.exe,
+                    cmdline: process.cmdline,
+                    cgroup: process.cgroup,
+                }),
+                raw_title: focused.raw_title,
+                detail: focused.detail,
+            })),
+            Kind::Alive(_) => Event::Alive,
+            Kind::Sleep(_) => Event::Sleep,
+            Kind::Awake(_) => Event::Awake,
+            Kind::Idle(_) => Event::Idle,
+            Kind::Active(_) => Event::Active,
+            Kind::Backfilled(backfilled) => {
+                Event::Backfilled(Box::new(Backfilled {
+                    app: backfilled.app,
+                    title: backfilled.title,
+                    source: backfilled.source,
+                    end: datetime(backfilled.end_ms)?,
+                }))
+            }
+            Kind::Present(present) => Event::Present(Present {
+                end: datetime(present.end_ms)?,
+            }),
+            Kind::StorageDegraded(degraded) => {
+                Event::StorageDegraded(StorageDegraded {
+                    since: datetime(degraded.since_ms)?,
+                    dropped: degraded.dropped as usize,
+                })
+            }
+            Kind::WorkspaceFocused(workspace) => {
+                Event::WorkspaceFocused(Box::new(WorkspaceFocused {
+                    name: workspace.name,
+                    output: workspace.output,
+                }))
+            }
+            Kind::OutputChanged(_) => Event::OutputChanged,
+            Kind::Annotation(annotation) => {
+                Event::Annotation(Box::new(Annotation {
+                    text: annotation.text,
+                    tags: annotation.tags,
+                }))
+            }
+            Kind::TimerStarted(timer) => {
+                Event::TimerStarted(Box::new(TimerStarted {
+                    task: timer.task,
+                    duration: timer.duration_secs,
+                }))
+            }
+            Kind::TimerEnded(timer) => {
+                Event::TimerEnded(Box::new(TimerEnded {
+                    task: timer.task,
+                    completed: timer.completed,
+                }))
+            }
+            Kind::TrackingPaused(_) => Event::TrackingPaused,
+            Kind::TrackingResumed(_) => Event::TrackingResumed,
+            Kind::Shutdown(_) => Event::Shutdown,
+            Kind::Started(started) => Event::Started(Box::new(Started {
+                version: started.version,
+                hostname: started.hostname,
+                boot_id: started.boot_id,
+                compositor: started.compositor,
+            })),
+            Kind::Unknown(unknown) => {
+                let payload = unknown
+                    .data
+                    .map(|data| serde_json::from_str(&data))
+                    .transpose()
+                    .map_err(|_| {
+                        Status::invalid_argument("Event data is not JSON")
+                    })?;
+
+                Event::Unknown(Box::new(Unknown {
+                    kind: unknown.r#type,
+                    payload,
+                }))
+            }
+        };
+
+        Ok(TimedEvent { timestamp, event })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn event_from_timed_event() {
+        let timestamp = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+        let event = TimedEvent {
+            timestamp,
+            event: Event::Focused(Box::new(Focused {
+                title: "matiane".to_string(),
+                id: "org.foo".to_string(),
+                pid: 42,
+                process: Some(Process {
+                    exe: Some("/usr/bin/foo".to_string()),
+                    cmdline: vec!["foo".to_string()],
+                    cgroup: None,
+                }),
+                raw_title: None,
+                detail: None,
+            })),
+        };
+
+        let event: proto::Event = event.into();
+
+        assert_eq!(event.timestamp_ms, timestamp.timestamp_millis());
+        assert_eq!(
+            event.kind,
+            Some(proto::event::Kind::Focused(proto::Focused {
+                title: "matiane".to_string(),
+                id: "org.foo".to_string(),
+                pid: 42,
+                process: Some(proto::Process {
+                    exe: Some("/usr/bin/foo".to_string()),
+                    cmdline: vec!["foo".to_string()],
+                    cgroup: None,
+                }),
+                raw_title: None,
+                detail: None,
+            }))
+        );
+
+        let event = TimedEvent::try_from(event).unwrap();
+        assert_eq!(event.timestamp, timestamp);
+        let Event::Focused(focused) = event.event else {
+            panic!("Must be a Focused event.");
+        };
+        assert_eq!(focused.pid, 42);
+        assert_eq!(focused.process.unwrap().cmdline, ["foo"]);
+
+        let empty = proto::Event {
+            timestamp_ms: 0,
+            kind: None,
+        };
+        assert!(TimedEvent::try_from(empty).is_err());
+
+        let unknown = TimedEvent {
+            timestamp,
+            event: Event::Unknown(Box::new(Unknown {
+                kind: "new".to_string(),
+                payload: Some(serde_json::json!({"a": 1})),
+            })),
+        };
+        let event: proto::Event = unknown.into();
+        assert_eq!(
+            event.kind,
+            Some(proto::event::Kind::Unknown(proto::Unknown {
+                r#type: "new".to_string(),
+                data: Some(r#"{"a":1}"#.to_string()),
+            }))
+        );
+        let event = TimedEvent::try_from(event).unwrap();
+        assert!(matches!(event.event, Event::Unknown(u) if u.kind == "new"));
+    }
+
+    #[test]
+    fn valid_host_test() {
+        assert!(valid_host("laptop-2.home"));
+        assert!(!valid_host(""));
+        assert!(!valid_host(".."));
+        assert!(!valid_host("a/b"));
+    }
+
+    #[test]
+    fn authorize_test() {
+        let service = Service {
+            store: StorePath::new("/nonexistent".into()),
+            max_gap: TimeDelta::minutes(5),
+            hosts: BTreeMap::from([("laptop".to_string(), "push".to_string())]),
+            read_tokens: vec!["read".to_string()],
+            push_lock: Mutex::new(()),
+        };
+        let request = |token: &str| {
+            let mut request = Request::new(());
+            let value = format!("Bearer {}", token).parse().unwrap();
+            request.metadata_mut().insert("authorization", value);
+            request
+        };
+
+        assert_eq!(service.authorize(&request("push")).unwrap(), "laptop");
+        assert!(service.authorize(&request("read")).is_err());
+        assert!(service.authorize_read(&request("read")).is_ok());
+        assert!(service.authorize_read(&request("push")).is_ok());
+        assert!(service.authorize_read(&request("pus")).is_err());
+        assert!(service.authorize_read(&Request::new(())).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use super::format;
+use crate::config::MatianeConfig;
+use anyhow::Result;
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity::{self, Activity};
+use matiane_core::xdg;
+use serde::Serialize;
+use std::io::Write;
+use std::time::Duration;
+
+pub const NAME: &str = "statusline";
+
+const MAX_APP_LEN: usize = 30;
+
+const AFTER_HELP: &str = "\
+i3status-rust has no click actions in the block output, set them in its
+config instead:
+
+    [[block]]
+    block = \"custom\"
+    command = \"matiane statusline --format i3status\"
+    json = true
+    interval = 10
+    [[block.click]]
+    button = \"left\"
+    cmd = \"matiane\"
+
+polybar gets the actions in the output:
+
+    [module/matiane]
+    type = custom/script
+    exec = matiane statusline --format polybar --follow
+    tail = true";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Print a status line for i3status-rust or polybar")
+        .after_help(AFTER_HELP)
+        .arg(
+            arg!(--format <FORMAT> "Status bar to print for")
+                .value_parser(["i3status", "polybar"])
+                .required(true),
+        )
+        .arg(arg!(-f --follow "Keep printing a line on every update"))
+        .arg(
+            arg!(--interval <SECONDS> "Update interval for --follow")
+                .value_parser(value_parser!(u64).range(1..))
+                .default_value("10"),
+        )
+        .arg(
+            arg!(--"on-click" <CMD> "polybar: command for a left click")
+                .default_value("matiane"),
+        )
+        .arg(
+            arg!(--"on-right-click" <CMD> "polybar: command for a right click"),
+        )
+}
+
+/// https://docs.rs/i3status-rs/latest/i3status_rs/blocks/custom/
+#[derive(Debug, Serialize)]
+struct I3Block {
+    text: String,
+    short_text: String,
+    state: &'static str,
+}
+
+struct Status {
+    /// Focused app, `None` when idle.
+    app: Option<String>,
+    total: String,
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let follow = matches.get_flag("follow");
+    let interval = *matches.get_one::<u64>("interval").unwrap();
+    let polybar = matches.get_one::<String>("format").unwrap() == "polybar";
+    let on_click = matches.get_one::<String>("on-click").unwrap();
+    let on_right_click = matches
+        .get_one::<String>("on-right-click")
+        .map(String::as_str);
+
+    let max_gap = cfg.sway.max_gap();
+    let store = cfg.general.store()?;
+    let runtime_dir = xdg::runtime_dir(Some(matiane_core::NAME));
+    let mut stdout = std::io::stdout();
+
+    loop {
+        let activity =
+            activity::read_today(store.clone(), &runtime_dir, max_gap).await?;
+        let status = status(&activity);
+
+        let line = if polybar {
+            polybar_line(&status, on_click, on_right_click)
+        } else {
+            serde_json::to_string(&i3_block(&status))?
+        };
+
+        writeln!(stdout, "{}", line)?;
+        stdout.flush()?;
+
+        if !follow {
+            return Ok(());
+        }
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+fn status(activity: &Activity) -> Status {
+    Status {
+        app: activity
+            .current
+            .as_ref()
+            .map(|current| format::truncate(&current.app, MAX_APP_LEN)),
+        total: format::duration(activity.total()),
+    }
+}
+
+fn i3_block(status: &Status) -> I3Block {
+    let (app, state) = match &status.app {
+        Some(app) => (format::markup_escape(app), "Info"),
+        None => ("idle".to_string(), "Idle"),
+    };
+
+    I3Block {
+        text: format!("{} {}", app, status.total),
+        short_text: status.total.clone(),
+        state,
+    }
+}
+
+fn polybar_line(
+    status: &Status,
+    on_click: &str,
+    on_right_click: Option<&str>,
+) -> String {
+    let text = match &status.app {
+        Some(app) => format!("{} {}", polybar_escape(app), status.total),
+        None => format!("idle {}", status.total),
+    };
+
+    let text = match on_right_click {
+        Some(command) => polybar_action(3, command, &text),
+        None => text,
+    };
+
+    polybar_action(1, on_click, &text)
+}
+
+/// Colons end the command of an action tag, so they are escaped.
+fn polybar_action(button: u8, command: &str, text: &str) -> String {
+    format!(
+        "%{{A{}:{}:}}{}%{{A}}",
+        button,
+        command.replace(':', "\\:"),
+        text
+    )
+}
+
+/// polybar has no escape for `%{`, it is broken up with a zero width space
+/// so window titles can not inject formatting tags.
+fn polybar_escape(text: &str) -> String {
+    text.replace('\n', " ").replace("%{", "%\u{200b}{")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn i3_block_test() {
+        let status = Status {
+            app: Some("<foot>".to_string()),
+            total: "1h 05m".to_string(),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&i3_block(&status)).unwrap(),
+            r#"{"text":"&lt;foot&gt; 1h 05m","short_text":"1h 05m","state":"Info"}"#
+        );
+
+        let idle = Status {
+            app: None,
+            total: "12m".to_string(),
+        };
+        assert_eq!(i3_block(&idle).text, "idle 12m");
+        assert_eq!(i3_block(&idle).state, "Idle");
+    }
+
+    #[test]
+    fn polybar_line_test() {
+        let status = Status {
+            app: Some("50%{F#f00}".to_string()),
+            total: "12m".to_string(),
+        };
+
+        assert_eq!(
+            polybar_line(&status, "matiane", None),
+            "%{A1:matiane:}50%\u{200b}{F#f00} 12m%{A}"
+        );
+        assert_eq!(
+            polybar_line(&status, "matiane", Some("xdg-open http://a:1")),
+            "%{A1:matiane:}%{A3:xdg-open http\\://a\\:1:}\
+             50%\u{200b}{F#f00} 12m%{A}%{A}"
+        );
+    }
+}
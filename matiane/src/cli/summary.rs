@@ -0,0 +1,214 @@
+use super::range::Range;
+use super::{format, range, report};
+use crate::config::MatianeConfig;
+use anyhow::{Context, Result};
+use chrono::{Local, TimeDelta};
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity;
+use matiane_core::aggregate::{self, Aggregator, Group, GroupBy};
+use matiane_core::categories::Categories;
+use serde::Serialize;
+
+pub const NAME: &str = "summary";
+
+const MAX_NAME_LEN: usize = 60;
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Print time spent, grouped by app, title, project and more")
+        .args(range::args())
+        .arg(
+            arg!(--by <GROUP> "Group time by, projects need [report.git]")
+                .value_parser([
+                    "app",
+                    "title",
+                    "category",
+                    "project",
+                    "workspace",
+                ])
+                .default_value("app"),
+        )
+        .arg(
+            arg!(--limit <N> "Show only the first N groups")
+                .value_parser(value_parser!(usize)),
+        )
+        .arg(
+            arg!(--format <FORMAT> "Output format")
+                .value_parser(["table", "json"])
+                .default_value("table"),
+        )
+}
+
+#[derive(Debug, Serialize)]
+struct Summary {
+    by: String,
+    from: String,
+    to: String,
+    total: i64,
+    untracked: i64,
+    groups: Vec<GroupTotal>,
+}
+
+/// Time in a range, grouped.
+struct Totals {
+    /// Tracked time, the sum of `groups`.
+    total: TimeDelta,
+    /// Time with no events, none of it in `groups`.
+    untracked: TimeDelta,
+    groups: Vec<(String, TimeDelta)>,
+}
+
+#[derive(Debug, Serialize)]
+struct GroupTotal {
+    name: String,
+    seconds: i64,
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let range = range::from_matches(matches);
+    let by = matches.get_one::<String>("by").unwrap();
+    let limit = matches.get_one::<usize>("limit").copied();
+    let output = matches.get_one::<String>("format").unwrap();
+
+    let Totals {
+        total,
+        untracked,
+        groups,
+    } = match GroupBy::from_name(by) {
+        Some(group_by) => totals(&cfg, &range, group_by).await?,
+        None => projects(&cfg, &range).await?,
+    };
+    let groups = groups.into_iter().take(limit.unwrap_or(usize::MAX));
+
+    if output == "json" {
+        let summary = Summary {
+            by: by.clone(),
+            from: range.from.to_rfc3339(),
+            to: range.to.to_rfc3339(),
+            total: total.num_seconds(),
+            untracked: untracked.num_seconds(),
+            groups: groups
+                .map(|(name, spent)| GroupTotal {
+                    name,
+                    seconds: spent.num_seconds(),
+                })
+                .collect(),
+        };
+
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    let percent = |spent: TimeDelta| match total.num_seconds() {
+        0 => "0%".to_string(),
+        total => format!("{}%", spent.num_seconds() * 100 / total),
+    };
+
+    let mut rows: Vec<Vec<String>> = groups
+        .map(|(name, spent)| {
+            vec![
+                format::truncate(&name, MAX_NAME_LEN),
+                format::duration(spent),
+                percent(spent),
+            ]
+        })
+        .collect();
+
+    if untracked > TimeDelta::zero() {
+        rows.push(vec![
+            "untracked".to_string(),
+            format::duration(untracked),
+            "".into(),
+        ]);
+    }
+
+    rows.push(vec![
+        "total".to_string(),
+        format::duration(total),
+        "".into(),
+    ]);
+
+    let header = by.to_uppercase();
+    print!("{}", format::table(&[&header, "TIME", "%"], &rows));
+
+    Ok(())
+}
+
+/// The totals of the sessions in `range` grouped by `by`.
+async fn totals(
+    cfg: &MatianeConfig,
+    range: &Range,
+    by: GroupBy,
+) -> Result<Totals> {
+    let categories = Categories::compile(&cfg.categories)
+        .context("Invalid [categories] rules")?;
+    let aggregator = Aggregator::new(by, Local).with_categories(categories);
+
+    let totals = aggregate::aggregate_store(
+        cfg.general.store()?,
+        range.from.to_utc(),
+        range.to,
+        cfg.sway.max_gap(),
+        aggregator,
+    )
+    .await?;
+
+    let (untracked, tracked): (Vec<_>, Vec<_>) = totals
+        .into_iter()
+        .partition(|total| total.group == Group::Untracked);
+    let groups: Vec<(String, TimeDelta)> = tracked
+        .into_iter()
+        .map(|total| (group_name(total.group), total.duration))
+        .collect();
+
+    Ok(Totals {
+        total: groups.iter().map(|(_, spent)| *spent).sum(),
+        untracked: untracked.iter().map(|total| total.duration).sum(),
+        groups,
+    })
+}
+
+fn group_name(group: Group) -> String {
+    match group {
+        Group::App(name) | Group::Title(name) => name,
+        Group::Category(category) => {
+            category.unwrap_or_else(|| "uncategorized".to_string())
+        }
+        Group::Workspace(workspace) => {
+            workspace.unwrap_or_else(|| "no workspace".to_string())
+        }
+        Group::Detail { app, detail } => match detail {
+            Some(detail) => format!("{} {}", app, detail),
+            None => app,
+        },
+        Group::Day(day) => day.to_string(),
+        Group::Weekday(weekday) => weekday.to_string(),
+        Group::Hour(hour) => hour.to_string(),
+        Group::Untracked => "untracked".to_string(),
+    }
+}
+
+/// Total time in `range` and the coding time of it attributed to the
+/// repositories and branches of `[report.git]`.
+async fn projects(cfg: &MatianeConfig, range: &Range) -> Result<Totals> {
+    let git = cfg
+        .report
+        .git
+        .as_ref()
+        .context("Grouping by project needs [report.git] repos")?;
+
+    let activity = activity::read_activity_parallel(
+        cfg.general.store()?,
+        range.from,
+        range.to,
+        cfg.sway.max_gap(),
+        cfg.general.threads(),
+    )
+    .await?;
+
+    Ok(Totals {
+        total: activity.total(),
+        untracked: activity.untracked_total(),
+        groups: report::repos(git, &activity, range.to).await,
+    })
+}
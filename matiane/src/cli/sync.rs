@@ -0,0 +1,269 @@
+use crate::config::MatianeConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Days, Local, TimeDelta, Utc};
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity::{self, Activity, Span};
+use matiane_core::store::StorePath;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+mod caldav;
+mod remote;
+mod tempo;
+mod toggl;
+
+pub const NAME: &str = "sync";
+
+/// Spans of the same app closer than this are pushed as one block.
+const JOIN_GAP: TimeDelta = TimeDelta::minutes(1);
+
+const CHECKPOINT_DIR: &str = "sync";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about(
+            "Push closed work blocks to time tracking services and new \
+             events to a remote matiane",
+        )
+        .arg(
+            arg!(--every <MINUTES> "Keep running and sync periodically")
+                .value_parser(value_parser!(u64).range(1..)),
+        )
+        .arg(arg!(-n --"dry-run" "Only print what would be pushed"))
+}
+
+/// A time tracking service work blocks are pushed to.
+pub trait Provider {
+    /// Name of the checkpoint file.
+    fn name(&self) -> &'static str;
+
+    fn push(&self, block: &Span) -> impl Future<Output = Result<()>>;
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let every = matches.get_one::<u64>("every").copied();
+    let dry_run = matches.get_flag("dry-run");
+
+    let toggl = cfg.sync.toggl.map(toggl::Toggl::new).transpose()?;
+    let caldav = cfg.sync.caldav.map(caldav::CalDav::new).transpose()?;
+    let remote = cfg.sync.remote.map(remote::Remote::new).transpose()?;
+    let tempo = cfg.sync.tempo.map(tempo::Tempo::new).transpose()?;
+
+    if toggl.is_none()
+        && caldav.is_none()
+        && remote.is_none()
+        && tempo.is_none()
+    {
+        anyhow::bail!(
+            "No sync provider configured, add a [sync.toggl], [sync.caldav], \
+             [sync.tempo] or [sync.remote] section."
+        );
+    }
+
+    let store = cfg.general.store()?;
+    let max_gap = cfg.sway.max_gap();
+
+    loop {
+        let mut failed = None;
+
+        if let Some(toggl) = &toggl {
+            failed =
+                sync(toggl, &store, max_gap, dry_run).await.err().or(failed);
+        }
+
+        if let Some(caldav) = &caldav {
+            failed = sync(caldav, &store, max_gap, dry_run)
+                .await
+                .err()
+                .or(failed);
+        }
+
+        if let Some(remote) = &remote {
+            let result = remote.sync(&store, dry_run).await;
+
+            if let Err(e) = &result {
+                log::error!("{} sync failed: {:#}", remote.name(), e);
+            }
+
+            failed = result.err().or(failed);
+        }
+
+        if let Some(tempo) = &tempo {
+            let result = tempo.sync(&store, max_gap, dry_run).await;
+
+            if let Err(e) = &result {
+                log::error!("{} sync failed: {:#}", tempo.name(), e);
+            }
+
+            failed = result.err().or(failed);
+        }
+
+        let Some(minutes) = every else {
+            return failed.map_or(Ok(()), Err);
+        };
+
+        tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+    }
+}
+
+async fn sync<P: Provider>(
+    provider: &P,
+    store: &StorePath,
+    max_gap: TimeDelta,
+    dry_run: bool,
+) -> Result<()> {
+    let result = sync_blocks(provider, store, max_gap, dry_run).await;
+
+    if let Err(e) = &result {
+        log::error!("{} sync failed: {:#}", provider.name(), e);
+    }
+
+    result
+}
+
+async fn sync_blocks<P: Provider>(
+    provider: &P,
+    store: &StorePath,
+    max_gap: TimeDelta,
+    dry_run: bool,
+) -> Result<()> {
+    let checkpoint = checkpoint_path(store.dir(), provider.name());
+    let since = match read_checkpoint(&checkpoint).await? {
+        Some(since) => since,
+        None => activity::start_of_today().to_utc(),
+    };
+
+    // Start a day earlier to know what was focused at `since`.
+    let from = activity::start_of_day(since.date_naive() - Days::new(1));
+    let now = Utc::now();
+    let activity =
+        activity::read_activity(store.clone(), from, now, max_gap).await?;
+
+    for block in closed_blocks(&activity, since, now) {
+        println!(
+            "{}: {} - {}  {}",
+            provider.name(),
+            block.start.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            block.end.with_timezone(&Local).format("%H:%M"),
+            block.app
+        );
+
+        if dry_run {
+            continue;
+        }
+
+        provider.push(&block).await?;
+        write_checkpoint(&checkpoint, block.end).await?;
+    }
+
+    Ok(())
+}
+
+/// Blocks after `since` that can not grow anymore.
+fn closed_blocks(
+    activity: &Activity,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Vec<Span> {
+    let mut blocks = activity.blocks(JOIN_GAP);
+
+    if let Some(last) = blocks.last()
+        && (activity.current.is_some() || now - last.end <= JOIN_GAP)
+    {
+        blocks.pop();
+    }
+
+    blocks
+        .into_iter()
+        .filter(|block| block.end > since)
+        .map(|mut block| {
+            block.start = block.start.max(since);
+            block
+        })
+        .collect()
+}
+
+fn checkpoint_path(state_dir: &Path, name: &str) -> PathBuf {
+    state_dir.join(CHECKPOINT_DIR).join(name)
+}
+
+async fn read_checkpoint(path: &Path) -> Result<Option<DateTime<Utc>>> {
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let checkpoint = DateTime::parse_from_rfc3339(content.trim())
+        .with_context(|| format!("Bad checkpoint in {}", path.display()))?;
+
+    Ok(Some(checkpoint.to_utc()))
+}
+
+async fn write_checkpoint(path: &Path, at: DateTime<Utc>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, at.to_rfc3339()).await?;
+    tokio::fs::rename(&tmp, path).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, 10, min, 0).unwrap()
+    }
+
+    fn span(app: &str, start: u32, end: u32) -> Span {
+        Span {
+            app: app.to_string(),
+            title: String::new(),
+            start: at(start),
+            end: at(end),
+        }
+    }
+
+    fn ranges(blocks: Vec<Span>) -> Vec<(String, u32, u32)> {
+        blocks
+            .into_iter()
+            .map(|b| {
+                let min = |t: DateTime<Utc>| (t - at(0)).num_minutes() as u32;
+                (b.app.clone(), min(b.start), min(b.end))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn closed_blocks_test() {
+        let mut activity = Activity {
+            spans: vec![span("a", 0, 5), span("a", 5, 10), span("b", 10, 20)],
+            current: Some(span("b", 21, 25)),
+            away: vec![],
+            untracked: vec![],
+        };
+
+        // b is still going on.
+        assert_eq!(
+            ranges(closed_blocks(&activity, at(0), at(25))),
+            vec![("a".to_string(), 0, 10)]
+        );
+
+        // Checkpoint in the middle of a block.
+        activity.current = None;
+        assert_eq!(
+            ranges(closed_blocks(&activity, at(7), at(30))),
+            vec![("a".to_string(), 7, 10), ("b".to_string(), 10, 20)]
+        );
+
+        // b may still be joined by the next span.
+        assert_eq!(ranges(closed_blocks(&activity, at(10), at(20))), vec![]);
+    }
+}
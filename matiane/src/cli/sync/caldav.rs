@@ -0,0 +1,154 @@
+use super::Provider;
+use crate::config::CalDavConfig;
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use matiane_core::activity::Span;
+use reqwest::header::CONTENT_TYPE;
+
+const PRODID: &str = "-//matiane//matiane//EN";
+
+/// Publishes blocks as events to CalDAV calendars. Event names are derived
+/// from the block, so publishing a block again overwrites it.
+pub struct CalDav {
+    client: reqwest::Client,
+    config: CalDavConfig,
+}
+
+impl CalDav {
+    pub fn new(config: CalDavConfig) -> Result<Self> {
+        let client =
+            reqwest::Client::builder().user_agent("matiane").build()?;
+
+        Ok(CalDav { client, config })
+    }
+
+    fn calendar(&self, app: &str) -> Option<&str> {
+        self.config
+            .calendars
+            .get(app)
+            .or(self.config.calendar.as_ref())
+            .map(String::as_str)
+    }
+}
+
+impl Provider for CalDav {
+    fn name(&self) -> &'static str {
+        "caldav"
+    }
+
+    async fn push(&self, block: &Span) -> Result<()> {
+        let min_duration = TimeDelta::seconds(self.config.min_duration as i64);
+
+        if block.duration() < min_duration {
+            log::debug!("Not publishing short block of {}", block.app);
+            return Ok(());
+        }
+
+        let Some(calendar) = self.calendar(&block.app) else {
+            log::debug!("No calendar for {}", block.app);
+            return Ok(());
+        };
+
+        let uid = uid(block);
+        let url = format!("{}/{}.ics", calendar.trim_end_matches('/'), uid);
+
+        self.client
+            .put(url)
+            .basic_auth(&self.config.username, Some(&self.config.password))
+            .header(CONTENT_TYPE, "text/calendar; charset=utf-8")
+            .body(vcalendar(&uid, block, Utc::now()))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+/// Stable id of a block, also used as the resource name.
+fn uid(block: &Span) -> String {
+    let app: String = block
+        .app
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '-' })
+        .collect();
+
+    format!("matiane-{}-{}", block.start.format("%Y%m%dT%H%M%SZ"), app)
+}
+
+fn vcalendar(uid: &str, block: &Span, now: DateTime<Utc>) -> String {
+    let lines = [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", uid),
+        format!("DTSTAMP:{}", datetime(now)),
+        format!("DTSTART:{}", datetime(block.start)),
+        format!("DTEND:{}", datetime(block.end)),
+        format!("SUMMARY:{}", escape(&block.app)),
+        "TRANSP:TRANSPARENT".to_string(),
+        "END:VEVENT".to_string(),
+        "END:VCALENDAR".to_string(),
+    ];
+
+    lines.join("\r\n") + "\r\n"
+}
+
+fn datetime(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for ch in text.chars() {
+        match ch {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(ch);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(ch),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn vcalendar_test() {
+        let at = |h| Utc.with_ymd_and_hms(2026, 1, 5, h, 0, 0).unwrap();
+        let block = Span {
+            app: "org.foo,bar".to_string(),
+            title: String::new(),
+            start: at(9),
+            end: at(10),
+        };
+
+        let uid = uid(&block);
+        assert_eq!(uid, "matiane-20260105T090000Z-org-foo-bar");
+
+        assert_eq!(
+            vcalendar(&uid, &block, at(12)),
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//matiane//matiane//EN\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:matiane-20260105T090000Z-org-foo-bar\r\n\
+             DTSTAMP:20260105T120000Z\r\n\
+             DTSTART:20260105T090000Z\r\n\
+             DTEND:20260105T100000Z\r\n\
+             SUMMARY:org.foo\\,bar\r\n\
+             TRANSP:TRANSPARENT\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n"
+        );
+    }
+}
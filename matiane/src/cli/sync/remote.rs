@@ -0,0 +1,82 @@
+use super::{checkpoint_path, read_checkpoint, write_checkpoint};
+use crate::cli::proto::{self, matiane_client::MatianeClient};
+use crate::config::RemoteConfig;
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use matiane_core::activity;
+use matiane_core::store::StorePath;
+use tonic::metadata::MetadataValue;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint};
+
+/// Events are pushed in requests of at most this many.
+const BATCH_SIZE: usize = 1000;
+
+/// Pushes new events to a central `matiane serve`, which keeps them in a
+/// store of this host.
+pub struct Remote {
+    client: MatianeClient<Channel>,
+    authorization: MetadataValue<tonic::metadata::Ascii>,
+}
+
+impl Remote {
+    pub fn new(config: RemoteConfig) -> Result<Self> {
+        let mut endpoint = Endpoint::from_shared(config.url)?;
+
+        if endpoint.uri().scheme_str() == Some("https") {
+            let mut tls = ClientTlsConfig::new().with_webpki_roots();
+
+            if let Some(path) = &config.ca_cert {
+                let pem = std::fs::read(path).with_context(|| {
+                    format!("Failed to read {}", path.display())
+                })?;
+                tls = tls.ca_certificate(Certificate::from_pem(pem));
+            }
+
+            endpoint = endpoint.tls_config(tls)?;
+        }
+
+        let authorization = format!("Bearer {}", config.token)
+            .parse()
+            .context("Invalid remote token")?;
+
+        Ok(Remote {
+            client: MatianeClient::new(endpoint.connect_lazy()),
+            authorization,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        "remote"
+    }
+
+    /// Push the events after the checkpoint, all of them on the first run.
+    pub async fn sync(&self, store: &StorePath, dry_run: bool) -> Result<()> {
+        let checkpoint = checkpoint_path(store.dir(), self.name());
+        let since = read_checkpoint(&checkpoint)
+            .await?
+            .unwrap_or(DateTime::UNIX_EPOCH);
+
+        let events = activity::events_after(store.clone(), since).await?;
+
+        println!("{}: {} new events", self.name(), events.len());
+
+        if dry_run {
+            return Ok(());
+        }
+
+        for batch in events.chunks(BATCH_SIZE) {
+            let last = batch[batch.len() - 1].timestamp;
+            let mut request = tonic::Request::new(proto::PushRequest {
+                events: batch.iter().cloned().map(Into::into).collect(),
+            });
+            request
+                .metadata_mut()
+                .insert("authorization", self.authorization.clone());
+
+            self.client.clone().push(request).await?;
+            write_checkpoint(&checkpoint, last).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+use super::{checkpoint_path, read_checkpoint, write_checkpoint};
+use crate::cli::format;
+use crate::config::TempoConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Days, Local, NaiveDate, TimeDelta, Utc};
+use matiane_core::activity::{self, Activity};
+use matiane_core::store::StorePath;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const API_URL: &str = "https://api.tempo.io/4";
+const USER_AGENT: &str = "matiane";
+
+/// Jira with Tempo, logs the daily time of every mapped issue as a worklog
+/// once the day is over.
+pub struct Tempo {
+    client: reqwest::Client,
+    config: TempoConfig,
+}
+
+/// Time spent on an issue in a day.
+#[derive(Debug, PartialEq)]
+struct IssueDay {
+    issue: String,
+    /// Start of the first span.
+    start: DateTime<Utc>,
+    spent: TimeDelta,
+    apps: Vec<String>,
+}
+
+/// https://apidocs.tempo.io/#tag/Worklogs/operation/createWorklog
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Worklog<'a> {
+    author_account_id: &'a str,
+    issue_id: u64,
+    start_date: String,
+    start_time: String,
+    time_spent_seconds: i64,
+    description: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Issue {
+    id: String,
+}
+
+impl Tempo {
+    pub fn new(config: TempoConfig) -> Result<Self> {
+        let client =
+            reqwest::Client::builder().user_agent(USER_AGENT).build()?;
+
+        Ok(Tempo { client, config })
+    }
+
+    pub fn name(&self) -> &'static str {
+        "tempo"
+    }
+
+    /// Log the days since the checkpoint, starting with today on the first
+    /// run. Today is logged tomorrow.
+    pub async fn sync(
+        &self,
+        store: &StorePath,
+        max_gap: TimeDelta,
+        dry_run: bool,
+    ) -> Result<()> {
+        let checkpoint = checkpoint_path(store.dir(), self.name());
+        let today = Local::now().date_naive();
+        let mut day = read_checkpoint(&checkpoint)
+            .await?
+            .map_or(today, |next| next.with_timezone(&Local).date_naive());
+
+        let min_duration = TimeDelta::seconds(self.config.min_duration as i64);
+
+        while day < today {
+            let next = day + Days::new(1);
+            let (from, to) = (
+                activity::start_of_day(day).to_utc(),
+                activity::start_of_day(next).to_utc(),
+            );
+
+            // Start a day earlier to know what was focused at midnight.
+            let activity = activity::read_activity(
+                store.clone(),
+                activity::start_of_day(day - Days::new(1)),
+                to,
+                max_gap,
+            )
+            .await?;
+
+            for total in issue_days(&activity, &self.config.issues, from, to) {
+                if total.spent < min_duration {
+                    continue;
+                }
+
+                println!(
+                    "{}: {}  {}  {}",
+                    self.name(),
+                    day.format("%Y-%m-%d"),
+                    total.issue,
+                    format::duration(total.spent)
+                );
+
+                if !dry_run {
+                    self.push(day, &total).await?;
+                }
+            }
+
+            if !dry_run {
+                write_checkpoint(&checkpoint, to).await?;
+            }
+
+            day = next;
+        }
+
+        Ok(())
+    }
+
+    async fn push(&self, day: NaiveDate, total: &IssueDay) -> Result<()> {
+        let worklog = Worklog {
+            author_account_id: &self.config.account_id,
+            issue_id: self.issue_id(&total.issue).await?,
+            start_date: day.format("%Y-%m-%d").to_string(),
+            start_time: total
+                .start
+                .with_timezone(&Local)
+                .format("%H:%M:%S")
+                .to_string(),
+            time_spent_seconds: total.spent.num_seconds(),
+            description: total.apps.join(", "),
+        };
+
+        self.client
+            .post(format!("{}/worklogs", API_URL))
+            .bearer_auth(&self.config.api_token)
+            .json(&worklog)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Tempo takes numeric issue ids, look the key up in Jira.
+    async fn issue_id(&self, key: &str) -> Result<u64> {
+        let url = format!(
+            "{}/rest/api/3/issue/{}?fields=id",
+            self.config.jira_url.trim_end_matches('/'),
+            key
+        );
+
+        let issue: Issue = self
+            .client
+            .get(url)
+            .basic_auth(&self.config.jira_email, Some(&self.config.jira_token))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        issue
+            .id
+            .parse()
+            .with_context(|| format!("Bad id of issue {}: {}", key, issue.id))
+    }
+}
+
+/// Time per issue in `[from, to)`, apps missing from `issues` are left out.
+fn issue_days(
+    activity: &Activity,
+    issues: &BTreeMap<String, String>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<IssueDay> {
+    let mut totals: BTreeMap<&str, IssueDay> = BTreeMap::new();
+
+    for span in activity.all_spans() {
+        let Some(issue) = issues.get(&span.app) else {
+            continue;
+        };
+
+        let (start, end) = (span.start.max(from), span.end.min(to));
+
+        if start >= end {
+            continue;
+        }
+
+        let total = totals.entry(issue).or_insert_with(|| IssueDay {
+            issue: issue.clone(),
+            start,
+            spent: TimeDelta::zero(),
+            apps: vec![],
+        });
+
+        total.start = total.start.min(start);
+        total.spent += end - start;
+
+        if !total.apps.contains(&span.app) {
+            total.apps.push(span.app.clone());
+        }
+    }
+
+    totals.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use matiane_core::activity::Span;
+
+    fn at(h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+            + TimeDelta::hours(h.into())
+    }
+
+    fn span(app: &str, start: u32, end: u32) -> Span {
+        Span {
+            app: app.to_string(),
+            title: String::new(),
+            start: at(start),
+            end: at(end),
+        }
+    }
+
+    #[test]
+    fn issue_days_test() {
+        let activity = Activity {
+            spans: vec![
+                span("code", 22, 26),
+                span("firefox", 26, 27),
+                span("foot", 28, 30),
+                span("code", 30, 31),
+            ],
+            current: None,
+            away: vec![],
+            untracked: vec![],
+        };
+        let issues = BTreeMap::from([
+            ("code".to_string(), "MAT-1".to_string()),
+            ("foot".to_string(), "MAT-1".to_string()),
+            ("firefox".to_string(), "OPS-2".to_string()),
+        ]);
+
+        assert_eq!(
+            issue_days(&activity, &issues, at(24), at(48)),
+            vec![
+                IssueDay {
+                    issue: "MAT-1".to_string(),
+                    start: at(24),
+                    spent: TimeDelta::hours(5),
+                    apps: vec!["code".to_string(), "foot".to_string()],
+                },
+                IssueDay {
+                    issue: "OPS-2".to_string(),
+                    start: at(26),
+                    spent: TimeDelta::hours(1),
+                    apps: vec!["firefox".to_string()],
+                },
+            ]
+        );
+        assert_eq!(issue_days(&activity, &issues, at(48), at(72)), vec![]);
+    }
+}
@@ -0,0 +1,79 @@
+use super::Provider;
+use crate::config::TogglConfig;
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use matiane_core::activity::Span;
+use serde::Serialize;
+
+const API_URL: &str = "https://api.track.toggl.com/api/v9";
+const CREATED_WITH: &str = "matiane";
+
+/// Toggl Track, pushes blocks as time entries of the configured workspace.
+pub struct Toggl {
+    client: reqwest::Client,
+    config: TogglConfig,
+}
+
+#[derive(Debug, Serialize)]
+struct TimeEntry<'a> {
+    created_with: &'static str,
+    description: &'a str,
+    workspace_id: u64,
+    project_id: Option<u64>,
+    start: DateTime<Utc>,
+    stop: DateTime<Utc>,
+    /// Seconds.
+    duration: i64,
+    tags: [&'static str; 1],
+}
+
+impl Toggl {
+    pub fn new(config: TogglConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .user_agent(CREATED_WITH)
+            .build()?;
+
+        Ok(Toggl { client, config })
+    }
+}
+
+impl Provider for Toggl {
+    fn name(&self) -> &'static str {
+        "toggl"
+    }
+
+    async fn push(&self, block: &Span) -> Result<()> {
+        let min_duration = TimeDelta::seconds(self.config.min_duration as i64);
+
+        if block.duration() < min_duration {
+            log::debug!("Not pushing short block of {}", block.app);
+            return Ok(());
+        }
+
+        let entry = TimeEntry {
+            created_with: CREATED_WITH,
+            description: &block.app,
+            workspace_id: self.config.workspace_id,
+            project_id: self.config.projects.get(&block.app).copied(),
+            start: block.start,
+            stop: block.end,
+            duration: block.duration().num_seconds(),
+            tags: [CREATED_WITH],
+        };
+
+        let url = format!(
+            "{}/workspaces/{}/time_entries",
+            API_URL, self.config.workspace_id
+        );
+
+        self.client
+            .post(url)
+            .basic_auth(&self.config.api_token, Some("api_token"))
+            .json(&entry)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
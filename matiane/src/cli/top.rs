@@ -0,0 +1,69 @@
+use super::{format, range};
+use crate::config::MatianeConfig;
+use anyhow::Result;
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity::{self, Span};
+
+pub const NAME: &str = "top";
+
+const MAX_NAME_LEN: usize = 80;
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("List the most used apps, or titles within an app")
+        .args(range::args())
+        .arg(arg!(--app <APP> "Only count time spent in this app"))
+        .arg(arg!(--titles "List window titles instead of apps"))
+        .arg(
+            arg!(-n --limit <N> "Number of entries")
+                .value_parser(value_parser!(usize))
+                .default_value("10"),
+        )
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let range = range::from_matches(matches);
+    let app = matches.get_one::<String>("app");
+    let titles = matches.get_flag("titles");
+    let limit = *matches.get_one::<usize>("limit").unwrap();
+
+    let mut activity = activity::read_activity_parallel(
+        cfg.general.store()?,
+        range.from,
+        range.to,
+        cfg.sway.max_gap(),
+        cfg.general.threads(),
+    )
+    .await?;
+
+    if let Some(app) = app {
+        activity.retain(|span| span.app.eq_ignore_ascii_case(app));
+    }
+
+    let (header, key): (_, fn(&Span) -> &str) = match titles {
+        true => ("TITLE", |span| &span.title),
+        false => ("APP", |span| &span.app),
+    };
+
+    let rows: Vec<Vec<String>> = activity
+        .totals_by(key)
+        .into_iter()
+        .take(limit)
+        .enumerate()
+        .map(|(i, (name, spent))| {
+            vec![
+                format!("{}. {}", i + 1, format::truncate(&name, MAX_NAME_LEN)),
+                format::duration(spent),
+            ]
+        })
+        .collect();
+
+    if rows.is_empty() {
+        println!("No activity.");
+        return Ok(());
+    }
+
+    print!("{}", format::table(&[header, "TIME"], &rows));
+
+    Ok(())
+}
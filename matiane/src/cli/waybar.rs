@@ -0,0 +1,93 @@
+use super::{current, format};
+use crate::config::MatianeConfig;
+use anyhow::Result;
+use clap::{ArgMatches, Command, arg, value_parser};
+use matiane_core::activity::Activity;
+use matiane_core::xdg;
+use serde::Serialize;
+use std::io::Write;
+use std::time::Duration;
+
+pub const NAME: &str = "waybar";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Print waybar custom module JSON")
+        .arg(arg!(-f --follow "Keep printing a line on every update"))
+        .arg(
+            arg!(--interval <SECONDS> "Update interval for --follow")
+                .value_parser(value_parser!(u64).range(1..))
+                .default_value("10"),
+        )
+        .arg(
+            arg!(--top <N> "Number of apps listed in the tooltip")
+                .value_parser(value_parser!(usize))
+                .default_value("5"),
+        )
+}
+
+/// https://github.com/Alexays/Waybar/wiki/Module:-Custom
+#[derive(Debug, Serialize)]
+struct Module {
+    text: String,
+    tooltip: String,
+    class: &'static str,
+}
+
+pub async fn run(cfg: MatianeConfig, matches: &ArgMatches) -> Result<()> {
+    let follow = matches.get_flag("follow");
+    let interval = *matches.get_one::<u64>("interval").unwrap();
+    let top = *matches.get_one::<usize>("top").unwrap();
+
+    let max_gap = cfg.sway.max_gap();
+    let store = cfg.general.store()?;
+    let runtime_dir = xdg::runtime_dir(Some(matiane_core::NAME));
+    let mut stdout = std::io::stdout();
+
+    loop {
+        let activity =
+            current::today(store.clone(), &runtime_dir, max_gap).await?;
+
+        let line = serde_json::to_string(&module(&activity, top))?;
+        writeln!(stdout, "{}", line)?;
+        stdout.flush()?;
+
+        if !follow {
+            return Ok(());
+        }
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+fn module(activity: &Activity, top: usize) -> Module {
+    let total = format::duration(activity.total());
+
+    let (text, class) = match &activity.current {
+        Some(current) => (
+            format!("{} {}", format::markup_escape(&current.app), total),
+            "active",
+        ),
+        None => (format!("idle {}", total), "idle"),
+    };
+
+    let tooltip = activity
+        .totals_by(|span| &span.app)
+        .into_iter()
+        .take(top)
+        .map(|(app, spent)| {
+            format!(
+                "{}  {}",
+                format::markup_escape(&app),
+                format::duration(spent)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Module {
+        text,
+        tooltip,
+        class,
+    }
+}
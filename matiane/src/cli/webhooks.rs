@@ -0,0 +1,305 @@
+use crate::config::{HookEvent, MatianeConfig, WebhookConfig};
+use anyhow::Result;
+use chrono::{
+    DateTime, Days, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+use clap::{ArgMatches, Command};
+use matiane_core::activity::{self, Activity};
+use matiane_core::diagnostic::render;
+use matiane_core::events::Event;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const NAME: &str = "webhooks";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+pub fn command() -> Command {
+    Command::new(NAME).about(
+        "Keep running and post JSON to webhooks on idle changes, goals \
+         and a daily summary",
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct Payload {
+    event: HookEvent,
+    timestamp: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    goal: Option<Goal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<Summary>,
+}
+
+#[derive(Debug, Serialize)]
+struct Goal {
+    app: String,
+    /// Seconds.
+    limit: i64,
+    /// Seconds spent today.
+    spent: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct Summary {
+    date: NaiveDate,
+    /// Seconds.
+    total: i64,
+    /// Seconds per app.
+    apps: BTreeMap<String, i64>,
+}
+
+impl Payload {
+    fn new(event: HookEvent, timestamp: DateTime<Utc>) -> Self {
+        Payload {
+            event,
+            timestamp,
+            goal: None,
+            summary: None,
+        }
+    }
+}
+
+pub async fn run(cfg: MatianeConfig, _matches: &ArgMatches) -> Result<()> {
+    let webhooks = cfg.webhooks;
+
+    if webhooks.hooks.is_empty() {
+        anyhow::bail!("No webhooks configured, add [[webhooks.hooks]].");
+    }
+
+    let sender = Sender {
+        client: reqwest::Client::builder().user_agent("matiane").build()?,
+        hooks: Arc::new(webhooks.hooks),
+        retries: webhooks.retries,
+    };
+
+    let store = cfg.general.store()?;
+    let max_gap = cfg.sway.max_gap();
+    let goals: Vec<(String, TimeDelta)> = webhooks
+        .goals
+        .into_iter()
+        .map(|(app, mins)| (app, TimeDelta::minutes(mins as i64)))
+        .collect();
+
+    let mut last_seen = Utc::now();
+    let mut today = Local::now().date_naive();
+    let mut exceeded: HashSet<String> = HashSet::new();
+    let mut next_summary =
+        webhooks.summary_at.map(|at| next_time(at, Local::now()));
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        match activity::events_after(store.clone(), last_seen).await {
+            Ok(events) => {
+                for event in events {
+                    last_seen = event.timestamp;
+
+                    if let Some(hook_event) = hook_event(&event.event) {
+                        sender.send(Payload::new(hook_event, event.timestamp));
+                    }
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to read new events: {}", render(&e))
+            }
+        }
+
+        let now = Local::now();
+
+        if now.date_naive() != today {
+            today = now.date_naive();
+            exceeded.clear();
+        }
+
+        let summary_due = next_summary.is_some_and(|at| now >= at);
+
+        if goals.is_empty() && !summary_due {
+            continue;
+        }
+
+        let activity = match activity::read_activity(
+            store.clone(),
+            activity::start_of_today(),
+            now.to_utc(),
+            max_gap,
+        )
+        .await
+        {
+            Ok(activity) => activity,
+            Err(e) => {
+                log::error!("Failed to read today's activity: {}", render(&e));
+                continue;
+            }
+        };
+
+        let totals: BTreeMap<String, TimeDelta> =
+            activity.totals_by(|span| &span.app).into_iter().collect();
+
+        for (app, limit) in &goals {
+            let spent = totals.get(app).copied().unwrap_or_default();
+
+            if spent > *limit && exceeded.insert(app.clone()) {
+                let mut payload =
+                    Payload::new(HookEvent::GoalExceeded, now.to_utc());
+                payload.goal = Some(Goal {
+                    app: app.clone(),
+                    limit: limit.num_seconds(),
+                    spent: spent.num_seconds(),
+                });
+                sender.send(payload);
+            }
+        }
+
+        if summary_due {
+            sender.send(summary(&activity, totals, now.to_utc()));
+            next_summary = webhooks.summary_at.map(|at| next_time(at, now));
+        }
+    }
+}
+
+fn summary(
+    activity: &Activity,
+    totals: BTreeMap<String, TimeDelta>,
+    now: DateTime<Utc>,
+) -> Payload {
+    let mut payload = Payload::new(HookEvent::DailySummary, now);
+
+    payload.summary = Some(Summary {
+        date: now.with_timezone(&Local).date_naive(),
+        total: activity.total().num_seconds(),
+        apps: totals
+            .into_iter()
+            .map(|(app, spent)| (app, spent.num_seconds()))
+            .collect(),
+    });
+
+    payload
+}
+
+fn hook_event(event: &Event) -> Option<HookEvent> {
+    match event {
+        Event::Idle => Some(HookEvent::IdleStart),
+        Event::Active => Some(HookEvent::IdleEnd),
+        Event::Sleep => Some(HookEvent::Sleep),
+        Event::Awake => Some(HookEvent::Awake),
+        _ => None,
+    }
+}
+
+/// First `at` local time after `now`.
+fn next_time<Tz: TimeZone>(at: NaiveTime, now: DateTime<Tz>) -> DateTime<Utc> {
+    let tz = now.timezone();
+    let mut day = now.date_naive();
+
+    loop {
+        if let Some(next) = tz.from_local_datetime(&day.and_time(at)).earliest()
+            && next > now
+        {
+            return next.to_utc();
+        }
+
+        day = day + Days::new(1);
+    }
+}
+
+#[derive(Clone)]
+struct Sender {
+    client: reqwest::Client,
+    hooks: Arc<Vec<WebhookConfig>>,
+    retries: u32,
+}
+
+impl Sender {
+    /// Deliver in the background to every hook subscribed to the event.
+    fn send(&self, payload: Payload) {
+        let payload = Arc::new(payload);
+
+        for (i, hook) in self.hooks.iter().enumerate() {
+            if !hook.events.contains(&payload.event) {
+                continue;
+            }
+
+            let sender = self.clone();
+            let payload = payload.clone();
+
+            tokio::spawn(async move {
+                let hook = &sender.hooks[i];
+
+                if let Err(e) = sender.deliver(hook, &payload).await {
+                    log::error!("Webhook {} failed: {:#}", hook.url, e);
+                }
+            });
+        }
+    }
+
+    async fn deliver(
+        &self,
+        hook: &WebhookConfig,
+        payload: &Payload,
+    ) -> Result<()> {
+        let mut delay = FIRST_RETRY_DELAY;
+        let mut attempt = 0;
+
+        loop {
+            let result = self
+                .client
+                .post(&hook.url)
+                .json(payload)
+                .send()
+                .await
+                .and_then(|res| res.error_for_status());
+
+            match result {
+                Ok(_) => return Ok(()),
+                Err(e) if attempt >= self.retries => return Err(e.into()),
+                Err(e) => {
+                    log::warn!("Webhook {} failed, retrying: {}", hook.url, e);
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn next_time_test() {
+        let tz = FixedOffset::east_opt(4 * 3600).unwrap();
+        let at = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+        let local = |d, h, m| tz.with_ymd_and_hms(2026, 1, d, h, m, 0).unwrap();
+
+        assert_eq!(next_time(at, local(1, 9, 0)), local(1, 18, 0).to_utc());
+        assert_eq!(next_time(at, local(1, 18, 0)), local(2, 18, 0).to_utc());
+        assert_eq!(next_time(at, local(1, 23, 0)), local(2, 18, 0).to_utc());
+    }
+
+    #[test]
+    fn payload_test() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+        let mut payload = Payload::new(HookEvent::GoalExceeded, now);
+        payload.goal = Some(Goal {
+            app: "firefox".to_string(),
+            limit: 60,
+            spent: 61,
+        });
+
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "event": "goal-exceeded",
+                "timestamp": "2026-01-01T10:00:00Z",
+                "goal": { "app": "firefox", "limit": 60, "spent": 61 }
+            })
+        );
+    }
+}
@@ -1,15 +1,279 @@
-use matiane_core::config::GeneralConfig;
-use serde::Deserialize;
+use chrono::NaiveTime;
+use matiane_core::categories::CategoriesConfig;
+use matiane_core::config::{GeneralConfig, LogConfig, SwayConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 
 #[derive(PartialEq, Debug, Deserialize, Default, Clone)]
-#[serde(rename_all = "kebab-case")]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct GuiConfig {}
 
+fn default_min_duration() -> u64 {
+    60
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TogglConfig {
+    pub api_token: String,
+    pub workspace_id: u64,
+    /// Blocks shorter than this many seconds are not pushed.
+    #[serde(default = "default_min_duration")]
+    pub min_duration: u64,
+    /// App id to Toggl project id.
+    #[serde(default)]
+    pub projects: BTreeMap<String, u64>,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CalDavConfig {
+    pub username: String,
+    pub password: String,
+    /// Collection URL for blocks of apps missing from `calendars`, those
+    /// are not published without it.
+    pub calendar: Option<String>,
+    /// Blocks shorter than this many seconds are not published.
+    #[serde(default = "default_min_duration")]
+    pub min_duration: u64,
+    /// App id to calendar collection URL.
+    #[serde(default)]
+    pub calendars: BTreeMap<String, String>,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RemoteConfig {
+    /// Address of `matiane serve`, e.g. `https://central:7479`.
+    pub url: String,
+    /// Token of this host in the server's `[serve.hosts]`.
+    pub token: String,
+    /// PEM certificate to trust besides the web PKI roots.
+    pub ca_cert: Option<PathBuf>,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TempoConfig {
+    pub api_token: String,
+    /// Jira account id the worklogs are logged for.
+    pub account_id: String,
+    /// Jira site, e.g. `https://example.atlassian.net`, to look issues up.
+    pub jira_url: String,
+    pub jira_email: String,
+    pub jira_token: String,
+    /// Daily totals shorter than this many seconds are not logged.
+    #[serde(default = "default_min_duration")]
+    pub min_duration: u64,
+    /// App id to Jira issue key, other apps are not logged.
+    pub issues: BTreeMap<String, String>,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SyncConfig {
+    pub toggl: Option<TogglConfig>,
+    pub caldav: Option<CalDavConfig>,
+    pub remote: Option<RemoteConfig>,
+    pub tempo: Option<TempoConfig>,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum SmtpSecurity {
+    #[default]
+    Starttls,
+    /// TLS from the start, usually port 465.
+    Tls,
+    /// Plain text, only for local relays.
+    None,
+}
+
+fn default_send_at() -> NaiveTime {
+    NaiveTime::from_hms_opt(9, 0, 0).expect("valid time")
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    /// Defaults to the port of `security`.
+    pub smtp_port: Option<u16>,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    /// Local time scheduled reports are sent at, on the first day of the
+    /// next period.
+    #[serde(default = "default_send_at")]
+    pub send_at: NaiveTime,
+}
+
+fn default_commit_window() -> u64 {
+    30
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct GitConfig {
+    /// Local repositories whose commits attribute coding time.
+    pub repos: Vec<PathBuf>,
+    /// App ids of editors and terminals counted as coding, all apps when
+    /// empty.
+    #[serde(default)]
+    pub apps: Vec<String>,
+    /// Minutes after a coding block its commits may still be made in.
+    #[serde(default = "default_commit_window")]
+    pub window: u64,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ReportConfig {
+    pub email: Option<EmailConfig>,
+    /// Markdown template used instead of the built-in one.
+    pub template: Option<PathBuf>,
+    pub git: Option<GitConfig>,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct JournalConfig {
+    /// Template used instead of the built-in one.
+    pub template: Option<PathBuf>,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Default, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ServeConfig {
+    /// Host name to the token it pushes events with. Pushes are refused
+    /// when empty.
+    #[serde(default)]
+    pub hosts: BTreeMap<String, String>,
+    /// Tokens that read events and sessions, besides the tokens of `hosts`.
+    /// Reads are refused without any.
+    #[serde(default)]
+    pub read_tokens: Vec<String>,
+    /// PEM certificate and key, TLS is used when both are set.
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+}
+
+fn default_retries() -> u32 {
+    5
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEvent {
+    IdleStart,
+    IdleEnd,
+    Sleep,
+    Awake,
+    GoalExceeded,
+    DailySummary,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub url: String,
+    pub events: Vec<HookEvent>,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct WebhooksConfig {
+    #[serde(default)]
+    pub hooks: Vec<WebhookConfig>,
+    /// App id to the minutes per day after which `goal-exceeded` fires.
+    #[serde(default)]
+    pub goals: BTreeMap<String, u64>,
+    /// Local time of the `daily-summary`.
+    pub summary_at: Option<NaiveTime>,
+    /// Attempts after a failed delivery, with exponential backoff.
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        WebhooksConfig {
+            hooks: vec![],
+            goals: BTreeMap::new(),
+            summary_at: None,
+            retries: default_retries(),
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Deserialize, Default, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct MatianeConfig {
     #[serde(default)]
     pub general: GeneralConfig,
+    /// Only the live interval, the gap events are tracked within.
+    #[serde(default)]
+    pub sway: SwayConfig,
     #[serde(default)]
     pub gui: GuiConfig,
+    #[serde(default)]
+    pub sync: SyncConfig,
+    #[serde(default)]
+    pub report: ReportConfig,
+    #[serde(default)]
+    pub journal: JournalConfig,
+    #[serde(default)]
+    pub serve: ServeConfig,
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    #[serde(default)]
+    pub log: LogConfig,
+    #[serde(default)]
+    pub categories: CategoriesConfig,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use matiane_core::config::DEFAULT_CONFIG;
+
+    /// The default config with every setting uncommented.
+    fn uncommented_default() -> String {
+        DEFAULT_CONFIG
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(setting)
+                    if setting.starts_with('[')
+                        || setting.split_once(" = ").is_some_and(
+                            |(key, _)| {
+                                key.chars()
+                                    .all(|c| c.is_ascii_lowercase() || c == '-')
+                            },
+                        ) =>
+                {
+                    setting
+                }
+                _ => line,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn default_config_test() {
+        let config: MatianeConfig = toml::from_str(DEFAULT_CONFIG).unwrap();
+        assert_eq!(config, MatianeConfig::default());
+
+        let config: MatianeConfig =
+            toml::from_str(&uncommented_default()).unwrap();
+        assert!(config.sync.toggl.is_some());
+        assert!(config.sync.tempo.is_some());
+        assert!(config.report.git.is_some());
+        assert_eq!(config.webhooks.hooks.len(), 1);
+        assert_eq!(config.categories.rules.len(), 1);
+    }
 }
@@ -0,0 +1,134 @@
+//! Single GUI instance, later launches ask the running one to show its
+//! window over a unix socket.
+
+use futures::Stream;
+use futures::channel::mpsc;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::time::Duration;
+
+pub const SOCKET_NAME: &str = "gui.sock";
+
+const ACTIVATE: &str = "activate";
+
+pub enum Instance {
+    /// No other instance runs, this one listens for activations.
+    Primary(Activations),
+    /// Another instance runs and was asked to show its window.
+    Running,
+}
+
+/// Become the GUI instance listening on `path`, or activate the one that
+/// already does.
+pub fn acquire(path: &Path) -> io::Result<Instance> {
+    match UnixStream::connect(path) {
+        Ok(mut stream) => {
+            writeln!(stream, "{}", ACTIVATE)?;
+            return Ok(Instance::Running);
+        }
+        Err(e)
+            if matches!(
+                e.kind(),
+                ErrorKind::NotFound | ErrorKind::ConnectionRefused
+            ) => {}
+        Err(e) => return Err(e),
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Left behind by an instance that did not exit cleanly.
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+
+    Ok(Instance::Primary(Activations(UnixListener::bind(path)?)))
+}
+
+pub struct Activations(UnixListener);
+
+impl Activations {
+    /// Yields every time another launch asks for the window.
+    pub fn into_stream(self) -> impl Stream<Item = ()> + Send + 'static {
+        let (sender, receiver) = mpsc::unbounded();
+
+        std::thread::spawn(move || {
+            for stream in self.0.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+
+                if is_activation(stream) && sender.unbounded_send(()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        receiver
+    }
+}
+
+fn is_activation(stream: UnixStream) -> bool {
+    if stream
+        .set_read_timeout(Some(Duration::from_secs(1)))
+        .is_err()
+    {
+        return false;
+    }
+
+    let mut line = String::new();
+
+    match BufReader::new(stream).read_line(&mut line) {
+        Ok(_) => line.trim_end() == ACTIVATE,
+        Err(e) => {
+            log::debug!("Failed to read an activation: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn acquire_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run").join(SOCKET_NAME);
+
+        let Instance::Primary(activations) = acquire(&path).unwrap() else {
+            panic!("expected the primary instance");
+        };
+        let mut activations = activations.into_stream();
+
+        assert!(matches!(acquire(&path).unwrap(), Instance::Running));
+        tokio::time::timeout(Duration::from_secs(5), activations.next())
+            .await
+            .unwrap()
+            .unwrap();
+
+        // Unrelated connections are no activations.
+        UnixStream::connect(&path).unwrap();
+        let next = tokio::time::timeout(
+            Duration::from_millis(100),
+            activations.next(),
+        )
+        .await;
+        assert!(next.is_err());
+    }
+
+    #[test]
+    fn acquire_stale_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SOCKET_NAME);
+
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        assert!(matches!(acquire(&path).unwrap(), Instance::Primary(_)));
+    }
+}
@@ -1,41 +1,68 @@
+use clap::command;
+use instance::Instance;
 use matiane_core::args;
 use matiane_core::config::load as load_config;
+use matiane_core::diagnostic::render;
 use matiane_core::log::init_global_logger;
 use matiane_core::xdg::Xdg;
+use std::process::ExitCode;
 
 mod app;
+mod cli;
 mod config;
 mod icon;
+mod instance;
 mod screen;
 
-use app::App;
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", render(&e));
+            ExitCode::FAILURE
+        }
+    }
+}
 
-fn main() -> anyhow::Result<()> {
+fn run() -> anyhow::Result<()> {
     let xdg = Xdg::new(matiane_core::NAME.into());
 
     let (
-        _,
+        matches,
         args::GeneralArgs {
             config_file,
             log_level,
+            write_default_config,
+            force,
         },
-    ) = matiane_core::args::parse_args(
+    ) = args::parse_command(
         &xdg,
-        "Sway matiane gui",
-        std::iter::empty::<clap::Arg>(),
+        command!("Sway matiane gui").subcommands(cli::subcommands()),
     );
 
-    init_global_logger(log_level)?;
+    if write_default_config {
+        matiane_core::config::write_default(&config_file, force)?;
+        println!("Wrote {}", config_file.display());
+        return Ok(());
+    }
 
     let cfg = load_config::<config::MatianeConfig>(&config_file)?;
+    let log_options =
+        cfg.log.options(&cfg.general.state_dir, matiane_core::NAME);
 
-    let app_init = move || App::new(cfg.clone());
+    init_global_logger(log_level, log_options)?;
+    cfg.general.load_key()?;
 
-    iced::application(app_init, App::update, App::view)
-        .title(App::title)
-        .theme(App::theme)
-        .font(icon::FONT)
-        .run()?;
+    let socket = xdg.runtime_dir().join(instance::SOCKET_NAME);
 
-    Ok(())
+    match matches.subcommand() {
+        Some((name, sub_matches)) => cli::run(cfg, name, sub_matches),
+        None => match instance::acquire(&socket)? {
+            Instance::Primary(activations) => Ok(app::run(cfg, activations)?),
+            Instance::Running => {
+                log::info!("Matiane is already running, showing its window.");
+                Ok(())
+            }
+        },
+    }
 }
@@ -1,5 +1,12 @@
-use matiane_core::config::GeneralConfig;
+use crate::detail::DetailRule;
+use crate::exclude::ExcludeRule;
+use crate::normalize::NormalizeConfig;
+use crate::redact::RedactRule;
+use crate::timer::TimerConfig;
+use matiane_core::config::{GeneralConfig, LogConfig};
+use matiane_core::store::{Durability, StoreFormat};
 use serde::{Deserialize, Deserializer};
+use std::path::PathBuf;
 use std::time::Duration;
 
 const LIVE_INTERVAL: Duration = Duration::from_secs(60);
@@ -12,7 +19,9 @@ fn default_idle_timeout() -> u32 {
     60
 }
 
-fn deserialize_interval<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+pub(crate) fn deserialize_interval<'de, D>(
+    deserializer: D,
+) -> Result<Duration, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -20,8 +29,84 @@ where
     Ok(Duration::from_secs(secs))
 }
 
-#[derive(PartialEq, Debug, Deserialize)]
+/// Where focused windows come from.
+#[derive(PartialEq, Debug, Deserialize, Default, Clone, Copy)]
 #[serde(rename_all = "kebab-case")]
+pub enum WindowBackend {
+    /// The backend of the session the daemon runs in.
+    #[default]
+    Auto,
+    Sway,
+    /// The IPC of i3, which sway's is derived from.
+    I3,
+    /// `wlr-foreign-toplevel-management` of Wayland compositors, like river,
+    /// labwc or niri.
+    Wlr,
+    /// EWMH properties of X11 window managers, like i3.
+    X11,
+}
+
+impl WindowBackend {
+    /// The backend, detected by the sockets of the session when it is auto.
+    pub fn resolve(self, is_set: impl Fn(&str) -> bool) -> Self {
+        if self != WindowBackend::Auto {
+            return self;
+        }
+
+        if is_set("SWAYSOCK") {
+            WindowBackend::Sway
+        } else if is_set("I3SOCK") {
+            WindowBackend::I3
+        } else if is_set("WAYLAND_DISPLAY") {
+            WindowBackend::Wlr
+        } else if is_set("DISPLAY") {
+            WindowBackend::X11
+        } else {
+            WindowBackend::Sway
+        }
+    }
+
+    /// Name of the backend in the started event.
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowBackend::Auto | WindowBackend::Sway => "sway",
+            WindowBackend::I3 => "i3",
+            WindowBackend::Wlr => "wlr",
+            WindowBackend::X11 => "x11",
+        }
+    }
+}
+
+/// Where idle, lock and sleep events come from.
+#[derive(PartialEq, Debug, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdleBackend {
+    /// `ext-idle-notify` of the Wayland compositor, with sleep and locking
+    /// from logind. Without it, the D-Bus screensaver is used.
+    #[default]
+    #[serde(alias = "swayidle")]
+    Wayland,
+    /// freedesktop and GNOME screensaver D-Bus interfaces, with sleep from
+    /// logind.
+    Dbus,
+    /// Idle time of the MIT-SCREEN-SAVER extension of the X server, with
+    /// sleep and locking from logind.
+    X11,
+}
+
+impl IdleBackend {
+    /// The backend used with windows of `backend`. i3 has no Wayland
+    /// compositor, so it polls the idle time of X11 instead.
+    pub fn resolve(self, backend: WindowBackend) -> Self {
+        match (self, backend) {
+            (IdleBackend::Wayland, WindowBackend::I3) => IdleBackend::X11,
+            _ => self,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct SwayMatianeConfig {
     #[serde(
         default = "default_live_interval",
@@ -29,15 +114,134 @@ pub struct SwayMatianeConfig {
     )]
     pub live_interval: Duration,
 
+    #[serde(default)]
+    pub backend: WindowBackend,
+
     #[serde(default = "default_idle_timeout")]
     pub idle_timeout: u32,
+
+    #[serde(default)]
+    pub idle_backend: IdleBackend,
+
+    /// Read the executable, command line and cgroup of focused windows
+    /// from `/proc`.
+    #[serde(default)]
+    pub process_info: bool,
+
+    /// Accept commands on `matiane.sock` in the runtime dir.
+    #[serde(default = "default_true")]
+    pub control_socket: bool,
+
+    /// Cleanup of window titles before they are recorded.
+    #[serde(default)]
+    pub normalize: NormalizeConfig,
+
+    /// Rules reading the site or document of windows from their titles,
+    /// after they are normalized.
+    #[serde(default)]
+    pub detail: Vec<DetailRule>,
+
+    /// Rules redacting window titles before they are recorded, after they
+    /// are normalized.
+    #[serde(default)]
+    pub redact: Vec<RedactRule>,
+
+    /// The timer clicking the tray icon starts.
+    #[serde(default)]
+    pub timer: TimerConfig,
 }
 
 impl Default for SwayMatianeConfig {
     fn default() -> Self {
         Self {
             live_interval: default_live_interval(),
+            backend: WindowBackend::default(),
             idle_timeout: default_idle_timeout(),
+            idle_backend: IdleBackend::default(),
+            process_info: false,
+            control_socket: true,
+            normalize: NormalizeConfig::default(),
+            detail: vec![],
+            redact: vec![],
+            timer: TimerConfig::default(),
+        }
+    }
+}
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+fn default_otlp_interval() -> Duration {
+    Duration::from_secs(60)
+}
+
+#[derive(PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct OtlpConfig {
+    /// OTLP gRPC endpoint of the collector.
+    #[serde(default = "default_otlp_endpoint")]
+    pub endpoint: String,
+
+    /// Seconds between metric exports.
+    #[serde(
+        default = "default_otlp_interval",
+        deserialize_with = "deserialize_interval"
+    )]
+    pub interval: Duration,
+
+    /// Also export focus sessions, these include window titles.
+    #[serde(default)]
+    pub sessions: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Where events are written to.
+#[derive(PartialEq, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SinkConfig {
+    /// The store in `state-dir`.
+    #[serde(default = "default_true")]
+    pub store: bool,
+
+    /// Every event as a JSON line on stdout.
+    #[serde(default)]
+    pub stdout: bool,
+
+    /// Every event as a JSON line to this named pipe, created if missing.
+    pub pipe: Option<PathBuf>,
+
+    /// The focused app and today's totals in `current.json` of the runtime
+    /// dir.
+    #[serde(default = "default_true")]
+    pub current: bool,
+
+    /// Compress day files of the store with zstd once they are over.
+    #[serde(default)]
+    pub compress: bool,
+
+    /// Encoding of new day files of the store.
+    #[serde(default)]
+    pub format: StoreFormat,
+
+    /// When events are flushed and synced to the store.
+    #[serde(default)]
+    pub durability: Durability,
+}
+
+impl Default for SinkConfig {
+    fn default() -> Self {
+        Self {
+            store: true,
+            stdout: false,
+            pipe: None,
+            current: true,
+            compress: false,
+            format: StoreFormat::default(),
+            durability: Durability::default(),
         }
     }
 }
@@ -49,12 +253,48 @@ pub struct SwayCliConfig {
     pub general: GeneralConfig,
     #[serde(default)]
     pub sway: SwayMatianeConfig,
+    #[serde(default)]
+    pub sink: SinkConfig,
+    /// Exporting is disabled without it.
+    pub otlp: Option<OtlpConfig>,
+    #[serde(default)]
+    pub log: LogConfig,
+    /// Windows that are not recorded, checked after their titles are
+    /// normalized.
+    #[serde(default)]
+    pub exclude: Vec<ExcludeRule>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exclude::ExcludeAction;
+    use crate::redact::RedactAction;
     use anyhow::Result;
+    use matiane_core::config::DEFAULT_CONFIG;
+    use std::num::NonZeroU32;
+
+    /// The default config with every setting uncommented.
+    fn uncommented_default() -> String {
+        DEFAULT_CONFIG
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(setting)
+                    if setting.starts_with('[')
+                        || setting.split_once(" = ").is_some_and(
+                            |(key, _)| {
+                                key.chars()
+                                    .all(|c| c.is_ascii_lowercase() || c == '-')
+                            },
+                        ) =>
+                {
+                    setting
+                }
+                _ => line,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
     #[test]
     fn deserialize_config() -> Result<()> {
@@ -75,6 +315,7 @@ mod tests {
                 config: SwayCliConfig {
                     general: GeneralConfig {
                         state_dir: "/root/state".into(),
+                        ..Default::default()
                     },
                     ..Default::default()
                 },
@@ -83,6 +324,25 @@ mod tests {
                 state-dir = "/root/state"
                 "#,
             },
+            SuccessCase {
+                config: SwayCliConfig {
+                    general: GeneralConfig {
+                        state_dir: "/root/state".into(),
+                        retention_days: NonZeroU32::new(30),
+                        archive_dir: Some("/root/archive".into()),
+                        key_file: Some("/root/store.key".into()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                raw: r#"
+                [general]
+                state-dir = "/root/state"
+                retention-days = 30
+                archive-dir = "/root/archive"
+                key-file = "/root/store.key"
+                "#,
+            },
             SuccessCase {
                 config: SwayCliConfig {
                     sway: SwayMatianeConfig {
@@ -109,15 +369,103 @@ mod tests {
                 idle-timeout = 150
                 "#,
             },
+            SuccessCase {
+                config: SwayCliConfig {
+                    sway: SwayMatianeConfig {
+                        backend: WindowBackend::X11,
+                        idle_backend: IdleBackend::X11,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                raw: r#"
+                [sway]
+                backend = "x11"
+                idle-backend = "x11"
+                "#,
+            },
+            SuccessCase {
+                config: SwayCliConfig {
+                    sway: SwayMatianeConfig {
+                        backend: WindowBackend::Wlr,
+                        idle_backend: IdleBackend::Wayland,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                raw: r#"
+                [sway]
+                backend = "wlr"
+                idle-backend = "wayland"
+                "#,
+            },
+            SuccessCase {
+                config: SwayCliConfig {
+                    sway: SwayMatianeConfig {
+                        idle_backend: IdleBackend::Wayland,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                raw: r#"
+                [sway]
+                idle-backend = "swayidle"
+                "#,
+            },
+            SuccessCase {
+                config: SwayCliConfig {
+                    sway: SwayMatianeConfig {
+                        backend: WindowBackend::I3,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                raw: r#"
+                [sway]
+                backend = "i3"
+                "#,
+            },
             SuccessCase {
                 config: SwayCliConfig {
                     general: GeneralConfig {
                         state_dir: "/root/state2".into(),
+                        ..Default::default()
                     },
                     sway: SwayMatianeConfig {
                         live_interval: Duration::from_secs(20),
+                        backend: WindowBackend::Sway,
                         idle_timeout: 21,
+                        idle_backend: IdleBackend::Dbus,
+                        process_info: true,
+                        control_socket: false,
+                        normalize: NormalizeConfig {
+                            counters: true,
+                            suffixes: vec![" - Chromium".to_string()],
+                            ..Default::default()
+                        },
+                        detail: vec![DetailRule {
+                            app_ids: vec!["chromium".to_string()],
+                            suffix: None,
+                            separators: vec![" | ".to_string()],
+                        }],
+                        redact: vec![RedactRule {
+                            app_ids: vec!["keepassxc".to_string()],
+                            title: None,
+                            action: RedactAction::Truncate(3),
+                        }],
+                        timer: TimerConfig {
+                            duration: Duration::from_secs(600),
+                            task: "review".to_string(),
+                        },
                     },
+                    sink: SinkConfig::default(),
+                    otlp: None,
+                    log: LogConfig::default(),
+                    exclude: vec![ExcludeRule {
+                        app_ids: vec![],
+                        title: Some("Bank".to_string()),
+                        action: ExcludeAction::Anonymize,
+                    }],
                 },
                 raw: r#"
                 [general]
@@ -125,7 +473,70 @@ mod tests {
 
                 [sway]
                 live-interval = 20
+                backend = "sway"
                 idle-timeout = 21
+                idle-backend = "dbus"
+                process-info = true
+                control-socket = false
+
+                [sway.normalize]
+                counters = true
+                suffixes = [" - Chromium"]
+
+                [[sway.detail]]
+                app-ids = ["chromium"]
+                separators = [" | "]
+
+                [[sway.redact]]
+                app-ids = ["keepassxc"]
+                action = { truncate = 3 }
+
+                [sway.timer]
+                duration = 600
+                task = "review"
+
+                [[exclude]]
+                title = "Bank"
+                action = "anonymize"
+                "#,
+            },
+            SuccessCase {
+                config: SwayCliConfig {
+                    otlp: Some(OtlpConfig {
+                        endpoint: default_otlp_endpoint(),
+                        interval: Duration::from_secs(30),
+                        sessions: true,
+                    }),
+                    ..Default::default()
+                },
+                raw: r#"
+                [otlp]
+                interval = 30
+                sessions = true
+                "#,
+            },
+            SuccessCase {
+                config: SwayCliConfig {
+                    sink: SinkConfig {
+                        store: false,
+                        stdout: true,
+                        pipe: Some("/run/matiane/events".into()),
+                        current: false,
+                        compress: true,
+                        format: StoreFormat::Cbor,
+                        durability: Durability::Interval(30),
+                    },
+                    ..Default::default()
+                },
+                raw: r#"
+                [sink]
+                store = false
+                stdout = true
+                pipe = "/run/matiane/events"
+                current = false
+                compress = true
+                format = "cbor"
+                durability = { interval = 30 }
                 "#,
             },
         ];
@@ -143,6 +554,63 @@ mod tests {
             assert_eq!(decoded, test.config);
         }
 
+        let err = toml::from_str::<SwayCliConfig>("[sway]\nidle-timout = 5")
+            .unwrap_err();
+        assert!(err.message().starts_with("unknown field `idle-timout`"));
+
         Ok(())
     }
+
+    #[test]
+    fn default_config_test() {
+        let config: SwayCliConfig = toml::from_str(DEFAULT_CONFIG).unwrap();
+        assert_eq!(config, SwayCliConfig::default());
+
+        let config: SwayCliConfig =
+            toml::from_str(&uncommented_default()).unwrap();
+        assert!(config.otlp.is_some());
+        assert_eq!(config.sway.redact.len(), 1);
+        assert_eq!(config.sway.normalize.suffixes.len(), 1);
+        assert_eq!(config.sway.detail.len(), 1);
+        assert_eq!(config.exclude.len(), 1);
+        assert_eq!(
+            config.sink.pipe,
+            Some("/run/user/1000/matiane-events".into())
+        );
+    }
+
+    #[test]
+    fn resolve_backend_test() {
+        let session = |vars: &'static [&'static str]| {
+            move |var: &str| vars.contains(&var)
+        };
+
+        let auto = WindowBackend::Auto;
+        assert_eq!(
+            auto.resolve(session(&["SWAYSOCK", "WAYLAND_DISPLAY"])),
+            WindowBackend::Sway
+        );
+        assert_eq!(
+            auto.resolve(session(&["WAYLAND_DISPLAY", "DISPLAY"])),
+            WindowBackend::Wlr
+        );
+        assert_eq!(
+            auto.resolve(session(&["I3SOCK", "DISPLAY"])),
+            WindowBackend::I3
+        );
+        assert_eq!(auto.resolve(session(&["DISPLAY"])), WindowBackend::X11);
+        assert_eq!(auto.resolve(session(&[])), WindowBackend::Sway);
+        assert_eq!(
+            WindowBackend::X11.resolve(session(&["SWAYSOCK"])),
+            WindowBackend::X11
+        );
+
+        let wayland = IdleBackend::Wayland;
+        assert_eq!(wayland.resolve(WindowBackend::I3), IdleBackend::X11);
+        assert_eq!(wayland.resolve(WindowBackend::Wlr), wayland);
+        assert_eq!(
+            IdleBackend::Dbus.resolve(WindowBackend::I3),
+            IdleBackend::Dbus
+        );
+    }
 }
@@ -0,0 +1,312 @@
+//! Commands of other programs, as JSON lines on a Unix socket in the
+//! runtime dir, e.g.
+//!
+//! ```sh
+//! echo '{"cmd":"status"}' | nc -U "$XDG_RUNTIME_DIR/matiane.sock"
+//! ```
+//!
+//! Every command gets one JSON line back, with `ok` and, if it failed, an
+//! `error`. Pausing is also served over D-Bus next to the tray icon:
+//!
+//! ```sh
+//! busctl --user call <tray bus name> /Tracking org.matiane.Tracking Toggle
+//! ```
+
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use matiane_core::activity::{CONTROL_SOCKET_NAME, Snapshot};
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::{JoinHandle, spawn};
+use tokio_util::sync::CancellationToken;
+use zbus::interface;
+
+pub const SOCKET_NAME: &str = CONTROL_SOCKET_NAME;
+
+/// Commands waiting for the writer.
+const COMMAND_CAPACITY: usize = 8;
+
+const STOPPING: &str = "The daemon is stopping";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Command {
+    Status,
+    /// Stop recording focused windows until resumed.
+    Pause,
+    Resume,
+    /// Pause, or resume when paused.
+    Toggle,
+    /// Record a note at the current time.
+    Annotate {
+        text: String,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    /// Sync the events recorded so far to disk.
+    Flush,
+}
+
+/// What the daemon is doing, the reply of `status`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Status {
+    pub paused: bool,
+    pub started: DateTime<Utc>,
+    /// Time of the last event recorded.
+    pub last_event: Option<DateTime<Utc>>,
+    /// The focused window, as last recorded.
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+    /// Today's activity, with `[sink] current`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub today: Option<Snapshot>,
+}
+
+impl Status {
+    pub fn new(started: DateTime<Utc>) -> Self {
+        Status {
+            paused: false,
+            started,
+            last_event: None,
+            app_id: None,
+            title: None,
+            today: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reply {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+}
+
+impl Reply {
+    pub fn ok() -> Self {
+        Reply {
+            ok: true,
+            error: None,
+            status: None,
+        }
+    }
+
+    pub fn error(error: impl ToString) -> Self {
+        Reply {
+            ok: false,
+            error: Some(error.to_string()),
+            status: None,
+        }
+    }
+
+    pub fn status(status: Status) -> Self {
+        Reply {
+            status: Some(status),
+            ..Reply::ok()
+        }
+    }
+}
+
+/// A command and where its reply goes.
+pub type Request = (Command, oneshot::Sender<Reply>);
+
+/// The channel of requests, read by the writer.
+pub fn channel() -> (mpsc::Sender<Request>, mpsc::Receiver<Request>) {
+    mpsc::channel(COMMAND_CAPACITY)
+}
+
+/// `$XDG_RUNTIME_DIR/matiane.sock`.
+pub fn socket_path() -> PathBuf {
+    matiane_core::xdg::runtime_dir(None::<&Path>).join(SOCKET_NAME)
+}
+
+/// Send `command` to the writer and wait for its reply.
+pub async fn request(
+    requests: &mpsc::Sender<Request>,
+    command: Command,
+) -> Reply {
+    let (reply, replied) = oneshot::channel();
+    if requests.send((command, reply)).await.is_err() {
+        return Reply::error(STOPPING);
+    }
+
+    replied.await.unwrap_or_else(|_| Reply::error(STOPPING))
+}
+
+/// The reply to one line of a client.
+async fn handle_line(line: &str, requests: &mpsc::Sender<Request>) -> Reply {
+    match serde_json::from_str(line) {
+        Ok(command) => request(requests, command).await,
+        Err(e) => Reply::error(format!("Bad command: {}", e)),
+    }
+}
+
+async fn serve_client(
+    stream: UnixStream,
+    requests: mpsc::Sender<Request>,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let reply = handle_line(&line, &requests).await;
+        let mut encoded = serde_json::to_vec(&reply)?;
+        encoded.push(b'\n');
+        writer.write_all(&encoded).await?;
+    }
+
+    Ok(())
+}
+
+/// Bind `path`, replacing the socket a previous run left behind.
+fn bind(path: &Path) -> std::io::Result<UnixListener> {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => std::fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(std::io::Error::new(
+                ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ));
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    UnixListener::bind(path)
+}
+
+/// Serves the clients of the socket at `path` until cancelled, then
+/// removes it.
+pub fn spawn_control(
+    path: PathBuf,
+    requests: mpsc::Sender<Request>,
+    token: CancellationToken,
+) -> std::io::Result<JoinHandle<()>> {
+    let listener = bind(&path)?;
+    debug!("Listening for commands on {}.", path.display());
+
+    Ok(spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                _ = token.cancelled() => break,
+                accepted = listener.accept() => accepted,
+            };
+
+            match stream {
+                Ok((stream, _)) => {
+                    let requests = requests.clone();
+                    spawn(async move {
+                        if let Err(e) = serve_client(stream, requests).await {
+                            debug!("Control client failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => warn!("Failed to accept a control client: {}", e),
+            }
+        }
+
+        let _ = std::fs::remove_file(&path);
+    }))
+}
+
+/// Pausing for other programs, served next to the tray icon.
+pub struct TrackingInterface {
+    pub requests: mpsc::Sender<Request>,
+}
+
+#[interface(name = "org.matiane.Tracking")]
+impl TrackingInterface {
+    async fn pause(&self) -> zbus::fdo::Result<()> {
+        self.send(Command::Pause).await
+    }
+
+    async fn resume(&self) -> zbus::fdo::Result<()> {
+        self.send(Command::Resume).await
+    }
+
+    async fn toggle(&self) -> zbus::fdo::Result<()> {
+        self.send(Command::Toggle).await
+    }
+}
+
+impl TrackingInterface {
+    async fn send(&self, command: Command) -> zbus::fdo::Result<()> {
+        match request(&self.requests, command).await {
+            Reply { ok: true, .. } => Ok(()),
+            Reply { error, .. } => {
+                Err(zbus::fdo::Error::Failed(error.unwrap_or_default()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_test() {
+        let command = |raw| serde_json::from_str::<Command>(raw).unwrap();
+
+        assert_eq!(command(r#"{"cmd":"status"}"#), Command::Status);
+        assert_eq!(command(r#"{"cmd":"flush"}"#), Command::Flush);
+        assert_eq!(command(r#"{"cmd":"toggle"}"#), Command::Toggle);
+        assert_eq!(
+            command(r#"{"cmd":"annotate","text":"standup"}"#),
+            Command::Annotate {
+                text: "standup".into(),
+                tags: vec![],
+            }
+        );
+        assert!(serde_json::from_str::<Command>(r#"{"cmd":"sleep"}"#).is_err());
+
+        let reply = serde_json::to_string(&Reply::error("bad")).unwrap();
+        assert_eq!(reply, r#"{"ok":false,"error":"bad"}"#);
+    }
+
+    #[tokio::test]
+    async fn serve_test() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(SOCKET_NAME);
+        let token = CancellationToken::new();
+
+        let (requests, mut commands) = channel();
+        let server = spawn_control(path.clone(), requests, token.clone())?;
+        spawn(async move {
+            while let Some((command, reply)) = commands.recv().await {
+                let _ = reply.send(match command {
+                    Command::Pause => Reply::ok(),
+                    _ => Reply::error("unexpected"),
+                });
+            }
+        });
+
+        let mut client = BufReader::new(UnixStream::connect(&path).await?);
+        client.write_all(b"{\"cmd\":\"pause\"}\n\nnope\n").await?;
+
+        let mut line = String::new();
+        client.read_line(&mut line).await?;
+        assert_eq!(line, "{\"ok\":true}\n");
+
+        line.clear();
+        client.read_line(&mut line).await?;
+        assert!(line.starts_with("{\"ok\":false,\"error\":\"Bad command"));
+
+        token.cancel();
+        server.await?;
+        assert!(!path.exists());
+
+        Ok(())
+    }
+}
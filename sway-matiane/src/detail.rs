@@ -0,0 +1,131 @@
+//! Details read from window titles, like the site of a browser tab, so
+//! time in one app can be told apart in reports, e.g.
+//!
+//! ```toml
+//! [[sway.detail]]
+//! app-ids = ["firefox"]
+//! suffix = " — Mozilla Firefox"
+//! ```
+//!
+//! The detail is the host of a URL in the title, or else its last part
+//! after the suffix is stripped, "GitHub" of "Issues · GitHub — Mozilla
+//! Firefox".
+
+use matiane_core::events::Focused;
+use serde::Deserialize;
+
+fn default_separators() -> Vec<String> {
+    [" — ", " – ", " - ", " · ", " | "]
+        .map(str::to_string)
+        .to_vec()
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DetailRule {
+    /// App ids of the windows, matched exactly.
+    pub app_ids: Vec<String>,
+    /// Name of the app the title ends with, stripped first.
+    pub suffix: Option<String>,
+    /// Separators of the parts of titles, the part after the last one is
+    /// the detail.
+    #[serde(default = "default_separators")]
+    pub separators: Vec<String>,
+}
+
+impl DetailRule {
+    fn extract(&self, title: &str) -> Option<String> {
+        // Some extensions put the address of the page in the title.
+        if let Some(host) = title.split_whitespace().find_map(url_host) {
+            return Some(host.to_string());
+        }
+
+        let title = self
+            .suffix
+            .as_deref()
+            .and_then(|suffix| title.strip_suffix(suffix))
+            .unwrap_or(title);
+
+        let last = self
+            .separators
+            .iter()
+            .filter_map(|separator| {
+                let (before, after) = title.rsplit_once(separator.as_str())?;
+                Some((before.len(), after))
+            })
+            .max_by_key(|(at, _)| *at)
+            .map_or(title, |(_, after)| after)
+            .trim();
+
+        (!last.is_empty()).then(|| last.to_string())
+    }
+}
+
+/// Set `detail` of `focused` by the first rule of its app id.
+pub fn extract(rules: &[DetailRule], focused: &mut Focused) {
+    let Some(rule) =
+        rules.iter().find(|rule| rule.app_ids.contains(&focused.id))
+    else {
+        return;
+    };
+
+    focused.detail = rule.extract(&focused.title);
+}
+
+/// Host of `word` when it is an http(s) URL, without `www.` and the port.
+fn url_host(word: &str) -> Option<&str> {
+    let rest = word
+        .strip_prefix("https://")
+        .or_else(|| word.strip_prefix("http://"))?;
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    let host = host.split(':').next()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detail(rules: &[DetailRule], id: &str, title: &str) -> Option<String> {
+        let mut focused = Focused {
+            title: title.to_string(),
+            id: id.to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        };
+        extract(rules, &mut focused);
+        focused.detail
+    }
+
+    #[test]
+    fn extract_test() {
+        let rules = [DetailRule {
+            app_ids: vec!["firefox".to_string()],
+            suffix: Some(" — Mozilla Firefox".to_string()),
+            separators: default_separators(),
+        }];
+        let firefox = |title| detail(&rules, "firefox", title);
+
+        assert_eq!(firefox("GitHub — Mozilla Firefox").unwrap(), "GitHub");
+        assert_eq!(firefox("Jira — Mozilla Firefox").unwrap(), "Jira");
+        assert_eq!(
+            firefox("Issues · nodech/matiane · GitHub — Mozilla Firefox")
+                .unwrap(),
+            "GitHub"
+        );
+        assert_eq!(
+            firefox(
+                "Docs - https://www.example.com:8080/a?b — Mozilla Firefox"
+            )
+            .unwrap(),
+            "example.com"
+        );
+        assert_eq!(firefox(" — Mozilla Firefox"), None);
+        assert_eq!(detail(&rules, "code", "main.rs - matiane"), None);
+    }
+}
@@ -0,0 +1,186 @@
+//! Windows that are not recorded, e.g.
+//!
+//! ```toml
+//! [[exclude]]
+//! app-ids = ["org.keepassxc.KeePassXC"]
+//!
+//! [[exclude]]
+//! title = "[Oo]nline [Bb]anking"
+//! action = "anonymize"
+//! ```
+//!
+//! The first rule a window matches excludes it.
+
+use matiane_core::events::Focused;
+use matiane_regex::{Regex, RegexCompileError};
+use serde::Deserialize;
+use thiserror::Error;
+
+/// App id of the windows anonymized by a rule.
+pub const EXCLUDED_ID: &str = "excluded";
+
+/// What is recorded of a matching window.
+#[derive(PartialEq, Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExcludeAction {
+    /// Nothing, its time is counted to the window focused before it.
+    #[default]
+    Drop,
+    /// A window with the app id `excluded` and no title.
+    Anonymize,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ExcludeRule {
+    /// App ids of the windows, matched exactly, every app when empty.
+    #[serde(default)]
+    pub app_ids: Vec<String>,
+    /// Regex the title must match too, every title when not set.
+    pub title: Option<String>,
+    #[serde(default)]
+    pub action: ExcludeAction,
+}
+
+#[derive(Debug, Error)]
+pub enum ExcludeError {
+    #[error("Invalid title regex `{regex}` of an exclude rule: {source}")]
+    InvalidRegex {
+        regex: String,
+        #[source]
+        source: RegexCompileError,
+    },
+    #[error("An exclude rule needs app-ids or a title")]
+    EmptyRule,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    app_ids: Vec<String>,
+    title: Option<Regex>,
+    action: ExcludeAction,
+}
+
+/// Compiled exclude rules.
+#[derive(Debug, Clone, Default)]
+pub struct Excluder {
+    rules: Vec<Rule>,
+}
+
+impl Excluder {
+    pub fn compile(rules: &[ExcludeRule]) -> Result<Self, ExcludeError> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                if rule.app_ids.is_empty() && rule.title.is_none() {
+                    return Err(ExcludeError::EmptyRule);
+                }
+
+                let title = rule
+                    .title
+                    .as_deref()
+                    .map(|raw| {
+                        Regex::compile(raw).map_err(|source| {
+                            ExcludeError::InvalidRegex {
+                                regex: raw.to_string(),
+                                source,
+                            }
+                        })
+                    })
+                    .transpose()?;
+
+                Ok(Rule {
+                    app_ids: rule.app_ids.clone(),
+                    title,
+                    action: rule.action,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Excluder { rules })
+    }
+
+    /// `focused` as it is recorded, none when it is dropped.
+    pub fn exclude(&self, focused: Focused) -> Option<Focused> {
+        let rule = self.rules.iter().find(|rule| {
+            (rule.app_ids.is_empty() || rule.app_ids.contains(&focused.id))
+                && rule
+                    .title
+                    .as_ref()
+                    .is_none_or(|re| re.is_match(&focused.title))
+        });
+
+        match rule.map(|rule| rule.action) {
+            None => Some(focused),
+            Some(ExcludeAction::Drop) => None,
+            Some(ExcludeAction::Anonymize) => Some(Focused {
+                title: String::new(),
+                id: EXCLUDED_ID.to_string(),
+                pid: 0,
+                process: None,
+                raw_title: None,
+                detail: None,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn focused(id: &str, title: &str) -> Focused {
+        Focused {
+            title: title.to_string(),
+            id: id.to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn exclude_test() {
+        #[derive(Deserialize)]
+        struct Rules {
+            exclude: Vec<ExcludeRule>,
+        }
+
+        let rules: Rules = toml::from_str(
+            r#"
+            [[exclude]]
+            app-ids = ["keepassxc"]
+
+            [[exclude]]
+            title = "[Bb]ank"
+            action = "anonymize"
+            "#,
+        )
+        .unwrap();
+        let excluder = Excluder::compile(&rules.exclude).unwrap();
+        let excluded = |id, title| {
+            excluder
+                .exclude(focused(id, title))
+                .map(|focused| focused.id)
+        };
+
+        assert_eq!(excluded("keepassxc", "bank.kdbx"), None);
+        assert_eq!(excluded("firefox", "My Bank"), Some(EXCLUDED_ID.into()));
+        assert_eq!(excluded("firefox", "News"), Some("firefox".into()));
+
+        let anonymized = excluder.exclude(focused("code", "bank.rs")).unwrap();
+        assert_eq!(anonymized.title, "");
+        assert_eq!(anonymized.pid, 0);
+
+        let empty = ExcludeRule {
+            app_ids: vec![],
+            title: None,
+            action: ExcludeAction::Drop,
+        };
+        assert!(matches!(
+            Excluder::compile(&[empty]),
+            Err(ExcludeError::EmptyRule)
+        ));
+    }
+}
@@ -1,4 +1,18 @@
 pub mod config;
+pub mod control;
+pub mod detail;
+pub mod exclude;
+pub mod logind;
+pub mod normalize;
+pub mod procfs;
+pub mod redact;
+pub mod screensaver;
+pub mod sink;
 pub mod sway;
-pub mod swayidle;
+pub mod systemd;
+#[cfg(feature = "otlp")]
+pub mod telemetry;
+pub mod timer;
 pub mod tray;
+pub mod wayland;
+pub mod x11;
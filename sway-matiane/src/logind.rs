@@ -0,0 +1,220 @@
+//! Sleep and locking of the session, from the signals of logind.
+//!
+//! Going to sleep is delayed by an inhibitor until the writer flushed the
+//! sleep event to disk.
+
+use futures::StreamExt;
+use futures::stream::{self, BoxStream};
+use log::{debug, warn};
+use matiane_core::bus::EventSink;
+use matiane_core::events::Event;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::{JoinHandle, spawn};
+use tokio_util::sync::CancellationToken;
+use zbus::Connection;
+use zbus::zvariant::{OwnedFd, OwnedObjectPath};
+
+/// Asks the writer to sync the events sent so far to disk, answered once
+/// it did.
+pub type FlushRequest = oneshot::Sender<()>;
+
+mod login1 {
+    use zbus::proxy;
+    use zbus::zvariant::{OwnedFd, OwnedObjectPath};
+
+    #[proxy(
+        interface = "org.freedesktop.login1.Manager",
+        default_service = "org.freedesktop.login1",
+        default_path = "/org/freedesktop/login1"
+    )]
+    pub trait Manager {
+        fn get_session(
+            &self,
+            session_id: &str,
+        ) -> zbus::Result<OwnedObjectPath>;
+
+        fn get_user(&self, uid: u32) -> zbus::Result<OwnedObjectPath>;
+
+        /// Holds off `what` until the returned descriptor is closed.
+        fn inhibit(
+            &self,
+            what: &str,
+            who: &str,
+            why: &str,
+            mode: &str,
+        ) -> zbus::Result<OwnedFd>;
+
+        /// True before going to sleep, false after waking up.
+        #[zbus(signal)]
+        fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+    }
+
+    #[proxy(
+        interface = "org.freedesktop.login1.User",
+        default_service = "org.freedesktop.login1"
+    )]
+    pub trait User {
+        /// Id and path of the graphical session of the user.
+        #[zbus(property)]
+        fn display(&self) -> zbus::Result<(String, OwnedObjectPath)>;
+    }
+
+    #[proxy(
+        interface = "org.freedesktop.login1.Session",
+        default_service = "org.freedesktop.login1"
+    )]
+    pub trait Session {
+        #[zbus(signal)]
+        fn lock(&self) -> zbus::Result<()>;
+
+        #[zbus(signal)]
+        fn unlock(&self) -> zbus::Result<()>;
+    }
+}
+
+fn sleep_event(start: bool) -> Event {
+    if start { Event::Sleep } else { Event::Awake }
+}
+
+/// The channel of flush requests, read by the writer.
+pub fn flush_channel()
+-> (mpsc::Sender<FlushRequest>, mpsc::Receiver<FlushRequest>) {
+    mpsc::channel(1)
+}
+
+/// The session of `XDG_SESSION_ID`, or the graphical session of the user
+/// for services of the user manager, which are in no session.
+async fn session_path(
+    connection: &Connection,
+    manager: &login1::ManagerProxy<'_>,
+) -> zbus::Result<OwnedObjectPath> {
+    if let Ok(id) = std::env::var("XDG_SESSION_ID") {
+        return manager.get_session(&id).await;
+    }
+
+    // SAFETY: getuid can not fail.
+    let uid = unsafe { libc::getuid() };
+    let user = login1::UserProxy::builder(connection)
+        .path(manager.get_user(uid).await?)?
+        .build()
+        .await?;
+
+    Ok(user.display().await?.1)
+}
+
+/// Lock and unlock events of the session, none when it is not found.
+async fn session_locks(
+    connection: &Connection,
+    manager: &login1::ManagerProxy<'_>,
+) -> zbus::Result<BoxStream<'static, Event>> {
+    let path = session_path(connection, manager).await?;
+    if path.as_str() == "/" {
+        warn!("No graphical session, locking is not tracked.");
+        return Ok(stream::pending().boxed());
+    }
+
+    let session = login1::SessionProxy::builder(connection)
+        .path(path)?
+        .build()
+        .await?;
+
+    let locks = session.receive_lock().await?.map(|_| Event::Sleep);
+    let unlocks = session.receive_unlock().await?.map(|_| Event::Awake);
+
+    Ok(stream::select(locks, unlocks).boxed())
+}
+
+/// A delay inhibitor of sleep, none when logind refuses it.
+async fn inhibit_sleep(manager: &login1::ManagerProxy<'_>) -> Option<OwnedFd> {
+    let inhibitor = manager
+        .inhibit("sleep", "matiane", "Recording going to sleep", "delay")
+        .await;
+
+    match inhibitor {
+        Ok(fd) => Some(fd),
+        Err(e) => {
+            warn!("Sleep is not delayed for the sleep event: {}", e);
+            None
+        }
+    }
+}
+
+/// Wait for the writer to sync the events sent so far.
+async fn flush(requests: &mpsc::Sender<FlushRequest>) {
+    let (done, flushed) = oneshot::channel();
+
+    if requests.send(done).await.is_ok() {
+        let _ = flushed.await;
+    }
+}
+
+/// Sends sleep, awake, lock and unlock events until cancelled, lock and
+/// unlock only with `track_locks`. Sleep is delayed until the sleep event is
+/// flushed by `flushes`.
+pub fn spawn_logind(
+    events: EventSink,
+    flushes: mpsc::Sender<FlushRequest>,
+    track_locks: bool,
+    token: CancellationToken,
+) -> JoinHandle<anyhow::Result<()>> {
+    spawn(async move {
+        let connection = Connection::system().await?;
+        let manager = login1::ManagerProxy::new(&connection).await?;
+        let mut sleeps = manager.receive_prepare_for_sleep().await?;
+
+        let mut locks = if track_locks {
+            session_locks(&connection, &manager)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Locking of the session is not tracked: {}", e);
+                    stream::pending().boxed()
+                })
+        } else {
+            stream::pending().boxed()
+        };
+
+        let mut inhibitor = inhibit_sleep(&manager).await;
+
+        loop {
+            // Whether sleep starts or ends, none for locking.
+            let (event, sleep) = tokio::select! {
+                _ = token.cancelled() => break,
+
+                Some(event) = locks.next() => (event, None),
+
+                signal = sleeps.next() => {
+                    let Some(signal) = signal else {
+                        warn!("logind stopped sending PrepareForSleep.");
+                        break;
+                    };
+
+                    match signal.args() {
+                        Ok(args) => (sleep_event(args.start), Some(args.start)),
+                        Err(e) => {
+                            warn!("Bad PrepareForSleep signal: {}", e);
+                            continue;
+                        }
+                    }
+                },
+            };
+
+            debug!("logind: {}.", event.kind());
+            if events.send(event).await.is_err() {
+                break;
+            }
+
+            match sleep {
+                Some(true) if inhibitor.is_some() => {
+                    flush(&flushes).await;
+                    inhibitor = None;
+                }
+                Some(false) if inhibitor.is_none() => {
+                    inhibitor = inhibit_sleep(&manager).await;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    })
+}
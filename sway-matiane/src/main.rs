@@ -1,160 +1,429 @@
 #![cfg(target_os = "linux")]
 use anyhow::{Context, Result};
 use chrono::Utc;
-use futures::{StreamExt, future::ready};
+use clap::arg;
+use futures::{Stream, StreamExt, future::ready};
 use log::{debug, error, info, trace, warn};
+use matiane_core::activity::{self, LiveActivity, Snapshot};
 use matiane_core::args;
+use matiane_core::bus::{self, EventSink, EventSource};
 use matiane_core::config::load as load_config;
-use matiane_core::events::{Event, Focused, TimedEvent};
+use matiane_core::diagnostic::render;
+use matiane_core::events::{
+    Annotation, Event, Focused, Started, TimedEvent, WorkspaceFocused,
+};
 use matiane_core::log::init_global_logger;
-use matiane_core::process::RunningHandle;
-use matiane_core::store::{EventWriter, acquire_lock_file};
+use matiane_core::sessions;
+use matiane_core::store::{
+    EventWriter, StoreFormat, StorePath, acquire_lock_file_with,
+};
 use matiane_core::xdg::Xdg;
-use std::path::PathBuf;
-use sway_matiane::{config, sway, swayidle, tray};
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::process::ExitCode;
+use std::time::Duration;
+use sway_matiane::control::{self, Command, Reply, Status};
+use sway_matiane::detail::{self, DetailRule};
+use sway_matiane::exclude::Excluder;
+use sway_matiane::normalize::NormalizeConfig;
+use sway_matiane::redact::Redactor;
+use sway_matiane::sink::Sink;
+#[cfg(feature = "otlp")]
+use sway_matiane::telemetry::Telemetry;
+use sway_matiane::wayland::WaylandError;
+use sway_matiane::wayland::idle::IdleNotification;
+use sway_matiane::wayland::toplevel::{ActiveToplevel, ToplevelTracker};
+use sway_matiane::x11::X11Error;
+use sway_matiane::x11::window::{ActiveWindow, WindowTracker};
+use sway_matiane::{
+    config, logind, procfs, screensaver, sway, systemd, timer, tray, wayland,
+    x11,
+};
 use tokio::signal::unix::{SignalKind, signal};
-use tokio::time::{MissedTickBehavior, interval};
+use tokio::sync::watch;
+use tokio::task::{JoinHandle, spawn};
+use tokio::time::{Interval, MissedTickBehavior, interval};
 use tokio_util::sync::CancellationToken;
 
 use sway::{
-    command::EventType, connection::subscribe, reply::Event as SwayEvent,
+    command::EventType,
+    connection::{SubscribeError, get_tree, i3_socket_path, subscribe_all},
+    reply::{Event as SwayEvent, Node, WorkspaceChange},
 };
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
+    let Err(e) = run().await else {
+        return ExitCode::SUCCESS;
+    };
+
+    // Errors go to the log once it is set up, stderr may not be seen.
+    if log::max_level() == log::LevelFilter::Off {
+        eprintln!("Error: {}", render(&e));
+    } else {
+        error!("{}", render(&e));
+    }
+
+    ExitCode::FAILURE
+}
+
+async fn run() -> Result<()> {
     let xdg = Xdg::new(matiane_core::NAME.into());
 
     let (
-        _,
+        matches,
         args::GeneralArgs {
             config_file,
             log_level,
+            write_default_config,
+            force,
         },
     ) = matiane_core::args::parse_args(
         &xdg,
         "Sway matiane logger",
-        std::iter::empty::<clap::Arg>(),
+        [
+            arg!(--"force-lock" "Takes over the store lock of a running instance"),
+            arg!(--"install-systemd-unit" "Writes a systemd user unit running the daemon and exits"),
+        ],
     );
 
-    init_global_logger(log_level)?;
+    if write_default_config {
+        matiane_core::config::write_default(&config_file, force)?;
+        println!("Wrote {}", config_file.display());
+        return Ok(());
+    }
+
+    if matches.get_flag("install-systemd-unit") {
+        let path = systemd::unit_path();
+        let config = matches
+            .get_one::<PathBuf>("config")
+            .map(std::path::absolute)
+            .transpose()?;
+        systemd::install_unit(&path, config.as_deref())?;
+        println!("Wrote {}", path.display());
+        println!(
+            "Enable it with `systemctl --user enable --now {}`.",
+            systemd::UNIT_NAME
+        );
+        return Ok(());
+    }
+
+    // The default store location depends on whether it was moved.
+    let migrated = matiane_core::config::migrate_state_dir(&config_file).await;
 
-    debug!("Loading config...");
+    // The config says where to log to, it is loaded first.
     let cfg = load_config::<config::SwayCliConfig>(&config_file)?;
+    let log_options = cfg.log.options(&cfg.general.state_dir, "sway-matiane");
+
+    init_global_logger(log_level, log_options)?;
     trace!("Config: {:?}", cfg);
 
-    let swaysock_path: PathBuf = std::env::var("SWAYSOCK")
-        .with_context(|| "Could not find swaysock env var.")?
-        .into();
+    match migrated {
+        Ok(Some(path)) => info!("Moved the store to {}.", path.display()),
+        Ok(None) => {}
+        Err(e) => warn!("Failed to move the store: {:#}", e),
+    }
+
+    let (rules_tx, rules) = watch::channel(WindowRules::new(&cfg)?);
 
+    let retention = cfg.general.retention();
+    let key = cfg.general.load_key()?;
+    let format = match key {
+        Some(_) => StoreFormat::Encrypted,
+        None => cfg.sink.format,
+    };
     let state_dir = cfg.general.state_dir;
     let now = Utc::now();
 
-    debug!("Acquiring lockfile...");
-    let lockfile = acquire_lock_file(state_dir.clone()).await?;
+    let live = if cfg.sink.current {
+        debug!("Reading today's activity...");
+        let today = activity::start_of_today();
+        let max_gap = sessions::max_gap(cfg.sway.live_interval);
+        let store = StorePath::new(state_dir.clone()).with_key(key.clone());
+        let live = LiveActivity::load(store, today, max_gap)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to read today's activity: {:#}", e);
+                LiveActivity::new(today, max_gap)
+            });
 
-    debug!("Opening store...");
-    let mut write_store = EventWriter::open(state_dir, now).await?;
+        Some(live)
+    } else {
+        None
+    };
+
+    let (lockfile, store) = if cfg.sink.store {
+        debug!("Acquiring lockfile...");
+        let force_lock = matches.get_flag("force-lock");
+        let lockfile =
+            acquire_lock_file_with(state_dir.clone(), force_lock).await?;
+        cfg.general.day_zone.save(&state_dir).await?;
+
+        debug!("Opening store...");
+        let store = EventWriter::open(state_dir, now)
+            .await?
+            .with_compression(cfg.sink.compress)
+            .with_retention(retention)
+            .with_format(format)
+            .with_key(key)
+            .with_durability(cfg.sink.durability);
+
+        (Some(lockfile), Some(store))
+    } else {
+        info!("Not writing to the store.");
+        (None, None)
+    };
+
+    let mut sinks = vec![];
+
+    if cfg.sink.stdout {
+        sinks.push(Sink::stdout());
+    }
 
-    debug!("Running swayidle...");
-    info!("Idle timoeut is set to: {} seconds.", cfg.sway.idle_timeout);
-    let cancel_tok = CancellationToken::new();
-    let sway_idle = run_swayidle(cfg.sway.idle_timeout, cancel_tok.clone())?;
+    if let Some(path) = &cfg.sink.pipe {
+        debug!("Opening event pipe {:?}...", path);
+        sinks.push(Sink::pipe(path)?);
+    }
 
-    debug!("Opening swaysocket...");
-    let events = subscribe(&swaysock_path, EventType::Window).await?;
-    let mut alive_interval = interval(cfg.sway.live_interval);
-    alive_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    #[cfg(feature = "otlp")]
+    let telemetry = match &cfg.otlp {
+        Some(otlp) => {
+            info!("Exporting telemetry to {}.", otlp.endpoint);
+            Some(Telemetry::new(otlp)?)
+        }
+        None => None,
+    };
+    #[cfg(not(feature = "otlp"))]
+    if cfg.otlp.is_some() {
+        warn!("Built without the otlp feature, telemetry is not exported.");
+    }
 
-    debug!("Showing tray...");
-    let _tray = tray::spawn_tray(cancel_tok.clone());
+    let mut recorder = Recorder {
+        store,
+        sinks,
+        #[cfg(feature = "otlp")]
+        telemetry,
+        live,
+        runtime_dir: xdg.runtime_dir(),
+        status: Status::new(now),
+        paused: watch::channel(false).0,
+        socket: None,
+        rules: rules.clone(),
+        paused_focus: None,
+    };
+
+    let notifier = systemd::Notifier::from_env().unwrap_or_else(|e| {
+        warn!("Failed to connect to NOTIFY_SOCKET: {}", e);
+        None
+    });
+    if let Some(timeout) = systemd::watchdog_timeout()
+        && timeout <= cfg.sway.live_interval
+    {
+        warn!(
+            "The watchdog timeout {:?} is not above the live interval {:?}.",
+            timeout, cfg.sway.live_interval
+        );
+    }
 
-    info!("Mematiane has started!");
+    info!("Idle timoeut is set to: {} seconds.", cfg.sway.idle_timeout);
+    let cancel_tok = CancellationToken::new();
+    let (sink, mut source) = bus::channel(bus::DEFAULT_CAPACITY);
+    let (flush_requests, mut flushes) = logind::flush_channel();
+    let (control_requests, mut control_commands) = control::channel();
+
+    let backend = cfg
+        .sway
+        .backend
+        .resolve(|var| std::env::var_os(var).is_some());
+    debug!("Window backend: {:?}.", backend);
+
+    sink.send(Event::Started(Box::new(Started {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        hostname: matiane_core::config::hostname(),
+        boot_id: procfs::boot_id(),
+        compositor: backend.name().to_string(),
+    })))
+    .await?;
+
+    let idle_token = cancel_tok.child_token();
+    let idle_backend = spawn_idle(
+        cfg.sway.idle_backend.resolve(backend),
+        cfg.sway.idle_timeout,
+        sink.clone(),
+        idle_token.clone(),
+    )
+    .await?;
+
+    // The screensaver of D-Bus has locking already.
+    logind::spawn_logind(
+        sink.clone(),
+        flush_requests,
+        idle_backend != config::IdleBackend::Dbus,
+        cancel_tok.clone(),
+    );
 
-    // Transform sway event into matiane event.
-    let mut mematiene_events = events
-        .filter(|event| match event {
-            Ok(SwayEvent::Window(_)) => ready(true),
-            Ok(_) => ready(false),
-            Err(err) => {
-                warn!("Sway event returned an error {:?}", err);
-                ready(false)
+    match backend {
+        config::WindowBackend::Auto | config::WindowBackend::Sway => {
+            let swaysock_path: PathBuf = std::env::var("SWAYSOCK")
+                .with_context(|| "Could not find swaysock env var.")?
+                .into();
+
+            debug!("Opening swaysocket...");
+            let events = subscribe_all(
+                &swaysock_path,
+                &[EventType::Window, EventType::Workspace, EventType::Output],
+            )
+            .await?;
+            if let Some(event) = focused_window(&swaysock_path, &rules).await {
+                sink.send(event).await?;
             }
-        })
-        .map(|event| {
-            let SwayEvent::Window(mut win_event) = event? else {
-                // must not happen, maybe rewrite to return concrete type?
-                return Err(anyhow::anyhow!("Incorrect sway event type!"));
-            };
-
-            let app_id = win_event.container.app_id.take().or_else(|| {
-                let win_props = win_event.container.window_properties.take()?;
-                win_props.instance.or(win_props.class)
-            });
-
-            let title =
-                win_event.container.name.take().or_else(|| app_id.clone());
-            let pid = win_event.container.pid.unwrap_or(0);
+            recorder.socket = Some(swaysock_path);
+            spawn_window_events(
+                "Sway socket",
+                sway_events(events, rules),
+                sink.clone(),
+                cancel_tok.clone(),
+            );
+        }
+        config::WindowBackend::I3 => {
+            let i3sock_path = i3_socket_path()
+                .await
+                .with_context(|| "Could not find the i3 socket.")?;
+
+            debug!("Opening the i3 socket...");
+            let events = subscribe_all(
+                &i3sock_path,
+                &[EventType::Window, EventType::Workspace, EventType::Output],
+            )
+            .await?;
+            if let Some(event) = focused_window(&i3sock_path, &rules).await {
+                sink.send(event).await?;
+            }
+            recorder.socket = Some(i3sock_path);
+            spawn_window_events(
+                "i3 socket",
+                sway_events(events, rules),
+                sink.clone(),
+                cancel_tok.clone(),
+            );
+        }
+        config::WindowBackend::X11 => {
+            debug!("Connecting to X11...");
+            let windows = WindowTracker::connect().await?;
+            spawn_window_events(
+                "X11 connection",
+                x11_events(windows.into_stream(), rules),
+                sink.clone(),
+                cancel_tok.clone(),
+            );
+        }
+        config::WindowBackend::Wlr => {
+            debug!("Connecting to the Wayland compositor...");
+            let toplevels = ToplevelTracker::connect().await?;
+            spawn_window_events(
+                "Wayland connection",
+                wayland_events(toplevels.into_stream(), rules),
+                sink.clone(),
+                cancel_tok.clone(),
+            );
+        }
+    }
+    let (live_interval, period) = watch::channel(cfg.sway.live_interval);
+    spawn_alive(period, sink.clone(), cancel_tok.clone());
+
+    let mut reloadable = Reloadable {
+        rules: rules_tx,
+        live_interval,
+        idle_backend,
+        idle_timeout: cfg.sway.idle_timeout,
+        idle_token,
+    };
+    let mut hangups = signal(SignalKind::hangup())?;
+    let mut terminates = signal(SignalKind::terminate())?;
+    let mut quits = signal(SignalKind::quit())?;
+
+    let (timer_commands, commands) = timer::channel();
+    timer::spawn_timer(
+        cfg.sway.timer,
+        commands,
+        sink.clone(),
+        cancel_tok.clone(),
+    );
 
-            let matiane_event = Box::new(Focused {
-                title: title.unwrap_or_else(|| "title-not-found".to_string()),
-                id: app_id.unwrap_or_else(|| "app-id-not-found".to_string()),
-                pid,
-            });
+    let control = if cfg.sway.control_socket {
+        control::spawn_control(
+            control::socket_path(),
+            control_requests.clone(),
+            cancel_tok.clone(),
+        )
+        .inspect_err(|e| warn!("Failed to listen for commands: {}", e))
+        .ok()
+    } else {
+        None
+    };
 
-            Ok::<Event, anyhow::Error>(Event::Focused(matiane_event))
-        });
+    debug!("Showing tray...");
+    let _tray = tray::spawn_tray(
+        timer_commands,
+        control_requests,
+        recorder.paused.subscribe(),
+        cancel_tok.clone(),
+    );
 
-    let mut sigusr1 = signal(SignalKind::user_defined1())?;
-    let mut sigusr2 = signal(SignalKind::user_defined2())?;
-    let mut idle = signal(SignalKind::from_raw(libc::SIGRTMIN() + 1))?;
-    let mut resume = signal(SignalKind::from_raw(libc::SIGRTMIN() + 2))?;
+    info!("Mematiane has started!");
+    notify(&notifier, systemd::Notifier::ready);
 
     loop {
         tokio::select! {
-            event = mematiene_events.next() => {
-                match event {
-                    Some(Ok(event)) => {
-                        trace!("Received an event.");
-                        write_store.write(&timed_event(event)).await?;
-                    }
-                    Some(Err(err)) => {
-                        error!("Received errored event: {:?}", err);
-                        break;
-                    },
-                    None => {
-                        error!("Sway socket has been closed.");
-                        break;
-                    },
-                };
+            Some(event) = source.recv() => {
+                trace!("Received an event.");
+                let alive = matches!(event.event, Event::Alive);
+                recorder.write(event).await?;
+
+                // Pings stop when writing is stuck, not only the live tick.
+                if alive {
+                    notify(&notifier, systemd::Notifier::watchdog);
+                }
             },
 
-            _ = alive_interval.tick() => {
-                trace!("Live tick.");
-                write_store.write(&timed_event(Event::Alive)).await?;
+            Some(flushed) = flushes.recv() => {
+                // Events sent before the request are synced with it.
+                recorder.drain(&mut source).await?;
+                recorder.flush().await;
+                let _ = flushed.send(());
             },
 
-            _ = sigusr1.recv() => {
-                debug!("Sleeping or locking...");
-                write_store.write(&timed_event(Event::Sleep)).await?;
+            Some((command, reply)) = control_commands.recv() => {
+                recorder.drain(&mut source).await?;
+                let _ = reply.send(recorder.command(command).await?);
             },
 
-            _ = sigusr2.recv() => {
-                debug!("Waking up or unlocking...");
-                write_store.write(&timed_event(Event::Awake)).await?;
+            Some(()) = hangups.recv() => {
+                info!("Reloading {}...", config_file.display());
+                if let Err(e) =
+                    reloadable.reload(&config_file, &sink, &cancel_tok).await
+                {
+                    warn!("Failed to reload the config: {}", render(&e));
+                }
             },
 
-            _ = idle.recv() => {
-                debug!("Idle for {} seconds.", cfg.sway.idle_timeout);
-                write_store.write(&timed_event(Event::Idle)).await?;
+            // A source that can not go on stops the daemon.
+            _ = cancel_tok.cancelled() => break,
+
+            _ = tokio::signal::ctrl_c() => {
+                debug!("SIGINT/CTRL-C detected!");
+                cancel_tok.cancel();
+                break;
             },
 
-            _ = resume.recv() => {
-                debug!("Resumed.");
-                write_store.write(&timed_event(Event::Active)).await?;
+            Some(()) = terminates.recv() => {
+                debug!("SIGTERM detected!");
+                cancel_tok.cancel();
+                break;
             },
 
-            _ = tokio::signal::ctrl_c() => {
-                debug!("SIGINT/CTRL-C detected!");
+            Some(()) = quits.recv() => {
+                debug!("SIGQUIT detected!");
                 cancel_tok.cancel();
                 break;
             },
@@ -162,45 +431,520 @@ async fn main() -> Result<()> {
     }
 
     info!("Closing matiane...");
-    drop(sway_idle);
+    notify(&notifier, systemd::Notifier::stopping);
+
+    // Events sent before stopping come before the shutdown.
+    recorder.drain(&mut source).await?;
+    recorder
+        .write(TimedEvent {
+            timestamp: Utc::now(),
+            event: Event::Shutdown,
+        })
+        .await?;
+    recorder.flush().await;
+
+    if let Some(control) = control {
+        let _ = control.await;
+    }
+
+    #[cfg(feature = "otlp")]
+    if let Some(telemetry) = recorder.telemetry {
+        telemetry.shutdown();
+    }
+
+    if recorder.live.is_some() {
+        let _ =
+            tokio::fs::remove_file(Snapshot::path(&recorder.runtime_dir)).await;
+    }
+
     drop(lockfile);
 
     Ok(())
 }
 
-fn timed_event(event: Event) -> TimedEvent {
-    TimedEvent {
-        timestamp: Utc::now(),
-        event,
+/// Writes events to the store and sinks, and reports them to the
+/// telemetry and the snapshot of today. Focused windows and live ticks are
+/// not written while paused.
+struct Recorder {
+    store: Option<EventWriter>,
+    sinks: Vec<Sink>,
+    #[cfg(feature = "otlp")]
+    telemetry: Option<Telemetry>,
+    live: Option<LiveActivity>,
+    runtime_dir: PathBuf,
+    status: Status,
+    /// Whether paused, for the tray menu.
+    paused: watch::Sender<bool>,
+    /// Socket of sway or i3, the focused window is read from it on resuming.
+    socket: Option<PathBuf>,
+    rules: watch::Receiver<WindowRules>,
+    /// The last window focused while paused, recorded on resuming without a
+    /// socket to read it from.
+    paused_focus: Option<Event>,
+}
+
+impl Recorder {
+    /// Sync the events written to the store to disk.
+    async fn flush(&mut self) {
+        if let Some(store) = &mut self.store
+            && let Err(e) = store.flush().await
+        {
+            warn!("Failed to flush the store: {}", e);
+        }
+    }
+
+    /// Write the events sent already, so a request comes after them.
+    async fn drain(&mut self, source: &mut EventSource) -> Result<()> {
+        while let Some(event) = source.try_recv() {
+            self.write(event).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Carry out a command of the control socket.
+    async fn command(&mut self, command: Command) -> Result<Reply> {
+        debug!("Command {:?}.", command);
+
+        match command {
+            Command::Status => {
+                let mut status = self.status.clone();
+                status.today = self.live.as_ref().map(LiveActivity::snapshot);
+                return Ok(Reply::status(status));
+            }
+            Command::Pause => self.set_paused(true).await?,
+            Command::Resume => self.set_paused(false).await?,
+            Command::Toggle => self.set_paused(!self.status.paused).await?,
+            Command::Annotate { text, tags } => {
+                let annotation = Annotation { text, tags };
+                self.write(TimedEvent {
+                    timestamp: Utc::now(),
+                    event: Event::Annotation(Box::new(annotation)),
+                })
+                .await?;
+            }
+            Command::Flush => self.flush().await,
+        }
+
+        Ok(Reply::ok())
+    }
+
+    /// Pause or resume, recording the change. On resuming, the window focused
+    /// since is recorded.
+    async fn set_paused(&mut self, paused: bool) -> Result<()> {
+        if self.status.paused == paused {
+            return Ok(());
+        }
+
+        info!("Tracking is {}.", if paused { "paused" } else { "resumed" });
+        let event = match paused {
+            true => Event::TrackingPaused,
+            false => Event::TrackingResumed,
+        };
+        self.write(TimedEvent {
+            timestamp: Utc::now(),
+            event,
+        })
+        .await?;
+
+        self.status.paused = paused;
+        self.paused.send_replace(paused);
+        if paused {
+            // The window focused until now is not shown while paused.
+            self.status.app_id = None;
+            self.status.title = None;
+            return Ok(());
+        }
+
+        let paused_focus = self.paused_focus.take();
+        let focused = match &self.socket {
+            Some(socket) => focused_window(socket, &self.rules).await,
+            None => paused_focus,
+        };
+        if let Some(event) = focused {
+            self.write(TimedEvent {
+                timestamp: Utc::now(),
+                event,
+            })
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn write(&mut self, event: TimedEvent) -> Result<()> {
+        let kind = event.event.kind();
+        if self.status.paused
+            && matches!(event.event, Event::Focused(_) | Event::Alive)
+        {
+            trace!(event_type = kind; "Paused, not recording {}.", kind);
+            if let Event::Focused(_) = event.event {
+                self.paused_focus = Some(event.event);
+            }
+            return Ok(());
+        }
+
+        match &event.event {
+            Event::Focused(focused) => debug!(
+                event_type = kind,
+                app_id = focused.id.as_str(),
+                pid = focused.pid;
+                "Focused {}.", focused.id
+            ),
+            Event::Alive => trace!(event_type = kind; "Alive."),
+            _ => debug!(event_type = kind; "Recording {}.", kind),
+        }
+
+        if let Some(store) = &mut self.store {
+            store.write(&event).await?;
+        }
+
+        for sink in &mut self.sinks {
+            if let Err(e) = sink.write(&event).await {
+                warn!("Failed to write an event to a sink: {}", e);
+            }
+        }
+
+        #[cfg(feature = "otlp")]
+        if let Some(telemetry) = &mut self.telemetry {
+            telemetry.record(&event);
+        }
+
+        if let Some(live) = &mut self.live {
+            live.push(&event);
+
+            if let Err(e) = live.snapshot().write(&self.runtime_dir).await {
+                warn!("Failed to write the snapshot: {:#}", e);
+            }
+        }
+
+        self.status.last_event = Some(event.timestamp);
+        if let Event::Focused(focused) = event.event {
+            self.status.app_id = Some(focused.id);
+            self.status.title = Some(focused.title);
+        }
+
+        Ok(())
+    }
+}
+
+/// What is done to focused windows before they are recorded. With
+/// `process_info`, they get the details of their process. Titles are
+/// normalized by `normalize`, windows excluded by `excluder`, their details
+/// read by `details`, then titles redacted by `redactor`.
+struct WindowRules {
+    process_info: bool,
+    normalize: NormalizeConfig,
+    excluder: Excluder,
+    details: Vec<DetailRule>,
+    redactor: Redactor,
+}
+
+impl WindowRules {
+    fn new(config: &config::SwayCliConfig) -> Result<Self> {
+        Ok(WindowRules {
+            process_info: config.sway.process_info,
+            normalize: config.sway.normalize.clone(),
+            excluder: Excluder::compile(&config.exclude)?,
+            details: config.sway.detail.clone(),
+            redactor: Redactor::compile(&config.sway.redact)?,
+        })
+    }
+
+    /// The event of a focused window, none when it is excluded.
+    fn focused(
+        &self,
+        title: Option<String>,
+        app_id: Option<String>,
+        pid: i32,
+    ) -> Option<Event> {
+        let title = title.or_else(|| app_id.clone());
+
+        let mut focused = Focused {
+            title: title.unwrap_or_else(|| "title-not-found".to_string()),
+            id: app_id.unwrap_or_else(|| "app-id-not-found".to_string()),
+            pid,
+            process: self
+                .process_info
+                .then(|| procfs::read_process(pid))
+                .flatten(),
+            raw_title: None,
+            detail: None,
+        };
+        self.normalize.normalize(&mut focused);
+        let mut focused = self.excluder.exclude(focused)?;
+        detail::extract(&self.details, &mut focused);
+        self.redactor.redact(&mut focused);
+
+        Some(Event::Focused(Box::new(focused)))
     }
 }
 
-fn run_swayidle(
-    idletimer: u32,
+/// Sends `events` until they end, when the connection of `source` was
+/// closed, then cancels `token`.
+fn spawn_window_events(
+    source: &'static str,
+    events: impl Stream<Item = Event> + Send + 'static,
+    sink: EventSink,
     token: CancellationToken,
-) -> Result<RunningHandle> {
-    let mut sway_idle = swayidle::SwayIdle::new();
-    let pid = std::process::id();
-
-    let idlesignal = libc::SIGRTMIN() + 1;
-    let resumesignal = libc::SIGRTMIN() + 2;
-    let sigusr1 = libc::SIGUSR1;
-    let sigusr2 = libc::SIGUSR2;
-
-    let before_sleep =
-        swayidle::BeforeSleep::new(format!("kill -{} {}", sigusr1, pid));
-    let after_sleep =
-        swayidle::AfterResume::new(format!("kill -{} {}", sigusr2, pid));
-
-    let on_idle = swayidle::Timeout::new_with_resume(
-        format!("kill -{} {}", idlesignal, pid),
-        idletimer,
-        format!("kill -{} {}", resumesignal, pid),
-    );
+) -> JoinHandle<()> {
+    spawn(async move {
+        let mut events = pin!(events);
+
+        loop {
+            let event = tokio::select! {
+                _ = token.cancelled() => return,
+                event = events.next() => event,
+            };
+
+            match event {
+                Some(event) => {
+                    if sink.send(event).await.is_err() {
+                        return;
+                    }
+                }
+                None => {
+                    error!("{} has been closed.", source);
+                    break;
+                }
+            }
+        }
+
+        token.cancel();
+    })
+}
 
-    sway_idle.add_command(before_sleep);
-    sway_idle.add_command(after_sleep);
-    sway_idle.add_command(on_idle);
+/// The focused windows, workspaces and output changes of sway or i3.
+fn sway_events(
+    events: impl Stream<Item = Result<SwayEvent, SubscribeError>>,
+    rules: watch::Receiver<WindowRules>,
+) -> impl Stream<Item = Event> {
+    events.filter_map(move |event| {
+        ready(match event {
+            Ok(event) => matiane_event(event, &rules.borrow()),
+            Err(err) => {
+                warn!("Sway event returned an error {:?}", err);
+                None
+            }
+        })
+    })
+}
+
+/// Transform a sway event into a matiane event, none for the ones that are
+/// not recorded.
+fn matiane_event(event: SwayEvent, rules: &WindowRules) -> Option<Event> {
+    match event {
+        SwayEvent::Window(win_event) => {
+            window_event(win_event.container, rules)
+        }
+        SwayEvent::Workspace(ws_event)
+            if ws_event.change == WorkspaceChange::Focus =>
+        {
+            let workspace = ws_event.current?;
+
+            Some(Event::WorkspaceFocused(Box::new(WorkspaceFocused {
+                name: workspace.name.unwrap_or_default(),
+                output: workspace.output,
+            })))
+        }
+        SwayEvent::Output(_) => Some(Event::OutputChanged),
+        _ => None,
+    }
+}
 
-    sway_idle.spawn(token)
+/// The event of the focused `window` of sway or i3.
+fn window_event(mut window: Node, rules: &WindowRules) -> Option<Event> {
+    let app_id = window.app_id.take().or_else(|| {
+        let win_props = window.window_properties.take()?;
+        win_props.instance.or(win_props.class)
+    });
+
+    let title = window.name.take();
+    let pid = window.pid.unwrap_or(0);
+
+    rules.focused(title, app_id, pid)
+}
+
+/// The window sway or i3 at `path` has focused, events only tell when the
+/// focus changes.
+async fn focused_window(
+    path: &PathBuf,
+    rules: &watch::Receiver<WindowRules>,
+) -> Option<Event> {
+    let tree = get_tree(path)
+        .await
+        .inspect_err(|e| warn!("Failed to get the focused window: {}", e))
+        .ok()?;
+    let window = tree.focused_window()?.clone();
+
+    window_event(window, &rules.borrow())
+}
+
+/// The active windows of X11, the stream ends with the first error.
+fn x11_events(
+    windows: impl Stream<Item = Result<ActiveWindow, X11Error>>,
+    rules: watch::Receiver<WindowRules>,
+) -> impl Stream<Item = Event> {
+    windows.filter_map(move |window| {
+        ready(match window {
+            Ok(window) => rules.borrow().focused(
+                window.title,
+                window.instance.or(window.class),
+                window.pid.unwrap_or(0),
+            ),
+            Err(err) => {
+                error!("X11 connection failed: {}", err);
+                None
+            }
+        })
+    })
+}
+
+/// The activated toplevels of the compositor, the stream ends with the
+/// first error. Their pid is not known.
+fn wayland_events(
+    toplevels: impl Stream<Item = Result<ActiveToplevel, WaylandError>>,
+    rules: watch::Receiver<WindowRules>,
+) -> impl Stream<Item = Event> {
+    toplevels.filter_map(move |toplevel| {
+        ready(match toplevel {
+            Ok(toplevel) => {
+                rules.borrow().focused(toplevel.title, toplevel.app_id, 0)
+            }
+            Err(err) => {
+                error!("Wayland connection failed: {}", err);
+                None
+            }
+        })
+    })
+}
+
+/// Send a notification to systemd, when run by it.
+fn notify(
+    notifier: &Option<systemd::Notifier>,
+    send: fn(&systemd::Notifier) -> std::io::Result<()>,
+) {
+    if let Some(notifier) = notifier
+        && let Err(e) = send(notifier)
+    {
+        warn!("Failed to notify systemd: {}", e);
+    }
+}
+
+fn alive_interval(period: Duration) -> Interval {
+    let mut alive_interval = interval(period);
+    alive_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    alive_interval
+}
+
+/// Sends `Alive` every `period` until cancelled, starting over when the
+/// period changes.
+fn spawn_alive(
+    mut period: watch::Receiver<Duration>,
+    sink: EventSink,
+    token: CancellationToken,
+) -> JoinHandle<()> {
+    let mut alive_interval = alive_interval(*period.borrow_and_update());
+
+    spawn(async move {
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => return,
+                Ok(()) = period.changed() => {
+                    let period = *period.borrow_and_update();
+                    debug!("Live interval is now {:?}.", period);
+                    alive_interval = self::alive_interval(period);
+                },
+                _ = alive_interval.tick() => {
+                    trace!("Live tick.");
+                    if sink.send(Event::Alive).await.is_err() {
+                        return;
+                    }
+                },
+            }
+        }
+    })
+}
+
+/// Sends idle events of `backend` until cancelled, and returns the backend
+/// it uses. Without idle notifications of the compositor, the D-Bus
+/// screensaver is used.
+async fn spawn_idle(
+    backend: config::IdleBackend,
+    idle_timeout: u32,
+    sink: EventSink,
+    token: CancellationToken,
+) -> Result<config::IdleBackend> {
+    match backend {
+        config::IdleBackend::Wayland => {
+            debug!("Watching idle notifications of the compositor...");
+            match IdleNotification::connect(idle_timeout).await {
+                Ok(notification) => {
+                    wayland::idle::spawn_idle(notification, sink, token);
+                    return Ok(backend);
+                }
+                Err(e) => warn!("No idle notifications, using D-Bus: {}", e),
+            }
+        }
+        config::IdleBackend::Dbus => {}
+        config::IdleBackend::X11 => {
+            debug!("Polling the idle time of X11...");
+            x11::idle::spawn_idle(idle_timeout, sink, token);
+            return Ok(backend);
+        }
+    }
+
+    debug!("Watching the screensaver over D-Bus...");
+    screensaver::spawn_screensaver(idle_timeout, sink, token);
+    Ok(config::IdleBackend::Dbus)
+}
+
+/// What a reload of the config changes without a restart: the rules of
+/// windows, the live interval, the idle timeout and the levels of modules.
+struct Reloadable {
+    rules: watch::Sender<WindowRules>,
+    live_interval: watch::Sender<Duration>,
+    idle_backend: config::IdleBackend,
+    idle_timeout: u32,
+    idle_token: CancellationToken,
+}
+
+impl Reloadable {
+    /// Apply the config file at `path`, nothing changes when it is not
+    /// valid.
+    async fn reload(
+        &mut self,
+        path: &Path,
+        sink: &EventSink,
+        token: &CancellationToken,
+    ) -> Result<()> {
+        let cfg = load_config::<config::SwayCliConfig>(path)?;
+        let rules = WindowRules::new(&cfg)?;
+
+        matiane_core::log::set_global_filters(
+            cfg.log.filters.into_iter().collect(),
+        );
+        self.rules.send_replace(rules);
+        self.live_interval.send_if_modified(|period| {
+            let changed = *period != cfg.sway.live_interval;
+            *period = cfg.sway.live_interval;
+            changed
+        });
+
+        if cfg.sway.idle_timeout != self.idle_timeout {
+            info!("Idle timeout is now {} seconds.", cfg.sway.idle_timeout);
+            self.idle_token.cancel();
+            self.idle_token = token.child_token();
+            self.idle_timeout = cfg.sway.idle_timeout;
+            self.idle_backend = spawn_idle(
+                self.idle_backend,
+                self.idle_timeout,
+                sink.clone(),
+                self.idle_token.clone(),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
 }
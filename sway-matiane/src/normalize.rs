@@ -0,0 +1,141 @@
+//! Normalization of window titles before they are recorded, so the same
+//! document or page has the same title while its counters and markers
+//! change, e.g.
+//!
+//! ```toml
+//! [sway.normalize]
+//! counters = true
+//! modified = true
+//! suffixes = [" — Mozilla Firefox", " - Visual Studio Code"]
+//! ```
+
+use matiane_core::events::Focused;
+use serde::Deserialize;
+
+/// Markers of unsaved changes editors put around titles.
+const MODIFIED_MARKERS: [char; 3] = ['●', '•', '*'];
+
+#[derive(PartialEq, Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct NormalizeConfig {
+    /// Strip counters of unread messages in front, like "(3) " or "[12] ".
+    #[serde(default)]
+    pub counters: bool,
+
+    /// Strip markers of unsaved changes in front or at the end, like "● "
+    /// or " *".
+    #[serde(default)]
+    pub modified: bool,
+
+    /// Strip the first of these the title ends with.
+    #[serde(default)]
+    pub suffixes: Vec<String>,
+
+    /// Keep the title as it was in `raw_title` of the event, when it was
+    /// changed.
+    #[serde(default)]
+    pub keep_raw: bool,
+}
+
+impl NormalizeConfig {
+    /// Normalize the title of `focused`. Titles with nothing left of them
+    /// are kept as they are.
+    pub fn normalize(&self, focused: &mut Focused) {
+        let normalized = self.normalize_title(&focused.title);
+
+        if normalized.is_empty() || normalized == focused.title {
+            return;
+        }
+
+        let normalized = normalized.to_string();
+        let raw = std::mem::replace(&mut focused.title, normalized);
+        if self.keep_raw {
+            focused.raw_title = Some(raw);
+        }
+    }
+
+    fn normalize_title<'a>(&self, mut title: &'a str) -> &'a str {
+        if let Some(suffix) =
+            self.suffixes.iter().find(|suffix| title.ends_with(*suffix))
+        {
+            title = &title[..title.len() - suffix.len()];
+        }
+
+        if self.counters {
+            title = strip_counter(title);
+        }
+
+        if self.modified {
+            title = title
+                .trim_start_matches(MODIFIED_MARKERS)
+                .trim_end_matches(MODIFIED_MARKERS);
+        }
+
+        title.trim()
+    }
+}
+
+/// `title` without a number in parentheses or brackets in front of it.
+fn strip_counter(title: &str) -> &str {
+    let counter = |open, close| {
+        let rest = title.strip_prefix(open)?;
+        let (count, rest) = rest.split_once(close)?;
+        let count = count.strip_suffix('+').unwrap_or(count);
+
+        (!count.is_empty() && count.chars().all(|c| c.is_ascii_digit()))
+            .then(|| rest.trim_start())
+    };
+
+    counter('(', ')')
+        .or_else(|| counter('[', ']'))
+        .unwrap_or(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalized(config: &NormalizeConfig, title: &str) -> Focused {
+        let mut focused = Focused {
+            title: title.to_string(),
+            id: "app".to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        };
+        config.normalize(&mut focused);
+        focused
+    }
+
+    #[test]
+    fn normalize_test() {
+        let config = NormalizeConfig {
+            counters: true,
+            modified: true,
+            suffixes: vec![" — Mozilla Firefox".to_string()],
+            keep_raw: false,
+        };
+        let title = |title| normalized(&config, title).title;
+
+        assert_eq!(title("(3) Inbox — Mozilla Firefox"), "Inbox");
+        assert_eq!(title("[99+] Chat"), "Chat");
+        assert_eq!(title("(draft) notes"), "(draft) notes");
+        assert_eq!(title("● main.rs - matiane"), "main.rs - matiane");
+        assert_eq!(title("*notes.txt"), "notes.txt");
+        assert_eq!(title("notes.txt *"), "notes.txt");
+        assert_eq!(title("(3) "), "(3) ");
+
+        let focused = normalized(&NormalizeConfig::default(), "(3) Inbox");
+        assert_eq!(focused.title, "(3) Inbox");
+
+        let config = NormalizeConfig {
+            keep_raw: true,
+            ..config
+        };
+        let focused = normalized(&config, "(3) Inbox");
+        assert_eq!(focused.title, "Inbox");
+        assert_eq!(focused.raw_title.as_deref(), Some("(3) Inbox"));
+        assert_eq!(normalized(&config, "Inbox").raw_title, None);
+    }
+}
@@ -0,0 +1,89 @@
+//! Details of the processes behind focused windows, read from `/proc`.
+
+use matiane_core::events::Process;
+use std::path::Path;
+
+/// What `/proc` has on `pid`, none for unknown pids or once the process
+/// exited.
+pub fn read_process(pid: i32) -> Option<Process> {
+    if pid <= 0 {
+        return None;
+    }
+
+    read_process_in(&Path::new("/proc").join(pid.to_string()))
+}
+
+/// Id of the current boot, none without `/proc`.
+pub fn boot_id() -> Option<String> {
+    let id = std::fs::read_to_string("/proc/sys/kernel/random/boot_id").ok()?;
+    Some(id.trim().to_string())
+}
+
+fn read_process_in(dir: &Path) -> Option<Process> {
+    // Every process has one, even when the other files can not be read.
+    let cmdline = std::fs::read(dir.join("cmdline")).ok()?;
+    let exe = std::fs::read_link(dir.join("exe"))
+        .ok()
+        .map(|exe| exe.to_string_lossy().into_owned());
+    let cgroup = std::fs::read_to_string(dir.join("cgroup"))
+        .ok()
+        .and_then(|cgroup| parse_cgroup(&cgroup));
+
+    Some(Process {
+        exe,
+        cmdline: parse_cmdline(&cmdline),
+        cgroup,
+    })
+}
+
+/// Arguments of a NUL separated command line.
+fn parse_cmdline(raw: &[u8]) -> Vec<String> {
+    let raw = raw.strip_suffix(b"\0").unwrap_or(raw);
+    if raw.is_empty() {
+        return vec![];
+    }
+
+    raw.split(|&b| b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect()
+}
+
+/// Path of the unified (v2) hierarchy.
+fn parse_cgroup(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn procfs_parse_test() {
+        assert_eq!(
+            parse_cmdline(b"/usr/bin/code\0--flag\0\0last\0"),
+            ["/usr/bin/code", "--flag", "", "last"]
+        );
+        assert!(parse_cmdline(b"").is_empty());
+
+        let cgroup = "1:name=systemd:/old\n\
+                      0::/user.slice/app-code-1234.scope\n";
+        assert_eq!(
+            parse_cgroup(cgroup).as_deref(),
+            Some("/user.slice/app-code-1234.scope")
+        );
+        assert_eq!(parse_cgroup("1:cpu:/\n"), None);
+    }
+
+    #[test]
+    fn procfs_read_test() {
+        let process = read_process(std::process::id() as i32).unwrap();
+        assert!(process.exe.is_some());
+        assert!(!process.cmdline.is_empty());
+
+        assert_eq!(read_process(0), None);
+        assert_eq!(read_process(-1), None);
+    }
+}
@@ -0,0 +1,209 @@
+//! Redaction of window titles before they are recorded, e.g.
+//!
+//! ```toml
+//! [[sway.redact]]
+//! app-ids = ["org.keepassxc.KeePassXC"]
+//! action = "drop"
+//!
+//! [[sway.redact]]
+//! app-ids = ["firefox"]
+//! title = "Private Browsing$"
+//! action = "hash"
+//! ```
+//!
+//! The first rule a window matches redacts its title.
+
+use matiane_core::events::Focused;
+use matiane_regex::{Regex, RegexCompileError};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Hex digits of the hash kept in hashed titles.
+const HASH_LEN: usize = 16;
+
+/// What is done to the title of a matching window.
+#[derive(PartialEq, Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum RedactAction {
+    /// The title is replaced by a hash of it, the same titles still have
+    /// the same hash. Short titles can be guessed from it.
+    Hash,
+    /// Only this many characters of the title are kept.
+    Truncate(usize),
+    /// The title is left empty.
+    Drop,
+}
+
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RedactRule {
+    /// App ids of the windows, matched exactly.
+    pub app_ids: Vec<String>,
+    /// Regex the title must match too, every title when not set.
+    pub title: Option<String>,
+    pub action: RedactAction,
+}
+
+#[derive(Debug, Error)]
+pub enum RedactError {
+    #[error("Invalid title regex `{regex}` of a redact rule: {source}")]
+    InvalidRegex {
+        regex: String,
+        #[source]
+        source: RegexCompileError,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    app_ids: Vec<String>,
+    title: Option<Regex>,
+    action: RedactAction,
+}
+
+/// Compiled redact rules.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    rules: Vec<Rule>,
+}
+
+impl Redactor {
+    pub fn compile(rules: &[RedactRule]) -> Result<Self, RedactError> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let title = rule
+                    .title
+                    .as_deref()
+                    .map(|raw| {
+                        Regex::compile(raw).map_err(|source| {
+                            RedactError::InvalidRegex {
+                                regex: raw.to_string(),
+                                source,
+                            }
+                        })
+                    })
+                    .transpose()?;
+
+                Ok(Rule {
+                    app_ids: rule.app_ids.clone(),
+                    title,
+                    action: rule.action,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Redactor { rules })
+    }
+
+    /// Redact the title of `focused` by the first rule it matches, the raw
+    /// title it was normalized from and the detail read from it are not
+    /// kept.
+    pub fn redact(&self, focused: &mut Focused) {
+        let rule = self.rules.iter().find(|rule| {
+            rule.app_ids.contains(&focused.id)
+                && rule
+                    .title
+                    .as_ref()
+                    .is_none_or(|re| re.is_match(&focused.title))
+        });
+
+        let Some(rule) = rule else {
+            return;
+        };
+
+        focused.title = match rule.action {
+            RedactAction::Hash => hash(&focused.title),
+            RedactAction::Truncate(len) => {
+                focused.title.chars().take(len).collect()
+            }
+            RedactAction::Drop => String::new(),
+        };
+        focused.raw_title = None;
+        focused.detail = None;
+    }
+}
+
+fn hash(title: &str) -> String {
+    let digest = Sha256::digest(title.as_bytes());
+    let mut hex: String =
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    hex.truncate(HASH_LEN);
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn focused(id: &str, title: &str) -> Focused {
+        Focused {
+            title: title.to_string(),
+            id: id.to_string(),
+            pid: 1,
+            process: None,
+            raw_title: Some(title.to_string()),
+            detail: Some(title.to_string()),
+        }
+    }
+
+    fn redacted(redactor: &Redactor, id: &str, title: &str) -> String {
+        let mut focused = focused(id, title);
+        redactor.redact(&mut focused);
+
+        if focused.title != title {
+            assert_eq!(focused.raw_title, None);
+            assert_eq!(focused.detail, None);
+        }
+        focused.title
+    }
+
+    #[test]
+    fn redact_test() {
+        #[derive(Deserialize)]
+        struct Rules {
+            redact: Vec<RedactRule>,
+        }
+
+        let rules: Rules = toml::from_str(
+            r#"
+            [[redact]]
+            app-ids = ["keepassxc"]
+            action = "drop"
+
+            [[redact]]
+            app-ids = ["firefox", "chromium"]
+            title = "Private Browsing$"
+            action = "hash"
+
+            [[redact]]
+            app-ids = ["firefox"]
+            action = { truncate = 5 }
+            "#,
+        )
+        .unwrap();
+        let redactor = Redactor::compile(&rules.redact).unwrap();
+
+        assert_eq!(redacted(&redactor, "keepassxc", "bank.kdbx"), "");
+        assert_eq!(redacted(&redactor, "code", "main.rs"), "main.rs");
+        assert_eq!(redacted(&redactor, "firefox", "Přehled - News"), "Přehl");
+
+        let hashed = redacted(&redactor, "chromium", "Bank - Private Browsing");
+        assert_eq!(hashed.len(), HASH_LEN);
+        assert_ne!(hashed, "Bank - Private Browsing");
+        assert_eq!(
+            redacted(&redactor, "firefox", "Bank - Private Browsing"),
+            hashed
+        );
+
+        let error = Redactor::compile(&[RedactRule {
+            app_ids: vec![],
+            title: Some("(a".to_string()),
+            action: RedactAction::Drop,
+        }])
+        .unwrap_err();
+        assert!(error.to_string().starts_with("Invalid title regex `(a`"));
+    }
+}
@@ -0,0 +1,206 @@
+//! Idle and lock detection over D-Bus, for desktops without ext-idle-notify.
+//!
+//! Locking comes from the `ActiveChanged` signals of the freedesktop and
+//! GNOME screensavers, in place of the lock signals of logind. Sleep still
+//! comes from logind. Idle time comes from the GNOME (Mutter) idle monitor,
+//! or by polling the freedesktop screensaver on KDE.
+
+use futures::StreamExt;
+use log::{debug, warn};
+use matiane_core::bus::EventSink;
+use matiane_core::events::Event;
+use std::time::Duration;
+use tokio::task::{JoinHandle, spawn};
+use tokio_util::sync::CancellationToken;
+use zbus::Connection;
+
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+mod freedesktop {
+    use zbus::proxy;
+
+    #[proxy(
+        interface = "org.freedesktop.ScreenSaver",
+        default_service = "org.freedesktop.ScreenSaver",
+        default_path = "/org/freedesktop/ScreenSaver"
+    )]
+    pub trait ScreenSaver {
+        #[zbus(signal)]
+        fn active_changed(&self, active: bool) -> zbus::Result<()>;
+
+        /// KDE returns milliseconds here.
+        fn get_session_idle_time(&self) -> zbus::Result<u32>;
+    }
+}
+
+mod gnome {
+    use zbus::proxy;
+
+    #[proxy(
+        interface = "org.gnome.ScreenSaver",
+        default_service = "org.gnome.ScreenSaver",
+        default_path = "/org/gnome/ScreenSaver"
+    )]
+    pub trait ScreenSaver {
+        #[zbus(signal)]
+        fn active_changed(&self, active: bool) -> zbus::Result<()>;
+    }
+
+    #[proxy(
+        interface = "org.gnome.Mutter.IdleMonitor",
+        default_service = "org.gnome.Mutter.IdleMonitor",
+        default_path = "/org/gnome/Mutter/IdleMonitor/Core"
+    )]
+    pub trait IdleMonitor {
+        /// Fires once after `interval` milliseconds of idle time.
+        fn add_idle_watch(&self, interval: u64) -> zbus::Result<u32>;
+
+        /// Fires once when the user is active again.
+        fn add_user_active_watch(&self) -> zbus::Result<u32>;
+
+        #[zbus(signal)]
+        fn watch_fired(&self, id: u32) -> zbus::Result<()>;
+    }
+}
+
+/// The screensaver turning on counts as going to sleep, like logind's
+/// `PrepareForSleep`.
+fn lock_event(active: bool) -> Event {
+    if active { Event::Sleep } else { Event::Awake }
+}
+
+/// The event for an idle time poll, if the state changed.
+pub(crate) fn poll_event(
+    idle: &mut bool,
+    idle_ms: u32,
+    timeout_ms: u64,
+) -> Option<Event> {
+    let now_idle = u64::from(idle_ms) >= timeout_ms;
+
+    if now_idle == *idle {
+        return None;
+    }
+
+    *idle = now_idle;
+    Some(if now_idle { Event::Idle } else { Event::Active })
+}
+
+/// Sends idle, active, lock and unlock events until cancelled.
+pub fn spawn_screensaver(
+    idle_timeout: u32,
+    events: EventSink,
+    token: CancellationToken,
+) -> JoinHandle<anyhow::Result<()>> {
+    spawn(async move {
+        let connection = Connection::session().await?;
+        let timeout_ms = u64::from(idle_timeout) * 1000;
+
+        let freedesktop =
+            freedesktop::ScreenSaverProxy::new(&connection).await?;
+        let gnome = gnome::ScreenSaverProxy::new(&connection).await?;
+        let monitor = gnome::IdleMonitorProxy::new(&connection).await?;
+
+        let mut locks = futures::stream::select(
+            freedesktop
+                .receive_active_changed()
+                .await?
+                .map(|signal| signal.args().map(|args| args.active)),
+            gnome
+                .receive_active_changed()
+                .await?
+                .map(|signal| signal.args().map(|args| args.active)),
+        );
+
+        let mut fired = monitor.receive_watch_fired().await?;
+        let idle_watch = match monitor.add_idle_watch(timeout_ms).await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                debug!("No GNOME idle monitor, polling instead: {}", e);
+                None
+            }
+        };
+        let mut active_watch = None;
+
+        let mut polling = idle_watch.is_none();
+        let mut poll = tokio::time::interval(POLL_INTERVAL);
+        let mut idle = false;
+
+        loop {
+            let event = tokio::select! {
+                _ = token.cancelled() => break,
+
+                Some(active) = locks.next() => {
+                    match active {
+                        Ok(active) => Some(lock_event(active)),
+                        Err(e) => {
+                            warn!("Bad ActiveChanged signal: {}", e);
+                            None
+                        }
+                    }
+                },
+
+                Some(signal) = fired.next() => {
+                    let id = signal.args()?.id;
+
+                    if Some(id) == idle_watch {
+                        active_watch =
+                            Some(monitor.add_user_active_watch().await?);
+                        Some(Event::Idle)
+                    } else if Some(id) == active_watch {
+                        active_watch = None;
+                        Some(Event::Active)
+                    } else {
+                        None
+                    }
+                },
+
+                _ = poll.tick(), if polling => {
+                    match freedesktop.get_session_idle_time().await {
+                        Ok(idle_ms) => {
+                            poll_event(&mut idle, idle_ms, timeout_ms)
+                        }
+                        Err(e) => {
+                            warn!(
+                                "No idle time source, only locking is \
+                                 tracked: {}",
+                                e
+                            );
+                            polling = false;
+                            None
+                        }
+                    }
+                },
+            };
+
+            if let Some(event) = event
+                && events.send(event).await.is_err()
+            {
+                break;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poll_event_test() {
+        let mut idle = false;
+
+        assert!(poll_event(&mut idle, 1_000, 60_000).is_none());
+        assert!(matches!(
+            poll_event(&mut idle, 60_000, 60_000),
+            Some(Event::Idle)
+        ));
+        assert!(poll_event(&mut idle, 70_000, 60_000).is_none());
+        assert!(matches!(
+            poll_event(&mut idle, 0, 60_000),
+            Some(Event::Active)
+        ));
+        assert!(!idle);
+    }
+}
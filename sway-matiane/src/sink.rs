@@ -0,0 +1,259 @@
+//! Outputs that get every event as a JSON line, next to the store.
+
+use matiane_core::events::TimedEvent;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::io::{AsyncWriteExt, Stdout};
+
+#[derive(Debug, Error)]
+pub enum SinkError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("{0} exists and is not a named pipe")]
+    NotAPipe(PathBuf),
+}
+
+pub enum Sink {
+    Stdout(Stdout),
+    Pipe(Pipe),
+}
+
+/// Never blocks the daemon. Events are only written while a reader has the
+/// pipe open, and dropped while it is full.
+pub struct Pipe {
+    path: PathBuf,
+    /// The write end, none while there is no reader.
+    file: Option<File>,
+    /// Rest of the last line, the pipe had no room for all of it. Written
+    /// before the next line, so lines are never cut.
+    pending: Vec<u8>,
+    dropping: bool,
+}
+
+impl Sink {
+    pub fn stdout() -> Self {
+        Sink::Stdout(tokio::io::stdout())
+    }
+
+    /// The named pipe at `path`, created if it does not exist.
+    pub fn pipe(path: &Path) -> Result<Self, SinkError> {
+        match std::fs::metadata(path) {
+            Ok(meta) if meta.file_type().is_fifo() => {}
+            Ok(_) => return Err(SinkError::NotAPipe(path.to_path_buf())),
+            Err(e) if e.kind() == ErrorKind::NotFound => mkfifo(path)?,
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(Sink::Pipe(Pipe {
+            path: path.to_path_buf(),
+            file: open_writer(path)?,
+            pending: vec![],
+            dropping: false,
+        }))
+    }
+
+    pub async fn write(&mut self, event: &TimedEvent) -> Result<(), SinkError> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+
+        match self {
+            Sink::Stdout(stdout) => {
+                stdout.write_all(&line).await?;
+                stdout.flush().await?;
+            }
+            Sink::Pipe(pipe) => pipe.write(&line)?,
+        }
+
+        Ok(())
+    }
+}
+
+impl Pipe {
+    fn write(&mut self, line: &[u8]) -> Result<(), SinkError> {
+        if self.file.is_none() {
+            self.file = open_writer(&self.path)?;
+        }
+
+        // Nobody reads, nothing is kept for the next reader.
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+
+        let written = write_available(file, &self.pending).and_then(|n| {
+            self.pending.drain(..n);
+            if !self.pending.is_empty() {
+                return Ok(false);
+            }
+
+            let n = write_available(file, line)?;
+            self.pending.extend_from_slice(&line[n..]);
+            Ok(n > 0)
+        });
+
+        match written {
+            Ok(true) => self.dropping = false,
+            Ok(false) => {
+                if !self.dropping {
+                    log::warn!("Event pipe is full, dropping events.");
+                    self.dropping = true;
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => {
+                log::debug!("The reader of the event pipe left.");
+                self.file = None;
+                self.pending.clear();
+                self.dropping = false;
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(())
+    }
+}
+
+/// The write end of the pipe at `path`, none while there is no reader.
+fn open_writer(path: &Path) -> Result<Option<File>, SinkError> {
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path);
+
+    match file {
+        Ok(file) => Ok(Some(file)),
+        Err(e) if e.raw_os_error() == Some(libc::ENXIO) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write as much of `buf` as the pipe has room for, returns how much.
+fn write_available(file: &mut File, buf: &[u8]) -> std::io::Result<usize> {
+    let mut written = 0;
+
+    while written < buf.len() {
+        match file.write(&buf[written..]) {
+            Ok(0) => break,
+            Ok(n) => written += n,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(written)
+}
+
+fn mkfifo(path: &Path) -> std::io::Result<()> {
+    let cpath = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(ErrorKind::InvalidInput, e))?;
+
+    // SAFETY: `cpath` is a valid nul terminated string.
+    if unsafe { libc::mkfifo(cpath.as_ptr(), 0o600) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use matiane_core::events::{Annotation, Event};
+    use tokio::io::AsyncBufReadExt;
+    use tokio::net::unix::pipe;
+
+    fn alive() -> TimedEvent {
+        TimedEvent {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap(),
+            event: Event::Alive,
+        }
+    }
+
+    const ALIVE: &str =
+        r#"{"timestamp":"2026-01-01T10:00:00Z","event":{"type":"alive"}}"#;
+
+    #[tokio::test]
+    async fn pipe_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events");
+
+        // Without a reader, events are not kept for the next one.
+        let mut sink = Sink::pipe(&path).unwrap();
+        sink.write(&alive()).await.unwrap();
+
+        let receiver = pipe::OpenOptions::new().open_receiver(&path).unwrap();
+        let mut lines = tokio::io::BufReader::new(receiver).lines();
+        let event = TimedEvent {
+            timestamp: alive().timestamp,
+            event: Event::Idle,
+        };
+        sink.write(&event).await.unwrap();
+        assert_eq!(
+            lines.next_line().await.unwrap().unwrap(),
+            r#"{"timestamp":"2026-01-01T10:00:00Z","event":{"type":"idle"}}"#
+        );
+
+        // More than the pipe holds, the rest is dropped instead of blocking.
+        for _ in 0..10_000 {
+            sink.write(&alive()).await.unwrap();
+        }
+
+        // Once the reader left, the next one starts with new events.
+        drop(lines);
+        sink.write(&alive()).await.unwrap();
+        let receiver = pipe::OpenOptions::new().open_receiver(&path).unwrap();
+        let mut lines = tokio::io::BufReader::new(receiver).lines();
+        sink.write(&alive()).await.unwrap();
+        assert_eq!(lines.next_line().await.unwrap().unwrap(), ALIVE);
+
+        // Opening it again reuses the pipe.
+        assert!(Sink::pipe(&path).is_ok());
+
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert!(matches!(Sink::pipe(&file), Err(SinkError::NotAPipe(_))));
+    }
+
+    #[tokio::test]
+    async fn pipe_sink_long_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events");
+
+        let mut sink = Sink::pipe(&path).unwrap();
+        let receiver = pipe::OpenOptions::new().open_receiver(&path).unwrap();
+        let reader = tokio::spawn(async move {
+            let mut lines = tokio::io::BufReader::new(receiver).lines();
+            let first = lines.next_line().await.unwrap().unwrap();
+            let second = lines.next_line().await.unwrap().unwrap();
+            (first, second)
+        });
+
+        // Longer than the pipe holds, the rest goes out with later events.
+        let title = "x".repeat(100_000);
+        let event = TimedEvent {
+            timestamp: alive().timestamp,
+            event: Event::Annotation(Box::new(Annotation {
+                text: title.clone(),
+                tags: vec![],
+            })),
+        };
+        sink.write(&event).await.unwrap();
+        while !reader.is_finished() {
+            sink.write(&alive()).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        let (first, second) = reader.await.unwrap();
+        assert!(first.contains(&title));
+        assert_eq!(second, ALIVE);
+    }
+}
@@ -84,6 +84,7 @@ impl Encoder<SwayPacketRaw> for SwayPacketCodec {
 }
 
 #[cfg(test)]
+#[allow(clippy::unnecessary_cast)]
 mod tests {
     use super::*;
     use futures::{SinkExt, StreamExt};
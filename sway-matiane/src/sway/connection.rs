@@ -1,7 +1,7 @@
 use super::codec::{SwayPacketCodec, SwayPacketCodecError};
 use super::command::{CommandType, CommandTypeError, EventType};
 use super::packet::SwayPacketRaw;
-use super::reply::{CommandOutcome, Event};
+use super::reply::{CommandOutcome, Event, Node};
 use futures::{SinkExt, StreamExt};
 use log::debug;
 use serde_json;
@@ -9,6 +9,7 @@ use std::fmt::Debug;
 use std::path::PathBuf;
 use thiserror::Error;
 use tokio::net::UnixStream;
+use tokio::process::Command;
 use tokio_util::codec::Framed;
 
 #[derive(Debug, Error)]
@@ -48,14 +49,40 @@ impl TryFrom<SwayPacketRaw> for Event {
             EventType::Window => {
                 Ok(Event::Window(serde_json::from_slice(&packet.payload)?))
             }
+            EventType::Workspace => {
+                Ok(Event::Workspace(serde_json::from_slice(&packet.payload)?))
+            }
+            EventType::Output => {
+                Ok(Event::Output(serde_json::from_slice(&packet.payload)?))
+            }
             _ => Err(SubscribeError::UnsupportedEvent(event_type as u32)),
         }
     }
 }
 
-fn subscribe_packet(event: EventType) -> Result<SwayPacketRaw, SubscribeError> {
-    let events = [event];
-    let encoded = serde_json::ser::to_string(&events)?;
+/// Socket of i3, `I3SOCK` or the one `i3 --get-socketpath` prints.
+pub async fn i3_socket_path() -> std::io::Result<PathBuf> {
+    if let Some(path) = std::env::var_os("I3SOCK") {
+        return Ok(path.into());
+    }
+
+    let output = Command::new("i3").arg("--get-socketpath").output().await?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "i3 --get-socketpath exited with {}",
+            output.status
+        )));
+    }
+
+    let path =
+        String::from_utf8(output.stdout).map_err(std::io::Error::other)?;
+    Ok(path.trim_end().into())
+}
+
+fn subscribe_packet(
+    events: &[EventType],
+) -> Result<SwayPacketRaw, SubscribeError> {
+    let encoded = serde_json::ser::to_string(events)?;
 
     Ok(SwayPacketRaw {
         packet_type: CommandType::Subscribe as u32,
@@ -67,7 +94,18 @@ pub async fn subscribe(
     path: &PathBuf,
     event: EventType,
 ) -> Result<
-    impl Debug + StreamExt<Item = Result<Event, SubscribeError>>,
+    impl Debug + StreamExt<Item = Result<Event, SubscribeError>> + use<>,
+    SubscribeError,
+> {
+    subscribe_all(path, &[event]).await
+}
+
+/// Subscribe to several event types on one connection.
+pub async fn subscribe_all(
+    path: &PathBuf,
+    events: &[EventType],
+) -> Result<
+    impl Debug + StreamExt<Item = Result<Event, SubscribeError>> + use<>,
     SubscribeError,
 > {
     debug!("Connecting to {:?}...", path);
@@ -76,8 +114,8 @@ pub async fn subscribe(
 
     let mut framer = Framed::new(socket, SwayPacketCodec);
 
-    debug!("Subscribing to events: {:?}...", event);
-    let packet = subscribe_packet(event)?;
+    debug!("Subscribing to events: {:?}...", events);
+    let packet = subscribe_packet(events)?;
     framer.send(packet).await?;
 
     let response = framer.next().await.ok_or(SubscribeError::Closed)??;
@@ -93,19 +131,43 @@ pub async fn subscribe(
         return Err(SubscribeError::SubscribeFailed(outcome.error.unwrap()));
     }
 
-    debug!("Subscribed to events: {:?}.", event);
+    debug!("Subscribed to events: {:?}.", events);
     Ok(framer.map(|res| Event::try_from(res?)))
 }
 
+/// The tree of outputs, workspaces and windows, on a connection of its own.
+pub async fn get_tree(path: &PathBuf) -> Result<Node, SubscribeError> {
+    let socket = UnixStream::connect(path).await?;
+    let mut framer = Framed::new(socket, SwayPacketCodec);
+
+    framer
+        .send(SwayPacketRaw {
+            packet_type: CommandType::GetTree as u32,
+            payload: Default::default(),
+        })
+        .await?;
+
+    let response = framer.next().await.ok_or(SubscribeError::Closed)??;
+
+    if response.packet_type != (CommandType::GetTree as u32) {
+        return Err(SubscribeError::IncorrectResponseType);
+    }
+
+    Ok(serde_json::from_slice(&response.payload)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn subscribe_packet_test() -> anyhow::Result<()> {
-        let packet = EventType::Window;
+        let packet = subscribe_packet(&[EventType::Window])?;
+        assert_eq!(&packet.payload[..], br#"["window"]"#);
 
-        subscribe_packet(packet)?;
+        let packet =
+            subscribe_packet(&[EventType::Window, EventType::Workspace])?;
+        assert_eq!(&packet.payload[..], br#"["window","workspace"]"#);
 
         Ok(())
     }
@@ -428,6 +428,24 @@ pub struct Node {
     pub output: Option<String>,
 }
 
+impl Node {
+    /// The focused window of the tree, none when a workspace or a container
+    /// of windows is focused.
+    pub fn focused_window(&self) -> Option<&Node> {
+        if self.focused {
+            let window =
+                matches!(self.node_type, NodeType::Con | NodeType::FloatingCon)
+                    && self.nodes.is_empty();
+            return window.then_some(self);
+        }
+
+        self.nodes
+            .iter()
+            .chain(&self.floating_nodes)
+            .find_map(Node::focused_window)
+    }
+}
+
 #[non_exhaustive]
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -751,6 +769,8 @@ pub enum WorkspaceChange {
     Urgent,
     /// The configuration file has been reloaded.
     Reload,
+    /// The workspace was restored after an in-place restart, i3 specific.
+    Restored,
 }
 
 #[non_exhaustive]
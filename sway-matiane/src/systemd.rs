@@ -0,0 +1,162 @@
+//! Readiness and watchdog notifications to systemd, and the user unit
+//! running the daemon: https://www.freedesktop.org/software/systemd/man/latest/sd_notify.html
+
+use anyhow::{Context, bail};
+use std::io;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const UNIT_NAME: &str = "sway-matiane.service";
+
+/// Seconds without a watchdog ping before systemd restarts the daemon, a
+/// few of the default live intervals.
+const WATCHDOG_SEC: u64 = 180;
+
+/// The socket of the service manager.
+pub struct Notifier {
+    socket: UnixDatagram,
+}
+
+impl Notifier {
+    /// The notifier of `NOTIFY_SOCKET`, none when not run by a service
+    /// manager that listens.
+    pub fn from_env() -> io::Result<Option<Self>> {
+        let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+            return Ok(None);
+        };
+
+        let address = match path.as_bytes().strip_prefix(b"@") {
+            Some(name) => SocketAddr::from_abstract_name(name)?,
+            None => SocketAddr::from_pathname(&path)?,
+        };
+
+        let socket = UnixDatagram::unbound()?;
+        socket.connect_addr(&address)?;
+
+        Ok(Some(Notifier { socket }))
+    }
+
+    fn notify(&self, state: &str) -> io::Result<()> {
+        self.socket.send(state.as_bytes())?;
+        Ok(())
+    }
+
+    /// The daemon has started.
+    pub fn ready(&self) -> io::Result<()> {
+        self.notify("READY=1")
+    }
+
+    /// The daemon is not stuck.
+    pub fn watchdog(&self) -> io::Result<()> {
+        self.notify("WATCHDOG=1")
+    }
+
+    pub fn stopping(&self) -> io::Result<()> {
+        self.notify("STOPPING=1")
+    }
+}
+
+/// Time without a watchdog ping after which the service manager restarts
+/// the daemon, none without a watchdog.
+pub fn watchdog_timeout() -> Option<Duration> {
+    // The watchdog may be meant for another process.
+    if let Ok(pid) = std::env::var("WATCHDOG_PID")
+        && pid != std::process::id().to_string()
+    {
+        return None;
+    }
+
+    let usec = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    Some(Duration::from_micros(usec))
+}
+
+/// Where user units are looked up first.
+pub fn unit_path() -> PathBuf {
+    matiane_core::xdg::config_dir(None::<&Path>)
+        .join("systemd/user")
+        .join(UNIT_NAME)
+}
+
+/// The user unit running `exe`, with the config file at `config`.
+pub fn unit(exe: &Path, config: Option<&Path>) -> String {
+    let mut exec_start = quote(exe);
+    if let Some(config) = config {
+        exec_start.push_str(" --config ");
+        exec_start.push_str(&quote(config));
+    }
+
+    format!(
+        "[Unit]
+Description=Matiane activity logger
+PartOf=graphical-session.target
+After=graphical-session.target
+
+[Service]
+Type=notify
+ExecStart={exec_start}
+ExecReload=/bin/kill -HUP $MAINPID
+Restart=on-failure
+WatchdogSec={WATCHDOG_SEC}
+
+[Install]
+WantedBy=graphical-session.target
+"
+    )
+}
+
+/// Write the user unit running this executable to `path`, with the config
+/// file at `config`.
+pub fn install_unit(path: &Path, config: Option<&Path>) -> anyhow::Result<()> {
+    if path.exists() {
+        bail!("{} exists, remove it to write it again", path.display());
+    }
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+
+    let exe = std::env::current_exe()?;
+    std::fs::write(path, unit(&exe, config))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// `path` as one argument of `ExecStart`, with specifiers escaped.
+fn quote(path: &Path) -> String {
+    let escaped = path
+        .to_string_lossy()
+        .replace('%', "%%")
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_test() {
+        let with_config = unit(
+            Path::new("/usr/bin/sway-matiane"),
+            Some(Path::new("/home/me/my 100%.toml")),
+        );
+
+        assert!(with_config.contains(
+            "\nExecStart=\"/usr/bin/sway-matiane\" \
+             --config \"/home/me/my 100%%.toml\"\n"
+        ));
+        assert!(with_config.contains("\nType=notify\n"));
+        assert!(with_config.contains("\nExecReload=/bin/kill -HUP $MAINPID\n"));
+        assert!(with_config.contains("\nWatchdogSec=180\n"));
+
+        assert!(
+            unit(Path::new("/bin/a\"b"), None)
+                .contains("\nExecStart=\"/bin/a\\\"b\"\n")
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use crate::config::OtlpConfig;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use matiane_core::events::{Event, TimedEvent};
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::{Counter, Histogram, MeterProvider};
+use opentelemetry::trace::{Span as _, Tracer as _, TracerProvider as _};
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use std::time::Instant;
+
+const SERVICE_NAME: &str = "sway-matiane";
+
+/// Exports daemon health metrics over OTLP and, when enabled, every focus
+/// session as a span and a duration histogram.
+pub struct Telemetry {
+    meter_provider: SdkMeterProvider,
+    events: Counter<u64>,
+    sessions: Option<Sessions>,
+}
+
+struct Sessions {
+    tracer_provider: SdkTracerProvider,
+    tracer: SdkTracer,
+    durations: Histogram<f64>,
+    tracker: SessionTracker,
+}
+
+impl Telemetry {
+    /// Must be called inside the tokio runtime, the exporters use it.
+    pub fn new(config: &OtlpConfig) -> Result<Self> {
+        let resource =
+            Resource::builder().with_service_name(SERVICE_NAME).build();
+
+        let exporter = MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(&config.endpoint)
+            .build()?;
+        let reader = PeriodicReader::builder(exporter)
+            .with_interval(config.interval)
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_resource(resource.clone())
+            .build();
+
+        let meter = meter_provider.meter(SERVICE_NAME);
+        let started = Instant::now();
+
+        let events = meter
+            .u64_counter("matiane.events")
+            .with_description("Events written to the store")
+            .build();
+        meter
+            .f64_observable_gauge("matiane.uptime")
+            .with_description("Time since the daemon started")
+            .with_unit("s")
+            .with_callback(move |observer| {
+                observer.observe(started.elapsed().as_secs_f64(), &[])
+            })
+            .build();
+
+        let sessions = if config.sessions {
+            let exporter = SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(&config.endpoint)
+                .build()?;
+            let tracer_provider = SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_resource(resource)
+                .build();
+
+            Some(Sessions {
+                tracer: tracer_provider.tracer(SERVICE_NAME),
+                tracer_provider,
+                durations: meter
+                    .f64_histogram("matiane.session.duration")
+                    .with_description("Duration of focus sessions")
+                    .with_unit("s")
+                    .build(),
+                tracker: SessionTracker::default(),
+            })
+        } else {
+            None
+        };
+
+        Ok(Telemetry {
+            meter_provider,
+            events,
+            sessions,
+        })
+    }
+
+    pub fn record(&mut self, event: &TimedEvent) {
+        self.events
+            .add(1, &[KeyValue::new("type", event.event.kind())]);
+
+        let Some(sessions) = &mut self.sessions else {
+            return;
+        };
+
+        let Some(session) = sessions.tracker.update(event) else {
+            return;
+        };
+
+        let app = KeyValue::new("app", session.app.clone());
+        let duration = (session.end - session.start).as_seconds_f64();
+        sessions
+            .durations
+            .record(duration, std::slice::from_ref(&app));
+
+        let mut span = sessions
+            .tracer
+            .span_builder("focus")
+            .with_start_time(session.start)
+            .with_attributes([app, KeyValue::new("title", session.title)])
+            .start(&sessions.tracer);
+        span.end_with_timestamp(session.end.into());
+    }
+
+    /// Export what is left.
+    pub fn shutdown(self) {
+        if let Some(sessions) = self.sessions
+            && let Err(e) = sessions.tracer_provider.shutdown()
+        {
+            log::warn!("Failed to shut down the tracer: {}", e);
+        }
+
+        if let Err(e) = self.meter_provider.shutdown() {
+            log::warn!("Failed to shut down the meter: {}", e);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Session {
+    app: String,
+    title: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+/// Follows the focus. A session ends when the focus changes or the user
+/// goes away, and starts again with the same window when they are back.
+#[derive(Default)]
+struct SessionTracker {
+    focused: Option<(String, String)>,
+    since: Option<DateTime<Utc>>,
+}
+
+impl SessionTracker {
+    /// Returns the session the event ended.
+    fn update(&mut self, event: &TimedEvent) -> Option<Session> {
+        let at = event.timestamp;
+
+        match &event.event {
+            Event::Focused(focused) => {
+                let ended = self.end(at);
+                self.focused =
+                    Some((focused.id.clone(), focused.title.clone()));
+                self.since = Some(at);
+                ended
+            }
+            Event::Idle | Event::Sleep => self.end(at),
+            Event::Active | Event::Awake => {
+                if self.since.is_none() && self.focused.is_some() {
+                    self.since = Some(at);
+                }
+                None
+            }
+            Event::TrackingPaused | Event::Shutdown | Event::Started(_) => {
+                let ended = self.end(at);
+                self.focused = None;
+                ended
+            }
+            Event::Alive
+            | Event::Backfilled(_)
+            | Event::Present(_)
+            | Event::StorageDegraded(_)
+            | Event::WorkspaceFocused(_)
+            | Event::OutputChanged
+            | Event::Annotation(_)
+            | Event::TimerStarted(_)
+            | Event::TimerEnded(_)
+            | Event::TrackingResumed
+            | Event::Unknown(_) => None,
+        }
+    }
+
+    fn end(&mut self, at: DateTime<Utc>) -> Option<Session> {
+        let start = self.since.take()?;
+        let (app, title) = self.focused.clone()?;
+
+        Some(Session {
+            app,
+            title,
+            start,
+            end: at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use matiane_core::events::Focused;
+
+    fn at(min: u32, event: Event) -> TimedEvent {
+        TimedEvent {
+            timestamp: Utc.with_ymd_and_hms(2026, 1, 1, 10, min, 0).unwrap(),
+            event,
+        }
+    }
+
+    fn focused(app: &str) -> Event {
+        Event::Focused(Box::new(Focused {
+            title: format!("{} title", app),
+            id: app.to_string(),
+            pid: 1,
+            process: None,
+            raw_title: None,
+            detail: None,
+        }))
+    }
+
+    #[test]
+    fn session_tracker() {
+        let mut tracker = SessionTracker::default();
+        let mut ended = |event| tracker.update(&event).map(|s| s.app);
+
+        assert_eq!(ended(at(0, Event::Awake)), None);
+        assert_eq!(ended(at(1, focused("a"))), None);
+        assert_eq!(ended(at(2, Event::Alive)), None);
+        assert_eq!(ended(at(3, focused("b"))), Some("a".to_string()));
+        assert_eq!(ended(at(4, Event::Idle)), Some("b".to_string()));
+        assert_eq!(ended(at(5, Event::Sleep)), None);
+        assert_eq!(ended(at(6, Event::Active)), None);
+        assert_eq!(ended(at(8, focused("c"))), Some("b".to_string()));
+    }
+
+    #[test]
+    fn session_tracker_times() {
+        let mut tracker = SessionTracker::default();
+
+        tracker.update(&at(1, focused("a")));
+        tracker.update(&at(2, Event::Sleep));
+        tracker.update(&at(5, Event::Awake));
+
+        assert_eq!(
+            tracker.update(&at(9, focused("b"))),
+            Some(Session {
+                app: "a".to_string(),
+                title: "a title".to_string(),
+                start: at(5, Event::Alive).timestamp,
+                end: at(9, Event::Alive).timestamp,
+            })
+        );
+    }
+}
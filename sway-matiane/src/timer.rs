@@ -0,0 +1,200 @@
+//! The timer of intended tasks, started and stopped by clicking the tray
+//! icon or over D-Bus, e.g.
+//!
+//! ```sh
+//! busctl --user call <tray bus name> /Timer org.matiane.Timer Start st write 1500
+//! ```
+
+use crate::config::deserialize_interval;
+use chrono::{DateTime, TimeDelta, Utc};
+use matiane_core::bus::EventSink;
+use matiane_core::events::Event;
+use matiane_core::timer::Timer;
+use serde::Deserialize;
+use std::future::pending;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, spawn};
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use zbus::interface;
+
+/// Commands waiting for the timer.
+const COMMAND_CAPACITY: usize = 8;
+
+fn default_duration() -> Duration {
+    Duration::from_secs(25 * 60)
+}
+
+fn default_task() -> String {
+    "focus".to_string()
+}
+
+/// The timer the tray icon starts.
+#[derive(PartialEq, Debug, Deserialize, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TimerConfig {
+    /// Seconds the timer runs, a pomodoro by default.
+    #[serde(
+        default = "default_duration",
+        deserialize_with = "deserialize_interval"
+    )]
+    pub duration: Duration,
+
+    #[serde(default = "default_task")]
+    pub task: String,
+}
+
+impl Default for TimerConfig {
+    fn default() -> Self {
+        Self {
+            duration: default_duration(),
+            task: default_task(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimerCommand {
+    Start {
+        task: String,
+        duration: Duration,
+    },
+    Stop,
+    /// Stop the running timer, or start the one of the config.
+    Toggle,
+}
+
+pub fn channel() -> (mpsc::Sender<TimerCommand>, mpsc::Receiver<TimerCommand>) {
+    mpsc::channel(COMMAND_CAPACITY)
+}
+
+/// The events of running `command` at `now`.
+fn run(
+    timer: &mut Timer,
+    config: &TimerConfig,
+    command: TimerCommand,
+    now: DateTime<Utc>,
+) -> Vec<Event> {
+    let start = |timer: &mut Timer, task, duration| {
+        let duration = TimeDelta::from_std(duration).unwrap_or(TimeDelta::MAX);
+        timer.start(task, duration, now)
+    };
+
+    match command {
+        TimerCommand::Start { task, duration } => start(timer, task, duration),
+        TimerCommand::Stop => timer.stop(now).into_iter().collect(),
+        TimerCommand::Toggle if timer.task().is_some() => {
+            timer.stop(now).into_iter().collect()
+        }
+        TimerCommand::Toggle => {
+            start(timer, config.task.clone(), config.duration)
+        }
+    }
+}
+
+/// Runs the commands of `commands` and sends the events of the timer until
+/// cancelled.
+pub fn spawn_timer(
+    config: TimerConfig,
+    mut commands: mpsc::Receiver<TimerCommand>,
+    sink: EventSink,
+    token: CancellationToken,
+) -> JoinHandle<()> {
+    spawn(async move {
+        let mut timer = Timer::default();
+
+        loop {
+            let deadline = timer.deadline();
+            let events = tokio::select! {
+                _ = token.cancelled() => return,
+                command = commands.recv() => match command {
+                    Some(command) => {
+                        run(&mut timer, &config, command, Utc::now())
+                    }
+                    None => return,
+                },
+                _ = until(deadline) => {
+                    timer.expire(Utc::now()).into_iter().collect()
+                },
+            };
+
+            for event in events {
+                if sink.send(event).await.is_err() {
+                    return;
+                }
+            }
+        }
+    })
+}
+
+/// Waits until `deadline`, forever without one.
+async fn until(deadline: Option<DateTime<Utc>>) {
+    match deadline {
+        Some(deadline) => {
+            let left = (deadline - Utc::now()).to_std().unwrap_or_default();
+            sleep(left).await;
+        }
+        None => pending().await,
+    }
+}
+
+/// Timer control for other programs, served next to the tray icon.
+pub struct TimerInterface {
+    pub commands: mpsc::Sender<TimerCommand>,
+}
+
+#[interface(name = "org.matiane.Timer")]
+impl TimerInterface {
+    /// Start a timer of `task` for `seconds`, stopping the running one.
+    async fn start(&self, task: String, seconds: u64) -> zbus::fdo::Result<()> {
+        let duration = Duration::from_secs(seconds);
+        self.send(TimerCommand::Start { task, duration }).await
+    }
+
+    async fn stop(&self) -> zbus::fdo::Result<()> {
+        self.send(TimerCommand::Stop).await
+    }
+}
+
+impl TimerInterface {
+    async fn send(&self, command: TimerCommand) -> zbus::fdo::Result<()> {
+        self.commands
+            .send(command)
+            .await
+            .map_err(|_| zbus::fdo::Error::Failed("The timer stopped".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn run_test() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+        let config = TimerConfig::default();
+        let mut timer = Timer::default();
+
+        let events = run(&mut timer, &config, TimerCommand::Toggle, now);
+        let [Event::TimerStarted(started)] = &events[..] else {
+            panic!("Must start the timer of the config.");
+        };
+        assert_eq!(started.task, "focus");
+        assert_eq!(started.duration, 1500);
+
+        let events = run(&mut timer, &config, TimerCommand::Toggle, now);
+        assert!(matches!(&events[..], [Event::TimerEnded(_)]));
+        assert_eq!(timer.task(), None);
+
+        let start = TimerCommand::Start {
+            task: "write".to_string(),
+            duration: Duration::from_secs(60),
+        };
+        run(&mut timer, &config, start, now);
+        assert_eq!(timer.deadline(), Some(now + TimeDelta::minutes(1)));
+        assert_eq!(run(&mut timer, &config, TimerCommand::Stop, now).len(), 1);
+        assert!(run(&mut timer, &config, TimerCommand::Stop, now).is_empty());
+    }
+}
@@ -5,16 +5,24 @@ use zbus::{
     zvariant::{ObjectPath, Type, Value},
 };
 
+use crate::control::{Request, TrackingInterface};
+use crate::timer::{TimerCommand, TimerInterface};
 use futures::StreamExt;
+use menu::{MENU_PATH, Menu};
 use std::time::Duration;
+use tokio::sync::{mpsc, watch};
 use tokio::task::{JoinHandle, spawn};
 use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
 
-use log::debug;
+use log::{debug, warn};
 use thiserror::Error;
 
-pub struct Tray;
+pub mod menu;
+
+pub struct Tray {
+    timer: mpsc::Sender<TimerCommand>,
+}
 
 const ICON_WIDTH: i32 = 256;
 const ICON_HEIGHT: i32 = 256;
@@ -37,6 +45,13 @@ struct ToolTip {
 
 #[interface(name = "org.kde.StatusNotifierItem")]
 impl Tray {
+    /// Clicking the icon starts or stops the timer.
+    async fn activate(&self, _x: i32, _y: i32) {
+        if self.timer.send(TimerCommand::Toggle).await.is_err() {
+            debug!("The timer stopped.");
+        }
+    }
+
     #[zbus(property)]
     async fn category(&self) -> String {
         "SystemServices".into()
@@ -74,7 +89,7 @@ impl Tray {
 
     #[zbus(property)]
     async fn menu(&self) -> ObjectPath<'_> {
-        ObjectPath::from_static_str_unchecked("/")
+        ObjectPath::from_static_str_unchecked(MENU_PATH)
     }
 
     #[zbus(property)]
@@ -171,14 +186,33 @@ impl TrayConn<'_> {
     }
 }
 
+/// Shows the tray icon and serves the timer control with `timer`. Its menu
+/// and the tracking control pause with `requests`, the menu shows `paused`.
 pub fn spawn_tray(
+    timer: mpsc::Sender<TimerCommand>,
+    requests: mpsc::Sender<Request>,
+    mut paused: watch::Receiver<bool>,
     token: CancellationToken,
 ) -> JoinHandle<Result<(), anyhow::Error>> {
     spawn(async move {
+        let tray = Tray {
+            timer: timer.clone(),
+        };
+        let menu = Menu {
+            requests: requests.clone(),
+            paused: paused.clone(),
+        };
         let connection = connection::Builder::session()?
-            .serve_at("/StatusNotifierItem", Tray)?
+            .serve_at("/StatusNotifierItem", tray)?
+            .serve_at("/Timer", TimerInterface { commands: timer })?
+            .serve_at("/Tracking", TrackingInterface { requests })?
+            .serve_at(MENU_PATH, menu)?
             .build()
             .await?;
+        let menu = connection
+            .object_server()
+            .interface::<_, Menu>(MENU_PATH)
+            .await?;
 
         let mut tcon = TrayConn {
             conn: &connection,
@@ -214,6 +248,12 @@ pub fn spawn_tray(
                     debug!("Shutting down.");
                     break;
                 },
+                Ok(()) = paused.changed() => {
+                    let emitter = menu.signal_emitter();
+                    if let Err(e) = menu.get().await.paused_changed(emitter).await {
+                        warn!("Failed to update the tray menu: {}", e);
+                    }
+                },
                 chsignal = change_signal.next() => {
                     if chsignal.is_none() {
                         log::error!("DBus name owner change stream ended unexpectedly.");
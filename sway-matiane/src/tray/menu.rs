@@ -0,0 +1,229 @@
+//! The menu of the tray icon, over `com.canonical.dbusmenu`. It has one
+//! item, pausing or resuming tracking.
+
+use crate::control::{self, Command, Request};
+use log::debug;
+use serde::Serialize;
+use std::collections::HashMap;
+use tokio::sync::{mpsc, watch};
+use zbus::interface;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::{OwnedValue, Str, Type, Value};
+
+pub const MENU_PATH: &str = "/MenuBar";
+
+const ROOT: i32 = 0;
+const PAUSE: i32 = 1;
+
+/// An item and its children, which are layouts too.
+#[derive(Debug, Serialize, Type, Value, zbus::zvariant::OwnedValue)]
+pub struct Layout {
+    id: i32,
+    properties: HashMap<String, OwnedValue>,
+    children: Vec<OwnedValue>,
+}
+
+type Properties = HashMap<String, OwnedValue>;
+
+pub struct Menu {
+    pub requests: mpsc::Sender<Request>,
+    pub paused: watch::Receiver<bool>,
+}
+
+impl Menu {
+    fn properties(&self, id: i32) -> Option<Properties> {
+        let property =
+            |name: &str, value: OwnedValue| (name.to_string(), value);
+
+        match id {
+            ROOT => Some(HashMap::from([property(
+                "children-display",
+                Str::from("submenu").into(),
+            )])),
+            PAUSE => {
+                let paused = *self.paused.borrow();
+                Some(HashMap::from([
+                    property("label", Str::from("Pause tracking").into()),
+                    property("toggle-type", Str::from("checkmark").into()),
+                    property("toggle-state", i32::from(paused).into()),
+                ]))
+            }
+            _ => None,
+        }
+    }
+
+    fn layout(&self, id: i32, depth: i32) -> Option<Layout> {
+        let children = match id {
+            ROOT if depth != 0 => vec![PAUSE],
+            _ => vec![],
+        };
+
+        Some(Layout {
+            id,
+            properties: self.properties(id)?,
+            children: children
+                .into_iter()
+                .filter_map(|child| self.layout(child, depth - 1))
+                .filter_map(|layout| OwnedValue::try_from(layout).ok())
+                .collect(),
+        })
+    }
+
+    /// Tell the host the item changed with the paused state.
+    pub async fn paused_changed(
+        &self,
+        emitter: &SignalEmitter<'_>,
+    ) -> zbus::Result<()> {
+        let updated = self.properties(PAUSE).map(|props| (PAUSE, props));
+        Self::items_properties_updated(
+            emitter,
+            updated.into_iter().collect(),
+            vec![],
+        )
+        .await
+    }
+}
+
+#[interface(name = "com.canonical.dbusmenu")]
+impl Menu {
+    /// Layout of `parent_id`, down to `recursion_depth` levels of children
+    /// or all of them when it is negative.
+    async fn get_layout(
+        &self,
+        parent_id: i32,
+        recursion_depth: i32,
+        _property_names: Vec<String>,
+    ) -> zbus::fdo::Result<(u32, Layout)> {
+        let layout =
+            self.layout(parent_id, recursion_depth).ok_or_else(|| {
+                zbus::fdo::Error::InvalidArgs(format!("No item {}", parent_id))
+            })?;
+
+        Ok((0, layout))
+    }
+
+    async fn get_group_properties(
+        &self,
+        ids: Vec<i32>,
+        _property_names: Vec<String>,
+    ) -> Vec<(i32, Properties)> {
+        ids.into_iter()
+            .filter_map(|id| Some((id, self.properties(id)?)))
+            .collect()
+    }
+
+    async fn get_property(
+        &self,
+        id: i32,
+        name: String,
+    ) -> zbus::fdo::Result<OwnedValue> {
+        self.properties(id)
+            .and_then(|mut props| props.remove(&name))
+            .ok_or_else(|| {
+                zbus::fdo::Error::InvalidArgs(format!("No {} of {}", name, id))
+            })
+    }
+
+    async fn event(
+        &self,
+        id: i32,
+        event_id: String,
+        _data: OwnedValue,
+        _timestamp: u32,
+    ) {
+        if id != PAUSE || event_id != "clicked" {
+            return;
+        }
+
+        let reply = control::request(&self.requests, Command::Toggle).await;
+        if let Some(error) = reply.error {
+            debug!("Failed to toggle pausing: {}", error);
+        }
+    }
+
+    /// Returns the ids of the events that were not found.
+    async fn event_group(
+        &self,
+        events: Vec<(i32, String, OwnedValue, u32)>,
+    ) -> Vec<i32> {
+        let mut missing = vec![];
+
+        for (id, event_id, data, timestamp) in events {
+            if self.properties(id).is_none() {
+                missing.push(id);
+                continue;
+            }
+
+            self.event(id, event_id, data, timestamp).await;
+        }
+
+        missing
+    }
+
+    /// The menu never changes before it is shown.
+    async fn about_to_show(&self, _id: i32) -> bool {
+        false
+    }
+
+    async fn about_to_show_group(&self, ids: Vec<i32>) -> (Vec<i32>, Vec<i32>) {
+        let missing = ids
+            .into_iter()
+            .filter(|id| self.properties(*id).is_none())
+            .collect();
+
+        (vec![], missing)
+    }
+
+    #[zbus(signal)]
+    async fn items_properties_updated(
+        emitter: &SignalEmitter<'_>,
+        updated: Vec<(i32, Properties)>,
+        removed: Vec<(i32, Vec<String>)>,
+    ) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    async fn version(&self) -> u32 {
+        3
+    }
+
+    #[zbus(property)]
+    async fn text_direction(&self) -> String {
+        "ltr".into()
+    }
+
+    #[zbus(property)]
+    async fn status(&self) -> String {
+        "normal".into()
+    }
+
+    #[zbus(property)]
+    async fn icon_theme_path(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_test() {
+        let (requests, _) = control::channel();
+        let (paused_tx, paused) = watch::channel(false);
+        let menu = Menu { requests, paused };
+
+        let root = menu.layout(ROOT, -1).unwrap();
+        assert_eq!(root.children.len(), 1);
+        assert!(menu.layout(ROOT, 0).unwrap().children.is_empty());
+        assert!(menu.layout(7, -1).is_none());
+
+        let state = |menu: &Menu| {
+            menu.properties(PAUSE).unwrap()["toggle-state"]
+                .downcast_ref::<i32>()
+                .unwrap()
+        };
+        assert_eq!(state(&menu), 0);
+        paused_tx.send_replace(true);
+        assert_eq!(state(&menu), 1);
+    }
+}
@@ -0,0 +1,80 @@
+//! The activated toplevel and idle state of Wayland compositors other than
+//! sway, from the `wlr-foreign-toplevel-management` and `ext-idle-notify`
+//! protocols.
+
+pub mod idle;
+pub mod toplevel;
+
+use thiserror::Error;
+use tokio::io::Interest;
+use tokio::io::unix::AsyncFd;
+use wayland_client::backend::WaylandError as BackendError;
+use wayland_client::globals::{
+    BindError, GlobalError, GlobalList, GlobalListContents, registry_queue_init,
+};
+use wayland_client::protocol::wl_registry::WlRegistry;
+use wayland_client::{
+    ConnectError, Connection, Dispatch, DispatchError, EventQueue,
+};
+
+#[derive(Debug, Error)]
+pub enum WaylandError {
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to connect to the compositor: {0}")]
+    Connect(#[from] ConnectError),
+    #[error("The connection to the compositor failed: {0}")]
+    Backend(#[from] BackendError),
+    #[error("Failed to read the globals of the compositor: {0}")]
+    Globals(#[from] GlobalError),
+    #[error("Failed to dispatch events of the compositor: {0}")]
+    Dispatch(#[from] DispatchError),
+    #[error("The compositor does not support {0}: {1}")]
+    MissingGlobal(&'static str, BindError),
+    #[error("The compositor finished {0}.")]
+    Finished(&'static str),
+}
+
+/// A connection to the compositor of `WAYLAND_DISPLAY`, with the globals it
+/// had when connected. Blocks for the roundtrip of the registry.
+fn connect<D>() -> Result<(GlobalList, EventQueue<D>), WaylandError>
+where
+    D: Dispatch<WlRegistry, GlobalListContents> + 'static,
+{
+    let connection = Connection::connect_to_env()?;
+    let (globals, queue) = registry_queue_init(&connection)?;
+
+    Ok((globals, queue))
+}
+
+/// Dispatch the events of `queue` to `state`, waiting for the socket until
+/// there are some.
+async fn dispatch<D>(
+    queue: &mut EventQueue<D>,
+    state: &mut D,
+) -> Result<usize, WaylandError> {
+    loop {
+        let dispatched = queue.dispatch_pending(state)?;
+        if dispatched > 0 {
+            return Ok(dispatched);
+        }
+
+        queue.flush()?;
+        // Events were queued in the meantime.
+        let Some(guard) = queue.prepare_read() else {
+            continue;
+        };
+
+        let fd =
+            AsyncFd::with_interest(guard.connection_fd(), Interest::READABLE)?;
+        fd.readable().await?.retain_ready();
+        drop(fd);
+
+        match guard.read() {
+            Ok(_) => {}
+            Err(BackendError::Io(e))
+                if e.kind() == std::io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
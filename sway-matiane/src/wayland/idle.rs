@@ -0,0 +1,142 @@
+//! Idle and active events of `ext_idle_notifier_v1`.
+
+use super::WaylandError;
+use log::warn;
+use matiane_core::bus::EventSink;
+use matiane_core::events::Event;
+use std::collections::VecDeque;
+use tokio::task::{JoinHandle, spawn, spawn_blocking};
+use tokio_util::sync::CancellationToken;
+use wayland_client::globals::GlobalListContents;
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::{Connection, Dispatch, EventQueue, QueueHandle};
+use wayland_protocols::ext::idle_notify::v1::client::{
+    ext_idle_notification_v1::{self, ExtIdleNotificationV1},
+    ext_idle_notifier_v1::ExtIdleNotifierV1,
+};
+
+const SEAT: &str = "wl_seat";
+const NOTIFIER: &str = "ext_idle_notifier_v1";
+
+/// Idle and active events not read yet.
+#[derive(Debug, Default)]
+struct Notifications(VecDeque<Event>);
+
+/// An idle notification of the compositor, for the first seat.
+pub struct IdleNotification {
+    queue: EventQueue<Notifications>,
+    notifications: Notifications,
+}
+
+impl IdleNotification {
+    /// Ask to be notified after `idle_timeout` seconds without input.
+    pub async fn connect(idle_timeout: u32) -> Result<Self, WaylandError> {
+        spawn_blocking(move || Self::connect_blocking(idle_timeout))
+            .await
+            .map_err(std::io::Error::other)?
+    }
+
+    fn connect_blocking(idle_timeout: u32) -> Result<Self, WaylandError> {
+        let (globals, mut queue) = super::connect()?;
+        let handle = queue.handle();
+
+        let seat: WlSeat = globals
+            .bind(&handle, 1..=1, ())
+            .map_err(|e| WaylandError::MissingGlobal(SEAT, e))?;
+        let notifier: ExtIdleNotifierV1 = globals
+            .bind(&handle, 1..=1, ())
+            .map_err(|e| WaylandError::MissingGlobal(NOTIFIER, e))?;
+
+        notifier.get_idle_notification(
+            idle_timeout.saturating_mul(1000),
+            &seat,
+            &handle,
+            (),
+        );
+
+        // Protocol errors of the request come before it is used.
+        let mut notifications = Notifications::default();
+        queue.roundtrip(&mut notifications)?;
+
+        Ok(IdleNotification {
+            queue,
+            notifications,
+        })
+    }
+
+    /// The next idle or active event.
+    pub async fn next(&mut self) -> Result<Event, WaylandError> {
+        loop {
+            if let Some(event) = self.notifications.0.pop_front() {
+                return Ok(event);
+            }
+
+            super::dispatch(&mut self.queue, &mut self.notifications).await?;
+        }
+    }
+}
+
+impl Dispatch<ExtIdleNotificationV1, ()> for Notifications {
+    fn event(
+        notifications: &mut Self,
+        _: &ExtIdleNotificationV1,
+        event: ext_idle_notification_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            ext_idle_notification_v1::Event::Idled => {
+                notifications.0.push_back(Event::Idle)
+            }
+            ext_idle_notification_v1::Event::Resumed => {
+                notifications.0.push_back(Event::Active)
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for Notifications {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+wayland_client::delegate_noop!(Notifications: ignore WlSeat);
+wayland_client::delegate_noop!(Notifications: ExtIdleNotifierV1);
+
+/// Sends idle and active events of `notification` until cancelled.
+pub fn spawn_idle(
+    mut notification: IdleNotification,
+    events: EventSink,
+    token: CancellationToken,
+) -> JoinHandle<Result<(), WaylandError>> {
+    spawn(async move {
+        loop {
+            let event = tokio::select! {
+                _ = token.cancelled() => return Ok(()),
+                event = notification.next() => event,
+            };
+
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Idle time of the compositor is not tracked: {}", e);
+                    return Err(e);
+                }
+            };
+
+            if events.send(event).await.is_err() {
+                return Ok(());
+            }
+        }
+    })
+}
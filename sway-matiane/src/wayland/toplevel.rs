@@ -0,0 +1,305 @@
+//! The activated toplevel of `zwlr_foreign_toplevel_manager_v1`, supported
+//! by wlroots compositors like river and labwc, and by niri.
+
+use super::WaylandError;
+use futures::Stream;
+use std::collections::{HashMap, VecDeque};
+use tokio::task::spawn_blocking;
+use wayland_client::globals::GlobalListContents;
+use wayland_client::protocol::wl_registry::{self, WlRegistry};
+use wayland_client::{
+    Connection, Dispatch, EventQueue, Proxy, QueueHandle, event_created_child,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{
+        self, State, ZwlrForeignToplevelHandleV1,
+    },
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+const MANAGER: &str = "zwlr_foreign_toplevel_manager_v1";
+const MANAGER_VERSION: u32 = 3;
+
+/// The toplevel the compositor activated.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ActiveToplevel {
+    pub title: Option<String>,
+    pub app_id: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Toplevel {
+    title: Option<String>,
+    app_id: Option<String>,
+    activated: bool,
+}
+
+/// Toplevels by the protocol id of their handle, from the events of the
+/// handles.
+#[derive(Debug, Default)]
+struct Toplevels {
+    toplevels: HashMap<u32, Toplevel>,
+    /// The toplevel last returned, while it is activated.
+    active: Option<(u32, ActiveToplevel)>,
+}
+
+impl Toplevels {
+    /// The activated toplevel, when an event of a handle changed it.
+    fn handle_event(
+        &mut self,
+        handle: u32,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+    ) -> Option<ActiveToplevel> {
+        let toplevel = self.toplevels.get_mut(&handle)?;
+
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => {
+                toplevel.title = Some(title)
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                toplevel.app_id = Some(app_id)
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state } => {
+                let activated = (State::Activated as u32).to_ne_bytes();
+                toplevel.activated =
+                    state.chunks_exact(4).any(|state| state == activated);
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Done => {
+                return self.done(handle);
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                self.toplevels.remove(&handle);
+                if self.is_active(handle) {
+                    self.active = None;
+                }
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn is_active(&self, handle: u32) -> bool {
+        self.active.as_ref().is_some_and(|(id, _)| *id == handle)
+    }
+
+    /// The toplevel, when it is activated and changed since it was last
+    /// returned.
+    fn done(&mut self, handle: u32) -> Option<ActiveToplevel> {
+        let toplevel = self.toplevels.get(&handle)?;
+
+        if !toplevel.activated {
+            if self.is_active(handle) {
+                self.active = None;
+            }
+            return None;
+        }
+
+        let active = (
+            handle,
+            ActiveToplevel {
+                title: toplevel.title.clone(),
+                app_id: toplevel.app_id.clone(),
+            },
+        );
+
+        if self.active.as_ref() == Some(&active) {
+            return None;
+        }
+
+        self.active = Some(active.clone());
+        Some(active.1)
+    }
+}
+
+/// Toplevels and the changes of the activated one not read yet.
+#[derive(Debug, Default)]
+struct TrackerState {
+    toplevels: Toplevels,
+    changes: VecDeque<ActiveToplevel>,
+    finished: bool,
+}
+
+/// Follows the activated toplevel, and the title of it.
+pub struct ToplevelTracker {
+    queue: EventQueue<TrackerState>,
+    state: TrackerState,
+}
+
+impl ToplevelTracker {
+    pub async fn connect() -> Result<Self, WaylandError> {
+        spawn_blocking(Self::connect_blocking)
+            .await
+            .map_err(std::io::Error::other)?
+    }
+
+    fn connect_blocking() -> Result<Self, WaylandError> {
+        let (globals, queue) = super::connect()?;
+
+        let _: ZwlrForeignToplevelManagerV1 = globals
+            .bind(&queue.handle(), 1..=MANAGER_VERSION, ())
+            .map_err(|e| WaylandError::MissingGlobal(MANAGER, e))?;
+
+        Ok(ToplevelTracker {
+            queue,
+            state: TrackerState::default(),
+        })
+    }
+
+    /// The next change of the activated toplevel or its title, the first
+    /// one is the toplevel activated now.
+    pub async fn next(&mut self) -> Result<ActiveToplevel, WaylandError> {
+        loop {
+            if let Some(toplevel) = self.state.changes.pop_front() {
+                return Ok(toplevel);
+            }
+
+            if self.state.finished {
+                return Err(WaylandError::Finished(MANAGER));
+            }
+
+            super::dispatch(&mut self.queue, &mut self.state).await?;
+        }
+    }
+
+    pub fn into_stream(
+        self,
+    ) -> impl Stream<Item = Result<ActiveToplevel, WaylandError>> {
+        // The connection can not go on after an error.
+        futures::stream::unfold(Some(self), |tracker| async {
+            let mut tracker = tracker?;
+
+            match tracker.next().await {
+                Ok(toplevel) => Some((Ok(toplevel), Some(tracker))),
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for TrackerState {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } => {
+                state
+                    .toplevels
+                    .toplevels
+                    .insert(toplevel.id().protocol_id(), Toplevel::default());
+            }
+            zwlr_foreign_toplevel_manager_v1::Event::Finished => {
+                state.finished = true;
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(TrackerState, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE
+            => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for TrackerState {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let closed =
+            matches!(event, zwlr_foreign_toplevel_handle_v1::Event::Closed);
+
+        let id = handle.id().protocol_id();
+        if let Some(toplevel) = state.toplevels.handle_event(id, event) {
+            state.changes.push_back(toplevel);
+        }
+
+        if closed {
+            handle.destroy();
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for TrackerState {
+    fn event(
+        _: &mut Self,
+        _: &WlRegistry,
+        _: wl_registry::Event,
+        _: &GlobalListContents,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zwlr_foreign_toplevel_handle_v1::Event as HandleEvent;
+
+    fn state(activated: bool) -> HandleEvent {
+        let mut state = (State::Maximized as u32).to_ne_bytes().to_vec();
+        if activated {
+            state.extend((State::Activated as u32).to_ne_bytes());
+        }
+        HandleEvent::State { state }
+    }
+
+    fn title(title: &str) -> HandleEvent {
+        HandleEvent::Title {
+            title: title.into(),
+        }
+    }
+
+    #[test]
+    fn toplevels_test() {
+        let mut toplevels = Toplevels::default();
+        toplevels.toplevels.insert(10, Toplevel::default());
+        toplevels.toplevels.insert(11, Toplevel::default());
+
+        let mut send = |handle, event| toplevels.handle_event(handle, event);
+
+        assert_eq!(send(10, title("one")), None);
+        let app_id = HandleEvent::AppId {
+            app_id: "foot".into(),
+        };
+        assert_eq!(send(10, app_id), None);
+        assert_eq!(send(10, state(true)), None);
+        let one = ActiveToplevel {
+            title: Some("one".into()),
+            app_id: Some("foot".into()),
+        };
+        assert_eq!(send(10, HandleEvent::Done), Some(one.clone()));
+        assert_eq!(send(10, HandleEvent::Done), None);
+
+        assert_eq!(send(10, title("two")), None);
+        let two = ActiveToplevel {
+            title: Some("two".into()),
+            ..one
+        };
+        assert_eq!(send(10, HandleEvent::Done), Some(two.clone()));
+
+        send(10, state(false));
+        assert_eq!(send(10, HandleEvent::Done), None);
+        send(11, state(true));
+        assert_eq!(
+            send(11, HandleEvent::Done),
+            Some(ActiveToplevel::default())
+        );
+
+        send(11, HandleEvent::Closed);
+        send(10, state(true));
+        assert_eq!(send(10, HandleEvent::Done), Some(two));
+        assert_eq!(send(11, HandleEvent::Done), None);
+        assert_eq!(send(12, HandleEvent::Done), None);
+    }
+}
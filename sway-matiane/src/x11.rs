@@ -0,0 +1,37 @@
+//! The active window and idle time of X11 window managers, like i3, read
+//! from the EWMH properties of the root window and the MIT-SCREEN-SAVER
+//! extension.
+//!
+//! The requests of x11rb block, they are made on threads of their own.
+
+pub mod idle;
+pub mod window;
+
+use thiserror::Error;
+use x11rb::connection::Connection as _;
+use x11rb::errors::{ConnectError, ConnectionError, ReplyError};
+use x11rb::protocol::xproto::Window;
+use x11rb::rust_connection::RustConnection;
+
+#[derive(Debug, Error)]
+pub enum X11Error {
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to connect to the X server: {0}")]
+    Connect(#[from] ConnectError),
+    #[error("The connection to the X server failed: {0}")]
+    Connection(#[from] ConnectionError),
+    #[error("X11 request failed: {0}")]
+    Reply(#[from] ReplyError),
+    #[error("The X server has no {0} extension.")]
+    MissingExtension(&'static str),
+}
+
+/// A connection to the display of `DISPLAY`, and the root window of its
+/// screen.
+fn connect() -> Result<(RustConnection, Window), X11Error> {
+    let (connection, screen) = x11rb::connect(None)?;
+    let root = connection.setup().roots[screen].root;
+
+    Ok((connection, root))
+}
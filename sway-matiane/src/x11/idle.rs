@@ -0,0 +1,90 @@
+//! Idle time of the X server, from the MIT-SCREEN-SAVER extension.
+
+use super::X11Error;
+use crate::screensaver::{POLL_INTERVAL, poll_event};
+use log::warn;
+use matiane_core::bus::EventSink;
+use std::sync::Arc;
+use tokio::task::{JoinHandle, spawn, spawn_blocking};
+use tokio::time::interval;
+use tokio_util::sync::CancellationToken;
+use x11rb::connection::RequestConnection;
+use x11rb::protocol::screensaver::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::Window;
+use x11rb::rust_connection::RustConnection;
+
+const SCREEN_SAVER: &str = "MIT-SCREEN-SAVER";
+
+/// A connection that has the screensaver extension.
+fn connect() -> Result<(RustConnection, Window), X11Error> {
+    let (connection, root) = super::connect()?;
+
+    if connection
+        .extension_information(screensaver::X11_EXTENSION_NAME)?
+        .is_none()
+    {
+        return Err(X11Error::MissingExtension(SCREEN_SAVER));
+    }
+
+    Ok((connection, root))
+}
+
+/// Milliseconds since the last input.
+fn idle_time(
+    connection: &RustConnection,
+    root: Window,
+) -> Result<u32, X11Error> {
+    let info = connection.screensaver_query_info(root)?.reply()?;
+
+    Ok(info.ms_since_user_input)
+}
+
+/// Sends idle and active events by polling the idle time of the display of
+/// `DISPLAY` until cancelled.
+pub fn spawn_idle(
+    idle_timeout: u32,
+    events: EventSink,
+    token: CancellationToken,
+) -> JoinHandle<Result<(), X11Error>> {
+    spawn(async move {
+        let result = poll_idle(idle_timeout, events, token).await;
+        if let Err(e) = &result {
+            warn!("Idle time of X11 is not tracked: {}", e);
+        }
+
+        result
+    })
+}
+
+async fn poll_idle(
+    idle_timeout: u32,
+    events: EventSink,
+    token: CancellationToken,
+) -> Result<(), X11Error> {
+    let (connection, root) = spawn_blocking(connect)
+        .await
+        .map_err(std::io::Error::other)??;
+    let connection = Arc::new(connection);
+
+    let timeout_ms = u64::from(idle_timeout) * 1000;
+    let mut poll = interval(POLL_INTERVAL);
+    let mut idle = false;
+
+    loop {
+        tokio::select! {
+            _ = token.cancelled() => return Ok(()),
+            _ = poll.tick() => {},
+        }
+
+        let connection = connection.clone();
+        let idle_ms = spawn_blocking(move || idle_time(&connection, root))
+            .await
+            .map_err(std::io::Error::other)??;
+
+        if let Some(event) = poll_event(&mut idle, idle_ms, timeout_ms)
+            && events.send(event).await.is_err()
+        {
+            return Ok(());
+        }
+    }
+}
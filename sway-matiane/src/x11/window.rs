@@ -0,0 +1,276 @@
+//! The active window of EWMH window managers and the changes of its title.
+
+use super::X11Error;
+use futures::Stream;
+use log::debug;
+use tokio::sync::mpsc;
+use x11rb::connection::Connection;
+use x11rb::errors::ReplyError;
+use x11rb::protocol::Event;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt as _, EventMask,
+    GetPropertyReply, Window,
+};
+use x11rb::rust_connection::RustConnection;
+
+/// Words of property values read, longer ones are cut.
+const MAX_PROPERTY_WORDS: u32 = 4096;
+
+/// The window the window manager focused.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ActiveWindow {
+    pub title: Option<String>,
+    /// Instance and class of `WM_CLASS`, like "firefox" and "Firefox".
+    pub instance: Option<String>,
+    pub class: Option<String>,
+    pub pid: Option<i32>,
+}
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+    }
+}
+
+/// Follows the active window, and the title of it.
+pub struct WindowTracker {
+    connection: RustConnection,
+    root: Window,
+    atoms: Atoms,
+    /// Window whose title changes are followed.
+    active: Option<Window>,
+    /// The active window may have changed since it was last read.
+    changed: bool,
+}
+
+impl WindowTracker {
+    /// Connect to the display of `DISPLAY`.
+    pub async fn connect() -> Result<Self, X11Error> {
+        tokio::task::spawn_blocking(Self::connect_blocking)
+            .await
+            .map_err(std::io::Error::other)?
+    }
+
+    fn connect_blocking() -> Result<Self, X11Error> {
+        let (connection, root) = super::connect()?;
+        let atoms = Atoms::new(&connection)?.reply()?;
+
+        select_properties(&connection, root, EventMask::PROPERTY_CHANGE)?
+            .check()?;
+
+        Ok(WindowTracker {
+            connection,
+            root,
+            atoms,
+            active: None,
+            changed: true,
+        })
+    }
+
+    /// The next change of the active window or its title, the first one is
+    /// the window active now. Blocks until there is one.
+    fn next_window(&mut self) -> Result<ActiveWindow, X11Error> {
+        loop {
+            if std::mem::take(&mut self.changed)
+                && let Some(window) = self.update_active()?
+            {
+                return Ok(window);
+            }
+
+            let Event::PropertyNotify(event) =
+                self.connection.wait_for_event()?
+            else {
+                continue;
+            };
+
+            if event.window == self.root {
+                self.changed |= event.atom == self.atoms._NET_ACTIVE_WINDOW;
+            } else if Some(event.window) == self.active
+                && (event.atom == self.atoms._NET_WM_NAME
+                    || event.atom == Atom::from(AtomEnum::WM_NAME))
+                && let Some(window) = self.read_or_gone(event.window)?
+            {
+                return Ok(window);
+            }
+        }
+    }
+
+    /// The windows of `next_window`, read on a thread of their own.
+    pub fn into_stream(
+        mut self,
+    ) -> impl Stream<Item = Result<ActiveWindow, X11Error>> {
+        let (tx, rx) = mpsc::channel(1);
+
+        // Not a blocking task of the runtime, which would wait for it on
+        // shutdown.
+        std::thread::spawn(move || {
+            loop {
+                let window = self.next_window();
+                // The connection can not go on after an error.
+                let failed = window.is_err();
+
+                if tx.blocking_send(window).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        futures::stream::unfold(rx, |mut rx| async {
+            rx.recv().await.map(|window| (window, rx))
+        })
+    }
+
+    /// Follow the window that is active now, if it is another one.
+    fn update_active(&mut self) -> Result<Option<ActiveWindow>, X11Error> {
+        let active = self
+            .property(self.root, self.atoms._NET_ACTIVE_WINDOW)?
+            .and_then(|property| property.value32()?.next())
+            .filter(|&window| window != 0);
+
+        if active == self.active {
+            return Ok(None);
+        }
+
+        // The previous window may be gone already.
+        if let Some(previous) = self.active {
+            select_properties(&self.connection, previous, EventMask::NO_EVENT)?
+                .ignore_error();
+        }
+        self.active = active;
+
+        let Some(window) = active else {
+            return Ok(None);
+        };
+
+        select_properties(
+            &self.connection,
+            window,
+            EventMask::PROPERTY_CHANGE,
+        )?
+        .ignore_error();
+        self.read_or_gone(window)
+    }
+
+    /// Properties of `window`, none when it is gone.
+    fn read_or_gone(
+        &self,
+        window: Window,
+    ) -> Result<Option<ActiveWindow>, X11Error> {
+        match self.read(window) {
+            Ok(window) => Ok(Some(window)),
+            Err(X11Error::Reply(ReplyError::X11Error(e))) => {
+                debug!(
+                    "Window {} is gone, X error {:?}.",
+                    window, e.error_kind
+                );
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read(&self, window: Window) -> Result<ActiveWindow, X11Error> {
+        let title = match self.property(window, self.atoms._NET_WM_NAME)? {
+            Some(name) => Some(text(&name)),
+            None => self
+                .property(window, AtomEnum::WM_NAME)?
+                .map(|name| text(&name)),
+        };
+
+        let (instance, class) = self
+            .property(window, AtomEnum::WM_CLASS)?
+            .map_or((None, None), |class| wm_class(&text(&class)));
+
+        let pid = self
+            .property(window, self.atoms._NET_WM_PID)?
+            .and_then(|pid| pid.value32()?.next())
+            .and_then(|pid| i32::try_from(pid).ok());
+
+        Ok(ActiveWindow {
+            title,
+            instance,
+            class,
+            pid,
+        })
+    }
+
+    /// Property of `window` of any type, none when it is not set.
+    fn property(
+        &self,
+        window: Window,
+        property: impl Into<Atom>,
+    ) -> Result<Option<GetPropertyReply>, X11Error> {
+        let reply = self
+            .connection
+            .get_property(
+                false,
+                window,
+                property,
+                AtomEnum::ANY,
+                0,
+                MAX_PROPERTY_WORDS,
+            )?
+            .reply()?;
+
+        Ok((reply.type_ != x11rb::NONE).then_some(reply))
+    }
+}
+
+/// Receive the events of `mask` of `window`.
+fn select_properties(
+    connection: &RustConnection,
+    window: Window,
+    mask: EventMask,
+) -> Result<x11rb::cookie::VoidCookie<'_, RustConnection>, X11Error> {
+    let attributes = ChangeWindowAttributesAux::new().event_mask(mask);
+    let cookie = connection.change_window_attributes(window, &attributes)?;
+    connection.flush()?;
+
+    Ok(cookie)
+}
+
+/// Text of a string property. UTF-8 and compound text, which is ASCII for
+/// the most part, are read as UTF-8.
+fn text(property: &GetPropertyReply) -> String {
+    decode_text(property.type_, &property.value)
+}
+
+fn decode_text(kind: Atom, value: &[u8]) -> String {
+    if kind == Atom::from(AtomEnum::STRING) {
+        value.iter().map(|&b| char::from(b)).collect()
+    } else {
+        String::from_utf8_lossy(value).into_owned()
+    }
+}
+
+/// Instance and class of `WM_CLASS`, both terminated by a nul.
+fn wm_class(raw: &str) -> (Option<String>, Option<String>) {
+    let mut parts = raw
+        .split('\0')
+        .map(|part| (!part.is_empty()).then(|| part.to_string()));
+
+    (parts.next().flatten(), parts.next().flatten())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_text_test() {
+        let string = Atom::from(AtomEnum::STRING);
+
+        let class = decode_text(string, b"navigator\0Firefox\0");
+        assert_eq!(
+            wm_class(&class),
+            (Some("navigator".into()), Some("Firefox".into()))
+        );
+        assert_eq!(wm_class("\0xterm\0"), (None, Some("xterm".into())));
+        assert_eq!(wm_class(""), (None, None));
+
+        assert_eq!(decode_text(string, b"caf\xe9"), "café");
+        assert_eq!(decode_text(300, "café".as_bytes()), "café");
+    }
+}
@@ -1,10 +1,16 @@
+#![allow(clippy::redundant_pattern_matching, clippy::unused_io_amount)]
+
 use anyhow::Result;
 use futures::StreamExt;
 use std::path::PathBuf;
 use sway_matiane::sway::codec::SwayPacketCodecError;
 use sway_matiane::sway::command::EventType;
-use sway_matiane::sway::connection::{SubscribeError, subscribe};
-use sway_matiane::sway::reply::{CommandError, Event, WindowChange};
+use sway_matiane::sway::connection::{
+    SubscribeError, get_tree, subscribe, subscribe_all,
+};
+use sway_matiane::sway::reply::{
+    CommandError, Event, WindowChange, WorkspaceChange,
+};
 use tempfile::{Builder, TempDir};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{UnixListener, UnixStream};
@@ -53,6 +59,221 @@ async fn sway_window_events_1() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn sway_workspace_and_output_events() -> Result<()> {
+    let subscribe_payload: &[u8] = br#"["workspace","output"]"#;
+    let server_recv = raw_packet_with_body! {
+        header: [magic, (u32_ne subscribe_payload.len()), (u32_ne 2)],
+        body: subscribe_payload
+    };
+
+    let workspace: &[u8] = br#"{"change":"focus","old":null,"current":{
+        "id":4,"name":"2","type":"workspace","border":"none",
+        "current_border_width":0,"layout":"splith",
+        "rect":{"x":0,"y":0,"width":1920,"height":1080},
+        "window_rect":{"x":0,"y":0,"width":0,"height":0},
+        "deco_rect":{"x":0,"y":0,"width":0,"height":0},
+        "geometry":{"x":0,"y":0,"width":0,"height":0},
+        "urgent":false,"focused":false,"focus":[],"floating_nodes":[],
+        "sticky":false,"num":2,"output":"eDP-1"}}"#;
+    let output: &[u8] = br#"{"change":"unspecified"}"#;
+    let response = [
+        raw_subscribe_success!(),
+        raw_packet_with_body! {
+            header: [
+                magic,
+                (u32_ne workspace.len()),
+                [be2ne_4 0x80, 0x00, 0x00, 0x00]
+            ],
+            body: workspace
+        },
+        raw_packet_with_body! {
+            header: [
+                magic,
+                (u32_ne output.len()),
+                [be2ne_4 0x80, 0x00, 0x00, 0x01]
+            ],
+            body: output
+        },
+    ]
+    .concat();
+
+    let MockServer {
+        dir: _dir,
+        bind_path,
+        handle,
+    } = setup_mock_server("workspace-events", server_recv, response)?;
+
+    let mut subbed =
+        subscribe_all(&bind_path, &[EventType::Workspace, EventType::Output])
+            .await?;
+
+    let Event::Workspace(workspace) = subbed.next().await.unwrap()? else {
+        panic!("Returned event must be a Workspace.");
+    };
+    assert_eq!(workspace.change, WorkspaceChange::Focus);
+    let current = workspace.current.unwrap();
+    assert_eq!(current.name, Some(String::from("2")));
+    assert_eq!(current.output, Some(String::from("eDP-1")));
+
+    let output = subbed.next().await.unwrap()?;
+    assert!(matches!(output, Event::Output(_)));
+    assert!(subbed.next().await.is_none());
+
+    handle.await??;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn i3_window_and_workspace_events() -> Result<()> {
+    let subscribe_payload: &[u8] = br#"["window","workspace"]"#;
+    let server_recv = raw_packet_with_body! {
+        header: [magic, (u32_ne subscribe_payload.len()), (u32_ne 2)],
+        body: subscribe_payload
+    };
+
+    let window: &[u8] = br#"{"change":"focus","container":{
+        "id":94372540,"type":"con","orientation":"none",
+        "scratchpad_state":"none","percent":1.0,"urgent":false,"marks":[],
+        "focused":true,"output":"eDP-1","layout":"splith",
+        "workspace_layout":"default","last_split_layout":"splith",
+        "border":"pixel","current_border_width":2,
+        "rect":{"x":0,"y":0,"width":1920,"height":1080},
+        "deco_rect":{"x":0,"y":0,"width":0,"height":0},
+        "window_rect":{"x":2,"y":0,"width":1916,"height":1078},
+        "geometry":{"x":0,"y":0,"width":1200,"height":800},
+        "name":"Mozilla Firefox","window_icon_padding":-1,
+        "window":23068675,"window_type":"normal",
+        "window_properties":{"class":"firefox","instance":"Navigator",
+        "machine":"laptop","title":"Mozilla Firefox","transient_for":null},
+        "nodes":[],"floating_nodes":[],"focus":[],"fullscreen_mode":0,
+        "sticky":false,"floating":"auto_off","swallows":[]}}"#;
+    let workspace: &[u8] = br#"{"change":"restored","old":null,"current":{
+        "id":4,"type":"workspace","orientation":"horizontal",
+        "scratchpad_state":"none","percent":null,"urgent":false,
+        "marks":[],"focused":false,"output":"eDP-1","layout":"splith",
+        "border":"normal","current_border_width":-1,
+        "rect":{"x":0,"y":0,"width":1920,"height":1080},
+        "deco_rect":{"x":0,"y":0,"width":0,"height":0},
+        "window_rect":{"x":0,"y":0,"width":0,"height":0},
+        "geometry":{"x":0,"y":0,"width":0,"height":0},
+        "name":"1","num":1,"window":null,"nodes":[],"floating_nodes":[],
+        "focus":[],"fullscreen_mode":1,"sticky":false,
+        "floating":"auto_off","swallows":[]}}"#;
+    let response = [
+        raw_subscribe_success!(),
+        raw_packet_with_body! {
+            header: [
+                magic,
+                (u32_ne window.len()),
+                [be2ne_4 0x80, 0x00, 0x00, 0x03]
+            ],
+            body: window
+        },
+        raw_packet_with_body! {
+            header: [
+                magic,
+                (u32_ne workspace.len()),
+                [be2ne_4 0x80, 0x00, 0x00, 0x00]
+            ],
+            body: workspace
+        },
+    ]
+    .concat();
+
+    let MockServer {
+        dir: _dir,
+        bind_path,
+        handle,
+    } = setup_mock_server("i3-events", server_recv, response)?;
+
+    let mut subbed =
+        subscribe_all(&bind_path, &[EventType::Window, EventType::Workspace])
+            .await?;
+
+    let Event::Window(window) = subbed.next().await.unwrap()? else {
+        panic!("Returned event must be a Window.");
+    };
+    assert_eq!(window.change, WindowChange::Focus);
+    assert_eq!(window.container.app_id, None);
+    assert_eq!(window.container.pid, None);
+    let properties = window.container.window_properties.unwrap();
+    assert_eq!(properties.instance, Some(String::from("Navigator")));
+    assert_eq!(properties.class, Some(String::from("firefox")));
+
+    let Event::Workspace(workspace) = subbed.next().await.unwrap()? else {
+        panic!("Returned event must be a Workspace.");
+    };
+    assert_eq!(workspace.change, WorkspaceChange::Restored);
+    assert!(subbed.next().await.is_none());
+
+    handle.await??;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn sway_get_tree_focused_window() -> Result<()> {
+    let server_recv = raw_packet![magic, (u32_ne 0), (u32_ne 4)];
+
+    let tree: &[u8] = br#"{"id":1,"name":"root","type":"root",
+        "border":"none","current_border_width":0,"layout":"splith",
+        "rect":{"x":0,"y":0,"width":1920,"height":1080},
+        "window_rect":{"x":0,"y":0,"width":0,"height":0},
+        "deco_rect":{"x":0,"y":0,"width":0,"height":0},
+        "geometry":{"x":0,"y":0,"width":0,"height":0},
+        "urgent":false,"focused":false,"focus":[4],"sticky":false,
+        "floating_nodes":[],"nodes":[{"id":4,"name":"1","type":"workspace",
+        "border":"none","current_border_width":0,"layout":"splith",
+        "rect":{"x":0,"y":0,"width":1920,"height":1080},
+        "window_rect":{"x":0,"y":0,"width":0,"height":0},
+        "deco_rect":{"x":0,"y":0,"width":0,"height":0},
+        "geometry":{"x":0,"y":0,"width":0,"height":0},
+        "urgent":false,"focused":false,"focus":[8,9],"sticky":false,
+        "num":1,"output":"eDP-1","nodes":[{"id":8,"name":"~","type":"con",
+        "border":"pixel","current_border_width":2,"layout":"none",
+        "rect":{"x":0,"y":0,"width":960,"height":1080},
+        "window_rect":{"x":0,"y":0,"width":960,"height":1080},
+        "deco_rect":{"x":0,"y":0,"width":0,"height":0},
+        "geometry":{"x":0,"y":0,"width":960,"height":1080},
+        "urgent":false,"focused":false,"focus":[],"sticky":false,
+        "nodes":[],"floating_nodes":[],"app_id":"foot","pid":41}],
+        "floating_nodes":[{"id":9,"name":"Calculator","type":"floating_con",
+        "border":"normal","current_border_width":2,"layout":"none",
+        "rect":{"x":100,"y":100,"width":400,"height":600},
+        "window_rect":{"x":100,"y":100,"width":400,"height":600},
+        "deco_rect":{"x":0,"y":0,"width":0,"height":0},
+        "geometry":{"x":0,"y":0,"width":400,"height":600},
+        "urgent":false,"focused":true,"focus":[],"sticky":false,
+        "nodes":[],"floating_nodes":[],"app_id":"gnome-calculator",
+        "pid":42}]}]}"#;
+    let response = raw_packet_with_body! {
+        header: [magic, (u32_ne tree.len()), (u32_ne 4)],
+        body: tree
+    };
+
+    let MockServer {
+        dir: _dir,
+        bind_path,
+        handle,
+    } = setup_mock_server("get-tree", server_recv, response)?;
+
+    let tree = get_tree(&bind_path).await?;
+    let window = tree.focused_window().unwrap();
+    assert_eq!(window.id, 9);
+    assert_eq!(window.app_id, Some(String::from("gnome-calculator")));
+    assert_eq!(window.pid, Some(42));
+
+    // The unfocused window has none under it.
+    let workspace = &tree.nodes[0];
+    assert!(workspace.nodes[0].focused_window().is_none());
+
+    handle.await??;
+
+    Ok(())
+}
+
 generate_sway_bad_subscribe_tests![
     [
         sway_subscribe_bad_magic,
@@ -121,10 +342,11 @@ generate_sway_bad_event_tests![
     [
         sway_bad_event_unsupported_event,
         raw_packet_with_body! {
-            header: [magic, (u32_ne 2), [be2ne_4 0x80, 0x00, 0x00, 0x00]],
+            // mode events are not parsed.
+            header: [magic, (u32_ne 2), [be2ne_4 0x80, 0x00, 0x00, 0x02]],
             body: br#"{}"#
         },
-        SubscribeError::UnsupportedEvent(0),
+        SubscribeError::UnsupportedEvent(2),
     ],
 ];
 
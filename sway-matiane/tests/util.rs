@@ -1,3 +1,5 @@
+#![allow(clippy::empty_line_after_doc_comments)]
+
 /// A not at all necessary macro for fun, brought to you by tears.
 
 #[macro_export]